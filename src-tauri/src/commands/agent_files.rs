@@ -0,0 +1,445 @@
+//! Tauri commands for sub-agent files on disk
+//!
+//! Reading, writing, trashing, importing and converting agent files, plus the
+//! checks run on them (lint, diff, compatibility). Commands that take an
+//! optional `project_path` use the home directory (global config) when it is
+//! not given.
+
+use crate::db::models::SubAgent;
+use crate::db::schema::Database;
+use crate::services::agent_compat::{self, CompatReport, ConvertReport};
+use crate::services::agent_diff::{diff_subagents, FieldChange};
+use crate::services::agent_inheritance::expand_inheritance;
+use crate::services::agent_lint::{lint_subagent_content, Lint};
+use crate::services::agent_table::format_agents_table;
+use crate::services::subagent_import::{self, AgentFormat, AgentGroup, ImportResult};
+use crate::services::subagent_writer::{
+    self, AgentFormatter, AgentScope, GenerateOptions, LineEnding, ShadowInfo, UpsertOutcome,
+    WriteOptions,
+};
+use crate::utils::paths::get_claude_paths;
+use log::info;
+use rusqlite::params;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, UNIX_EPOCH};
+use tauri::State;
+
+/// Base directory of an agent config: the project, or the home directory
+fn base_dir(project_path: Option<String>) -> Result<PathBuf, String> {
+    match project_path {
+        Some(path) => Ok(PathBuf::from(path)),
+        None => get_claude_paths()
+            .map(|paths| paths.home)
+            .map_err(|e| e.to_string()),
+    }
+}
+
+fn load_subagent(db: &Database, id: i64) -> Result<SubAgent, String> {
+    db.get_subagent_by_id(id)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Sub-agent {} not found", id))
+}
+
+pub(crate) fn load_subagents(db: &Database, ids: &[i64]) -> Result<Vec<SubAgent>, String> {
+    ids.iter().map(|id| load_subagent(db, *id)).collect()
+}
+
+fn path_strings(paths: Vec<PathBuf>) -> Vec<String> {
+    paths
+        .into_iter()
+        .map(|p| p.to_string_lossy().to_string())
+        .collect()
+}
+
+// ============================================================================
+// Reading
+// ============================================================================
+
+/// Agents in a config's agents directory, optionally only those modified
+/// after `modified_after` (milliseconds since the Unix epoch)
+#[tauri::command(rename_all = "camelCase")]
+pub fn list_subagent_files(
+    project_path: Option<String>,
+    modified_after: Option<u64>,
+) -> Result<Vec<SubAgent>, String> {
+    let since = modified_after.map(|ms| UNIX_EPOCH + Duration::from_millis(ms));
+    subagent_writer::list_subagents(&base_dir(project_path)?, since).map_err(|e| e.to_string())
+}
+
+/// Names of the agents in a config's agents directory, without reading them
+#[tauri::command(rename_all = "camelCase")]
+pub fn list_subagent_file_names(project_path: Option<String>) -> Result<Vec<String>, String> {
+    subagent_writer::list_subagent_names(&base_dir(project_path)?).map_err(|e| e.to_string())
+}
+
+/// Read one agent file for import
+#[tauri::command]
+pub fn read_subagent_file(path: String, format: AgentFormat) -> Result<SubAgent, String> {
+    let path = Path::new(&path);
+    match format {
+        AgentFormat::Claude => subagent_writer::parse_subagent_file(path),
+        AgentFormat::OpenCode => subagent_writer::load_subagent_file_opencode(path),
+    }
+    .map_err(|e| e.to_string())
+}
+
+/// Close an unterminated frontmatter block in an agent file so it can be
+/// imported. Returns false if there was nothing to repair.
+#[tauri::command]
+pub fn repair_subagent_frontmatter(path: String) -> Result<bool, String> {
+    let content = std::fs::read_to_string(&path).map_err(|e| e.to_string())?;
+    let Some(repaired) = subagent_writer::try_repair_frontmatter(&content) else {
+        return Ok(false);
+    };
+    info!("[AgentFiles] Repairing frontmatter of {}", path);
+    std::fs::write(&path, repaired).map_err(|e| e.to_string())?;
+    Ok(true)
+}
+
+/// Parse agent markdown in either format
+#[tauri::command]
+pub fn parse_subagent_text(content: String, format: AgentFormat) -> Result<SubAgent, String> {
+    agent_compat::formatter(format)
+        .parse(&content)
+        .map_err(|e| e.to_string())
+}
+
+/// Parse several Claude-format agents; a malformed one only fails its own entry
+#[tauri::command]
+pub fn parse_subagent_texts(inputs: Vec<String>) -> Result<Vec<Result<SubAgent, String>>, String> {
+    let inputs: Vec<&str> = inputs.iter().map(String::as_str).collect();
+    Ok(subagent_writer::parse_many_subagents(&inputs)
+        .into_iter()
+        .map(|result| result.map_err(|e| e.to_string()))
+        .collect())
+}
+
+/// Check an agent given as JSON, naming the offending field if it is invalid
+#[tauri::command]
+pub fn parse_subagent_json(value: serde_json::Value) -> Result<SubAgent, String> {
+    subagent_writer::subagent_from_value(value).map_err(|e| e.to_string())
+}
+
+// ============================================================================
+// Writing
+// ============================================================================
+
+/// Markdown of a library agent, e.g. to copy it with Windows line endings
+#[tauri::command(rename_all = "camelCase")]
+pub fn generate_subagent_text(
+    db: State<'_, Arc<Mutex<Database>>>,
+    id: i64,
+    line_ending: LineEnding,
+) -> Result<String, String> {
+    let db = db.lock().map_err(|e| e.to_string())?;
+    let subagent = load_subagent(&db, id)?;
+    let options = GenerateOptions {
+        line_ending,
+        ..Default::default()
+    };
+    Ok(subagent_writer::generate_subagent_markdown_with_options(
+        &subagent, &options,
+    ))
+}
+
+/// Write a library agent to a config and record the write time in the library
+#[tauri::command(rename_all = "camelCase")]
+pub fn write_subagent_to_config(
+    db: State<'_, Arc<Mutex<Database>>>,
+    id: i64,
+    project_path: Option<String>,
+) -> Result<SubAgent, String> {
+    let db = db.lock().map_err(|e| e.to_string())?;
+    let subagent = load_subagent(&db, id)?;
+    let written = subagent_writer::write_subagent_file_stamped(&base_dir(project_path)?, &subagent)
+        .map_err(|e| e.to_string())?;
+    db.conn()
+        .execute(
+            "UPDATE subagents SET updated_at = ? WHERE id = ?",
+            params![written.updated_at, id],
+        )
+        .map_err(|e| e.to_string())?;
+    Ok(written)
+}
+
+/// Write a library agent with file permissions and model checks
+#[tauri::command(rename_all = "camelCase")]
+pub fn write_subagent_with_options(
+    db: State<'_, Arc<Mutex<Database>>>,
+    id: i64,
+    project_path: Option<String>,
+    options: WriteOptions,
+) -> Result<(), String> {
+    let db = db.lock().map_err(|e| e.to_string())?;
+    let subagent = load_subagent(&db, id)?;
+    subagent_writer::write_subagent_file_with_write_options(
+        &base_dir(project_path)?,
+        &subagent,
+        &options,
+    )
+    .map_err(|e| e.to_string())
+}
+
+/// Write a library agent unless its file changed since it was read
+/// (`expected_hash` is the hash of the content last read)
+#[tauri::command(rename_all = "camelCase")]
+pub fn upsert_subagent_file(
+    db: State<'_, Arc<Mutex<Database>>>,
+    id: i64,
+    project_path: Option<String>,
+    expected_hash: Option<String>,
+) -> Result<UpsertOutcome, String> {
+    let db = db.lock().map_err(|e| e.to_string())?;
+    let subagent = load_subagent(&db, id)?;
+    subagent_writer::upsert_subagent(&base_dir(project_path)?, &subagent, expected_hash)
+        .map_err(|e| e.to_string())
+}
+
+/// Hash of a library agent that ignores cosmetic differences
+#[tauri::command]
+pub fn get_subagent_canonical_hash(
+    db: State<'_, Arc<Mutex<Database>>>,
+    id: i64,
+) -> Result<String, String> {
+    let db = db.lock().map_err(|e| e.to_string())?;
+    let subagent = load_subagent(&db, id)?;
+    Ok(subagent_writer::canonical_subagent_hash(&subagent))
+}
+
+/// Write library agents to a config, skipping files that are already up to
+/// date. Returns the names of the agents written.
+#[tauri::command(rename_all = "camelCase")]
+pub fn sync_subagent_files(
+    db: State<'_, Arc<Mutex<Database>>>,
+    ids: Vec<i64>,
+    project_path: Option<String>,
+) -> Result<Vec<String>, String> {
+    let db = db.lock().map_err(|e| e.to_string())?;
+    let agents = load_subagents(&db, &ids)?;
+    subagent_writer::write_subagents_incremental(&base_dir(project_path)?, &agents)
+        .map_err(|e| e.to_string())
+}
+
+/// Write a library agent to a project, using `default_model` if the agent has no model
+#[tauri::command(rename_all = "camelCase")]
+pub fn write_project_subagent_with_default_model(
+    db: State<'_, Arc<Mutex<Database>>>,
+    id: i64,
+    project_path: String,
+    default_model: Option<String>,
+) -> Result<(), String> {
+    let db = db.lock().map_err(|e| e.to_string())?;
+    let subagent = load_subagent(&db, id)?;
+    subagent_writer::write_project_subagent_with_default_model(
+        Path::new(&project_path),
+        &subagent,
+        default_model.as_deref(),
+    )
+    .map_err(|e| e.to_string())
+}
+
+/// List agents in the `enabledAgents` setting of a project
+#[tauri::command(rename_all = "camelCase")]
+pub fn enable_agents_in_project_settings(
+    project_path: String,
+    agent_names: Vec<String>,
+) -> Result<(), String> {
+    subagent_writer::update_project_settings_for_agents(Path::new(&project_path), &agent_names)
+        .map_err(|e| e.to_string())
+}
+
+/// Write a project's AGENTS.md (for OpenCode) listing library agents
+#[tauri::command(rename_all = "camelCase")]
+pub fn write_project_agents_md(
+    db: State<'_, Arc<Mutex<Database>>>,
+    project_path: String,
+    ids: Vec<i64>,
+    preamble: String,
+) -> Result<(), String> {
+    let db = db.lock().map_err(|e| e.to_string())?;
+    let agents = load_subagents(&db, &ids)?;
+    subagent_writer::write_agents_md(Path::new(&project_path), &agents, &preamble)
+        .map_err(|e| e.to_string())
+}
+
+/// Export library agents as plain `{name}.md` files into a folder.
+/// Returns the written paths.
+#[tauri::command(rename_all = "camelCase")]
+pub fn export_subagents_flat(
+    db: State<'_, Arc<Mutex<Database>>>,
+    ids: Vec<i64>,
+    out_dir: String,
+    overwrite: bool,
+) -> Result<Vec<String>, String> {
+    let db = db.lock().map_err(|e| e.to_string())?;
+    let agents = load_subagents(&db, &ids)?;
+    subagent_writer::export_agents_flat(&agents, Path::new(&out_dir), overwrite)
+        .map(path_strings)
+        .map_err(|e| e.to_string())
+}
+
+// ============================================================================
+// Trash
+// ============================================================================
+
+/// Move an agent file to the trash. Returns its name in the trash.
+#[tauri::command(rename_all = "camelCase")]
+pub fn trash_subagent_file(project_path: Option<String>, name: String) -> Result<String, String> {
+    subagent_writer::delete_subagent_trashed(&base_dir(project_path)?, &name)
+        .map_err(|e| e.to_string())
+}
+
+/// Move a trashed agent file back. Returns the restored path.
+#[tauri::command(rename_all = "camelCase")]
+pub fn restore_subagent_file(
+    project_path: Option<String>,
+    trashed_name: String,
+) -> Result<String, String> {
+    subagent_writer::restore_from_trash(&base_dir(project_path)?, &trashed_name)
+        .map(|path| path.to_string_lossy().to_string())
+        .map_err(|e| e.to_string())
+}
+
+/// Delete agents trashed at least `max_age_days` ago. Returns how many were removed.
+#[tauri::command(rename_all = "camelCase")]
+pub fn purge_subagent_trash(
+    project_path: Option<String>,
+    max_age_days: i64,
+) -> Result<usize, String> {
+    subagent_writer::purge_trash(
+        &base_dir(project_path)?,
+        chrono::Duration::days(max_age_days),
+    )
+    .map_err(|e| e.to_string())
+}
+
+// ============================================================================
+// Import
+// ============================================================================
+
+/// Read the agents of a shared directory (e.g. a folder kept in a repo)
+#[tauri::command]
+pub fn import_subagents_from_directory(
+    dir: String,
+    recursive: bool,
+) -> Result<Vec<SubAgent>, String> {
+    subagent_import::import_from_directory(Path::new(&dir), recursive).map_err(|e| e.to_string())
+}
+
+/// Re-read a shared directory, skipping files whose hash is in `known_hashes`
+#[tauri::command(rename_all = "camelCase")]
+pub fn import_changed_subagents(
+    dir: String,
+    known_hashes: HashMap<String, String>,
+) -> Result<ImportResult, String> {
+    subagent_import::import_changed_only(Path::new(&dir), &known_hashes).map_err(|e| e.to_string())
+}
+
+/// Group agent files describing the same agent across Claude and OpenCode formats
+#[tauri::command]
+pub fn group_subagent_files(files: Vec<String>) -> Result<Vec<AgentGroup>, String> {
+    let files: Vec<PathBuf> = files.into_iter().map(PathBuf::from).collect();
+    Ok(subagent_import::group_imported_agents(&files))
+}
+
+// ============================================================================
+// Checks
+// ============================================================================
+
+/// Common mistakes in agent markdown
+#[tauri::command]
+pub fn lint_subagent(content: String) -> Result<Vec<Lint>, String> {
+    Ok(lint_subagent_content(&content))
+}
+
+/// Fields that differ between two library agents
+#[tauri::command(rename_all = "camelCase")]
+pub fn diff_library_subagents(
+    db: State<'_, Arc<Mutex<Database>>>,
+    a_id: i64,
+    b_id: i64,
+) -> Result<Vec<FieldChange>, String> {
+    let db = db.lock().map_err(|e| e.to_string())?;
+    Ok(diff_subagents(
+        &load_subagent(&db, a_id)?,
+        &load_subagent(&db, b_id)?,
+    ))
+}
+
+/// An agent with its `extends` chain merged in, parents taken from the library
+#[tauri::command]
+pub fn expand_subagent_inheritance(
+    db: State<'_, Arc<Mutex<Database>>>,
+    subagent: SubAgent,
+) -> Result<SubAgent, String> {
+    let db = db.lock().map_err(|e| e.to_string())?;
+    let library = db.get_all_subagents().map_err(|e| e.to_string())?;
+    expand_inheritance(&subagent, |name| {
+        library.iter().find(|agent| agent.name == name).cloned()
+    })
+    .map_err(|e| e.to_string())
+}
+
+/// The library's agents as a plain-text table
+#[tauri::command]
+pub fn format_subagents_table(db: State<'_, Arc<Mutex<Database>>>) -> Result<String, String> {
+    let db = db.lock().map_err(|e| e.to_string())?;
+    let agents = db.get_all_subagents().map_err(|e| e.to_string())?;
+    Ok(format_agents_table(&agents))
+}
+
+/// What a library agent loses when written in `format`
+#[tauri::command]
+pub fn get_subagent_compatibility(
+    db: State<'_, Arc<Mutex<Database>>>,
+    id: i64,
+    format: AgentFormat,
+) -> Result<CompatReport, String> {
+    let db = db.lock().map_err(|e| e.to_string())?;
+    let subagent = load_subagent(&db, id)?;
+    Ok(agent_compat::compatibility_report(&subagent, format))
+}
+
+/// Convert every agent of a directory to another format
+#[tauri::command(rename_all = "camelCase")]
+pub fn convert_subagent_directory(
+    from_base: String,
+    to_base: String,
+    from_format: AgentFormat,
+    to_format: AgentFormat,
+) -> Result<ConvertReport, String> {
+    agent_compat::convert_directory(
+        Path::new(&from_base),
+        Path::new(&to_base),
+        from_format,
+        to_format,
+    )
+    .map_err(|e| e.to_string())
+}
+
+/// A same-named agent in the other scope that writing `name` to `scope` would
+/// hide or be hidden by
+#[tauri::command(rename_all = "camelCase")]
+pub fn get_subagent_shadow_warning(
+    name: String,
+    scope: AgentScope,
+    project_path: String,
+) -> Result<Option<ShadowInfo>, String> {
+    Ok(subagent_writer::shadow_warning(
+        &name,
+        scope,
+        Path::new(&project_path),
+    ))
+}
+
+/// Tools of the agent `name` in effect in a project (project agent first, then global)
+#[tauri::command(rename_all = "camelCase")]
+pub fn get_effective_agent_tools(
+    name: String,
+    project_path: String,
+) -> Result<Vec<String>, String> {
+    subagent_writer::effective_agent_tools(&name, Path::new(&project_path))
+        .map_err(|e| e.to_string())
+}
@@ -0,0 +1,44 @@
+//! Tauri commands exporting library agents and skills as zip bundles
+
+use crate::commands::agent_files::load_subagents;
+use crate::commands::skills::load_skill;
+use crate::db::models::Skill;
+use crate::db::schema::Database;
+use crate::services::bundle;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use tauri::State;
+
+fn load_skills(db: &Database, ids: &[i64]) -> Result<Vec<Skill>, String> {
+    ids.iter().map(|id| load_skill(db, *id)).collect()
+}
+
+/// Export library agents and skills to a zip bundle at `out_path`
+#[tauri::command(rename_all = "camelCase")]
+pub fn export_bundle(
+    db: State<'_, Arc<Mutex<Database>>>,
+    agent_ids: Vec<i64>,
+    skill_ids: Vec<i64>,
+    out_path: String,
+) -> Result<(), String> {
+    let db = db.lock().map_err(|e| e.to_string())?;
+    let agents = load_subagents(&db, &agent_ids)?;
+    let skills = load_skills(&db, &skill_ids)?;
+    bundle::export_bundle(&agents, &skills, Path::new(&out_path)).map_err(|e| e.to_string())
+}
+
+/// Export library agents and skills together with redacted project settings
+#[tauri::command(rename_all = "camelCase")]
+pub fn export_team_pack(
+    db: State<'_, Arc<Mutex<Database>>>,
+    agent_ids: Vec<i64>,
+    skill_ids: Vec<i64>,
+    settings: serde_json::Value,
+    out_path: String,
+) -> Result<(), String> {
+    let db = db.lock().map_err(|e| e.to_string())?;
+    let agents = load_subagents(&db, &agent_ids)?;
+    let skills = load_skills(&db, &skill_ids)?;
+    bundle::export_team_pack(&agents, &skills, settings, Path::new(&out_path))
+        .map_err(|e| e.to_string())
+}
@@ -1,7 +1,10 @@
 use crate::db::{ClaudePaths, Database, GlobalMcp, Mcp};
-use crate::services::config_writer;
+use crate::services::config_doctor::{self, AuditReport};
+use crate::services::config_usage::{self, UsageReport};
+use crate::services::{config_writer, scanner};
 use crate::utils::paths;
 use rusqlite::params;
+use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex};
 use tauri::State;
 
@@ -283,6 +286,51 @@ pub fn backup_configs() -> Result<(), String> {
     Ok(())
 }
 
+/// Audit the agents and skills of a project's `.claude` directory, or of
+/// ~/.claude when no project is given
+#[tauri::command(rename_all = "camelCase")]
+pub fn audit_claude_config(
+    project_path: Option<String>,
+    known_tools: Vec<String>,
+) -> Result<AuditReport, String> {
+    let config_dir = match project_path {
+        Some(path) => PathBuf::from(path).join(".claude"),
+        None => {
+            paths::get_claude_paths()
+                .map_err(|e| e.to_string())?
+                .claude_dir
+        }
+    };
+    let known_tools: Vec<&str> = known_tools.iter().map(String::as_str).collect();
+    config_doctor::audit_claude_config(&config_dir, &known_tools).map_err(|e| e.to_string())
+}
+
+/// Disk usage of the agents and skills of a project, or of the global config
+#[tauri::command(rename_all = "camelCase")]
+pub fn get_config_usage(
+    db: State<'_, Arc<Mutex<Database>>>,
+    project_path: Option<String>,
+) -> Result<UsageReport, String> {
+    let recursive_skills = {
+        let db = db.lock().map_err(|e| e.to_string())?;
+        scanner::recursive_skill_discovery(&db)
+    };
+    let base_path = match project_path {
+        Some(path) => PathBuf::from(path),
+        None => paths::get_claude_paths().map_err(|e| e.to_string())?.home,
+    };
+    config_usage::config_usage(&base_path, recursive_skills).map_err(|e| e.to_string())
+}
+
+/// Find projects with a `.claude` directory below `search_root`
+#[tauri::command(rename_all = "camelCase")]
+pub fn discover_claude_projects(search_root: String) -> Vec<String> {
+    paths::discover_claude_roots(Path::new(&search_root))
+        .into_iter()
+        .map(|path| path.to_string_lossy().to_string())
+        .collect()
+}
+
 // ============================================================================
 // Testable helper functions (no Tauri State dependency)
 // ============================================================================
@@ -4,11 +4,14 @@
 
 use crate::db::models::GatewayMcp;
 use crate::db::Database;
-use crate::mcp_gateway::backend::BackendInfo;
-use crate::mcp_gateway::server::{GatewayServerConfig, GatewayServerStatus};
+use crate::mcp_gateway::backend::{BackendId, BackendInfo, BackendOptions, ToolProfile};
+use crate::mcp_gateway::server::{self, GatewayServerConfig, GatewayServerStatus};
+use crate::mcp_gateway::usage::ToolUsage;
 use crate::mcp_gateway::GatewayServerState;
+use crate::services::mcp_client::ToolCallResult;
 use log::info;
 use serde_json::Value;
+use std::path::PathBuf;
 use std::sync::{Arc, Mutex};
 use tauri::State;
 
@@ -50,6 +53,35 @@ pub fn update_gateway_config(
         .map_err(|e| e.to_string())?;
     db.set_setting("gateway_auto_start", &config.auto_start.to_string())
         .map_err(|e| e.to_string())?;
+    db.set_setting(
+        "gateway_allow_backend_registration",
+        &config.allow_backend_registration.to_string(),
+    )
+    .map_err(|e| e.to_string())?;
+    db.set_setting("gateway_audit_log_path", &config.audit_log_path)
+        .map_err(|e| e.to_string())?;
+    db.set_setting(
+        "gateway_result_cache_ttl_secs",
+        &config.result_cache_ttl_secs.to_string(),
+    )
+    .map_err(|e| e.to_string())?;
+    db.set_setting(
+        "gateway_idle_timeout_secs",
+        &config.idle_timeout_secs.to_string(),
+    )
+    .map_err(|e| e.to_string())?;
+    db.set_setting(
+        "gateway_circuit_breaker_threshold",
+        &config.circuit_breaker_threshold.to_string(),
+    )
+    .map_err(|e| e.to_string())?;
+    db.set_setting(
+        "gateway_circuit_breaker_cooldown_secs",
+        &config.circuit_breaker_cooldown_secs.to_string(),
+    )
+    .map_err(|e| e.to_string())?;
+    db.set_setting("gateway_http_pool_size", &config.http_pool_size.to_string())
+        .map_err(|e| e.to_string())?;
 
     Ok(())
 }
@@ -203,3 +235,156 @@ pub async fn restart_gateway_backend(
     info!("[GatewayCmd] Restarting gateway backend for MCP {}", mcp_id);
     gateway_state.restart_backend(mcp_id).await
 }
+
+/// Get the gateway options of a backend (defaults if none were set)
+#[tauri::command]
+pub async fn get_gateway_backend_options(
+    gateway_state: State<'_, Arc<GatewayServerState>>,
+    mcp_id: i64,
+) -> Result<BackendOptions, String> {
+    info!(
+        "[GatewayCmd] Getting options for gateway backend {}",
+        mcp_id
+    );
+    let backend_manager = gateway_state.backend_manager.lock().await;
+    Ok(backend_manager.backend_options(mcp_id))
+}
+
+/// Set and persist the gateway options of a backend
+#[tauri::command]
+pub async fn set_gateway_backend_options(
+    gateway_state: State<'_, Arc<GatewayServerState>>,
+    mcp_id: i64,
+    options: BackendOptions,
+) -> Result<(), String> {
+    info!(
+        "[GatewayCmd] Setting options for gateway backend {}",
+        mcp_id
+    );
+    let mut backend_manager = gateway_state.backend_manager.lock().await;
+    backend_manager
+        .set_backend_options(mcp_id, options)
+        .map_err(|e| e.to_string())
+}
+
+/// Set a backend's log level (None to stop overriding it). Returns whether a
+/// connected backend applied it at once.
+#[tauri::command]
+pub async fn set_gateway_backend_log_level(
+    gateway_state: State<'_, Arc<GatewayServerState>>,
+    mcp_id: i64,
+    level: Option<String>,
+) -> Result<bool, String> {
+    info!(
+        "[GatewayCmd] Setting log level of gateway backend {} to {:?}",
+        mcp_id, level
+    );
+    Ok(server::set_backend_log_level(&gateway_state, mcp_id, level).await)
+}
+
+/// Number of tools a backend offers, from its current or last connection
+#[tauri::command]
+pub async fn probe_gateway_tool_count(
+    gateway_state: State<'_, Arc<GatewayServerState>>,
+    mcp_name: String,
+) -> Result<Option<usize>, String> {
+    info!("[GatewayCmd] Probing tool count of {}", mcp_name);
+    let backend_manager = gateway_state.backend_manager.lock().await;
+    Ok(backend_manager.probe_tool_count(&mcp_name))
+}
+
+/// Status and declared capabilities of one backend, connecting it if needed
+#[tauri::command]
+pub async fn get_gateway_backend_info(
+    gateway_state: State<'_, Arc<GatewayServerState>>,
+    backend_id: String,
+) -> Result<BackendInfo, String> {
+    info!(
+        "[GatewayCmd] Getting info of gateway backend {}",
+        backend_id
+    );
+    server::backend_info(&gateway_state, &backend_id)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Ids of the backends connected right now
+#[tauri::command]
+pub async fn list_connected_gateway_backends(
+    gateway_state: State<'_, Arc<GatewayServerState>>,
+) -> Result<Vec<BackendId>, String> {
+    info!("[GatewayCmd] Listing connected gateway backends");
+    Ok(server::list_connected_backends(&gateway_state).await)
+}
+
+/// Every tool exposed through the gateway, for documentation
+#[tauri::command]
+pub async fn export_gateway_catalog(
+    gateway_state: State<'_, Arc<GatewayServerState>>,
+) -> Result<Value, String> {
+    info!("[GatewayCmd] Exporting gateway catalog");
+    Ok(server::export_catalog(&gateway_state).await)
+}
+
+/// Set the workspace roots offered to backends when they connect
+#[tauri::command]
+pub async fn set_gateway_roots(
+    gateway_state: State<'_, Arc<GatewayServerState>>,
+    roots: Vec<String>,
+) -> Result<(), String> {
+    info!("[GatewayCmd] Setting gateway roots: {:?}", roots);
+    server::set_roots(
+        &gateway_state,
+        roots.into_iter().map(PathBuf::from).collect(),
+    )
+    .await;
+    Ok(())
+}
+
+/// Names of connected backends offering a tool, highest priority first
+#[tauri::command]
+pub async fn find_gateway_backends_with_tool(
+    gateway_state: State<'_, Arc<GatewayServerState>>,
+    tool_name: String,
+) -> Result<Vec<String>, String> {
+    info!(
+        "[GatewayCmd] Finding gateway backends with tool {}",
+        tool_name
+    );
+    Ok(server::find_backends_with_tool(&gateway_state, &tool_name).await)
+}
+
+/// Call a tool on whichever connected backend offers it
+#[tauri::command]
+pub async fn invoke_gateway_tool(
+    gateway_state: State<'_, Arc<GatewayServerState>>,
+    tool_name: String,
+    arguments: Value,
+) -> Result<ToolCallResult, String> {
+    info!("[GatewayCmd] Invoking gateway tool {}", tool_name);
+    server::invoke_any(&gateway_state, &tool_name, arguments)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Per-tool invocation counts and last call times
+#[tauri::command]
+pub async fn get_gateway_usage_stats(
+    gateway_state: State<'_, Arc<GatewayServerState>>,
+) -> Result<Vec<ToolUsage>, String> {
+    info!("[GatewayCmd] Getting gateway usage stats");
+    Ok(server::usage_stats(&gateway_state).await)
+}
+
+/// Define (or replace) and persist a tool profile
+#[tauri::command]
+pub async fn define_gateway_profile(
+    gateway_state: State<'_, Arc<GatewayServerState>>,
+    name: String,
+    profile: ToolProfile,
+) -> Result<(), String> {
+    info!("[GatewayCmd] Defining gateway profile {}", name);
+    server::define_profile(&gateway_state, &name, profile)
+        .await
+        .map_err(|e| e.to_string())
+}
@@ -1,3 +1,5 @@
+pub mod agent_files;
+pub mod bundle;
 pub mod claude_json;
 pub mod commands;
 pub mod config;
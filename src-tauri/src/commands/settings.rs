@@ -2,6 +2,7 @@ use crate::db::{
     AppSettings, CodexPaths, CopilotPaths, CursorPaths, Database, EditorInfo, GeminiPaths,
     OpenCodePaths,
 };
+use crate::services::scanner::{recursive_skill_discovery, RECURSIVE_SKILLS_SETTING};
 use crate::utils::codex_paths::{get_codex_paths, is_codex_installed};
 use crate::utils::copilot_paths::{get_copilot_paths, is_copilot_installed};
 use crate::utils::cursor_paths::{get_cursor_paths, is_cursor_installed};
@@ -136,6 +137,7 @@ pub fn toggle_editor(
 
     let settings = AppSettings {
         enabled_editors: editors,
+        ..get_app_settings_from_db(&db)?
     };
     update_app_settings_in_db(&db, &settings)
 }
@@ -255,7 +257,10 @@ pub fn get_github_token_from_db(db: &Database) -> Option<String> {
 /// Get app settings directly from the database
 pub fn get_app_settings_from_db(db: &Database) -> Result<AppSettings, String> {
     let enabled_editors = get_enabled_editors_from_db(db);
-    Ok(AppSettings { enabled_editors })
+    Ok(AppSettings {
+        enabled_editors,
+        recursive_skill_discovery: recursive_skill_discovery(db),
+    })
 }
 
 /// Get list of enabled editors from the database
@@ -269,7 +274,12 @@ pub fn get_enabled_editors_from_db(db: &Database) -> Vec<String> {
 pub fn update_app_settings_in_db(db: &Database, settings: &AppSettings) -> Result<(), String> {
     let json = serde_json::to_string(&settings.enabled_editors).map_err(|e| e.to_string())?;
     db.set_setting("enabled_editors", &json)
-        .map_err(|e| e.to_string())
+        .map_err(|e| e.to_string())?;
+    db.set_setting(
+        RECURSIVE_SKILLS_SETTING,
+        &settings.recursive_skill_discovery.to_string(),
+    )
+    .map_err(|e| e.to_string())
 }
 
 /// Check if a specific editor is enabled
@@ -303,11 +313,13 @@ mod tests {
         // Enable both editors
         let settings = AppSettings {
             enabled_editors: vec!["claude_code".to_string(), "opencode".to_string()],
+            ..Default::default()
         };
         update_app_settings_in_db(&db, &settings).unwrap();
 
         let fetched = get_app_settings_from_db(&db).unwrap();
         assert_eq!(fetched.enabled_editors.len(), 2);
+        assert!(!fetched.recursive_skill_discovery);
         assert!(fetched.enabled_editors.contains(&"claude_code".to_string()));
         assert!(fetched.enabled_editors.contains(&"opencode".to_string()));
     }
@@ -319,6 +331,7 @@ mod tests {
         // Disable all editors
         let settings = AppSettings {
             enabled_editors: vec![],
+            ..Default::default()
         };
         update_app_settings_in_db(&db, &settings).unwrap();
 
@@ -337,6 +350,7 @@ mod tests {
         // Enable opencode
         let settings = AppSettings {
             enabled_editors: vec!["claude_code".to_string(), "opencode".to_string()],
+            ..Default::default()
         };
         update_app_settings_in_db(&db, &settings).unwrap();
 
@@ -348,6 +362,7 @@ mod tests {
     fn test_app_settings_serde() {
         let settings = AppSettings {
             enabled_editors: vec!["claude_code".to_string(), "opencode".to_string()],
+            ..Default::default()
         };
 
         let json = serde_json::to_string(&settings).unwrap();
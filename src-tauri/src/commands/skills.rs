@@ -3,11 +3,16 @@ use crate::db::models::{
     CreateSkillFileRequest, CreateSkillRequest, GlobalSkill, ProjectSkill, Skill, SkillFile,
 };
 use crate::db::schema::Database;
-use crate::services::skill_writer;
+use crate::services::skill_writer::{
+    self, ContentStats, SkillValidation, SkillValidationOptions, VerifyResult,
+    DEFAULT_MAX_SKILL_TOKENS,
+};
+use crate::utils::opencode_paths::get_opencode_paths;
+use crate::utils::paths::get_claude_paths;
 use log::warn;
 use regex::Regex;
 use rusqlite::params;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex};
 use tauri::State;
 
@@ -197,6 +202,10 @@ fn row_to_skill(row: &rusqlite::Row) -> rusqlite::Result<Skill> {
         is_favorite: row.get::<_, i32>(10).unwrap_or(0) != 0,
         created_at: row.get(11)?,
         updated_at: row.get(12)?,
+        when_to_use: None,
+        examples: None,
+        reference_files: Vec::new(),
+        shared_assets: Vec::new(),
     })
 }
 
@@ -215,6 +224,10 @@ fn row_to_skill_with_offset(row: &rusqlite::Row, offset: usize) -> rusqlite::Res
         is_favorite: row.get::<_, i32>(offset + 10).unwrap_or(0) != 0,
         created_at: row.get(offset + 11)?,
         updated_at: row.get(offset + 12)?,
+        when_to_use: None,
+        examples: None,
+        reference_files: Vec::new(),
+        shared_assets: Vec::new(),
     })
 }
 
@@ -813,6 +826,103 @@ pub fn delete_skill_file(db: State<'_, Arc<Mutex<Database>>>, id: i64) -> Result
     Ok(())
 }
 
+// ============================================================================
+// Skill files on disk
+// ============================================================================
+
+/// Base directory of a skill config: the project, or the home directory
+fn skills_base_dir(project_path: Option<String>) -> Result<PathBuf, String> {
+    match project_path {
+        Some(path) => Ok(PathBuf::from(path)),
+        None => get_claude_paths()
+            .map(|paths| paths.home)
+            .map_err(|e| e.to_string()),
+    }
+}
+
+pub(crate) fn load_skill(db: &Database, id: i64) -> Result<Skill, String> {
+    db.get_skill_by_id(id)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Skill {} not found", id))
+}
+
+/// Size of the SKILL.md a skill would be written as
+#[tauri::command]
+pub fn get_skill_content_stats(
+    db: State<'_, Arc<Mutex<Database>>>,
+    id: i64,
+) -> Result<ContentStats, String> {
+    let db = db.lock().map_err(|e| e.to_string())?;
+    Ok(skill_writer::skill_content_stats(&load_skill(&db, id)?))
+}
+
+/// Errors and warnings for a skill before it is written
+#[tauri::command(rename_all = "camelCase")]
+pub fn check_skill(
+    db: State<'_, Arc<Mutex<Database>>>,
+    id: i64,
+    max_estimated_tokens: Option<usize>,
+) -> Result<SkillValidation, String> {
+    let db = db.lock().map_err(|e| e.to_string())?;
+    let options = SkillValidationOptions {
+        max_estimated_tokens: max_estimated_tokens.unwrap_or(DEFAULT_MAX_SKILL_TOKENS),
+    };
+    Ok(skill_writer::validate_skill(
+        &load_skill(&db, id)?,
+        &options,
+    ))
+}
+
+/// Write a skill to a project with the project's baseline tools added to its allowed-tools
+#[tauri::command(rename_all = "camelCase")]
+pub fn write_project_skill_with_tool_policy(
+    db: State<'_, Arc<Mutex<Database>>>,
+    id: i64,
+    project_path: String,
+    baseline_tools: Vec<String>,
+) -> Result<(), String> {
+    let db = db.lock().map_err(|e| e.to_string())?;
+    let mut skill = load_skill(&db, id)?;
+    skill_writer::apply_skill_tool_policy(&mut skill, &baseline_tools);
+    skill_writer::write_project_skill(Path::new(&project_path), &skill).map_err(|e| e.to_string())
+}
+
+/// Check that an installed skill still matches the library
+#[tauri::command(rename_all = "camelCase")]
+pub fn verify_installed_skill(
+    db: State<'_, Arc<Mutex<Database>>>,
+    id: i64,
+    project_path: Option<String>,
+) -> Result<VerifyResult, String> {
+    let db = db.lock().map_err(|e| e.to_string())?;
+    let skill = load_skill(&db, id)?;
+    skill_writer::verify_skill(&skills_base_dir(project_path)?, &skill).map_err(|e| e.to_string())
+}
+
+/// Rename an installed skill's directory and the name in its SKILL.md
+#[tauri::command(rename_all = "camelCase")]
+pub fn rename_installed_skill(
+    project_path: Option<String>,
+    old_name: String,
+    new_name: String,
+) -> Result<(), String> {
+    skill_writer::rename_skill(&skills_base_dir(project_path)?, &old_name, &new_name)
+        .map_err(|e| e.to_string())
+}
+
+/// Move skills written by the legacy OpenCode writer to the current layout.
+/// Returns the migrated names.
+#[tauri::command(rename_all = "camelCase")]
+pub fn migrate_opencode_skills(project_path: Option<String>) -> Result<Vec<String>, String> {
+    let base = match project_path {
+        Some(path) => Path::new(&path).join(".opencode"),
+        None => get_opencode_paths()
+            .map(|paths| paths.config_dir)
+            .map_err(|e| e.to_string())?,
+    };
+    skill_writer::migrate_opencode_skills(&base).map_err(|e| e.to_string())
+}
+
 // ============================================================================
 // Database operations (for testing without Tauri state)
 // ============================================================================
@@ -22,12 +22,18 @@ fn row_to_subagent(row: &rusqlite::Row) -> rusqlite::Result<SubAgent> {
         model: row.get(5)?,
         permission_mode: row.get(6)?,
         skills: parse_json_array(row.get(7)?),
+        color: row.get(16)?,
         tags: parse_json_array(row.get(8)?),
         source: row.get(9)?,
         source_path: row.get(10)?,
         is_favorite: row.get::<_, i32>(11).unwrap_or(0) != 0,
         created_at: row.get(12)?,
         updated_at: row.get(13)?,
+        env: parse_json_array(row.get(17)?),
+        mcp_servers: parse_json_array(row.get(15)?),
+        enabled: row.get::<_, i32>(14).unwrap_or(1) != 0,
+        extends: None,
+        extra: Default::default(),
     })
 }
 
@@ -41,12 +47,18 @@ fn row_to_subagent_with_offset(row: &rusqlite::Row, offset: usize) -> rusqlite::
         model: row.get(offset + 5)?,
         permission_mode: row.get(offset + 6)?,
         skills: parse_json_array(row.get(offset + 7)?),
+        color: row.get(offset + 16)?,
         tags: parse_json_array(row.get(offset + 8)?),
         source: row.get(offset + 9)?,
         source_path: row.get(offset + 10)?,
         is_favorite: row.get::<_, i32>(offset + 11).unwrap_or(0) != 0,
         created_at: row.get(offset + 12)?,
         updated_at: row.get(offset + 13)?,
+        env: parse_json_array(row.get(offset + 17)?),
+        mcp_servers: parse_json_array(row.get(offset + 15)?),
+        enabled: row.get::<_, i32>(offset + 14).unwrap_or(1) != 0,
+        extends: None,
+        extra: Default::default(),
     })
 }
 
@@ -56,7 +68,7 @@ pub fn get_all_subagents(db: State<'_, Arc<Mutex<Database>>>) -> Result<Vec<SubA
     let mut stmt = db
         .conn()
         .prepare(
-            "SELECT id, name, description, content, tools, model, permission_mode, skills, tags, source, source_path, is_favorite, created_at, updated_at
+            "SELECT id, name, description, content, tools, model, permission_mode, skills, tags, source, source_path, is_favorite, created_at, updated_at, enabled, mcp_servers, color, env
              FROM subagents ORDER BY name",
         )
         .map_err(|e| e.to_string())?;
@@ -89,12 +101,16 @@ pub fn create_subagent(
         .tags
         .as_ref()
         .map(|t| serde_json::to_string(t).unwrap());
+    let env_json = subagent
+        .env
+        .as_ref()
+        .map(|t| serde_json::to_string(t).unwrap());
 
     db_guard.conn()
         .execute(
-            "INSERT INTO subagents (name, description, content, tools, model, permission_mode, skills, tags, source)
-             VALUES (?, ?, ?, ?, ?, ?, ?, ?, 'manual')",
-            params![subagent.name, subagent.description, subagent.content, tools_json, subagent.model, subagent.permission_mode, skills_json, tags_json],
+            "INSERT INTO subagents (name, description, content, tools, model, permission_mode, skills, tags, color, env, source)
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, 'manual')",
+            params![subagent.name, subagent.description, subagent.content, tools_json, subagent.model, subagent.permission_mode, skills_json, tags_json, subagent.color, env_json],
         )
         .map_err(|e| e.to_string())?;
 
@@ -103,7 +119,7 @@ pub fn create_subagent(
     let mut stmt = db_guard
         .conn()
         .prepare(
-            "SELECT id, name, description, content, tools, model, permission_mode, skills, tags, source, source_path, is_favorite, created_at, updated_at
+            "SELECT id, name, description, content, tools, model, permission_mode, skills, tags, source, source_path, is_favorite, created_at, updated_at, enabled, mcp_servers, color, env
              FROM subagents WHERE id = ?",
         )
         .map_err(|e| e.to_string())?;
@@ -132,19 +148,23 @@ pub fn update_subagent(
         .tags
         .as_ref()
         .map(|t| serde_json::to_string(t).unwrap());
+    let env_json = subagent
+        .env
+        .as_ref()
+        .map(|t| serde_json::to_string(t).unwrap());
 
     db.conn()
         .execute(
-            "UPDATE subagents SET name = ?, description = ?, content = ?, tools = ?, model = ?, permission_mode = ?, skills = ?, tags = ?, updated_at = CURRENT_TIMESTAMP
+            "UPDATE subagents SET name = ?, description = ?, content = ?, tools = ?, model = ?, permission_mode = ?, skills = ?, tags = ?, color = ?, env = ?, updated_at = CURRENT_TIMESTAMP
              WHERE id = ?",
-            params![subagent.name, subagent.description, subagent.content, tools_json, subagent.model, subagent.permission_mode, skills_json, tags_json, id],
+            params![subagent.name, subagent.description, subagent.content, tools_json, subagent.model, subagent.permission_mode, skills_json, tags_json, subagent.color, env_json, id],
         )
         .map_err(|e| e.to_string())?;
 
     let mut stmt = db
         .conn()
         .prepare(
-            "SELECT id, name, description, content, tools, model, permission_mode, skills, tags, source, source_path, is_favorite, created_at, updated_at
+            "SELECT id, name, description, content, tools, model, permission_mode, skills, tags, source, source_path, is_favorite, created_at, updated_at, enabled, mcp_servers, color, env
              FROM subagents WHERE id = ?",
         )
         .map_err(|e| e.to_string())?;
@@ -181,7 +201,7 @@ pub fn get_global_subagents(
         .conn()
         .prepare(
             "SELECT gs.id, gs.subagent_id, gs.is_enabled,
-                    s.id, s.name, s.description, s.content, s.tools, s.model, s.permission_mode, s.skills, s.tags, s.source, s.source_path, s.is_favorite, s.created_at, s.updated_at
+                    s.id, s.name, s.description, s.content, s.tools, s.model, s.permission_mode, s.skills, s.tags, s.source, s.source_path, s.is_favorite, s.created_at, s.updated_at, s.enabled, s.mcp_servers, s.color, s.env
              FROM global_subagents gs
              JOIN subagents s ON gs.subagent_id = s.id
              ORDER BY s.name",
@@ -213,7 +233,7 @@ pub fn add_global_subagent(
 
     // Get the subagent details for file writing
     let mut stmt = db_guard.conn()
-        .prepare("SELECT id, name, description, content, tools, model, permission_mode, skills, tags, source, source_path, is_favorite, created_at, updated_at FROM subagents WHERE id = ?")
+        .prepare("SELECT id, name, description, content, tools, model, permission_mode, skills, tags, source, source_path, is_favorite, created_at, updated_at, enabled, mcp_servers, color, env FROM subagents WHERE id = ?")
         .map_err(|e| e.to_string())?;
 
     let subagent: SubAgent = stmt
@@ -310,7 +330,7 @@ pub fn toggle_global_subagent(
     // Get the subagent details
     let mut stmt = db_guard.conn()
         .prepare(
-            "SELECT s.id, s.name, s.description, s.content, s.tools, s.model, s.permission_mode, s.skills, s.tags, s.source, s.source_path, s.is_favorite, s.created_at, s.updated_at
+            "SELECT s.id, s.name, s.description, s.content, s.tools, s.model, s.permission_mode, s.skills, s.tags, s.source, s.source_path, s.is_favorite, s.created_at, s.updated_at, s.enabled, s.mcp_servers, s.color, s.env
              FROM global_subagents gs
              JOIN subagents s ON gs.subagent_id = s.id
              WHERE gs.id = ?"
@@ -373,7 +393,7 @@ pub fn assign_subagent_to_project(
         .map_err(|e| e.to_string())?;
 
     let mut stmt = db_guard.conn()
-        .prepare("SELECT id, name, description, content, tools, model, permission_mode, skills, tags, source, source_path, is_favorite, created_at, updated_at FROM subagents WHERE id = ?")
+        .prepare("SELECT id, name, description, content, tools, model, permission_mode, skills, tags, source, source_path, is_favorite, created_at, updated_at, enabled, mcp_servers, color, env FROM subagents WHERE id = ?")
         .map_err(|e| e.to_string())?;
 
     let subagent: SubAgent = stmt
@@ -487,7 +507,7 @@ pub fn toggle_project_subagent(
     // Get project path and subagent details
     let mut stmt = db_guard.conn()
         .prepare(
-            "SELECT p.path, s.id, s.name, s.description, s.content, s.tools, s.model, s.permission_mode, s.skills, s.tags, s.source, s.source_path, s.is_favorite, s.created_at, s.updated_at
+            "SELECT p.path, s.id, s.name, s.description, s.content, s.tools, s.model, s.permission_mode, s.skills, s.tags, s.source, s.source_path, s.is_favorite, s.created_at, s.updated_at, s.enabled, s.mcp_servers, s.color, s.env
              FROM project_subagents ps
              JOIN projects p ON ps.project_id = p.id
              JOIN subagents s ON ps.subagent_id = s.id
@@ -555,7 +575,7 @@ pub fn get_project_subagents(
         .conn()
         .prepare(
             "SELECT ps.id, ps.subagent_id, ps.is_enabled,
-                    s.id, s.name, s.description, s.content, s.tools, s.model, s.permission_mode, s.skills, s.tags, s.source, s.source_path, s.is_favorite, s.created_at, s.updated_at
+                    s.id, s.name, s.description, s.content, s.tools, s.model, s.permission_mode, s.skills, s.tags, s.source, s.source_path, s.is_favorite, s.created_at, s.updated_at, s.enabled, s.mcp_servers, s.color, s.env
              FROM project_subagents ps
              JOIN subagents s ON ps.subagent_id = s.id
              WHERE ps.project_id = ?
@@ -601,12 +621,16 @@ pub fn create_subagent_in_db(
         .tags
         .as_ref()
         .map(|t| serde_json::to_string(t).unwrap());
+    let env_json = subagent
+        .env
+        .as_ref()
+        .map(|t| serde_json::to_string(t).unwrap());
 
     db.conn()
         .execute(
-            "INSERT INTO subagents (name, description, content, tools, model, permission_mode, skills, tags, source)
-             VALUES (?, ?, ?, ?, ?, ?, ?, ?, 'manual')",
-            params![subagent.name, subagent.description, subagent.content, tools_json, subagent.model, subagent.permission_mode, skills_json, tags_json],
+            "INSERT INTO subagents (name, description, content, tools, model, permission_mode, skills, tags, color, env, source)
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, 'manual')",
+            params![subagent.name, subagent.description, subagent.content, tools_json, subagent.model, subagent.permission_mode, skills_json, tags_json, subagent.color, env_json],
         )
         .map_err(|e| e.to_string())?;
 
@@ -620,7 +644,7 @@ pub fn get_subagent_by_id(db: &Database, id: i64) -> Result<SubAgent, String> {
     let mut stmt = db
         .conn()
         .prepare(
-            "SELECT id, name, description, content, tools, model, permission_mode, skills, tags, source, source_path, is_favorite, created_at, updated_at
+            "SELECT id, name, description, content, tools, model, permission_mode, skills, tags, source, source_path, is_favorite, created_at, updated_at, enabled, mcp_servers, color, env
              FROM subagents WHERE id = ?",
         )
         .map_err(|e| e.to_string())?;
@@ -635,7 +659,7 @@ pub fn get_all_subagents_from_db(db: &Database) -> Result<Vec<SubAgent>, String>
     let mut stmt = db
         .conn()
         .prepare(
-            "SELECT id, name, description, content, tools, model, permission_mode, skills, tags, source, source_path, is_favorite, created_at, updated_at
+            "SELECT id, name, description, content, tools, model, permission_mode, skills, tags, source, source_path, is_favorite, created_at, updated_at, enabled, mcp_servers, color, env
              FROM subagents ORDER BY name",
         )
         .map_err(|e| e.to_string())?;
@@ -668,12 +692,16 @@ pub fn update_subagent_in_db(
         .tags
         .as_ref()
         .map(|t| serde_json::to_string(t).unwrap());
+    let env_json = subagent
+        .env
+        .as_ref()
+        .map(|t| serde_json::to_string(t).unwrap());
 
     db.conn()
         .execute(
-            "UPDATE subagents SET name = ?, description = ?, content = ?, tools = ?, model = ?, permission_mode = ?, skills = ?, tags = ?, updated_at = CURRENT_TIMESTAMP
+            "UPDATE subagents SET name = ?, description = ?, content = ?, tools = ?, model = ?, permission_mode = ?, skills = ?, tags = ?, color = ?, env = ?, updated_at = CURRENT_TIMESTAMP
              WHERE id = ?",
-            params![subagent.name, subagent.description, subagent.content, tools_json, subagent.model, subagent.permission_mode, skills_json, tags_json, id],
+            params![subagent.name, subagent.description, subagent.content, tools_json, subagent.model, subagent.permission_mode, skills_json, tags_json, subagent.color, env_json, id],
         )
         .map_err(|e| e.to_string())?;
 
@@ -718,7 +746,9 @@ mod tests {
             model: Some("sonnet".to_string()),
             permission_mode: Some("bypassPermissions".to_string()),
             skills: Some(vec!["lint".to_string(), "format".to_string()]),
+            color: Some("blue".to_string()),
             tags: Some(vec!["review".to_string(), "quality".to_string()]),
+            env: None,
         }
     }
 
@@ -737,7 +767,9 @@ mod tests {
             model: Some("opus".to_string()),
             permission_mode: None,
             skills: None,
+            color: None,
             tags: Some(vec!["testing".to_string()]),
+            env: None,
         }
     }
 
@@ -750,7 +782,9 @@ mod tests {
             model: None,
             permission_mode: None,
             skills: None,
+            color: None,
             tags: None,
+            env: None,
         }
     }
 
@@ -904,6 +938,7 @@ mod tests {
         let db = Database::in_memory().unwrap();
         let req = sample_code_reviewer();
         let created = create_subagent_in_db(&db, &req).unwrap();
+        assert_eq!(created.color, Some("blue".to_string()));
 
         let update_req = CreateSubAgentRequest {
             name: "updated-agent".to_string(),
@@ -913,7 +948,9 @@ mod tests {
             model: Some("haiku".to_string()),
             permission_mode: Some("default".to_string()),
             skills: Some(vec!["new-skill".to_string()]),
+            color: Some("green".to_string()),
             tags: Some(vec!["updated".to_string()]),
+            env: Some(vec!["GITHUB_TOKEN".to_string()]),
         };
 
         let updated = update_subagent_in_db(&db, created.id, &update_req).unwrap();
@@ -925,6 +962,8 @@ mod tests {
         assert_eq!(updated.tools, Some(vec!["Bash".to_string()]));
         assert_eq!(updated.model, Some("haiku".to_string()));
         assert_eq!(updated.permission_mode, Some("default".to_string()));
+        assert_eq!(updated.color, Some("green".to_string()));
+        assert_eq!(updated.env, Some(vec!["GITHUB_TOKEN".to_string()]));
     }
 
     #[test]
@@ -971,6 +1010,47 @@ mod tests {
         assert!(!remaining.iter().any(|s| s.id == s2.id));
     }
 
+    #[test]
+    fn test_update_subagent_keeps_enabled_flag() {
+        let db = Database::in_memory().unwrap();
+        let created = create_subagent_in_db(&db, &sample_minimal_subagent()).unwrap();
+        assert!(created.enabled);
+        db.conn()
+            .execute(
+                "UPDATE subagents SET enabled = 0 WHERE id = ?",
+                [created.id],
+            )
+            .unwrap();
+
+        let mut req = sample_minimal_subagent();
+        req.description = "Edited".to_string();
+        let updated = update_subagent_in_db(&db, created.id, &req).unwrap();
+
+        assert_eq!(updated.description, "Edited");
+        assert!(!updated.enabled);
+        assert!(!db.get_subagent_by_id(created.id).unwrap().unwrap().enabled);
+    }
+
+    // ========================================================================
+    // Writing DB-loaded subagents tests
+    // ========================================================================
+
+    #[test]
+    fn test_writing_db_subagent_keeps_sidecar() {
+        let db = Database::in_memory().unwrap();
+        let project = tempfile::TempDir::new().unwrap();
+        let agents_dir = project.path().join(".claude/agents");
+        std::fs::create_dir_all(&agents_dir).unwrap();
+        std::fs::write(agents_dir.join("minimal.meta.json"), "{\"retries\": 3}").unwrap();
+
+        let created = create_subagent_in_db(&db, &sample_minimal_subagent()).unwrap();
+        let subagent = get_subagent_by_id(&db, created.id).unwrap();
+        subagent_writer::write_project_subagent(project.path(), &subagent).unwrap();
+
+        let extra = subagent_writer::read_sidecar(&agents_dir.join("minimal.md")).unwrap();
+        assert_eq!(extra["retries"], serde_json::json!(3));
+    }
+
     // ========================================================================
     // parse_json_array tests
     // ========================================================================
@@ -177,6 +177,20 @@ pub struct Skill {
     pub is_favorite: bool,
     pub created_at: String,
     pub updated_at: String,
+    /// When the skill applies (`when-to-use` block scalar). Not stored in the database.
+    #[serde(default)]
+    pub when_to_use: Option<String>,
+    /// Example requests that should trigger the skill. Not stored in the database.
+    #[serde(default)]
+    pub examples: Option<Vec<String>>,
+    /// Deeper reference files as (relative path, content), written next to
+    /// SKILL.md and linked from it. Not stored in the database.
+    #[serde(default)]
+    pub reference_files: Vec<(String, String)>,
+    /// Assets shared with other skills as (path under `skills/_shared/`, content),
+    /// written there once and linked from SKILL.md. Not stored in the database.
+    #[serde(default)]
+    pub shared_assets: Vec<(String, String)>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -243,12 +257,65 @@ pub struct SubAgent {
     pub model: Option<String>,
     pub permission_mode: Option<String>,
     pub skills: Option<Vec<String>>,
+    /// UI color Claude Code shows for the agent (frontmatter only)
+    #[serde(default)]
+    pub color: Option<String>,
     pub tags: Option<Vec<String>>,
+    /// Environment variables the agent expects (frontmatter metadata only, never set)
+    #[serde(default)]
+    pub env: Option<Vec<String>>,
+    /// MCP servers the agent may use (frontmatter only, not stored in the database)
+    #[serde(default)]
+    pub mcp_servers: Option<Vec<String>>,
     pub source: String,
     pub source_path: Option<String>,
     pub is_favorite: bool,
     pub created_at: String,
     pub updated_at: String,
+    /// False writes `disabled: true` to the frontmatter so Claude skips the
+    /// agent (not stored in the database)
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    /// Base agent whose content, tools and skills this agent inherits
+    /// (resolved by expand_inheritance, not stored in the database)
+    #[serde(default)]
+    pub extends: Option<String>,
+    /// Rich fields from the `{name}.meta.json` sidecar next to the agent file
+    /// (not stored in the database)
+    #[serde(default)]
+    pub extra: HashMap<String, serde_json::Value>,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+/// Manual agent with only the required fields set; tests override the fields
+/// they care about with struct update syntax
+#[cfg(test)]
+pub(crate) fn sample_subagent() -> SubAgent {
+    SubAgent {
+        id: 1,
+        name: "reviewer".to_string(),
+        description: "Reviews code".to_string(),
+        content: String::new(),
+        tools: None,
+        model: None,
+        permission_mode: None,
+        skills: None,
+        color: None,
+        tags: None,
+        env: None,
+        mcp_servers: None,
+        source: "manual".to_string(),
+        source_path: None,
+        is_favorite: false,
+        created_at: "2024-01-01".to_string(),
+        updated_at: "2024-01-01".to_string(),
+        enabled: true,
+        extends: None,
+        extra: Default::default(),
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -261,7 +328,13 @@ pub struct CreateSubAgentRequest {
     pub model: Option<String>,
     pub permission_mode: Option<String>,
     pub skills: Option<Vec<String>>,
+    /// UI color Claude Code shows for the agent
+    #[serde(default)]
+    pub color: Option<String>,
     pub tags: Option<Vec<String>>,
+    /// Environment variables the agent expects
+    #[serde(default)]
+    pub env: Option<Vec<String>>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -418,6 +491,10 @@ pub struct AppSettings {
     /// List of enabled editor IDs (e.g., ["claude_code", "opencode"])
     /// When an editor is enabled, skills/commands/subagents/MCPs sync to it
     pub enabled_editors: Vec<String>,
+    /// Also import skills nested under category folders
+    /// (`skills/web/fetch/SKILL.md`); off by default
+    #[serde(default)]
+    pub recursive_skill_discovery: bool,
 }
 
 impl Default for AppSettings {
@@ -425,6 +502,7 @@ impl Default for AppSettings {
         Self {
             // By default, only Claude Code is enabled
             enabled_editors: vec!["claude_code".to_string()],
+            recursive_skill_discovery: false,
         }
     }
 }
@@ -854,6 +932,10 @@ mod tests {
             is_favorite: false,
             created_at: "2024-01-01".to_string(),
             updated_at: "2024-01-01".to_string(),
+            when_to_use: None,
+            examples: None,
+            reference_files: Vec::new(),
+            shared_assets: Vec::new(),
         };
 
         let json = serde_json::to_string(&skill).unwrap();
@@ -898,12 +980,18 @@ mod tests {
             model: Some("opus".to_string()),
             permission_mode: Some("bypassPermissions".to_string()),
             skills: Some(vec!["lint".to_string()]),
+            color: Some("blue".to_string()),
             tags: Some(vec!["review".to_string()]),
             source: "manual".to_string(),
             source_path: None,
             is_favorite: false,
             created_at: "2024-01-01".to_string(),
             updated_at: "2024-01-01".to_string(),
+            env: None,
+            mcp_servers: None,
+            enabled: true,
+            extends: None,
+            extra: Default::default(),
         };
 
         let json = serde_json::to_string(&agent).unwrap();
@@ -1098,6 +1186,7 @@ mod tests {
     fn test_app_settings_serde() {
         let settings = AppSettings {
             enabled_editors: vec!["claude_code".to_string(), "opencode".to_string()],
+            ..Default::default()
         };
 
         let json = serde_json::to_string(&settings).unwrap();
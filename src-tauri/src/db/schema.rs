@@ -686,6 +686,68 @@ impl Database {
             )?;
         }
 
+        // Migration 14: Add enabled column to subagents table
+        let has_subagents_enabled: bool = self
+            .conn
+            .query_row(
+                "SELECT COUNT(*) > 0 FROM pragma_table_info('subagents') WHERE name = 'enabled'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap_or(false);
+
+        if !has_subagents_enabled {
+            self.conn.execute(
+                "ALTER TABLE subagents ADD COLUMN enabled INTEGER DEFAULT 1",
+                [],
+            )?;
+        }
+
+        // Migration 15: Add mcp_servers column to subagents table
+        let has_subagents_mcp_servers: bool = self
+            .conn
+            .query_row(
+                "SELECT COUNT(*) > 0 FROM pragma_table_info('subagents') WHERE name = 'mcp_servers'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap_or(false);
+
+        if !has_subagents_mcp_servers {
+            self.conn
+                .execute("ALTER TABLE subagents ADD COLUMN mcp_servers TEXT", [])?;
+        }
+
+        // Migration 16: Add color column to subagents table
+        let has_subagents_color: bool = self
+            .conn
+            .query_row(
+                "SELECT COUNT(*) > 0 FROM pragma_table_info('subagents') WHERE name = 'color'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap_or(false);
+
+        if !has_subagents_color {
+            self.conn
+                .execute("ALTER TABLE subagents ADD COLUMN color TEXT", [])?;
+        }
+
+        // Migration 17: Add env column to subagents table
+        let has_subagents_env: bool = self
+            .conn
+            .query_row(
+                "SELECT COUNT(*) > 0 FROM pragma_table_info('subagents') WHERE name = 'env'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap_or(false);
+
+        if !has_subagents_env {
+            self.conn
+                .execute("ALTER TABLE subagents ADD COLUMN env TEXT", [])?;
+        }
+
         Ok(())
     }
 
@@ -1254,6 +1316,10 @@ impl Database {
                     is_favorite: row.get::<_, i32>(10)? != 0,
                     created_at: row.get(11)?,
                     updated_at: row.get(12)?,
+                    when_to_use: None,
+                    examples: None,
+                    reference_files: Vec::new(),
+                    shared_assets: Vec::new(),
                 })
             })?
             .filter_map(|r| r.ok())
@@ -1282,6 +1348,10 @@ impl Database {
                     is_favorite: row.get::<_, i32>(10)? != 0,
                     created_at: row.get(11)?,
                     updated_at: row.get(12)?,
+                    when_to_use: None,
+                    examples: None,
+                    reference_files: Vec::new(),
+                    shared_assets: Vec::new(),
                 })
             },
         );
@@ -1343,7 +1413,7 @@ impl Database {
 
     pub fn get_all_subagents(&self) -> Result<Vec<crate::db::models::SubAgent>> {
         let mut stmt = self.conn.prepare(
-            "SELECT id, name, description, content, tools, model, permission_mode, skills, tags, source, source_path, is_favorite, created_at, updated_at
+            "SELECT id, name, description, content, tools, model, permission_mode, skills, tags, source, source_path, is_favorite, created_at, updated_at, enabled, mcp_servers, color, env
              FROM subagents ORDER BY name"
         )?;
 
@@ -1362,6 +1432,7 @@ impl Database {
                     skills: row
                         .get::<_, Option<String>>(7)?
                         .and_then(|s| serde_json::from_str(&s).ok()),
+                    color: row.get(16)?,
                     tags: row
                         .get::<_, Option<String>>(8)?
                         .and_then(|s| serde_json::from_str(&s).ok()),
@@ -1370,6 +1441,15 @@ impl Database {
                     is_favorite: row.get::<_, i32>(11)? != 0,
                     created_at: row.get(12)?,
                     updated_at: row.get(13)?,
+                    env: row
+                        .get::<_, Option<String>>(17)?
+                        .and_then(|s| serde_json::from_str(&s).ok()),
+                    mcp_servers: row
+                        .get::<_, Option<String>>(15)?
+                        .and_then(|s| serde_json::from_str(&s).ok()),
+                    enabled: row.get::<_, i32>(14).unwrap_or(1) != 0,
+                    extends: None,
+                    extra: Default::default(),
                 })
             })?
             .filter_map(|r| r.ok())
@@ -1380,7 +1460,7 @@ impl Database {
 
     pub fn get_subagent_by_id(&self, id: i64) -> Result<Option<crate::db::models::SubAgent>> {
         let result = self.conn.query_row(
-            "SELECT id, name, description, content, tools, model, permission_mode, skills, tags, source, source_path, is_favorite, created_at, updated_at
+            "SELECT id, name, description, content, tools, model, permission_mode, skills, tags, source, source_path, is_favorite, created_at, updated_at, enabled, mcp_servers, color, env
              FROM subagents WHERE id = ?",
             [id],
             |row| {
@@ -1393,12 +1473,22 @@ impl Database {
                     model: row.get(5)?,
                     permission_mode: row.get(6)?,
                     skills: row.get::<_, Option<String>>(7)?.and_then(|s| serde_json::from_str(&s).ok()),
+                    color: row.get(16)?,
                     tags: row.get::<_, Option<String>>(8)?.and_then(|s| serde_json::from_str(&s).ok()),
                     source: row.get(9)?,
                     source_path: row.get(10)?,
                     is_favorite: row.get::<_, i32>(11)? != 0,
                     created_at: row.get(12)?,
                     updated_at: row.get(13)?,
+                    env: row
+                        .get::<_, Option<String>>(17)?
+                        .and_then(|s| serde_json::from_str(&s).ok()),
+                    mcp_servers: row
+                        .get::<_, Option<String>>(15)?
+                        .and_then(|s| serde_json::from_str(&s).ok()),
+                    enabled: row.get::<_, i32>(14).unwrap_or(1) != 0,
+                    extends: None,
+                    extra: Default::default(),
                 })
             },
         );
@@ -1423,13 +1513,14 @@ impl Database {
             .as_ref()
             .map(|s| serde_json::to_string(s).unwrap());
         let tags_json = req.tags.as_ref().map(|t| serde_json::to_string(t).unwrap());
+        let env_json = req.env.as_ref().map(|e| serde_json::to_string(e).unwrap());
 
         self.conn.execute(
-            "INSERT INTO subagents (name, description, content, tools, model, permission_mode, skills, tags, source)
-             VALUES (?, ?, ?, ?, ?, ?, ?, ?, 'manual')",
+            "INSERT INTO subagents (name, description, content, tools, model, permission_mode, skills, tags, color, env, source)
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, 'manual')",
             rusqlite::params![
                 req.name, req.description, req.content, tools_json,
-                req.model, req.permission_mode, skills_json, tags_json
+                req.model, req.permission_mode, skills_json, tags_json, req.color, env_json
             ],
         )?;
 
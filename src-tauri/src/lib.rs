@@ -88,7 +88,39 @@ pub fn run() {
                 let auto_start = db.get_setting("gateway_auto_start")
                     .and_then(|s| s.parse().ok())
                     .unwrap_or(false);
-                GatewayServerConfig { enabled, port, auto_start }
+                let allow_backend_registration = db.get_setting("gateway_allow_backend_registration")
+                    .and_then(|s| s.parse().ok())
+                    .unwrap_or(false);
+                let defaults = GatewayServerConfig::default();
+                let audit_log_path = db.get_setting("gateway_audit_log_path")
+                    .unwrap_or(defaults.audit_log_path);
+                let result_cache_ttl_secs = db.get_setting("gateway_result_cache_ttl_secs")
+                    .and_then(|s| s.parse().ok())
+                    .unwrap_or(defaults.result_cache_ttl_secs);
+                let idle_timeout_secs = db.get_setting("gateway_idle_timeout_secs")
+                    .and_then(|s| s.parse().ok())
+                    .unwrap_or(defaults.idle_timeout_secs);
+                let circuit_breaker_threshold = db.get_setting("gateway_circuit_breaker_threshold")
+                    .and_then(|s| s.parse().ok())
+                    .unwrap_or(defaults.circuit_breaker_threshold);
+                let circuit_breaker_cooldown_secs = db.get_setting("gateway_circuit_breaker_cooldown_secs")
+                    .and_then(|s| s.parse().ok())
+                    .unwrap_or(defaults.circuit_breaker_cooldown_secs);
+                let http_pool_size = db.get_setting("gateway_http_pool_size")
+                    .and_then(|s| s.parse().ok())
+                    .unwrap_or(defaults.http_pool_size);
+                GatewayServerConfig {
+                    enabled,
+                    port,
+                    auto_start,
+                    allow_backend_registration,
+                    audit_log_path,
+                    result_cache_ttl_secs,
+                    idle_timeout_secs,
+                    circuit_breaker_threshold,
+                    circuit_breaker_cooldown_secs,
+                    http_pool_size,
+                }
             };
 
             let gateway_state = Arc::new(GatewayServerState::with_config(gateway_config.clone(), database_arc.clone()));
@@ -226,6 +258,9 @@ pub fn run() {
             commands::config::get_claude_paths,
             commands::config::open_config_file,
             commands::config::backup_configs,
+            commands::config::audit_claude_config,
+            commands::config::get_config_usage,
+            commands::config::discover_claude_projects,
             // Scanner Commands
             commands::scanner::scan_claude_directory,
             // Claude.json Commands
@@ -254,6 +289,12 @@ pub fn run() {
             commands::skills::update_skill_file,
             commands::skills::delete_skill_file,
             commands::skills::toggle_skill_favorite,
+            commands::skills::get_skill_content_stats,
+            commands::skills::check_skill,
+            commands::skills::write_project_skill_with_tool_policy,
+            commands::skills::verify_installed_skill,
+            commands::skills::rename_installed_skill,
+            commands::skills::migrate_opencode_skills,
             // Slash Command Commands
             commands::commands::get_all_commands,
             commands::commands::create_command,
@@ -282,6 +323,41 @@ pub fn run() {
             commands::subagents::toggle_project_subagent,
             commands::subagents::get_project_subagents,
             commands::subagents::toggle_subagent_favorite,
+            // Sub-Agent File Commands
+            commands::agent_files::list_subagent_files,
+            commands::agent_files::list_subagent_file_names,
+            commands::agent_files::read_subagent_file,
+            commands::agent_files::repair_subagent_frontmatter,
+            commands::agent_files::parse_subagent_text,
+            commands::agent_files::parse_subagent_texts,
+            commands::agent_files::parse_subagent_json,
+            commands::agent_files::generate_subagent_text,
+            commands::agent_files::write_subagent_to_config,
+            commands::agent_files::write_subagent_with_options,
+            commands::agent_files::upsert_subagent_file,
+            commands::agent_files::get_subagent_canonical_hash,
+            commands::agent_files::sync_subagent_files,
+            commands::agent_files::write_project_subagent_with_default_model,
+            commands::agent_files::enable_agents_in_project_settings,
+            commands::agent_files::write_project_agents_md,
+            commands::agent_files::export_subagents_flat,
+            commands::agent_files::trash_subagent_file,
+            commands::agent_files::restore_subagent_file,
+            commands::agent_files::purge_subagent_trash,
+            commands::agent_files::import_subagents_from_directory,
+            commands::agent_files::import_changed_subagents,
+            commands::agent_files::group_subagent_files,
+            commands::agent_files::lint_subagent,
+            commands::agent_files::diff_library_subagents,
+            commands::agent_files::expand_subagent_inheritance,
+            commands::agent_files::format_subagents_table,
+            commands::agent_files::get_subagent_compatibility,
+            commands::agent_files::convert_subagent_directory,
+            commands::agent_files::get_subagent_shadow_warning,
+            commands::agent_files::get_effective_agent_tools,
+            // Bundle Commands
+            commands::bundle::export_bundle,
+            commands::bundle::export_team_pack,
             // Hook Commands
             commands::hooks::get_all_hooks,
             commands::hooks::get_hook_templates,
@@ -413,6 +489,18 @@ pub fn run() {
             commands::mcp_gateway::is_mcp_in_gateway,
             commands::mcp_gateway::get_gateway_backends,
             commands::mcp_gateway::restart_gateway_backend,
+            commands::mcp_gateway::get_gateway_backend_options,
+            commands::mcp_gateway::set_gateway_backend_options,
+            commands::mcp_gateway::set_gateway_backend_log_level,
+            commands::mcp_gateway::probe_gateway_tool_count,
+            commands::mcp_gateway::get_gateway_backend_info,
+            commands::mcp_gateway::list_connected_gateway_backends,
+            commands::mcp_gateway::export_gateway_catalog,
+            commands::mcp_gateway::set_gateway_roots,
+            commands::mcp_gateway::find_gateway_backends_with_tool,
+            commands::mcp_gateway::invoke_gateway_tool,
+            commands::mcp_gateway::get_gateway_usage_stats,
+            commands::mcp_gateway::define_gateway_profile,
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
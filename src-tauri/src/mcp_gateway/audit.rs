@@ -0,0 +1,200 @@
+//! Gateway Tool-Call Audit Log
+//!
+//! Append-only JSONL record of every tool invoked through the gateway.
+//! Arguments are stored as a SHA-256 hash, never raw, so the log can be kept
+//! for compliance without leaking secrets passed to tools.
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::PathBuf;
+
+/// Default size cap before the audit log is rotated (10 MB)
+pub const DEFAULT_AUDIT_MAX_BYTES: u64 = 10 * 1024 * 1024;
+
+/// Audit log configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AuditLogConfig {
+    pub path: PathBuf,
+    /// Rotate to `{path}.1` once the log reaches this many bytes
+    pub max_bytes: u64,
+}
+
+impl AuditLogConfig {
+    pub fn new(path: PathBuf) -> Self {
+        Self {
+            path,
+            max_bytes: DEFAULT_AUDIT_MAX_BYTES,
+        }
+    }
+}
+
+/// Outcome of an audited tool call
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum AuditOutcome {
+    Success,
+    /// The backend returned a result flagged as an error
+    ToolError,
+    /// The call could not be completed
+    Failed(String),
+}
+
+/// A single line in the audit log
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AuditEntry {
+    pub timestamp: String,
+    pub mcp_id: i64,
+    pub mcp_name: String,
+    pub tool_name: String,
+    pub args_hash: String,
+    pub outcome: AuditOutcome,
+}
+
+impl AuditEntry {
+    pub fn new(
+        mcp_id: i64,
+        mcp_name: &str,
+        tool_name: &str,
+        arguments: &serde_json::Value,
+        outcome: AuditOutcome,
+    ) -> Self {
+        Self {
+            timestamp: chrono::Utc::now().to_rfc3339(),
+            mcp_id,
+            mcp_name: mcp_name.to_string(),
+            tool_name: tool_name.to_string(),
+            args_hash: hash_arguments(arguments),
+            outcome,
+        }
+    }
+}
+
+/// Hash tool arguments so the log records identity, not content
+pub fn hash_arguments(arguments: &serde_json::Value) -> String {
+    let serialized = serde_json::to_string(arguments).unwrap_or_default();
+    format!("{:x}", Sha256::digest(serialized.as_bytes()))
+}
+
+/// Append-only JSONL audit log with size-based rotation
+#[derive(Debug, Clone)]
+pub struct AuditLog {
+    config: AuditLogConfig,
+}
+
+impl AuditLog {
+    pub fn new(config: AuditLogConfig) -> Self {
+        Self { config }
+    }
+
+    /// Path of the rotated log file
+    pub fn rotated_path(&self) -> PathBuf {
+        let mut name = self.config.path.as_os_str().to_owned();
+        name.push(".1");
+        PathBuf::from(name)
+    }
+
+    /// Append an entry, rotating first if the log has reached its size cap
+    pub fn record(&self, entry: &AuditEntry) -> Result<()> {
+        if let Some(parent) = self.config.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        self.rotate_if_needed()?;
+
+        let mut line = serde_json::to_string(entry)?;
+        line.push('\n');
+
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.config.path)?;
+        file.write_all(line.as_bytes())?;
+
+        Ok(())
+    }
+
+    fn rotate_if_needed(&self) -> Result<()> {
+        let size = match std::fs::metadata(&self.config.path) {
+            Ok(metadata) => metadata.len(),
+            Err(_) => return Ok(()),
+        };
+
+        if size >= self.config.max_bytes {
+            std::fs::rename(&self.config.path, self.rotated_path())?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use tempfile::TempDir;
+
+    fn entry(tool_name: &str) -> AuditEntry {
+        AuditEntry::new(
+            1,
+            "filesystem",
+            tool_name,
+            &json!({"path": "/tmp/secret"}),
+            AuditOutcome::Success,
+        )
+    }
+
+    #[test]
+    fn test_hash_arguments_is_stable_and_hides_content() {
+        let args = json!({"token": "abc123"});
+        let hash = hash_arguments(&args);
+
+        assert_eq!(hash, hash_arguments(&args));
+        assert_eq!(hash.len(), 64);
+        assert!(!hash.contains("abc123"));
+        assert_ne!(hash, hash_arguments(&json!({"token": "other"})));
+    }
+
+    #[test]
+    fn test_record_appends_lines() {
+        let temp_dir = TempDir::new().unwrap();
+        let log = AuditLog::new(AuditLogConfig::new(temp_dir.path().join("audit.jsonl")));
+
+        log.record(&entry("read_file")).unwrap();
+        log.record(&entry("write_file")).unwrap();
+
+        let content = std::fs::read_to_string(temp_dir.path().join("audit.jsonl")).unwrap();
+        let lines: Vec<&str> = content.lines().collect();
+        assert_eq!(lines.len(), 2);
+
+        let first: AuditEntry = serde_json::from_str(lines[0]).unwrap();
+        assert_eq!(first.tool_name, "read_file");
+        assert!(!lines[0].contains("/tmp/secret"));
+    }
+
+    #[test]
+    fn test_record_rotates_past_size_cap() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("audit.jsonl");
+        let log = AuditLog::new(AuditLogConfig {
+            path: path.clone(),
+            max_bytes: 10,
+        });
+
+        log.record(&entry("read_file")).unwrap();
+        assert!(!log.rotated_path().exists());
+
+        log.record(&entry("write_file")).unwrap();
+        assert!(log.rotated_path().exists());
+
+        let rotated = std::fs::read_to_string(log.rotated_path()).unwrap();
+        assert!(rotated.contains("read_file"));
+        let current = std::fs::read_to_string(&path).unwrap();
+        assert_eq!(current.lines().count(), 1);
+        assert!(current.contains("write_file"));
+    }
+}
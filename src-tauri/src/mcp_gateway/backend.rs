@@ -2,14 +2,33 @@
 //!
 //! Manages connections to backend MCP servers and aggregates their tools.
 
-use crate::db::models::{GatewayMcp, Mcp};
+use crate::db::models::Mcp;
 use crate::db::Database;
-use crate::services::mcp_client::{McpServerInfo, McpTool, StdioMcpClient, ToolCallResult};
+use crate::mcp_gateway::audit::{AuditEntry, AuditLog, AuditLogConfig, AuditOutcome};
+use crate::mcp_gateway::breaker::{CircuitBreaker, CircuitBreakerConfig};
+use crate::mcp_gateway::cache::ResultCache;
+use crate::mcp_gateway::error::{gateway_error, GatewayErrorCode};
+use crate::mcp_gateway::transform::ArgTransform;
+use crate::mcp_gateway::usage::{ToolUsage, UsageTracker};
+use crate::services::http_pool::{http_client_pool, HttpClientPool, PoolConfig};
+use crate::services::json_schema::{validate_json, SchemaChecks};
+use crate::services::mcp_client::{
+    CancellationToken, HttpMcpClient, McpPrompt, McpResource, McpServerCapabilities, McpServerInfo,
+    McpTool, RefreshConfig, RequestCancelled, SamplingHandler, StdioMcpClient, ToolCallResult,
+};
 use anyhow::{anyhow, Result};
 use log::{error, info, warn};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// How often the idle sweeper checks for backends to evict
+pub const IDLE_SWEEP_INTERVAL: Duration = Duration::from_secs(30);
+
+/// How often the keepalive task checks for HTTP backends due a ping
+pub const KEEPALIVE_CHECK_INTERVAL: Duration = Duration::from_secs(5);
 
 /// Status of a backend MCP connection
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -32,6 +51,9 @@ pub struct BackendInfo {
     pub status: BackendStatus,
     pub tool_count: usize,
     pub server_info: Option<McpServerInfo>,
+    /// Capabilities declared in the initialize handshake (None until connected)
+    #[serde(default)]
+    pub capabilities: Option<McpServerCapabilities>,
     pub error_message: Option<String>,
     pub restart_count: u32,
 }
@@ -45,8 +67,266 @@ pub struct AvailableMcp {
     pub description: Option<String>,
     pub mcp_type: String,
     pub status: BackendStatus,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub alias: Option<String>,
+}
+
+/// Retry policy for background connection attempts (exponential backoff)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct RetryPolicy {
+    /// Total attempts, including the first one
+    pub max_attempts: u32,
+    pub initial_backoff_ms: u64,
+    pub max_backoff_ms: u64,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            initial_backoff_ms: 500,
+            max_backoff_ms: 10_000,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Delay before the attempt following failed attempt number `attempt` (1-based)
+    pub fn backoff_for_attempt(&self, attempt: u32) -> Duration {
+        let factor = 2u64.saturating_pow(attempt.saturating_sub(1));
+        Duration::from_millis(
+            self.initial_backoff_ms
+                .saturating_mul(factor)
+                .min(self.max_backoff_ms),
+        )
+    }
+}
+
+/// Per-backend gateway options (not stored in the MCP config itself)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct BackendOptions {
+    /// Higher priority backends rank first when search results tie
+    pub priority: i32,
+    /// Connect in the background after the gateway starts instead of on first use
+    pub warmup: bool,
+    /// Retry policy for background connection attempts
+    pub retry: RetryPolicy,
+    /// Cache results of every tool on this backend (tools must be pure)
+    pub cacheable: bool,
+    /// Individual tools whose results may be cached
+    pub cacheable_tools: Vec<String>,
+    /// Re-fetch the access token when an HTTP backend answers 401
+    pub token_refresh: Option<RefreshConfig>,
+    /// Only these tools are listed and callable (empty allows every tool)
+    pub allowed_tools: Vec<String>,
+    /// Tools that are never listed or callable (takes precedence over allowed_tools)
+    pub denied_tools: Vec<String>,
+    /// Argument key renames applied to outgoing calls (inverted for results)
+    pub arg_transform: ArgTransform,
+    /// Check arguments against the tool's input schema before forwarding a call
+    pub validate_arguments: bool,
+    /// Friendly name accepted wherever the MCP name is, and used to namespace
+    /// the backend's tools. Must not clash with another backend's name or alias.
+    pub alias: Option<String>,
+    /// MCP ids tried in order when a call to this backend fails or it is
+    /// quarantined. A fallback is only used for tools it also offers.
+    pub fallbacks: Vec<i64>,
+    /// Truncate tool descriptions in list_tools/search_tools to this many
+    /// characters (describe_tool always returns the full description)
+    pub description_max_chars: Option<usize>,
+    /// Extra parameters merged into the backend's initialize request
+    /// (e.g. a workspace path or API base). Must be a JSON object.
+    #[serde(deserialize_with = "deserialize_init_params")]
+    pub init_params: Option<serde_json::Value>,
+    /// Ping an HTTP backend after this many seconds without a call, so the
+    /// server doesn't drop its idle session. Ignored for other transports and
+    /// when idle eviction (set_idle_timeout) would disconnect the backend first.
+    pub keepalive_interval_secs: Option<u64>,
+    /// Minimum level of the backend's own logging, RUST_LOG-style (e.g.
+    /// "warn"). Stdio backends get it as RUST_LOG; backends declaring the
+    /// logging capability are also sent logging/setLevel after connecting.
+    pub log_level: Option<String>,
+    /// Workspace roots offered to the backend when it connects. Not configured
+    /// per backend: filled in from the gateway's roots (see set_roots).
+    #[serde(skip)]
+    pub roots: Vec<PathBuf>,
+    /// Answers the backend's sampling requests. Filled in from the gateway's
+    /// handler (see set_sampling_handler); backends' requests are refused if None.
+    #[serde(skip)]
+    pub sampling: Option<SamplingHandler>,
+    /// Connection pool of an HTTP backend, kept by the gateway across
+    /// reconnects (see set_http_pool_config). None for other transports.
+    #[serde(skip)]
+    pub http_pool: Option<Arc<HttpClientPool>>,
+}
+
+/// Reject init params that are not a JSON object when options are loaded
+fn deserialize_init_params<'de, D>(deserializer: D) -> Result<Option<serde_json::Value>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let value = Option::<serde_json::Value>::deserialize(deserializer)?;
+    check_init_params(value.as_ref()).map_err(serde::de::Error::custom)?;
+    Ok(value)
+}
+
+fn check_init_params(init_params: Option<&serde_json::Value>) -> Result<()> {
+    match init_params {
+        Some(value) if !value.is_object() => {
+            Err(anyhow!("initParams must be a JSON object, got {}", value))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Reject a registered stdio command that isn't a program name or absolute
+/// path: shell syntax, control characters and relative paths (which would
+/// resolve against the gateway's working directory) are refused.
+fn check_registered_command(name: &str, command: &str) -> Result<()> {
+    let program = command.split_whitespace().next().unwrap_or_default();
+    if command
+        .chars()
+        .any(|c| c.is_control() || ";&|`$<>".contains(c))
+    {
+        return Err(anyhow!(
+            "Backend '{}' command must not contain shell syntax or control characters",
+            name
+        ));
+    }
+    let has_separator = program.contains('/') || program.contains('\\');
+    if has_separator && !Path::new(program).is_absolute() {
+        return Err(anyhow!(
+            "Backend '{}' command must be a program name or an absolute path, got '{}'",
+            name,
+            program
+        ));
+    }
+    Ok(())
+}
+
+impl BackendOptions {
+    /// Whether `tool_name` passes this backend's allow/deny lists
+    pub fn is_tool_allowed(&self, tool_name: &str) -> bool {
+        !self.denied_tools.iter().any(|t| t == tool_name)
+            && (self.allowed_tools.is_empty() || self.allowed_tools.iter().any(|t| t == tool_name))
+    }
+
+    /// Whether results of `tool_name` on this backend may be served from cache
+    pub fn is_cacheable(&self, tool_name: &str) -> bool {
+        self.cacheable || self.cacheable_tools.iter().any(|t| t == tool_name)
+    }
+
+    /// Shorten a tool description to description_max_chars, ending with an ellipsis
+    pub fn listed_description(&self, description: Option<&String>) -> Option<String> {
+        let description = description?;
+        match self.description_max_chars {
+            Some(max) if description.chars().count() > max => {
+                let mut truncated: String =
+                    description.chars().take(max.saturating_sub(1)).collect();
+                truncated.push('…');
+                Some(truncated)
+            }
+            _ => Some(description.clone()),
+        }
+    }
+}
+
+/// Setting holding the tool profiles, as a JSON object keyed by profile name
+pub const TOOL_PROFILES_SETTING: &str = "gateway_tool_profiles";
+
+/// Setting holding the options of database backends, as a JSON object keyed by MCP id
+pub const BACKEND_OPTIONS_SETTING: &str = "gateway_backend_options";
+
+/// A named subset of backends and tools, exposed in place of the full catalog
+/// while a session uses the profile (see lock_for_session)
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ToolProfile {
+    /// Backends (by name or alias) whose allowed tools are all exposed
+    pub backends: Vec<String>,
+    /// Individual tools exposed, as `backend/tool` (backend by name or alias)
+    pub tools: Vec<String>,
+}
+
+impl ToolProfile {
+    /// Whether the profile exposes any tool of a backend known by `names`
+    fn exposes_backend(&self, names: &[&str]) -> bool {
+        self.backends.iter().any(|b| names.contains(&b.as_str()))
+            || self
+                .tools
+                .iter()
+                .any(|t| t.split_once('/').is_some_and(|(b, _)| names.contains(&b)))
+    }
+
+    /// Whether the profile exposes `tool_name` of a backend known by `names`
+    fn exposes_tool(&self, names: &[&str], tool_name: &str) -> bool {
+        self.backends.iter().any(|b| names.contains(&b.as_str()))
+            || self.tools.iter().any(|t| {
+                t.split_once('/')
+                    .is_some_and(|(b, tool)| names.contains(&b) && tool == tool_name)
+            })
+    }
+}
+
+/// Configuration for a backend registered at runtime via register_backend.
+/// Runtime backends live only in memory and are not written to the database.
+#[derive(Debug, Clone, Deserialize)]
+pub struct BackendRegistration {
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(rename = "type", default = "default_registration_type")]
+    pub mcp_type: String,
+    #[serde(default)]
+    pub command: Option<String>,
+    #[serde(default)]
+    pub args: Option<Vec<String>>,
+    #[serde(default)]
+    pub url: Option<String>,
+    #[serde(default)]
+    pub env: Option<HashMap<String, String>>,
+    #[serde(default)]
+    pub options: BackendOptions,
+}
+
+fn default_registration_type() -> String {
+    "stdio".to_string()
+}
+
+/// A tool matched by search_tools
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ToolSearchResult {
+    pub mcp_id: i64,
+    pub mcp_name: String,
+    pub tool_name: String,
+    pub description: Option<String>,
+    pub score: u32,
+}
+
+/// One tool call in a batch (for call_mcp_tools_batch)
+#[derive(Debug, Clone, Deserialize)]
+pub struct BatchCall {
+    pub mcp_name: String,
+    pub tool_name: String,
+    #[serde(default)]
+    pub arguments: serde_json::Value,
+}
+
+/// One backend's entries for a single capability category (tools, resources or prompts)
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CapabilityListing<T> {
+    pub mcp_id: i64,
+    pub mcp_name: String,
+    pub items: Vec<T>,
 }
 
+/// Id of a backend: its MCP id (negative for backends registered at runtime)
+pub type BackendId = i64;
+
 /// Mapping from namespaced tool name to original tool info
 #[derive(Debug, Clone)]
 #[allow(dead_code)]
@@ -57,14 +337,314 @@ pub struct ToolMapping {
     pub tool: McpTool,
 }
 
+/// Client operations the gateway needs from a connected backend
+pub trait BackendClient: Send {
+    fn call_tool(&mut self, name: &str, arguments: serde_json::Value) -> Result<ToolCallResult>;
+
+    /// Call a tool, giving up with RequestCancelled when `cancel` fires.
+    /// Clients that can't notify their server only check the token before
+    /// and after the call.
+    fn call_tool_cancellable(
+        &mut self,
+        name: &str,
+        arguments: serde_json::Value,
+        cancel: &CancellationToken,
+    ) -> Result<ToolCallResult> {
+        if cancel.is_cancelled() {
+            return Err(RequestCancelled.into());
+        }
+        let result = self.call_tool(name, arguments);
+        if cancel.is_cancelled() {
+            return Err(RequestCancelled.into());
+        }
+        result
+    }
+
+    /// Send a lightweight keepalive request. Clients whose connection doesn't
+    /// go stale while idle need not implement it.
+    fn ping(&mut self) -> Result<()> {
+        Ok(())
+    }
+
+    /// Set the minimum level (an MCP logging level) of the server's log
+    /// messages. Clients that can't reach the server's logging ignore it.
+    fn set_log_level(&mut self, _level: &str) -> Result<()> {
+        Ok(())
+    }
+
+    /// Release the connection, terminating the server process if the client
+    /// spawned one. Clients with nothing to clean up need not implement it.
+    fn close(self: Box<Self>) {}
+}
+
+impl BackendClient for StdioMcpClient {
+    fn call_tool(&mut self, name: &str, arguments: serde_json::Value) -> Result<ToolCallResult> {
+        StdioMcpClient::call_tool(self, name, arguments)
+    }
+
+    fn call_tool_cancellable(
+        &mut self,
+        name: &str,
+        arguments: serde_json::Value,
+        cancel: &CancellationToken,
+    ) -> Result<ToolCallResult> {
+        StdioMcpClient::call_tool_cancellable(self, name, arguments, cancel)
+    }
+
+    fn set_log_level(&mut self, level: &str) -> Result<()> {
+        StdioMcpClient::set_log_level(self, level)
+    }
+
+    fn close(self: Box<Self>) {
+        StdioMcpClient::close(*self)
+    }
+}
+
+impl BackendClient for HttpMcpClient {
+    fn call_tool(&mut self, name: &str, arguments: serde_json::Value) -> Result<ToolCallResult> {
+        HttpMcpClient::call_tool(self, name, arguments)
+    }
+
+    fn ping(&mut self) -> Result<()> {
+        HttpMcpClient::ping(self)
+    }
+
+    fn set_log_level(&mut self, level: &str) -> Result<()> {
+        HttpMcpClient::set_log_level(self, level)
+    }
+
+    fn close(self: Box<Self>) {
+        HttpMcpClient::close(*self)
+    }
+}
+
+/// A freshly connected backend, as produced by a BackendConnector
+pub struct ConnectedBackend {
+    pub client: Box<dyn BackendClient>,
+    pub server_info: McpServerInfo,
+    pub capabilities: McpServerCapabilities,
+    pub tools: Vec<McpTool>,
+    pub resources: Option<Vec<McpResource>>,
+    pub prompts: Option<Vec<McpPrompt>>,
+}
+
+/// Establishes connections to backend MCPs
+pub trait BackendConnector: Send + Sync {
+    fn connect(&self, mcp: &Mcp, options: &BackendOptions) -> Result<ConnectedBackend>;
+}
+
+/// Default connector: spawns stdio MCPs and connects to HTTP MCPs (SSE is not proxied)
+pub struct DefaultConnector;
+
+impl BackendConnector for DefaultConnector {
+    fn connect(&self, mcp: &Mcp, options: &BackendOptions) -> Result<ConnectedBackend> {
+        match mcp.mcp_type.as_str() {
+            "stdio" => connect_stdio(mcp, options),
+            "http" => connect_http(mcp, options),
+            _ => Err(anyhow!(
+                "Only stdio and http MCPs are supported for gateway proxying"
+            )),
+        }
+    }
+}
+
+fn connect_stdio(mcp: &Mcp, options: &BackendOptions) -> Result<ConnectedBackend> {
+    let command = mcp
+        .command
+        .as_ref()
+        .ok_or_else(|| anyhow!("STDIO MCP requires a command"))?;
+
+    let args: Vec<String> = mcp.args.clone().unwrap_or_default();
+    let env = stdio_env(mcp, options);
+
+    info!("[Gateway] Starting stdio MCP: {} {:?}", command, args);
+
+    // Spawn and initialize the client (spawn calls initialize internally)
+    let mut client = StdioMcpClient::spawn_with_init(
+        command,
+        &args,
+        env.as_ref(),
+        30,
+        &options.roots,
+        options.sampling.as_ref(),
+        options.init_params.as_ref(),
+    )?;
+
+    // Get server info and tools from the initialized client
+    let server_info = client
+        .server_info()
+        .cloned()
+        .unwrap_or_else(|| McpServerInfo {
+            name: mcp.name.clone(),
+            version: None,
+        });
+    let capabilities = client.capabilities().clone();
+    let tools = client.tools().to_vec();
+    let (resources, prompts) = list_optional_capabilities(&mut client);
+
+    Ok(ConnectedBackend {
+        client: Box::new(client),
+        server_info,
+        capabilities,
+        tools,
+        resources,
+        prompts,
+    })
+}
+
+fn connect_http(mcp: &Mcp, options: &BackendOptions) -> Result<ConnectedBackend> {
+    let url = mcp
+        .url
+        .as_ref()
+        .ok_or_else(|| anyhow!("HTTP MCP requires a url"))?;
+
+    info!("[Gateway] Connecting to HTTP MCP: {}", url);
+
+    let mut client = HttpMcpClient::connect_with_pool(
+        url,
+        mcp.headers.as_ref(),
+        30,
+        options.token_refresh.clone(),
+        options.init_params.as_ref(),
+        options.http_pool.clone(),
+    )?;
+
+    let server_info = client
+        .server_info()
+        .cloned()
+        .unwrap_or_else(|| McpServerInfo {
+            name: mcp.name.clone(),
+            version: None,
+        });
+    let capabilities = client.capabilities().clone();
+    let tools = client.tools().to_vec();
+    let (resources, prompts) = list_optional_capabilities(&mut client);
+
+    Ok(ConnectedBackend {
+        client: Box::new(client),
+        server_info,
+        capabilities,
+        tools,
+        resources,
+        prompts,
+    })
+}
+
+/// Environment for a stdio backend: its configured env, with RUST_LOG set
+/// to the backend's log level if one is configured
+fn stdio_env(mcp: &Mcp, options: &BackendOptions) -> Option<HashMap<String, String>> {
+    let Some(level) = &options.log_level else {
+        return mcp.env.clone();
+    };
+    let mut env = mcp.env.clone().unwrap_or_default();
+    env.insert("RUST_LOG".to_string(), level.clone());
+    Some(env)
+}
+
+/// Map a RUST_LOG-style level to the nearest MCP logging level
+fn mcp_log_level(level: &str) -> String {
+    match level.trim().to_ascii_lowercase().as_str() {
+        "trace" => "debug".to_string(),
+        "warn" => "warning".to_string(),
+        "off" => "emergency".to_string(),
+        other => other.to_string(),
+    }
+}
+
+/// Send a log level to a connected backend's server. A failure is logged
+/// rather than returned, since the backend works fine at its own level.
+fn send_log_level(backend: &mut BackendConnection, level: &str) -> bool {
+    let Some(client) = backend.client.as_mut() else {
+        return false;
+    };
+    match client.set_log_level(&mcp_log_level(level)) {
+        Ok(()) => true,
+        Err(e) => {
+            warn!(
+                "[Gateway] Failed to set log level of MCP '{}': {}",
+                backend.mcp.name, e
+            );
+            false
+        }
+    }
+}
+
+/// Clients that can list the resources and prompts of the server they connect to
+trait ListsOptionalCapabilities {
+    fn resources_supported(&self) -> bool;
+    fn prompts_supported(&self) -> bool;
+    fn list_resources(&mut self) -> Result<Vec<McpResource>>;
+    fn list_prompts(&mut self) -> Result<Vec<McpPrompt>>;
+}
+
+impl ListsOptionalCapabilities for StdioMcpClient {
+    fn resources_supported(&self) -> bool {
+        StdioMcpClient::resources_supported(self)
+    }
+    fn prompts_supported(&self) -> bool {
+        StdioMcpClient::prompts_supported(self)
+    }
+    fn list_resources(&mut self) -> Result<Vec<McpResource>> {
+        StdioMcpClient::list_resources(self)
+    }
+    fn list_prompts(&mut self) -> Result<Vec<McpPrompt>> {
+        StdioMcpClient::list_prompts(self)
+    }
+}
+
+impl ListsOptionalCapabilities for HttpMcpClient {
+    fn resources_supported(&self) -> bool {
+        HttpMcpClient::resources_supported(self)
+    }
+    fn prompts_supported(&self) -> bool {
+        HttpMcpClient::prompts_supported(self)
+    }
+    fn list_resources(&mut self) -> Result<Vec<McpResource>> {
+        HttpMcpClient::list_resources(self)
+    }
+    fn list_prompts(&mut self) -> Result<Vec<McpPrompt>> {
+        HttpMcpClient::list_prompts(self)
+    }
+}
+
+/// Fetch resources and prompts for the capabilities the backend advertises.
+/// A failed listing is logged and treated as empty rather than failing the connection.
+fn list_optional_capabilities(
+    client: &mut impl ListsOptionalCapabilities,
+) -> (Option<Vec<McpResource>>, Option<Vec<McpPrompt>>) {
+    let resources = client.resources_supported().then(|| {
+        client.list_resources().unwrap_or_else(|e| {
+            warn!("[Gateway] Failed to list resources: {}", e);
+            Vec::new()
+        })
+    });
+    let prompts = client.prompts_supported().then(|| {
+        client.list_prompts().unwrap_or_else(|e| {
+            warn!("[Gateway] Failed to list prompts: {}", e);
+            Vec::new()
+        })
+    });
+
+    (resources, prompts)
+}
+
 /// Backend connection wrapping an MCP client
 pub struct BackendConnection {
     pub mcp: Mcp,
     pub status: BackendStatus,
-    pub client: Option<StdioMcpClient>,
+    pub client: Option<Box<dyn BackendClient>>,
     pub tools: Vec<McpTool>,
+    /// Resources exposed by the backend (None if it does not support resources)
+    pub resources: Option<Vec<McpResource>>,
+    /// Prompts exposed by the backend (None if it does not support prompts)
+    pub prompts: Option<Vec<McpPrompt>>,
     pub server_info: Option<McpServerInfo>,
+    pub capabilities: Option<McpServerCapabilities>,
     pub restart_count: u32,
+    /// When the backend last connected or served a tool call
+    pub last_used: Instant,
+    /// When the backend was last sent a keepalive ping (pings don't count as use)
+    pub last_ping: Option<Instant>,
 }
 
 impl BackendConnection {
@@ -74,9 +654,23 @@ impl BackendConnection {
             status: BackendStatus::Disconnected,
             client: None,
             tools: Vec::new(),
+            resources: None,
+            prompts: None,
             server_info: None,
+            capabilities: None,
             restart_count: 0,
+            last_used: Instant::now(),
+            last_ping: None,
+        }
+    }
+
+    /// Close the client, if connected, and mark the backend disconnected
+    pub fn close(&mut self) {
+        if let Some(client) = self.client.take() {
+            info!("[Gateway] Closing connection to MCP {}", self.mcp.name);
+            client.close();
         }
+        self.status = BackendStatus::Disconnected;
     }
 
     pub fn to_info(&self) -> BackendInfo {
@@ -87,6 +681,7 @@ impl BackendConnection {
             status: self.status.clone(),
             tool_count: self.tools.len(),
             server_info: self.server_info.clone(),
+            capabilities: self.capabilities.clone(),
             error_message: match &self.status {
                 BackendStatus::Failed(msg) => Some(msg.clone()),
                 _ => None,
@@ -107,6 +702,46 @@ pub struct GatewayBackendManager {
     backends: HashMap<i64, BackendConnection>,
     /// Tool index for connected backends
     tool_index: HashMap<String, ToolMapping>,
+    /// Gateway-level options per backend, keyed by MCP id
+    backend_options: HashMap<i64, BackendOptions>,
+    /// Optional audit trail of tool calls
+    audit_log: Option<AuditLog>,
+    /// Results of cacheable tool calls
+    result_cache: ResultCache,
+    /// Quarantines backends whose calls keep failing
+    circuit_breaker: CircuitBreaker,
+    /// Backends registered at runtime, keyed by their (negative) assigned id
+    runtime_mcps: HashMap<i64, Mcp>,
+    /// Next id handed out to a runtime backend (negative to never clash with database ids)
+    next_runtime_id: i64,
+    /// How backends get connected
+    connector: Box<dyn BackendConnector>,
+    /// Disconnect backends unused for this long (None disables eviction)
+    idle_timeout: Option<Duration>,
+    /// Backends disconnected for being idle; they reconnect on their next tool call
+    evicted: HashSet<i64>,
+    /// Workspace roots of the gateway's client, offered to backends on connect
+    roots: Vec<PathBuf>,
+    /// Handler for backends' sampling requests, given to backends on connect
+    sampling: Option<SamplingHandler>,
+    /// Tool count of each backend's last connection, kept after it disconnects
+    known_tool_counts: HashMap<i64, usize>,
+    /// Token of the cancellable call in progress (see call_tool_on_mcp_cancellable)
+    active_cancel: Option<CancellationToken>,
+    /// Invocation counts per tool
+    usage: UsageTracker,
+    /// Tool profiles by name, saved to the TOOL_PROFILES_SETTING setting
+    profiles: HashMap<String, ToolProfile>,
+    /// Profile limiting the exposed catalog for the calls being served (None
+    /// exposes every backend). Sessions keep their own choice and apply it
+    /// each time they take the manager (see lock_for_session).
+    active_profile: Option<String>,
+    /// Size and timeouts of HTTP backends' connection pools
+    http_pool_config: PoolConfig,
+    /// Connection pool of each HTTP backend, created on first connect
+    http_pools: HashMap<i64, Arc<HttpClientPool>>,
+    /// Whether register_backend may add backends (off unless enabled in settings)
+    allow_backend_registration: bool,
     db: Arc<Mutex<Database>>,
 }
 
@@ -116,113 +751,590 @@ impl GatewayBackendManager {
             available_mcps: Vec::new(),
             backends: HashMap::new(),
             tool_index: HashMap::new(),
+            backend_options: HashMap::new(),
+            audit_log: None,
+            result_cache: ResultCache::default(),
+            circuit_breaker: CircuitBreaker::default(),
+            runtime_mcps: HashMap::new(),
+            next_runtime_id: -1,
+            connector: Box::new(DefaultConnector),
+            idle_timeout: None,
+            evicted: HashSet::new(),
+            roots: Vec::new(),
+            sampling: None,
+            known_tool_counts: HashMap::new(),
+            active_cancel: None,
+            usage: UsageTracker::default(),
+            profiles: HashMap::new(),
+            active_profile: None,
+            http_pool_config: PoolConfig::default(),
+            http_pools: HashMap::new(),
+            allow_backend_registration: false,
             db,
         }
     }
 
-    /// Create a namespaced tool name from MCP name and original tool name
-    pub fn namespace_tool(mcp_name: &str, tool_name: &str) -> String {
-        // Sanitize MCP name: replace non-alphanumeric with underscore
-        let safe_mcp_name: String = mcp_name
-            .chars()
-            .map(|c| {
-                if c.is_alphanumeric() || c == '_' || c == '-' {
-                    c
-                } else {
-                    '_'
-                }
-            })
-            .collect();
-        format!("{}__{}", safe_mcp_name, tool_name)
+    /// Allow or refuse runtime backend registration
+    pub fn set_allow_backend_registration(&mut self, allowed: bool) {
+        self.allow_backend_registration = allowed;
     }
 
-    /// Load available MCPs from database (no connections made - lazy loading)
-    pub fn load_available_mcps(&mut self) -> Result<()> {
-        let gateway_mcps = {
-            let db = self
-                .db
-                .lock()
-                .map_err(|e| anyhow!("Failed to lock database: {}", e))?;
-            db.get_enabled_gateway_mcps()?
-        };
+    /// Replace the connector used to establish backend connections (for testing)
+    #[cfg(test)]
+    pub fn set_connector(&mut self, connector: Box<dyn BackendConnector>) {
+        self.connector = connector;
+    }
 
-        info!(
-            "[Gateway] Loaded {} available MCPs (lazy mode - no connections yet)",
-            gateway_mcps.len()
-        );
+    /// Enable (or disable with None) the tool-call audit log
+    pub fn set_audit_log(&mut self, config: Option<AuditLogConfig>) {
+        self.audit_log = config.map(AuditLog::new);
+    }
 
-        self.available_mcps = gateway_mcps
-            .into_iter()
-            .map(|gm| AvailableMcp {
-                id: gm.mcp.id,
-                name: gm.mcp.name,
-                description: gm.mcp.description,
-                mcp_type: gm.mcp.mcp_type,
-                status: BackendStatus::Disconnected,
-            })
-            .collect();
+    /// Set how long cached tool results stay valid
+    pub fn set_result_cache_ttl(&mut self, ttl: Duration) {
+        self.result_cache.set_ttl(ttl);
+    }
 
-        Ok(())
+    /// Set how many consecutive failed calls quarantine a backend, and for how long
+    pub fn set_circuit_breaker(&mut self, config: CircuitBreakerConfig) {
+        self.circuit_breaker.set_config(config);
     }
 
-    /// Get list of available MCPs (for list_available_mcps meta-tool)
-    pub fn get_available_mcps(&self) -> Vec<AvailableMcp> {
-        self.available_mcps
-            .iter()
-            .map(|mcp| {
-                // Update status based on backend connection state
-                let status = self
-                    .backends
-                    .values()
-                    .find(|b| b.mcp.id == mcp.id)
-                    .map(|b| b.status.clone())
-                    .unwrap_or(BackendStatus::Disconnected);
+    /// Set the size and timeouts of HTTP backends' connection pools. Backends
+    /// get a pool with the new config on their next connection.
+    pub fn set_http_pool_config(&mut self, config: PoolConfig) {
+        self.http_pool_config = config;
+        self.http_pools.clear();
+    }
 
-                AvailableMcp {
-                    status,
-                    ..mcp.clone()
-                }
-            })
-            .collect()
+    /// The connection pool of an HTTP backend, created if it has none yet
+    fn http_pool(&mut self, mcp_id: i64) -> Arc<HttpClientPool> {
+        let config = &self.http_pool_config;
+        self.http_pools
+            .entry(mcp_id)
+            .or_insert_with(|| Arc::new(http_client_pool(config.clone())))
+            .clone()
     }
 
-    /// Connect to an MCP lazily by name (for load_mcp_tools meta-tool)
-    pub async fn connect_backend_lazy(&mut self, mcp_name: &str) -> Result<Vec<McpTool>> {
-        // Find the MCP in available_mcps
-        let mcp_meta = self
-            .available_mcps
-            .iter()
-            .find(|m| m.name == mcp_name)
-            .ok_or_else(|| anyhow!("MCP '{}' not found in gateway", mcp_name))?
-            .clone();
+    /// Set how long a backend may go unused before the idle sweeper disconnects it
+    /// (None disables eviction). Warmup backends are never evicted.
+    pub fn set_idle_timeout(&mut self, timeout: Option<Duration>) {
+        self.idle_timeout = timeout;
+    }
 
-        // Check if already connected
-        if let Some(backend) = self.backends.get(&mcp_meta.id) {
-            if matches!(backend.status, BackendStatus::Connected) {
-                info!(
-                    "[Gateway] MCP '{}' already connected, returning cached tools",
-                    mcp_name
+    /// Get the gateway options for a backend (defaults if none were set)
+    pub fn backend_options(&self, mcp_id: i64) -> BackendOptions {
+        let mut options = self
+            .backend_options
+            .get(&mcp_id)
+            .cloned()
+            .unwrap_or_default();
+        options.roots = self.roots.clone();
+        options.sampling = self.sampling.clone();
+        options
+    }
+
+    /// Set the workspace roots offered to backends. Backends that are already
+    /// connected keep the roots they were given.
+    pub fn set_roots(&mut self, roots: Vec<PathBuf>) {
+        self.roots = roots;
+    }
+
+    /// Set the handler that runs model completions for backends' sampling
+    /// requests (None refuses them). Only stdio backends can send requests to
+    /// the gateway; backends that are already connected keep their handler.
+    pub fn set_sampling_handler(&mut self, handler: Option<SamplingHandler>) {
+        self.sampling = handler;
+    }
+
+    /// Set a backend's log level, saving it for future connections. Returns
+    /// whether it was applied to a live connection: backends that are not
+    /// connected or don't declare the logging capability are left as is.
+    pub fn set_backend_log_level(&mut self, mcp_id: i64, level: Option<String>) -> bool {
+        let mut options = self
+            .backend_options
+            .get(&mcp_id)
+            .cloned()
+            .unwrap_or_default();
+        options.log_level = level.clone();
+        self.backend_options.insert(mcp_id, options);
+        if mcp_id >= 0 {
+            if let Err(e) = self.save_backend_options() {
+                warn!(
+                    "[Gateway] Failed to save log level of backend {}: {}",
+                    mcp_id, e
                 );
-                return Ok(backend.tools.clone());
             }
         }
 
-        // Load full MCP config from database
-        let gateway_mcp = {
+        let Some(level) = level else {
+            return false;
+        };
+        match self.backends.get_mut(&mcp_id) {
+            Some(backend)
+                if matches!(backend.status, BackendStatus::Connected)
+                    && backend.capabilities.as_ref().is_some_and(|c| c.logging) =>
+            {
+                send_log_level(backend, &level)
+            }
+            _ => false,
+        }
+    }
+
+    /// Set the gateway options for a backend, saving them to the database
+    /// unless it was registered at runtime. Fails if the alias is taken or
+    /// the init params are not an object.
+    pub fn set_backend_options(&mut self, mcp_id: i64, options: BackendOptions) -> Result<()> {
+        check_init_params(options.init_params.as_ref())?;
+        if let Some(alias) = &options.alias {
+            self.ensure_alias_available(mcp_id, alias)?;
+        }
+        self.backend_options.insert(mcp_id, options);
+        self.build_tool_index();
+        if mcp_id >= 0 {
+            self.save_backend_options()?;
+        }
+        Ok(())
+    }
+
+    /// Load the backend options saved in the database, replacing those of
+    /// database backends in memory (runtime backends keep theirs). A setting
+    /// that doesn't parse, or a backend whose options are invalid, is logged
+    /// and left with defaults.
+    fn load_backend_options(&mut self) -> Result<()> {
+        let saved = {
             let db = self
                 .db
                 .lock()
                 .map_err(|e| anyhow!("Failed to lock database: {}", e))?;
-            db.get_gateway_mcps()?
-                .into_iter()
-                .find(|gm| gm.mcp.id == mcp_meta.id)
-                .ok_or_else(|| anyhow!("MCP '{}' not found in gateway database", mcp_name))?
+            db.get_setting(BACKEND_OPTIONS_SETTING)
         };
 
-        info!("[Gateway] Lazy-connecting to MCP '{}'", mcp_name);
+        let saved: HashMap<i64, serde_json::Value> = match saved {
+            Some(json) => serde_json::from_str(&json).unwrap_or_else(|e| {
+                warn!(
+                    "[Gateway] Ignoring invalid {}: {}",
+                    BACKEND_OPTIONS_SETTING, e
+                );
+                HashMap::new()
+            }),
+            None => HashMap::new(),
+        };
+
+        self.backend_options.retain(|id, _| *id < 0);
+        for (mcp_id, value) in saved {
+            let options = serde_json::from_value::<BackendOptions>(value)
+                .map_err(anyhow::Error::from)
+                .and_then(|options| {
+                    check_init_params(options.init_params.as_ref())?;
+                    Ok(options)
+                });
+            match options {
+                Ok(options) => {
+                    self.backend_options.insert(mcp_id, options);
+                }
+                Err(e) => warn!(
+                    "[Gateway] Ignoring invalid saved options of backend {}: {}",
+                    mcp_id, e
+                ),
+            }
+        }
+        Ok(())
+    }
+
+    fn save_backend_options(&self) -> Result<()> {
+        let saved: HashMap<i64, &BackendOptions> = self
+            .backend_options
+            .iter()
+            .filter(|(id, _)| **id >= 0)
+            .map(|(id, options)| (*id, options))
+            .collect();
+        let json = serde_json::to_string(&saved)?;
+        let db = self
+            .db
+            .lock()
+            .map_err(|e| anyhow!("Failed to lock database: {}", e))?;
+        db.set_setting(BACKEND_OPTIONS_SETTING, &json)
+    }
+
+    /// Error if `alias` is empty or already names or aliases another backend
+    fn ensure_alias_available(&self, mcp_id: i64, alias: &str) -> Result<()> {
+        if alias.trim().is_empty() {
+            return Err(anyhow!("Backend alias must not be empty"));
+        }
+
+        let name_owner = self
+            .available_mcps
+            .iter()
+            .find(|m| m.id != mcp_id && m.name == alias)
+            .map(|m| m.name.clone());
+        let alias_owner = self
+            .backend_options
+            .iter()
+            .find(|(id, options)| **id != mcp_id && options.alias.as_deref() == Some(alias))
+            .map(|(id, _)| self.backend_name(*id).unwrap_or_else(|| id.to_string()));
+
+        match name_owner.or(alias_owner) {
+            Some(owner) => Err(anyhow!(
+                "Alias '{}' is already used by backend '{}'",
+                alias,
+                owner
+            )),
+            None => Ok(()),
+        }
+    }
+
+    /// MCP name of a backend by id
+    fn backend_name(&self, mcp_id: i64) -> Option<String> {
+        self.available_mcps
+            .iter()
+            .find(|m| m.id == mcp_id)
+            .map(|m| m.name.clone())
+            .or_else(|| self.backends.get(&mcp_id).map(|b| b.mcp.name.clone()))
+    }
+
+    /// Resolve a backend alias to its MCP name. Anything that isn't an alias
+    /// (including MCP names) is returned unchanged.
+    fn resolve_backend_name(&self, name_or_alias: &str) -> String {
+        let is_name = self.available_mcps.iter().any(|m| m.name == name_or_alias)
+            || self.backends.values().any(|b| b.mcp.name == name_or_alias);
+        if is_name {
+            return name_or_alias.to_string();
+        }
+
+        self.backend_options
+            .iter()
+            .find(|(_, options)| options.alias.as_deref() == Some(name_or_alias))
+            .and_then(|(id, _)| self.backend_name(*id))
+            .unwrap_or_else(|| name_or_alias.to_string())
+    }
+
+    /// Define (or replace) a tool profile and save it to the database. An
+    /// active profile of the same name takes effect immediately.
+    pub fn define_profile(&mut self, name: &str, profile: ToolProfile) -> Result<()> {
+        if name.trim().is_empty() {
+            return Err(anyhow!("Profile name must not be empty"));
+        }
+        self.profiles.insert(name.to_string(), profile);
+        self.save_profiles()
+    }
+
+    /// Names of the defined tool profiles, sorted
+    pub fn profile_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.profiles.keys().cloned().collect();
+        names.sort();
+        names
+    }
+
+    /// Load the tool profiles saved in the database, replacing those in memory.
+    /// A setting that doesn't parse is logged and leaves no profiles.
+    pub fn load_profiles(&mut self) -> Result<()> {
+        let saved = {
+            let db = self
+                .db
+                .lock()
+                .map_err(|e| anyhow!("Failed to lock database: {}", e))?;
+            db.get_setting(TOOL_PROFILES_SETTING)
+        };
+
+        self.profiles = match saved {
+            Some(json) => serde_json::from_str(&json).unwrap_or_else(|e| {
+                warn!(
+                    "[Gateway] Ignoring invalid {}: {}",
+                    TOOL_PROFILES_SETTING, e
+                );
+                HashMap::new()
+            }),
+            None => HashMap::new(),
+        };
+        Ok(())
+    }
+
+    fn save_profiles(&self) -> Result<()> {
+        let json = serde_json::to_string(&self.profiles)?;
+        let db = self
+            .db
+            .lock()
+            .map_err(|e| anyhow!("Failed to lock database: {}", e))?;
+        db.set_setting(TOOL_PROFILES_SETTING, &json)
+    }
+
+    /// Expose only the backends and tools of profile `profile`, or every
+    /// backend if it is None or no longer exists
+    fn apply_session_profile(&mut self, profile: Option<&str>) {
+        self.active_profile = profile
+            .filter(|name| self.profiles.contains_key(*name))
+            .map(str::to_string);
+    }
+
+    fn current_profile(&self) -> Option<&ToolProfile> {
+        self.active_profile
+            .as_ref()
+            .and_then(|name| self.profiles.get(name))
+    }
+
+    /// Whether the active profile (if any) exposes a backend
+    fn is_backend_exposed(&self, mcp_id: i64, mcp_name: &str) -> bool {
+        let Some(profile) = self.current_profile() else {
+            return true;
+        };
+        let alias = self.backend_options(mcp_id).alias;
+        let names: Vec<&str> = std::iter::once(mcp_name).chain(alias.as_deref()).collect();
+        profile.exposes_backend(&names)
+    }
+
+    /// Whether `tool_name` passes the backend's allow/deny lists and the active profile
+    fn is_tool_exposed(&self, mcp_id: i64, mcp_name: &str, tool_name: &str) -> bool {
+        let options = self.backend_options(mcp_id);
+        if !options.is_tool_allowed(tool_name) {
+            return false;
+        }
+        let Some(profile) = self.current_profile() else {
+            return true;
+        };
+        let names: Vec<&str> = std::iter::once(mcp_name)
+            .chain(options.alias.as_deref())
+            .collect();
+        profile.exposes_tool(&names, tool_name)
+    }
+
+    /// Create a namespaced tool name from MCP name and original tool name
+    pub fn namespace_tool(mcp_name: &str, tool_name: &str) -> String {
+        // Sanitize MCP name: replace non-alphanumeric with underscore
+        let safe_mcp_name: String = mcp_name
+            .chars()
+            .map(|c| {
+                if c.is_alphanumeric() || c == '_' || c == '-' {
+                    c
+                } else {
+                    '_'
+                }
+            })
+            .collect();
+        format!("{}__{}", safe_mcp_name, tool_name)
+    }
+
+    /// Load available MCPs from database (no connections made - lazy loading)
+    pub fn load_available_mcps(&mut self) -> Result<()> {
+        let gateway_mcps = {
+            let db = self
+                .db
+                .lock()
+                .map_err(|e| anyhow!("Failed to lock database: {}", e))?;
+            db.get_enabled_gateway_mcps()?
+        };
+
+        info!(
+            "[Gateway] Loaded {} available MCPs (lazy mode - no connections yet)",
+            gateway_mcps.len()
+        );
+
+        self.available_mcps = gateway_mcps
+            .into_iter()
+            .map(|gm| AvailableMcp {
+                id: gm.mcp.id,
+                name: gm.mcp.name,
+                description: gm.mcp.description,
+                mcp_type: gm.mcp.mcp_type,
+                status: BackendStatus::Disconnected,
+                alias: None,
+            })
+            .collect();
+
+        // Keep backends registered at runtime (in registration order)
+        let mut runtime: Vec<&Mcp> = self.runtime_mcps.values().collect();
+        runtime.sort_by(|a, b| b.id.cmp(&a.id));
+        self.available_mcps
+            .extend(runtime.into_iter().map(|mcp| AvailableMcp {
+                id: mcp.id,
+                name: mcp.name.clone(),
+                description: mcp.description.clone(),
+                mcp_type: mcp.mcp_type.clone(),
+                status: BackendStatus::Disconnected,
+                alias: None,
+            }));
+
+        self.load_backend_options()?;
+        self.build_tool_index();
+        self.load_profiles()
+    }
+
+    /// Register a new backend at runtime (for register_backend meta-tool)
+    ///
+    /// The backend is validated and added to the available MCPs but not connected;
+    /// it connects lazily like any other backend. Returns the assigned id.
+    /// Fails unless registration was allowed with set_allow_backend_registration.
+    pub fn register_backend(&mut self, registration: BackendRegistration) -> Result<i64> {
+        if !self.allow_backend_registration {
+            return Err(anyhow!(
+                "Runtime backend registration is disabled. Enable \"Allow backend registration\" \
+                 in the gateway settings to let clients add MCPs."
+            ));
+        }
+
+        let name = registration.name.trim().to_string();
+        if name.is_empty() {
+            return Err(anyhow!("Backend name is required"));
+        }
+
+        match registration.mcp_type.as_str() {
+            "stdio" => match registration.command.as_deref() {
+                Some(command) if !command.trim().is_empty() => {
+                    check_registered_command(&name, command)?
+                }
+                _ => return Err(anyhow!("STDIO backend '{}' requires a command", name)),
+            },
+            "sse" | "http" => {
+                if registration
+                    .url
+                    .as_deref()
+                    .is_none_or(|u| u.trim().is_empty())
+                {
+                    return Err(anyhow!("Backend '{}' requires a url", name));
+                }
+            }
+            other => {
+                return Err(anyhow!(
+                    "Unsupported backend type '{}' (expected stdio, sse or http)",
+                    other
+                ))
+            }
+        }
+
+        if self.available_mcps.iter().any(|m| m.name == name) {
+            return Err(anyhow!("Backend '{}' is already registered", name));
+        }
+
+        check_init_params(registration.options.init_params.as_ref())?;
+
+        let id = self.next_runtime_id;
+        // The name must not shadow an existing alias, nor the alias any name
+        self.ensure_alias_available(id, &name)?;
+        if let Some(alias) = &registration.options.alias {
+            if *alias == name {
+                return Err(anyhow!(
+                    "Backend '{}' alias must differ from its name",
+                    name
+                ));
+            }
+            self.ensure_alias_available(id, alias)?;
+        }
+        self.next_runtime_id -= 1;
+
+        let now = chrono::Utc::now().to_rfc3339();
+        let mcp = Mcp {
+            id,
+            name: name.clone(),
+            description: registration.description,
+            mcp_type: registration.mcp_type,
+            command: registration.command,
+            args: registration.args,
+            url: registration.url,
+            headers: None,
+            env: registration.env,
+            icon: None,
+            tags: None,
+            source: "runtime".to_string(),
+            source_path: None,
+            is_enabled_global: false,
+            is_favorite: false,
+            created_at: now.clone(),
+            updated_at: now,
+        };
+
+        self.available_mcps.push(AvailableMcp {
+            id,
+            name: mcp.name.clone(),
+            description: mcp.description.clone(),
+            mcp_type: mcp.mcp_type.clone(),
+            status: BackendStatus::Disconnected,
+            alias: None,
+        });
+        self.backend_options.insert(id, registration.options);
+        self.runtime_mcps.insert(id, mcp);
+
+        info!(
+            "[Gateway] Registered runtime backend '{}' with id {}",
+            name, id
+        );
+        Ok(id)
+    }
+
+    /// Resolve the full MCP config for a backend (runtime registry first, then database)
+    fn resolve_mcp_config(&self, mcp_id: i64) -> Result<Mcp> {
+        if let Some(mcp) = self.runtime_mcps.get(&mcp_id) {
+            return Ok(mcp.clone());
+        }
+
+        let db = self
+            .db
+            .lock()
+            .map_err(|e| anyhow!("Failed to lock database: {}", e))?;
+        db.get_gateway_mcps()?
+            .into_iter()
+            .find(|gm| gm.mcp_id == mcp_id)
+            .map(|gm| gm.mcp)
+            .ok_or_else(|| anyhow!("MCP {} not found in gateway", mcp_id))
+    }
+
+    /// Get list of available MCPs (for list_available_mcps meta-tool)
+    pub fn get_available_mcps(&self) -> Vec<AvailableMcp> {
+        self.available_mcps
+            .iter()
+            .filter(|mcp| self.is_backend_exposed(mcp.id, &mcp.name))
+            .map(|mcp| {
+                // Update status based on backend connection state
+                let status = self
+                    .backends
+                    .values()
+                    .find(|b| b.mcp.id == mcp.id)
+                    .map(|b| b.status.clone())
+                    .unwrap_or(BackendStatus::Disconnected);
+
+                AvailableMcp {
+                    status,
+                    alias: self.backend_options(mcp.id).alias,
+                    ..mcp.clone()
+                }
+            })
+            .collect()
+    }
+
+    /// Connect to an MCP lazily by name (for load_mcp_tools meta-tool)
+    pub async fn connect_backend_lazy(&mut self, mcp_name: &str) -> Result<Vec<McpTool>> {
+        let mcp_name = self.resolve_backend_name(mcp_name);
+        let mcp_name = mcp_name.as_str();
+        // Find the MCP in available_mcps
+        let mcp_meta = self
+            .available_mcps
+            .iter()
+            .find(|m| m.name == mcp_name)
+            .ok_or_else(|| {
+                gateway_error(
+                    GatewayErrorCode::BackendNotFound,
+                    format!("MCP '{}' not found in gateway", mcp_name),
+                )
+            })?
+            .clone();
+
+        // Check if already connected
+        if let Some(backend) = self.backends.get(&mcp_meta.id) {
+            if matches!(backend.status, BackendStatus::Connected) {
+                info!(
+                    "[Gateway] MCP '{}' already connected, returning cached tools",
+                    mcp_name
+                );
+                return Ok(backend.tools.clone());
+            }
+        }
+
+        // Load full MCP config (runtime registry or database)
+        let mcp = self.resolve_mcp_config(mcp_meta.id)?;
+
+        info!("[Gateway] Lazy-connecting to MCP '{}'", mcp_name);
 
         // Connect to the backend
-        self.add_backend(gateway_mcp).await;
+        self.add_backend(mcp);
         self.build_tool_index();
 
         // Return the tools
@@ -232,89 +1344,160 @@ impl GatewayBackendManager {
             .ok_or_else(|| anyhow!("Failed to connect to MCP '{}'", mcp_name))
     }
 
+    /// Status, server name/version and declared capabilities of one backend
+    /// (backend_info meta-tool). Connects the backend first if needed.
+    pub async fn backend_info(&mut self, mcp_name: &str) -> Result<BackendInfo> {
+        let mcp_name = self.resolve_backend_name(mcp_name);
+        self.connect_backend_lazy(&mcp_name).await?;
+        self.backends
+            .values()
+            .find(|b| b.mcp.name == mcp_name)
+            .map(BackendConnection::to_info)
+            .ok_or_else(|| {
+                gateway_error(
+                    GatewayErrorCode::BackendNotFound,
+                    format!("MCP '{}' not found in gateway", mcp_name),
+                )
+            })
+    }
+
+    /// Tool count of a backend without connecting it: the live count when it
+    /// is connected, otherwise the count from its last connection (e.g. before
+    /// idle eviction). None when it has never connected, since no supported
+    /// transport can list tools without the full initialize handshake.
+    pub fn probe_tool_count(&self, mcp_name: &str) -> Option<usize> {
+        let mcp_name = self.resolve_backend_name(mcp_name);
+        let mcp_id = self.available_mcps.iter().find(|m| m.name == mcp_name)?.id;
+
+        match self.backends.get(&mcp_id) {
+            Some(backend) if matches!(backend.status, BackendStatus::Connected) => {
+                Some(backend.tools.len())
+            }
+            _ => self.known_tool_counts.get(&mcp_id).copied(),
+        }
+    }
+
     /// Get tools for a specific MCP (returns None if not connected)
     #[allow(dead_code)]
     pub fn get_backend_tools(&self, mcp_name: &str) -> Option<Vec<McpTool>> {
+        let mcp_name = self.resolve_backend_name(mcp_name);
         self.backends
             .values()
             .find(|b| b.mcp.name == mcp_name && matches!(b.status, BackendStatus::Connected))
             .map(|b| b.tools.clone())
     }
 
-    /// Add a backend connection for an MCP
-    async fn add_backend(&mut self, gateway_mcp: GatewayMcp) {
-        let mcp_id = gateway_mcp.mcp.id;
-        let mcp_name = gateway_mcp.mcp.name.clone();
-        let mcp_type = gateway_mcp.mcp.mcp_type.clone();
-
-        info!("[Gateway] Adding backend: {} ({})", mcp_name, mcp_type);
-
-        let mut backend = BackendConnection::new(gateway_mcp.mcp.clone());
+    /// Connect every available backend (reusing live connections) and describe
+    /// all exposed tools, for documentation (export_catalog meta-tool).
+    ///
+    /// Backends are sorted by name and tools by name within each backend.
+    /// Backends that fail to connect are listed with an `error` instead of tools.
+    pub async fn export_catalog(&mut self) -> serde_json::Value {
+        let mut names: Vec<String> = self.available_mcps.iter().map(|m| m.name.clone()).collect();
+        names.sort();
 
-        // Only support stdio MCPs for now (HTTP/SSE would need different client handling)
-        if mcp_type == "stdio" {
-            backend.status = BackendStatus::Connecting;
+        let mut backends = Vec::new();
+        for name in names {
+            let connected = match self.connect_backend_lazy(&name).await {
+                Ok(_) => self.get_backend_tools(&name).ok_or_else(|| {
+                    match self.backends.values().find(|b| b.mcp.name == name) {
+                        Some(BackendConnection {
+                            status: BackendStatus::Failed(e),
+                            ..
+                        }) => e.clone(),
+                        _ => "Not connected".to_string(),
+                    }
+                }),
+                Err(e) => Err(e.to_string()),
+            };
 
-            match self.connect_stdio_backend(&gateway_mcp.mcp).await {
-                Ok((client, server_info, tools)) => {
-                    info!(
-                        "[Gateway] Connected to {} with {} tools",
-                        mcp_name,
-                        tools.len()
-                    );
-                    backend.client = Some(client);
-                    backend.server_info = Some(server_info);
-                    backend.tools = tools;
-                    backend.status = BackendStatus::Connected;
+            backends.push(match connected {
+                Ok(mut tools) => {
+                    tools.sort_by(|a, b| a.name.cmp(&b.name));
+                    serde_json::json!({ "mcpName": name, "tools": tools })
                 }
-                Err(e) => {
-                    error!("[Gateway] Failed to connect to {}: {}", mcp_name, e);
-                    backend.status = BackendStatus::Failed(e.to_string());
+                Err(error) => {
+                    warn!("[Gateway] Catalog: MCP '{}' unavailable: {}", name, error);
+                    serde_json::json!({ "mcpName": name, "error": error })
                 }
-            }
-        } else {
-            // HTTP/SSE MCPs are not supported for gateway proxying yet
-            warn!(
-                "[Gateway] Skipping {} - only stdio MCPs are supported for gateway",
-                mcp_name
-            );
-            backend.status = BackendStatus::Failed(
-                "Only stdio MCPs are supported for gateway proxying".to_string(),
-            );
+            });
         }
 
-        self.backends.insert(mcp_id, backend);
+        serde_json::json!({ "backends": backends })
     }
 
-    /// Connect to a stdio-based MCP
-    async fn connect_stdio_backend(
-        &self,
-        mcp: &Mcp,
-    ) -> Result<(StdioMcpClient, McpServerInfo, Vec<McpTool>)> {
-        let command = mcp
-            .command
-            .as_ref()
-            .ok_or_else(|| anyhow!("STDIO MCP requires a command"))?;
+    /// Add a backend connection for an MCP
+    fn add_backend(&mut self, mcp: Mcp) {
+        let mcp_id = mcp.id;
+        self.evicted.remove(&mcp_id);
+        let mcp_name = mcp.name.clone();
+
+        info!("[Gateway] Adding backend: {} ({})", mcp_name, mcp.mcp_type);
 
-        let args: Vec<String> = mcp.args.clone().unwrap_or_default();
-        let env = mcp.env.clone();
+        let mut backend = BackendConnection::new(mcp.clone());
+        backend.status = BackendStatus::Connecting;
+
+        let mut options = self.backend_options(mcp_id);
+        if mcp.mcp_type == "http" {
+            options.http_pool = Some(self.http_pool(mcp_id));
+        }
+        match self.connector.connect(&mcp, &options) {
+            Ok(connected) => {
+                info!(
+                    "[Gateway] Connected to {} with {} tools",
+                    mcp_name,
+                    connected.tools.len()
+                );
+                backend.client = Some(connected.client);
+                backend.server_info = Some(connected.server_info);
+                if let (Some(level), true) = (&options.log_level, connected.capabilities.logging) {
+                    send_log_level(&mut backend, level);
+                }
+                backend.capabilities = Some(connected.capabilities);
+                backend.tools = connected
+                    .tools
+                    .into_iter()
+                    .filter(|t| options.is_tool_allowed(&t.name))
+                    .collect();
+                backend.resources = connected.resources;
+                backend.prompts = connected.prompts;
+                backend.status = BackendStatus::Connected;
+                self.known_tool_counts.insert(mcp_id, backend.tools.len());
+            }
+            Err(e) => {
+                error!("[Gateway] Failed to connect to {}: {}", mcp_name, e);
+                backend.status = BackendStatus::Failed(e.to_string());
+            }
+        }
 
-        info!("[Gateway] Starting stdio MCP: {} {:?}", command, args);
+        self.backends.insert(mcp_id, backend);
+    }
 
-        // Spawn and initialize the client (spawn calls initialize internally)
-        let client = StdioMcpClient::spawn(command, &args, env.as_ref(), 30)?;
+    /// Names and retry policies of available backends flagged for warmup
+    pub fn warmup_targets(&self) -> Vec<(String, RetryPolicy)> {
+        self.available_mcps
+            .iter()
+            .filter_map(|mcp| {
+                let options = self.backend_options(mcp.id);
+                options.warmup.then(|| (mcp.name.clone(), options.retry))
+            })
+            .collect()
+    }
 
-        // Get server info and tools from the initialized client
-        let server_info = client
-            .server_info()
-            .cloned()
-            .unwrap_or_else(|| McpServerInfo {
-                name: mcp.name.clone(),
-                version: None,
-            });
-        let tools = client.tools().to_vec();
+    /// Connect a backend by name, failing if it does not end up connected
+    pub async fn warmup_backend(&mut self, mcp_name: &str) -> Result<()> {
+        self.connect_backend_lazy(mcp_name).await?;
 
-        Ok((client, server_info, tools))
+        match self
+            .backends
+            .values()
+            .find(|b| b.mcp.name == mcp_name)
+            .map(|b| &b.status)
+        {
+            Some(BackendStatus::Connected) => Ok(()),
+            Some(BackendStatus::Failed(msg)) => Err(anyhow!(msg.clone())),
+            _ => Err(anyhow!("MCP '{}' did not connect", mcp_name)),
+        }
     }
 
     /// Build the aggregated tool index from all connected backends
@@ -323,8 +1506,13 @@ impl GatewayBackendManager {
 
         for (mcp_id, backend) in &self.backends {
             if matches!(backend.status, BackendStatus::Connected) {
+                let prefix = self
+                    .backend_options
+                    .get(mcp_id)
+                    .and_then(|o| o.alias.clone())
+                    .unwrap_or_else(|| backend.mcp.name.clone());
                 for tool in &backend.tools {
-                    let namespaced = Self::namespace_tool(&backend.mcp.name, &tool.name);
+                    let namespaced = Self::namespace_tool(&prefix, &tool.name);
                     self.tool_index.insert(
                         namespaced,
                         ToolMapping {
@@ -344,6 +1532,7 @@ impl GatewayBackendManager {
     pub fn get_tools(&self) -> Vec<McpTool> {
         self.tool_index
             .values()
+            .filter(|m| self.is_tool_exposed(m.mcp_id, &m.mcp_name, &m.original_name))
             .map(|mapping| {
                 let mut tool = mapping.tool.clone();
                 // Update the name to the namespaced version
@@ -359,143 +1548,3259 @@ impl GatewayBackendManager {
             .collect()
     }
 
-    /// Call a tool on a specific MCP by name (for call_mcp_tool meta-tool)
-    /// This is the primary method for lazy-loading mode
-    pub fn call_tool_on_mcp(
-        &mut self,
-        mcp_name: &str,
-        tool_name: &str,
-        arguments: serde_json::Value,
-    ) -> Result<ToolCallResult> {
-        // Find the backend by MCP name
-        let backend = self
+    /// Collect one capability category from connected backends, ordered by backend id.
+    /// Backends for which `select` returns None are omitted.
+    fn capability_listings<T: Clone>(
+        &self,
+        select: impl Fn(&BackendConnection) -> Option<&Vec<T>>,
+    ) -> Vec<CapabilityListing<T>> {
+        let mut listings: Vec<CapabilityListing<T>> = self
             .backends
-            .values_mut()
-            .find(|b| b.mcp.name == mcp_name)
-            .ok_or_else(|| {
-                anyhow!(
-                    "MCP '{}' is not connected. Call load_mcp_tools first to connect.",
-                    mcp_name
-                )
+            .values()
+            .filter(|b| matches!(b.status, BackendStatus::Connected))
+            .filter_map(|b| {
+                select(b).map(|items| CapabilityListing {
+                    mcp_id: b.mcp.id,
+                    mcp_name: b.mcp.name.clone(),
+                    items: items.clone(),
+                })
+            })
+            .collect();
+        listings.sort_by_key(|l| l.mcp_id);
+        listings
+    }
+
+    /// Tools of connected backends, grouped per backend (for list_tools meta-tool).
+    /// Descriptions are shortened per the backend's description_max_chars.
+    pub fn list_tools_by_backend(&self) -> Vec<CapabilityListing<McpTool>> {
+        let mut listings = self.capability_listings(|b| (!b.tools.is_empty()).then_some(&b.tools));
+        for listing in &mut listings {
+            let options = self.backend_options(listing.mcp_id);
+            let (mcp_id, mcp_name) = (listing.mcp_id, listing.mcp_name.clone());
+            listing
+                .items
+                .retain(|tool| self.is_tool_exposed(mcp_id, &mcp_name, &tool.name));
+            for tool in &mut listing.items {
+                tool.description = options.listed_description(tool.description.as_ref());
+            }
+        }
+        listings.retain(|listing| !listing.items.is_empty());
+        listings
+    }
+
+    /// Full definition of one tool on a connected backend (for describe_tool meta-tool)
+    pub fn describe_tool(&self, mcp_name: &str, tool_name: &str) -> Result<McpTool> {
+        let mcp_name = self.resolve_backend_name(mcp_name);
+        let mcp_name = mcp_name.as_str();
+        let backend = self
+            .backends
+            .values()
+            .find(|b| b.mcp.name == mcp_name && matches!(b.status, BackendStatus::Connected))
+            .ok_or_else(|| {
+                gateway_error(
+                    GatewayErrorCode::BackendNotFound,
+                    format!(
+                        "MCP '{}' is not connected. Call load_mcp_tools first to connect.",
+                        mcp_name
+                    ),
+                )
             })?;
 
-        if !matches!(backend.status, BackendStatus::Connected) {
-            return Err(anyhow!(
-                "MCP '{}' is not connected (status: {:?}). Call load_mcp_tools first.",
-                mcp_name,
-                backend.status
-            ));
+        backend
+            .tools
+            .iter()
+            .find(|t| t.name == tool_name)
+            .cloned()
+            .ok_or_else(|| {
+                gateway_error(
+                    GatewayErrorCode::ToolNotExposed,
+                    format!("Tool '{}' not found on MCP '{}'", tool_name, mcp_name),
+                )
+            })
+    }
+
+    /// Resources of connected backends that support them (for list_resources meta-tool)
+    pub fn list_resources_by_backend(&self) -> Vec<CapabilityListing<McpResource>> {
+        self.capability_listings(|b| b.resources.as_ref())
+    }
+
+    /// Prompts of connected backends that support them (for list_prompts meta-tool)
+    pub fn list_prompts_by_backend(&self) -> Vec<CapabilityListing<McpPrompt>> {
+        self.capability_listings(|b| b.prompts.as_ref())
+    }
+
+    /// Search tools of connected backends by name and description (for search_tools meta-tool)
+    ///
+    /// Results are ranked by match score, then backend priority (higher first),
+    /// then backend id and tool name so the ordering is deterministic.
+    pub fn search_tools(&self, query: &str) -> Vec<ToolSearchResult> {
+        let query = query.trim().to_lowercase();
+        if query.is_empty() {
+            return Vec::new();
         }
 
-        let client = backend
-            .client
-            .as_mut()
-            .ok_or_else(|| anyhow!("MCP '{}' has no active client", mcp_name))?;
+        let mut results: Vec<(i32, ToolSearchResult)> = Vec::new();
+        for (mcp_id, backend) in &self.backends {
+            if !matches!(backend.status, BackendStatus::Connected) {
+                continue;
+            }
+            let options = self.backend_options(*mcp_id);
 
-        info!(
-            "[Gateway] Calling tool '{}' on MCP '{}'",
-            tool_name, mcp_name
-        );
+            for tool in &backend.tools {
+                if !self.is_tool_exposed(*mcp_id, &backend.mcp.name, &tool.name) {
+                    continue;
+                }
+                let name = tool.name.to_lowercase();
+                let score = if name == query {
+                    3
+                } else if name.contains(&query) {
+                    2
+                } else if tool
+                    .description
+                    .as_ref()
+                    .is_some_and(|d| d.to_lowercase().contains(&query))
+                {
+                    1
+                } else {
+                    0
+                };
+
+                if score > 0 {
+                    results.push((
+                        options.priority,
+                        ToolSearchResult {
+                            mcp_id: *mcp_id,
+                            mcp_name: backend.mcp.name.clone(),
+                            tool_name: tool.name.clone(),
+                            description: options.listed_description(tool.description.as_ref()),
+                            score,
+                        },
+                    ));
+                }
+            }
+        }
+
+        results.sort_by(|(pa, a), (pb, b)| {
+            b.score
+                .cmp(&a.score)
+                .then(pb.cmp(pa))
+                .then(a.mcp_id.cmp(&b.mcp_id))
+                .then_with(|| a.tool_name.cmp(&b.tool_name))
+        });
 
-        client.call_tool(tool_name, arguments)
+        results.into_iter().map(|(_, r)| r).collect()
     }
 
-    /// Call a tool on the appropriate backend (legacy method for namespaced tools)
-    #[allow(dead_code)]
-    pub fn call_tool(
+    /// Call a tool on a specific MCP by name (for call_mcp_tool meta-tool)
+    /// This is the primary method for lazy-loading mode
+    pub fn call_tool_on_mcp(
         &mut self,
-        namespaced_name: &str,
+        mcp_name: &str,
+        tool_name: &str,
         arguments: serde_json::Value,
     ) -> Result<ToolCallResult> {
-        let mapping = self
-            .tool_index
-            .get(namespaced_name)
-            .ok_or_else(|| anyhow!("Unknown tool: {}", namespaced_name))?
-            .clone();
+        let mcp_name = self.resolve_backend_name(mcp_name);
+        let mcp_name = mcp_name.as_str();
+        let audit_arguments = self.audit_log.as_ref().map(|_| arguments.clone());
 
-        let backend = self
-            .backends
-            .get_mut(&mapping.mcp_id)
-            .ok_or_else(|| anyhow!("Backend not found for MCP {}", mapping.mcp_name))?;
+        // Enforced here as well as in listings, so guessing a hidden tool's name doesn't work
+        if let Err(e) = self.ensure_tool_allowed(mcp_name, tool_name) {
+            let result = Err(e);
+            if let Some(arguments) = audit_arguments {
+                self.record_audit(mcp_name, tool_name, &arguments, &result);
+            }
+            return result;
+        }
 
-        if !matches!(backend.status, BackendStatus::Connected) {
-            return Err(anyhow!(
-                "Backend {} is not connected (status: {:?})",
-                mapping.mcp_name,
-                backend.status
-            ));
+        self.reconnect_if_evicted(mcp_name)?;
+
+        if let Err(e) = self.check_arguments(mcp_name, tool_name, &arguments) {
+            let result = Err(e);
+            if let Some(arguments) = audit_arguments {
+                self.record_audit(mcp_name, tool_name, &arguments, &result);
+            }
+            return result;
         }
+        self.record_usage(mcp_name, tool_name);
 
-        let client = backend
-            .client
-            .as_mut()
-            .ok_or_else(|| anyhow!("Backend {} has no active client", mapping.mcp_name))?;
+        let result = if self.is_cacheable_call(mcp_name, tool_name) {
+            self.cached_tool_call(mcp_name, tool_name, arguments)
+        } else {
+            self.dispatch_tool_call(mcp_name, tool_name, arguments)
+        };
 
-        info!(
-            "[Gateway] Calling tool {} on backend {}",
-            mapping.original_name, mapping.mcp_name
-        );
+        if let Some(arguments) = audit_arguments {
+            self.record_audit(mcp_name, tool_name, &arguments, &result);
+        }
 
-        client.call_tool(&mapping.original_name, arguments)
+        result
     }
 
-    /// Get status of all backends
-    pub fn get_backends_info(&self) -> Vec<BackendInfo> {
-        self.backends.values().map(|b| b.to_info()).collect()
+    /// Names of connected backends that offer (and allow) `tool_name`, highest
+    /// priority first, then by backend id
+    pub fn find_backends_with_tool(&self, tool_name: &str) -> Vec<String> {
+        let mut matches: Vec<(i32, i64, String)> = self
+            .backends
+            .iter()
+            .filter(|(_, b)| matches!(b.status, BackendStatus::Connected))
+            .filter(|(_, b)| b.tools.iter().any(|t| t.name == tool_name))
+            .filter(|(id, b)| self.is_tool_exposed(**id, &b.mcp.name, tool_name))
+            .map(|(id, b)| (self.backend_options(*id).priority, *id, b.mcp.name.clone()))
+            .collect();
+        matches.sort_by(|(pa, ia, _), (pb, ib, _)| pb.cmp(pa).then(ia.cmp(ib)));
+        matches.into_iter().map(|(_, _, name)| name).collect()
     }
 
-    /// Get the total number of aggregated tools
-    pub fn tool_count(&self) -> usize {
-        self.tool_index.len()
+    /// Call `tool_name` on the highest-priority connected backend offering it
+    /// (see find_backends_with_tool), as call_tool_on_mcp would
+    pub fn call_tool_on_any(
+        &mut self,
+        tool_name: &str,
+        arguments: serde_json::Value,
+    ) -> Result<ToolCallResult> {
+        let mcp_name = self
+            .find_backends_with_tool(tool_name)
+            .into_iter()
+            .next()
+            .ok_or_else(|| {
+                gateway_error(
+                    GatewayErrorCode::ToolNotExposed,
+                    format!(
+                        "No connected MCP offers tool '{}'. Call load_mcp_tools to connect one first.",
+                        tool_name
+                    ),
+                )
+            })?;
+        self.call_tool_on_mcp(&mcp_name, tool_name, arguments)
     }
 
-    /// Shutdown all backend connections
-    pub fn shutdown(&mut self) {
-        info!("[Gateway] Shutting down all backend connections");
-        for (_mcp_id, backend) in self.backends.iter_mut() {
-            if let Some(client) = backend.client.take() {
-                info!("[Gateway] Closing connection to MCP {}", backend.mcp.name);
-                drop(client);
+    /// Call a tool like call_tool_on_mcp, giving up with RequestCancelled when
+    /// `cancel` fires. Backends that support it are told to stop working on the
+    /// call, and a cancelled call neither falls back nor counts as a failure.
+    pub fn call_tool_on_mcp_cancellable(
+        &mut self,
+        mcp_name: &str,
+        tool_name: &str,
+        arguments: serde_json::Value,
+        cancel: &CancellationToken,
+    ) -> Result<ToolCallResult> {
+        self.active_cancel = Some(cancel.clone());
+        let result = self.call_tool_on_mcp(mcp_name, tool_name, arguments);
+        self.active_cancel = None;
+        result
+    }
+
+    /// Count a call in the usage stats, if a connected backend of that name
+    /// offers the tool. Calls that fail the allow/deny lists, name an unknown
+    /// backend or tool, or have invalid arguments are not counted.
+    fn record_usage(&mut self, mcp_name: &str, tool_name: &str) {
+        let known = self
+            .backends
+            .values()
+            .any(|b| b.mcp.name == mcp_name && b.tools.iter().any(|t| t.name == tool_name));
+        if known {
+            self.usage.record(mcp_name, tool_name, chrono::Utc::now());
+        }
+    }
+
+    /// Reject calls to tools excluded by the backend's allow/deny lists or the
+    /// active tool profile
+    fn ensure_tool_allowed(&self, mcp_name: &str, tool_name: &str) -> Result<()> {
+        let mcp_id = self
+            .available_mcps
+            .iter()
+            .find(|m| m.name == mcp_name)
+            .map(|m| m.id)
+            .or_else(|| {
+                self.backends
+                    .values()
+                    .find(|b| b.mcp.name == mcp_name)
+                    .map(|b| b.mcp.id)
+            });
+
+        match mcp_id {
+            Some(id) if !self.is_tool_exposed(id, mcp_name, tool_name) => {
+                warn!(
+                    "[Gateway] Denied call to tool '{}' on MCP '{}'",
+                    tool_name, mcp_name
+                );
+                Err(gateway_error(
+                    GatewayErrorCode::ToolNotExposed,
+                    format!(
+                        "Permission denied: tool '{}' is not allowed on MCP '{}'",
+                        tool_name, mcp_name
+                    ),
+                ))
             }
-            backend.status = BackendStatus::Disconnected;
+            _ => Ok(()),
         }
-        self.tool_index.clear();
     }
 
-    /// Restart a specific backend
-    pub async fn restart_backend(&mut self, mcp_id: i64) -> Result<BackendInfo> {
-        let gateway_mcp = {
-            let db = self
-                .db
-                .lock()
-                .map_err(|e| anyhow!("Failed to lock database: {}", e))?;
-            db.get_gateway_mcps()?
-                .into_iter()
-                .find(|gm| gm.mcp_id == mcp_id)
-                .ok_or_else(|| anyhow!("MCP {} not found in gateway", mcp_id))?
+    /// Validate arguments against the tool's cached input schema, for backends
+    /// with validate_arguments set. The schema is the backend's, so arguments
+    /// are checked after arg_transform renames them, as they will be sent.
+    /// Unknown tools and tools without a schema pass.
+    fn check_arguments(
+        &self,
+        mcp_name: &str,
+        tool_name: &str,
+        arguments: &serde_json::Value,
+    ) -> Result<()> {
+        let Some(backend) = self.backends.values().find(|b| b.mcp.name == mcp_name) else {
+            return Ok(());
         };
+        let options = self.backend_options(backend.mcp.id);
+        if !options.validate_arguments {
+            return Ok(());
+        }
 
-        // Remove old backend if exists
-        if let Some(mut backend) = self.backends.remove(&mcp_id) {
-            if let Some(client) = backend.client.take() {
-                drop(client);
+        let schema = backend
+            .tools
+            .iter()
+            .find(|t| t.name == tool_name)
+            .and_then(|t| t.input_schema.as_ref());
+        let arguments = options.arg_transform.apply_to_arguments(arguments.clone());
+        match schema {
+            Some(schema) => {
+                validate_json(schema, &arguments, SchemaChecks::default()).map_err(|reason| {
+                    gateway_error(
+                        GatewayErrorCode::InvalidArgs,
+                        format!("invalid arguments: {}", reason),
+                    )
+                })
             }
+            None => Ok(()),
         }
+    }
 
-        // Re-add the backend
-        self.add_backend(gateway_mcp).await;
+    /// Whether results of `tool_name` on a connected MCP may be served from cache
+    fn is_cacheable_call(&self, mcp_name: &str, tool_name: &str) -> bool {
+        self.backends
+            .values()
+            .find(|b| b.mcp.name == mcp_name)
+            .is_some_and(|b| self.backend_options(b.mcp.id).is_cacheable(tool_name))
+    }
+
+    /// Call several tools at once, returning results in request order.
+    ///
+    /// Calls are grouped by MCP: different MCPs are called concurrently, calls
+    /// to the same MCP run one after another in request order (a backend
+    /// client handles one request at a time). A failed call only fails its
+    /// own slot. Caching and auditing apply as for call_tool_on_mcp.
+    pub fn call_tools_batch(&mut self, calls: &[BatchCall]) -> Vec<Result<ToolCallResult>> {
+        let calls: Vec<BatchCall> = calls
+            .iter()
+            .map(|call| BatchCall {
+                mcp_name: self.resolve_backend_name(&call.mcp_name),
+                ..call.clone()
+            })
+            .collect();
+        let calls = calls.as_slice();
+        let mut results: Vec<Option<Result<ToolCallResult>>> = calls.iter().map(|_| None).collect();
+
+        let mut groups: HashMap<&str, Vec<usize>> = HashMap::new();
+        for (index, call) in calls.iter().enumerate() {
+            let mcp_name = call.mcp_name.as_str();
+            if let Err(e) = self.ensure_tool_allowed(mcp_name, &call.tool_name) {
+                results[index] = Some(Err(e));
+                continue;
+            }
+            if !groups.contains_key(mcp_name) {
+                if let Err(e) = self.reconnect_if_evicted(mcp_name) {
+                    results[index] = Some(Err(e));
+                    continue;
+                }
+            }
+            if let Err(e) = self.check_arguments(mcp_name, &call.tool_name, &call.arguments) {
+                results[index] = Some(Err(e));
+                continue;
+            }
+            self.record_usage(mcp_name, &call.tool_name);
+
+            if self.is_cacheable_call(mcp_name, &call.tool_name) {
+                if let Some(cached) =
+                    self.result_cache
+                        .get(mcp_name, &call.tool_name, &call.arguments)
+                {
+                    results[index] = Some(Ok(cached));
+                    continue;
+                }
+            }
+            if let Err(e) = self.circuit_breaker.check(mcp_name) {
+                results[index] = Some(Err(e));
+                continue;
+            }
+
+            groups.entry(mcp_name).or_default().push(index);
+        }
+
+        let transforms: HashMap<i64, ArgTransform> = self
+            .backends
+            .keys()
+            .map(|&id| (id, self.backend_options(id).arg_transform))
+            .filter(|(_, t)| !t.is_empty())
+            .collect();
+
+        // Connected backends run their group on a thread each; the rest are
+        // dispatched afterwards so they produce the usual not-connected errors
+        let dispatched: Vec<(usize, Result<ToolCallResult>)> = std::thread::scope(|scope| {
+            let handles: Vec<_> = self
+                .backends
+                .values_mut()
+                .filter(|b| matches!(b.status, BackendStatus::Connected) && b.client.is_some())
+                .filter_map(|backend| {
+                    let indices = groups.remove(backend.mcp.name.as_str())?;
+                    let client = backend.client.as_mut()?;
+                    let transform = transforms.get(&backend.mcp.id).cloned().unwrap_or_default();
+                    backend.last_used = Instant::now();
+                    info!(
+                        "[Gateway] Batch calling {} tool(s) on MCP '{}'",
+                        indices.len(),
+                        backend.mcp.name
+                    );
+                    Some(scope.spawn(move || {
+                        indices
+                            .into_iter()
+                            .map(|index| {
+                                let call = &calls[index];
+                                let result = client
+                                    .call_tool(
+                                        &call.tool_name,
+                                        transform.apply_to_arguments(call.arguments.clone()),
+                                    )
+                                    .map(|r| transform.apply_inverse_to_result(r));
+                                (index, result)
+                            })
+                            .collect::<Vec<_>>()
+                    }))
+                })
+                .collect();
+
+            handles
+                .into_iter()
+                .flat_map(|handle| handle.join().unwrap_or_else(|_| Vec::new()))
+                .collect()
+        });
+
+        for (index, result) in dispatched {
+            let call = &calls[index];
+            self.circuit_breaker.record(&call.mcp_name, result.is_ok());
+            if let Ok(r) = &result {
+                if r.success
+                    && !r.is_error
+                    && self.is_cacheable_call(&call.mcp_name, &call.tool_name)
+                {
+                    self.result_cache.insert(
+                        &call.mcp_name,
+                        &call.tool_name,
+                        &call.arguments,
+                        r.clone(),
+                    );
+                }
+            }
+            results[index] = Some(result);
+        }
+
+        for index in groups.into_values().flatten() {
+            let call = &calls[index];
+            results[index] = Some(self.dispatch_tool_call(
+                &call.mcp_name,
+                &call.tool_name,
+                call.arguments.clone(),
+            ));
+        }
+
+        let results: Vec<Result<ToolCallResult>> = results
+            .into_iter()
+            .map(|r| r.unwrap_or_else(|| Err(anyhow!("Backend call panicked"))))
+            .collect();
+
+        if self.audit_log.is_some() {
+            for (call, result) in calls.iter().zip(&results) {
+                self.record_audit(&call.mcp_name, &call.tool_name, &call.arguments, result);
+            }
+        }
+
+        results
+    }
+
+    /// Reconnect a backend that the idle sweeper disconnected
+    fn reconnect_if_evicted(&mut self, mcp_name: &str) -> Result<()> {
+        let Some(mcp_id) = self
+            .available_mcps
+            .iter()
+            .find(|m| m.name == mcp_name)
+            .map(|m| m.id)
+            .filter(|id| self.evicted.contains(id))
+        else {
+            return Ok(());
+        };
+
+        info!("[Gateway] Reconnecting idle-evicted MCP '{}'", mcp_name);
+        let mcp = self.resolve_mcp_config(mcp_id)?;
+        self.add_backend(mcp);
         self.build_tool_index();
+        Ok(())
+    }
 
-        self.backends
-            .get(&mcp_id)
-            .map(|b| b.to_info())
-            .ok_or_else(|| anyhow!("Failed to restart backend"))
+    /// Disconnect connected backends that have been unused for longer than the
+    /// idle timeout, skipping warmup backends. Returns the evicted MCP names.
+    pub fn evict_idle_backends(&mut self) -> Vec<String> {
+        let Some(idle_timeout) = self.idle_timeout else {
+            return Vec::new();
+        };
+
+        let idle_ids: Vec<i64> = self
+            .backends
+            .values()
+            .filter(|b| {
+                matches!(b.status, BackendStatus::Connected)
+                    && b.last_used.elapsed() >= idle_timeout
+                    && !self.backend_options(b.mcp.id).warmup
+            })
+            .map(|b| b.mcp.id)
+            .collect();
+
+        let mut evicted = Vec::new();
+        for mcp_id in idle_ids {
+            if let Some(mut backend) = self.backends.remove(&mcp_id) {
+                info!("[Gateway] Evicting idle MCP '{}'", backend.mcp.name);
+                backend.close();
+                self.result_cache.invalidate_mcp(&backend.mcp.name);
+                self.evicted.insert(mcp_id);
+                evicted.push(backend.mcp.name);
+            }
+        }
+
+        if !evicted.is_empty() {
+            self.build_tool_index();
+        }
+        evicted
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    /// Invocation count and last call time of every tool of the connected
+    /// backends (zero if never called) and of every tool called so far.
+    /// Every call requested counts, whatever its outcome.
+    pub fn usage_stats(&self) -> Vec<ToolUsage> {
+        let known = self
+            .backends
+            .values()
+            .filter(|b| matches!(b.status, BackendStatus::Connected))
+            .flat_map(|b| {
+                b.tools
+                    .iter()
+                    .map(|t| (b.mcp.name.as_str(), t.name.as_str()))
+            });
+        self.usage.stats(known)
+    }
+
+    /// Keepalive interval of a backend: only HTTP backends with an interval
+    /// configured get one, and not when idle eviction would come first
+    fn keepalive_interval(&self, backend: &BackendConnection) -> Option<Duration> {
+        if backend.mcp.mcp_type != "http" {
+            return None;
+        }
+        let options = self.backend_options(backend.mcp.id);
+        let interval = Duration::from_secs(options.keepalive_interval_secs?);
+        let evicted_first =
+            !options.warmup && self.idle_timeout.is_some_and(|timeout| timeout <= interval);
+        (!interval.is_zero() && !evicted_first).then_some(interval)
+    }
+
+    /// Ping every connected HTTP backend whose keepalive interval has passed
+    /// since its last call or ping, as of `now`. Returns the names pinged.
+    pub fn send_keepalives(&mut self, now: Instant) -> Vec<String> {
+        let due: Vec<i64> = self
+            .backends
+            .values()
+            .filter(|b| matches!(b.status, BackendStatus::Connected))
+            .filter(|b| {
+                let last_active = b.last_ping.map_or(b.last_used, |p| p.max(b.last_used));
+                self.keepalive_interval(b)
+                    .is_some_and(|interval| now.saturating_duration_since(last_active) >= interval)
+            })
+            .map(|b| b.mcp.id)
+            .collect();
+
+        let mut pinged = Vec::new();
+        for mcp_id in due {
+            let Some(backend) = self.backends.get_mut(&mcp_id) else {
+                continue;
+            };
+            let Some(client) = backend.client.as_mut() else {
+                continue;
+            };
+            if let Err(e) = client.ping() {
+                warn!(
+                    "[Gateway] Keepalive ping to MCP '{}' failed: {}",
+                    backend.mcp.name, e
+                );
+            }
+            backend.last_ping = Some(now);
+            pinged.push(backend.mcp.name.clone());
+        }
+        pinged
+    }
+
+    /// Serve a cacheable tool call from cache, or dispatch it and cache a successful result
+    fn cached_tool_call(
+        &mut self,
+        mcp_name: &str,
+        tool_name: &str,
+        arguments: serde_json::Value,
+    ) -> Result<ToolCallResult> {
+        if let Some(cached) = self.result_cache.get(mcp_name, tool_name, &arguments) {
+            info!(
+                "[Gateway] Cache hit for tool '{}' on MCP '{}'",
+                tool_name, mcp_name
+            );
+            return Ok(cached);
+        }
+
+        let result = self.dispatch_tool_call(mcp_name, tool_name, arguments.clone())?;
+        if result.success && !result.is_error {
+            self.result_cache
+                .insert(mcp_name, tool_name, &arguments, result.clone());
+        }
+        Ok(result)
+    }
+
+    /// Append an audit entry for a tool call (failures are logged, never propagated)
+    fn record_audit(
+        &self,
+        mcp_name: &str,
+        tool_name: &str,
+        arguments: &serde_json::Value,
+        result: &Result<ToolCallResult>,
+    ) {
+        let Some(audit_log) = &self.audit_log else {
+            return;
+        };
+
+        let mcp_id = self
+            .backends
+            .values()
+            .find(|b| b.mcp.name == mcp_name)
+            .map(|b| b.mcp.id)
+            .or_else(|| {
+                self.available_mcps
+                    .iter()
+                    .find(|m| m.name == mcp_name)
+                    .map(|m| m.id)
+            })
+            .unwrap_or_default();
+
+        let outcome = match result {
+            Ok(r) if r.is_error => AuditOutcome::ToolError,
+            Ok(_) => AuditOutcome::Success,
+            Err(e) => AuditOutcome::Failed(e.to_string()),
+        };
+
+        let entry = AuditEntry::new(mcp_id, mcp_name, tool_name, arguments, outcome);
+        if let Err(e) = audit_log.record(&entry) {
+            warn!("[Gateway] Failed to write audit log entry: {}", e);
+        }
+    }
+
+    /// Forward a tool call to a connected backend, retrying on the backend's
+    /// fallbacks if it fails. The primary's error is returned when no fallback
+    /// can serve the call.
+    fn dispatch_tool_call(
+        &mut self,
+        mcp_name: &str,
+        tool_name: &str,
+        arguments: serde_json::Value,
+    ) -> Result<ToolCallResult> {
+        let fallbacks = self.fallbacks_for(mcp_name, tool_name);
+        if fallbacks.is_empty() {
+            return self.dispatch_to_backend(mcp_name, tool_name, arguments);
+        }
+
+        let primary_error = match self.dispatch_to_backend(mcp_name, tool_name, arguments.clone()) {
+            Ok(result) => return Ok(result),
+            Err(e) if e.is::<RequestCancelled>() => return Err(e),
+            Err(e) => e,
+        };
+
+        for fallback in fallbacks {
+            warn!(
+                "[Gateway] Tool '{}' on MCP '{}' failed ({}), falling back to '{}'",
+                tool_name, mcp_name, primary_error, fallback
+            );
+            match self.dispatch_to_backend(&fallback, tool_name, arguments.clone()) {
+                Ok(result) => return Ok(result),
+                Err(e) => warn!("[Gateway] Fallback MCP '{}' failed: {}", fallback, e),
+            }
+        }
+
+        Err(primary_error)
+    }
+
+    /// Names of connected fallback backends that offer `tool_name`, in configured order
+    fn fallbacks_for(&self, mcp_name: &str, tool_name: &str) -> Vec<String> {
+        let Some(mcp_id) = self
+            .backends
+            .values()
+            .find(|b| b.mcp.name == mcp_name)
+            .map(|b| b.mcp.id)
+            .or_else(|| {
+                self.available_mcps
+                    .iter()
+                    .find(|m| m.name == mcp_name)
+                    .map(|m| m.id)
+            })
+        else {
+            return Vec::new();
+        };
+
+        self.backend_options(mcp_id)
+            .fallbacks
+            .iter()
+            .filter(|id| **id != mcp_id)
+            .filter_map(|id| self.backends.get(id))
+            .filter(|b| matches!(b.status, BackendStatus::Connected))
+            .filter(|b| b.tools.iter().any(|t| t.name == tool_name))
+            .filter(|b| self.is_tool_exposed(b.mcp.id, &b.mcp.name, tool_name))
+            .map(|b| b.mcp.name.clone())
+            .collect()
+    }
+
+    /// Forward a tool call to one connected backend
+    fn dispatch_to_backend(
+        &mut self,
+        mcp_name: &str,
+        tool_name: &str,
+        arguments: serde_json::Value,
+    ) -> Result<ToolCallResult> {
+        self.circuit_breaker.check(mcp_name)?;
+
+        let transform = self
+            .backends
+            .values()
+            .find(|b| b.mcp.name == mcp_name)
+            .map(|b| self.backend_options(b.mcp.id).arg_transform)
+            .unwrap_or_default();
+        let cancel = self.active_cancel.clone();
+
+        // Find the backend by MCP name
+        let backend = self
+            .backends
+            .values_mut()
+            .find(|b| b.mcp.name == mcp_name)
+            .ok_or_else(|| {
+                gateway_error(
+                    GatewayErrorCode::BackendNotFound,
+                    format!(
+                        "MCP '{}' is not connected. Call load_mcp_tools first to connect.",
+                        mcp_name
+                    ),
+                )
+            })?;
+
+        if !matches!(backend.status, BackendStatus::Connected) {
+            return Err(anyhow!(
+                "MCP '{}' is not connected (status: {:?}). Call load_mcp_tools first.",
+                mcp_name,
+                backend.status
+            ));
+        }
+
+        backend.last_used = Instant::now();
+        let client = backend
+            .client
+            .as_mut()
+            .ok_or_else(|| anyhow!("MCP '{}' has no active client", mcp_name))?;
+
+        info!(
+            "[Gateway] Calling tool '{}' on MCP '{}'",
+            tool_name, mcp_name
+        );
+
+        let arguments = transform.apply_to_arguments(arguments);
+        let result = match &cancel {
+            Some(cancel) => client.call_tool_cancellable(tool_name, arguments, cancel),
+            None => client.call_tool(tool_name, arguments),
+        }
+        .map(|r| transform.apply_inverse_to_result(r));
+        if !result.as_ref().is_err_and(|e| e.is::<RequestCancelled>()) {
+            self.circuit_breaker.record(mcp_name, result.is_ok());
+        }
+        result
+    }
+
+    /// Call a tool on the appropriate backend (legacy method for namespaced tools)
+    #[allow(dead_code)]
+    pub fn call_tool(
+        &mut self,
+        namespaced_name: &str,
+        arguments: serde_json::Value,
+    ) -> Result<ToolCallResult> {
+        let mapping = self
+            .tool_index
+            .get(namespaced_name)
+            .ok_or_else(|| {
+                gateway_error(
+                    GatewayErrorCode::ToolNotExposed,
+                    format!("Unknown tool: {}", namespaced_name),
+                )
+            })?
+            .clone();
+        self.ensure_tool_allowed(&mapping.mcp_name, &mapping.original_name)?;
+        self.check_arguments(&mapping.mcp_name, &mapping.original_name, &arguments)?;
+        let transform = self.backend_options(mapping.mcp_id).arg_transform;
+        self.circuit_breaker.check(&mapping.mcp_name)?;
+
+        let backend = self.backends.get_mut(&mapping.mcp_id).ok_or_else(|| {
+            gateway_error(
+                GatewayErrorCode::BackendNotFound,
+                format!("Backend not found for MCP {}", mapping.mcp_name),
+            )
+        })?;
+
+        if !matches!(backend.status, BackendStatus::Connected) {
+            return Err(anyhow!(
+                "Backend {} is not connected (status: {:?})",
+                mapping.mcp_name,
+                backend.status
+            ));
+        }
+
+        let client = backend
+            .client
+            .as_mut()
+            .ok_or_else(|| anyhow!("Backend {} has no active client", mapping.mcp_name))?;
+
+        info!(
+            "[Gateway] Calling tool {} on backend {}",
+            mapping.original_name, mapping.mcp_name
+        );
+
+        let result = client
+            .call_tool(
+                &mapping.original_name,
+                transform.apply_to_arguments(arguments),
+            )
+            .map(|r| transform.apply_inverse_to_result(r));
+        self.circuit_breaker
+            .record(&mapping.mcp_name, result.is_ok());
+        result
+    }
+
+    /// Get status of all backends
+    pub fn get_backends_info(&self) -> Vec<BackendInfo> {
+        self.backends.values().map(|b| b.to_info()).collect()
+    }
+
+    /// Ids of backends with a live connection, sorted. Doesn't probe or
+    /// connect anything.
+    pub fn connected_backend_ids(&self) -> Vec<BackendId> {
+        let mut ids: Vec<BackendId> = self
+            .backends
+            .iter()
+            .filter(|(_, b)| matches!(b.status, BackendStatus::Connected) && b.client.is_some())
+            .map(|(id, _)| *id)
+            .collect();
+        ids.sort();
+        ids
+    }
+
+    /// Get the total number of aggregated tools
+    pub fn tool_count(&self) -> usize {
+        self.tool_index.len()
+    }
+
+    /// Shutdown all backend connections
+    pub fn shutdown(&mut self) {
+        info!("[Gateway] Shutting down all backend connections");
+        for backend in self.backends.values_mut() {
+            backend.close();
+        }
+        self.tool_index.clear();
+        self.result_cache.clear();
+        self.evicted.clear();
+    }
+
+    /// Restart a specific backend
+    pub async fn restart_backend(&mut self, mcp_id: i64) -> Result<BackendInfo> {
+        let mcp = self.resolve_mcp_config(mcp_id)?;
+
+        // Remove old backend if exists
+        if let Some(mut backend) = self.backends.remove(&mcp_id) {
+            backend.close();
+        }
+        self.result_cache.invalidate_mcp(&mcp.name);
+        self.circuit_breaker.reset(&mcp.name);
+
+        // Re-add the backend
+        self.add_backend(mcp);
+        self.build_tool_index();
+
+        self.backends
+            .get(&mcp_id)
+            .map(|b| b.to_info())
+            .ok_or_else(|| anyhow!("Failed to restart backend"))
+    }
+}
+
+/// Connect every warmup-flagged backend, retrying failures per each backend's policy.
+/// The manager is locked per attempt, never across a backoff sleep.
+pub async fn run_warmup(backend_manager: Arc<tokio::sync::Mutex<GatewayBackendManager>>) {
+    let targets = backend_manager.lock().await.warmup_targets();
+    if targets.is_empty() {
+        return;
+    }
+
+    info!("[Gateway] Warming up {} backend(s)", targets.len());
+
+    for (mcp_name, retry) in targets {
+        let max_attempts = retry.max_attempts.max(1);
+        for attempt in 1..=max_attempts {
+            let result = backend_manager.lock().await.warmup_backend(&mcp_name).await;
+
+            match result {
+                Ok(()) => {
+                    info!("[Gateway] Warmed up MCP '{}'", mcp_name);
+                    break;
+                }
+                Err(e) if attempt < max_attempts => {
+                    let delay = retry.backoff_for_attempt(attempt);
+                    warn!(
+                        "[Gateway] Warmup of '{}' failed (attempt {}/{}): {}. Retrying in {:?}",
+                        mcp_name, attempt, max_attempts, e, delay
+                    );
+                    tokio::time::sleep(delay).await;
+                }
+                Err(e) => {
+                    error!(
+                        "[Gateway] Warmup of '{}' failed after {} attempts: {}",
+                        mcp_name, max_attempts, e
+                    );
+                }
+            }
+        }
+    }
+}
+
+/// Forward a batch of tool calls (see call_tools_batch) for a session with
+/// tool profile `profile` (see lock_for_session), returning each result as
+/// JSON in request order
+pub async fn invoke_batch(
+    backend_manager: &tokio::sync::Mutex<GatewayBackendManager>,
+    profile: Option<&str>,
+    calls: Vec<BatchCall>,
+) -> Vec<Result<serde_json::Value>> {
+    let results = lock_for_session(backend_manager, profile)
+        .await
+        .call_tools_batch(&calls);
+    results
+        .into_iter()
+        .map(|result| result.and_then(|r| Ok(serde_json::to_value(r)?)))
+        .collect()
+}
+
+/// Lock the manager for a call from a session whose chosen tool profile is
+/// `profile`, so the session sees only that profile's catalog while other
+/// sessions keep theirs. A profile that no longer exists exposes everything.
+pub async fn lock_for_session<'a>(
+    backend_manager: &'a tokio::sync::Mutex<GatewayBackendManager>,
+    profile: Option<&str>,
+) -> tokio::sync::MutexGuard<'a, GatewayBackendManager> {
+    let mut manager = backend_manager.lock().await;
+    manager.apply_session_profile(profile);
+    manager
+}
+
+/// Periodically ping HTTP backends due a keepalive. Runs until the task is aborted.
+pub async fn run_keepalive(
+    backend_manager: Arc<tokio::sync::Mutex<GatewayBackendManager>>,
+    interval: Duration,
+) {
+    loop {
+        tokio::time::sleep(interval).await;
+        let pinged = backend_manager.lock().await.send_keepalives(Instant::now());
+        if !pinged.is_empty() {
+            log::debug!("[Gateway] Sent keepalive to: {}", pinged.join(", "));
+        }
+    }
+}
+
+/// Forward one tool call (see call_tool_on_mcp_cancellable) for a session
+/// with tool profile `profile` (see lock_for_session) that `cancel` can abort.
+/// The call resolves with RequestCancelled as soon as the token fires; the
+/// manager stays locked until the backend gives up on the call.
+pub async fn invoke_tool(
+    backend_manager: Arc<tokio::sync::Mutex<GatewayBackendManager>>,
+    profile: Option<String>,
+    mcp_name: String,
+    tool_name: String,
+    arguments: serde_json::Value,
+    cancel: CancellationToken,
+) -> Result<ToolCallResult> {
+    let call = {
+        let cancel = cancel.clone();
+        tokio::task::spawn_blocking(move || {
+            let mut manager = backend_manager.blocking_lock();
+            manager.apply_session_profile(profile.as_deref());
+            manager.call_tool_on_mcp_cancellable(&mcp_name, &tool_name, arguments, &cancel)
+        })
+    };
+
+    tokio::select! {
+        result = call => result.map_err(|e| anyhow!("Tool call task failed: {}", e))?,
+        _ = cancel.cancelled() => Err(RequestCancelled.into()),
+    }
+}
+
+/// Periodically disconnect idle backends. Runs until the task is aborted.
+pub async fn run_idle_sweeper(
+    backend_manager: Arc<tokio::sync::Mutex<GatewayBackendManager>>,
+    interval: Duration,
+) {
+    loop {
+        tokio::time::sleep(interval).await;
+        let evicted = backend_manager.lock().await.evict_idle_backends();
+        if !evicted.is_empty() {
+            info!("[Gateway] Evicted idle backend(s): {}", evicted.join(", "));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::mcp_client::ToolContent;
+
+    /// Backend client that answers every call with a text result naming the tool
+    struct MockClient;
+
+    impl BackendClient for MockClient {
+        fn call_tool(
+            &mut self,
+            name: &str,
+            _arguments: serde_json::Value,
+        ) -> Result<ToolCallResult> {
+            Ok(ToolCallResult {
+                success: true,
+                content: vec![ToolContent::Text {
+                    text: format!("called {}", name),
+                }],
+                is_error: false,
+                error: None,
+                execution_time_ms: 0,
+            })
+        }
+    }
+
+    /// Backend client that counts calls and echoes the arguments back
+    struct CountingClient {
+        calls: Arc<std::sync::atomic::AtomicU32>,
+    }
+
+    impl BackendClient for CountingClient {
+        fn call_tool(
+            &mut self,
+            _name: &str,
+            arguments: serde_json::Value,
+        ) -> Result<ToolCallResult> {
+            self.calls.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            Ok(ToolCallResult {
+                success: true,
+                content: vec![ToolContent::Text {
+                    text: arguments.to_string(),
+                }],
+                is_error: false,
+                error: None,
+                execution_time_ms: 0,
+            })
+        }
+    }
+
+    /// Backend client that remembers the arguments of the last call
+    struct RecordingClient {
+        last_arguments: Arc<Mutex<Option<serde_json::Value>>>,
+    }
+
+    impl BackendClient for RecordingClient {
+        fn call_tool(
+            &mut self,
+            _name: &str,
+            arguments: serde_json::Value,
+        ) -> Result<ToolCallResult> {
+            *self.last_arguments.lock().unwrap() = Some(arguments.clone());
+            Ok(ToolCallResult {
+                success: true,
+                content: vec![ToolContent::Text {
+                    text: arguments.to_string(),
+                }],
+                is_error: false,
+                error: None,
+                execution_time_ms: 0,
+            })
+        }
+    }
+
+    /// Backend client whose calls run until cancelled (or give up and succeed
+    /// after a few seconds), recording the cancel notifications it receives
+    struct SlowClient {
+        cancellations: Arc<std::sync::atomic::AtomicU32>,
+    }
+
+    impl BackendClient for SlowClient {
+        fn call_tool(
+            &mut self,
+            name: &str,
+            arguments: serde_json::Value,
+        ) -> Result<ToolCallResult> {
+            std::thread::sleep(Duration::from_secs(3));
+            MockClient.call_tool(name, arguments)
+        }
+
+        fn call_tool_cancellable(
+            &mut self,
+            name: &str,
+            arguments: serde_json::Value,
+            cancel: &CancellationToken,
+        ) -> Result<ToolCallResult> {
+            let cancellations = Arc::clone(&self.cancellations);
+            cancel.on_cancel(move || {
+                cancellations.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            });
+            let start = Instant::now();
+            while start.elapsed() < Duration::from_secs(3) {
+                if cancel.is_cancelled() {
+                    return Err(RequestCancelled.into());
+                }
+                std::thread::sleep(Duration::from_millis(10));
+            }
+            MockClient.call_tool(name, arguments)
+        }
+    }
+
+    /// Backend client that counts how often it is closed
+    struct ClosingClient {
+        closes: Arc<std::sync::atomic::AtomicU32>,
+    }
+
+    impl BackendClient for ClosingClient {
+        fn call_tool(
+            &mut self,
+            name: &str,
+            arguments: serde_json::Value,
+        ) -> Result<ToolCallResult> {
+            MockClient.call_tool(name, arguments)
+        }
+
+        fn close(self: Box<Self>) {
+            self.closes
+                .fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+        }
+    }
+
+    /// Backend client that counts keepalive pings
+    struct PingClient {
+        pings: Arc<std::sync::atomic::AtomicU32>,
+    }
+
+    impl BackendClient for PingClient {
+        fn call_tool(
+            &mut self,
+            name: &str,
+            arguments: serde_json::Value,
+        ) -> Result<ToolCallResult> {
+            MockClient.call_tool(name, arguments)
+        }
+
+        fn ping(&mut self) -> Result<()> {
+            self.pings.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            Ok(())
+        }
+    }
+
+    fn test_mcp(id: i64, name: &str) -> Mcp {
+        Mcp {
+            id,
+            name: name.to_string(),
+            description: None,
+            mcp_type: "stdio".to_string(),
+            command: Some("echo".to_string()),
+            args: None,
+            url: None,
+            headers: None,
+            env: None,
+            icon: None,
+            tags: None,
+            source: "manual".to_string(),
+            source_path: None,
+            is_enabled_global: false,
+            is_favorite: false,
+            created_at: "2024-01-01".to_string(),
+            updated_at: "2024-01-01".to_string(),
+        }
+    }
+
+    fn test_tool(name: &str, description: &str) -> McpTool {
+        McpTool {
+            name: name.to_string(),
+            description: Some(description.to_string()),
+            input_schema: None,
+        }
+    }
+
+    fn test_manager() -> GatewayBackendManager {
+        let db = Database::in_memory().unwrap();
+        let mut manager = GatewayBackendManager::new(Arc::new(Mutex::new(db)));
+        manager.set_allow_backend_registration(true);
+        manager
+    }
+
+    /// Insert a backend that is already connected with the given tools
+    fn insert_connected(
+        manager: &mut GatewayBackendManager,
+        id: i64,
+        name: &str,
+        tools: Vec<McpTool>,
+    ) {
+        let mut backend = BackendConnection::new(test_mcp(id, name));
+        backend.status = BackendStatus::Connected;
+        backend.client = Some(Box::new(MockClient));
+        backend.tools = tools;
+        manager.backends.insert(id, backend);
+    }
+
+    #[test]
+    fn test_capability_listings_per_backend() {
+        let mut manager = test_manager();
+        insert_connected(
+            &mut manager,
+            2,
+            "docs",
+            vec![test_tool("search_docs", "Search docs")],
+        );
+        let docs = manager.backends.get_mut(&2).unwrap();
+        docs.resources = Some(vec![McpResource {
+            uri: "docs://index".to_string(),
+            name: "index".to_string(),
+            description: None,
+            mime_type: Some("text/markdown".to_string()),
+        }]);
+        docs.prompts = Some(vec![McpPrompt {
+            name: "summarize".to_string(),
+            description: Some("Summarize a page".to_string()),
+            arguments: Vec::new(),
+        }]);
+        insert_connected(
+            &mut manager,
+            1,
+            "filesystem",
+            vec![test_tool("read_file", "Read")],
+        );
+
+        let tools = manager.list_tools_by_backend();
+        assert_eq!(tools.len(), 2);
+        assert_eq!(tools[0].mcp_name, "filesystem");
+        assert_eq!(tools[1].items[0].name, "search_docs");
+
+        let resources = manager.list_resources_by_backend();
+        assert_eq!(resources.len(), 1);
+        assert_eq!(resources[0].mcp_name, "docs");
+        assert_eq!(resources[0].items[0].uri, "docs://index");
+
+        let prompts = manager.list_prompts_by_backend();
+        assert_eq!(prompts.len(), 1);
+        assert_eq!(prompts[0].items[0].name, "summarize");
+    }
+
+    #[test]
+    fn test_capability_listings_skip_disconnected_backends() {
+        let mut manager = test_manager();
+        insert_connected(
+            &mut manager,
+            1,
+            "docs",
+            vec![test_tool("search_docs", "Search docs")],
+        );
+        let docs = manager.backends.get_mut(&1).unwrap();
+        docs.resources = Some(Vec::new());
+        docs.status = BackendStatus::Disconnected;
+
+        assert!(manager.list_tools_by_backend().is_empty());
+        assert!(manager.list_resources_by_backend().is_empty());
+    }
+
+    #[test]
+    fn test_call_tool_on_mcp_writes_audit_log() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let audit_path = temp_dir.path().join("audit.jsonl");
+        let mut manager = test_manager();
+        manager.set_audit_log(Some(AuditLogConfig::new(audit_path.clone())));
+        insert_connected(
+            &mut manager,
+            1,
+            "filesystem",
+            vec![test_tool("read_file", "Read")],
+        );
+
+        manager
+            .call_tool_on_mcp("filesystem", "read_file", serde_json::json!({"path": "a"}))
+            .unwrap();
+        let _ = manager.call_tool_on_mcp("missing", "read_file", serde_json::json!({}));
+
+        let content = std::fs::read_to_string(&audit_path).unwrap();
+        let entries: Vec<AuditEntry> = content
+            .lines()
+            .map(|l| serde_json::from_str(l).unwrap())
+            .collect();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].mcp_id, 1);
+        assert_eq!(entries[0].tool_name, "read_file");
+        assert_eq!(entries[0].outcome, AuditOutcome::Success);
+        assert!(matches!(entries[1].outcome, AuditOutcome::Failed(_)));
+    }
+
+    fn registration(name: &str) -> BackendRegistration {
+        serde_json::from_value(serde_json::json!({
+            "name": name,
+            "command": "npx local-server"
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_register_backend_appears_in_available_mcps() {
+        let mut manager = test_manager();
+
+        let id = manager
+            .register_backend(registration("local-server"))
+            .unwrap();
+        assert!(id < 0);
+
+        let available = manager.get_available_mcps();
+        let registered = available.iter().find(|m| m.id == id).unwrap();
+        assert_eq!(registered.name, "local-server");
+        assert_eq!(registered.mcp_type, "stdio");
+        assert_eq!(registered.status, BackendStatus::Disconnected);
+        // Registration never connects
+        assert!(manager.get_backends_info().is_empty());
+    }
+
+    #[test]
+    fn test_register_backend_duplicate_name_errors() {
+        let mut manager = test_manager();
+        manager
+            .register_backend(registration("local-server"))
+            .unwrap();
+
+        let err = manager
+            .register_backend(registration("local-server"))
+            .unwrap_err();
+        assert!(err.to_string().contains("already registered"));
+    }
+
+    #[test]
+    fn test_register_backend_validates_config() {
+        let mut manager = test_manager();
+
+        let missing_command: BackendRegistration =
+            serde_json::from_value(serde_json::json!({"name": "broken"})).unwrap();
+        assert!(manager.register_backend(missing_command).is_err());
+
+        let bad_type: BackendRegistration =
+            serde_json::from_value(serde_json::json!({"name": "broken", "type": "carrier-pigeon"}))
+                .unwrap();
+        assert!(manager.register_backend(bad_type).is_err());
+
+        for command in [
+            "npx server; rm -rf ~",
+            "server $(whoami)",
+            "npx\nserver",
+            "../bin/server",
+            "bin/server",
+        ] {
+            let unsafe_command: BackendRegistration =
+                serde_json::from_value(serde_json::json!({"name": "sneaky", "command": command}))
+                    .unwrap();
+            assert!(
+                manager.register_backend(unsafe_command).is_err(),
+                "accepted {:?}",
+                command
+            );
+        }
+        let absolute: BackendRegistration = serde_json::from_value(
+            serde_json::json!({"name": "pinned", "command": "/usr/local/bin/server"}),
+        )
+        .unwrap();
+        assert!(manager.register_backend(absolute).is_ok());
+    }
+
+    #[test]
+    fn test_register_backend_disabled_by_default() {
+        let db = Database::in_memory().unwrap();
+        let mut manager = GatewayBackendManager::new(Arc::new(Mutex::new(db)));
+
+        let err = manager
+            .register_backend(registration("local-server"))
+            .unwrap_err();
+
+        assert!(err.to_string().contains("registration is disabled"));
+        assert!(manager.get_available_mcps().is_empty());
+    }
+
+    #[test]
+    fn test_register_backend_survives_reload() {
+        let mut manager = test_manager();
+        let id = manager
+            .register_backend(registration("local-server"))
+            .unwrap();
+
+        manager.load_available_mcps().unwrap();
+
+        assert!(manager.get_available_mcps().iter().any(|m| m.id == id));
+        assert_eq!(manager.resolve_mcp_config(id).unwrap().name, "local-server");
+    }
+
+    #[test]
+    fn test_call_tool_on_any_picks_backend_offering_tool() {
+        let mut manager = test_manager();
+        insert_connected(
+            &mut manager,
+            1,
+            "docs",
+            vec![test_tool("search_docs", "Search docs")],
+        );
+        insert_connected(
+            &mut manager,
+            2,
+            "web",
+            vec![test_tool("fetch_url", "Fetch a URL")],
+        );
+
+        assert_eq!(manager.find_backends_with_tool("fetch_url"), vec!["web"]);
+        let result = manager
+            .call_tool_on_any("fetch_url", serde_json::json!({}))
+            .unwrap();
+        match &result.content[0] {
+            ToolContent::Text { text } => assert_eq!(text, "called fetch_url"),
+            other => panic!("unexpected content: {:?}", other),
+        }
+
+        assert!(manager.find_backends_with_tool("send_email").is_empty());
+        let err = manager
+            .call_tool_on_any("send_email", serde_json::json!({}))
+            .unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("No connected MCP offers tool 'send_email'"));
+    }
+
+    #[test]
+    fn test_find_backends_with_tool_orders_by_priority() {
+        let mut manager = test_manager();
+        insert_connected(
+            &mut manager,
+            1,
+            "web-a",
+            vec![test_tool("fetch_url", "Fetch")],
+        );
+        insert_connected(
+            &mut manager,
+            2,
+            "web-b",
+            vec![test_tool("fetch_url", "Fetch")],
+        );
+        manager
+            .set_backend_options(
+                2,
+                BackendOptions {
+                    priority: 10,
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+
+        assert_eq!(
+            manager.find_backends_with_tool("fetch_url"),
+            vec!["web-b", "web-a"]
+        );
+    }
+
+    #[test]
+    fn test_search_tools_priority_breaks_ties() {
+        let mut manager = test_manager();
+        insert_connected(
+            &mut manager,
+            1,
+            "search-a",
+            vec![test_tool("web_search", "Search the web")],
+        );
+        insert_connected(
+            &mut manager,
+            2,
+            "search-b",
+            vec![test_tool("web_search", "Search the web")],
+        );
+        manager
+            .set_backend_options(
+                2,
+                BackendOptions {
+                    priority: 10,
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+
+        let results = manager.search_tools("web_search");
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].mcp_name, "search-b");
+        assert_eq!(results[1].mcp_name, "search-a");
+    }
+
+    #[test]
+    fn test_search_tools_equal_priority_orders_by_id() {
+        let mut manager = test_manager();
+        insert_connected(
+            &mut manager,
+            7,
+            "second",
+            vec![test_tool("fetch", "Fetch a URL")],
+        );
+        insert_connected(
+            &mut manager,
+            3,
+            "first",
+            vec![test_tool("fetch", "Fetch a URL")],
+        );
+
+        let results = manager.search_tools("fetch");
+        assert_eq!(results[0].mcp_id, 3);
+        assert_eq!(results[1].mcp_id, 7);
+    }
+
+    #[test]
+    fn test_search_tools_score_beats_priority() {
+        let mut manager = test_manager();
+        insert_connected(
+            &mut manager,
+            1,
+            "exact",
+            vec![test_tool("fetch", "Fetch a URL")],
+        );
+        insert_connected(
+            &mut manager,
+            2,
+            "partial",
+            vec![test_tool("fetch_json", "Fetch JSON")],
+        );
+        manager
+            .set_backend_options(
+                2,
+                BackendOptions {
+                    priority: 10,
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+
+        let results = manager.search_tools("fetch");
+        assert_eq!(results[0].tool_name, "fetch");
+        assert_eq!(results[1].tool_name, "fetch_json");
+    }
+
+    fn counting_backend(
+        manager: &mut GatewayBackendManager,
+        options: BackendOptions,
+    ) -> Arc<std::sync::atomic::AtomicU32> {
+        let calls = Arc::new(std::sync::atomic::AtomicU32::new(0));
+        insert_connected(manager, 1, "math", vec![test_tool("add", "Add numbers")]);
+        manager.backends.get_mut(&1).unwrap().client = Some(Box::new(CountingClient {
+            calls: calls.clone(),
+        }));
+        manager.set_backend_options(1, options).unwrap();
+        calls
+    }
+
+    #[test]
+    fn test_cacheable_tool_hits_cache_on_identical_args() {
+        let mut manager = test_manager();
+        let calls = counting_backend(
+            &mut manager,
+            BackendOptions {
+                cacheable_tools: vec!["add".to_string()],
+                ..Default::default()
+            },
+        );
+
+        let args = serde_json::json!({"a": 1, "b": 2});
+        let first = manager
+            .call_tool_on_mcp("math", "add", args.clone())
+            .unwrap();
+        let second = manager.call_tool_on_mcp("math", "add", args).unwrap();
+
+        assert_eq!(calls.load(std::sync::atomic::Ordering::SeqCst), 1);
+        assert_eq!(
+            serde_json::to_value(&first.content).unwrap(),
+            serde_json::to_value(&second.content).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_cacheable_tool_misses_on_different_args() {
+        let mut manager = test_manager();
+        let calls = counting_backend(
+            &mut manager,
+            BackendOptions {
+                cacheable: true,
+                ..Default::default()
+            },
+        );
+
+        manager
+            .call_tool_on_mcp("math", "add", serde_json::json!({"a": 1}))
+            .unwrap();
+        manager
+            .call_tool_on_mcp("math", "add", serde_json::json!({"a": 2}))
+            .unwrap();
+
+        assert_eq!(calls.load(std::sync::atomic::Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn test_non_cacheable_tool_bypasses_cache() {
+        let mut manager = test_manager();
+        let calls = counting_backend(&mut manager, BackendOptions::default());
+
+        let args = serde_json::json!({"a": 1});
+        manager
+            .call_tool_on_mcp("math", "add", args.clone())
+            .unwrap();
+        manager.call_tool_on_mcp("math", "add", args).unwrap();
+
+        assert_eq!(calls.load(std::sync::atomic::Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn test_arg_transform_renames_outgoing_arguments() {
+        let mut manager = test_manager();
+        let last_arguments = Arc::new(Mutex::new(None));
+        insert_connected(&mut manager, 1, "search", vec![test_tool("find", "Find")]);
+        manager.backends.get_mut(&1).unwrap().client = Some(Box::new(RecordingClient {
+            last_arguments: last_arguments.clone(),
+        }));
+        manager
+            .set_backend_options(
+                1,
+                serde_json::from_value(serde_json::json!({
+                    "argTransform": {"maxResults": "max_results"}
+                }))
+                .unwrap(),
+            )
+            .unwrap();
+
+        let result = manager
+            .call_tool_on_mcp(
+                "search",
+                "find",
+                serde_json::json!({"maxResults": 5, "query": "rust"}),
+            )
+            .unwrap();
+
+        assert_eq!(
+            last_arguments.lock().unwrap().clone().unwrap(),
+            serde_json::json!({"max_results": 5, "query": "rust"})
+        );
+        // The echoed result is mapped back to the agent's key names
+        let ToolContent::Text { text } = &result.content[0] else {
+            panic!("expected text content");
+        };
+        assert_eq!(
+            serde_json::from_str::<serde_json::Value>(text).unwrap(),
+            serde_json::json!({"maxResults": 5, "query": "rust"})
+        );
+    }
+
+    #[test]
+    fn test_description_max_chars_truncates_listings_only() {
+        let mut manager = test_manager();
+        let long = "Searches the full document index and returns ranked matches";
+        insert_connected(&mut manager, 1, "docs", vec![test_tool("search", long)]);
+        manager
+            .set_backend_options(
+                1,
+                BackendOptions {
+                    description_max_chars: Some(20),
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+
+        let listed = manager.list_tools_by_backend()[0].items[0]
+            .description
+            .clone()
+            .unwrap();
+        assert_eq!(listed, "Searches the full d…");
+        assert_eq!(listed.chars().count(), 20);
+
+        let found = manager.search_tools("search");
+        assert_eq!(found[0].description.as_deref(), Some(listed.as_str()));
+
+        let described = manager.describe_tool("docs", "search").unwrap();
+        assert_eq!(described.description.as_deref(), Some(long));
+        assert!(manager.describe_tool("docs", "missing").is_err());
+    }
+
+    /// Backend client that fails while `failing` is set, counting every call it receives
+    struct FlakyClient {
+        failing: Arc<std::sync::atomic::AtomicBool>,
+        calls: Arc<std::sync::atomic::AtomicU32>,
+    }
+
+    impl BackendClient for FlakyClient {
+        fn call_tool(
+            &mut self,
+            name: &str,
+            arguments: serde_json::Value,
+        ) -> Result<ToolCallResult> {
+            self.calls.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            if self.failing.load(std::sync::atomic::Ordering::SeqCst) {
+                return Err(anyhow!("connection reset"));
+            }
+            MockClient.call_tool(name, arguments)
+        }
+    }
+
+    /// MCP server offering a `ping` tool that answers every call with `call_response`
+    async fn http_backend_server(
+        call_response: wiremock::ResponseTemplate,
+    ) -> wiremock::MockServer {
+        use wiremock::matchers::{body_string_contains, method};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(body_string_contains("tools/call"))
+            .respond_with(call_response)
+            .with_priority(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "result": {
+                    "serverInfo": {"name": "remote"},
+                    "capabilities": {},
+                    "tools": [{"name": "ping"}]
+                }
+            })))
+            .with_priority(2)
+            .mount(&server)
+            .await;
+        server
+    }
+
+    /// Insert a backend connected through a real HTTP client to `url`.
+    /// Must run outside the async runtime (the client blocks).
+    fn insert_http_backend(manager: &mut GatewayBackendManager, id: i64, name: &str, url: &str) {
+        let client = HttpMcpClient::connect(url, None, 1).unwrap();
+        insert_connected(manager, id, name, client.tools().to_vec());
+        manager.backends.get_mut(&id).unwrap().client = Some(Box::new(client));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_http_backend_lists_resources_and_prompts() {
+        use wiremock::matchers::{body_string_contains, method};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        let reply = |result: serde_json::Value| {
+            ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "result": result
+            }))
+        };
+        Mock::given(method("POST"))
+            .and(body_string_contains("resources/list"))
+            .respond_with(reply(serde_json::json!({
+                "resources": [{"uri": "file:///notes.md", "name": "notes"}]
+            })))
+            .with_priority(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(body_string_contains("prompts/list"))
+            .respond_with(reply(serde_json::json!({
+                "prompts": [{"name": "summarize"}]
+            })))
+            .with_priority(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .respond_with(reply(serde_json::json!({
+                "serverInfo": {"name": "remote"},
+                "capabilities": {"tools": {}, "resources": {}, "prompts": {}},
+                "tools": [{"name": "ping"}]
+            })))
+            .with_priority(2)
+            .mount(&server)
+            .await;
+
+        let mut mcp = test_mcp(1, "remote");
+        mcp.mcp_type = "http".to_string();
+        mcp.command = None;
+        mcp.url = Some(server.uri());
+
+        let connected = tokio::task::spawn_blocking(move || {
+            DefaultConnector.connect(&mcp, &BackendOptions::default())
+        })
+        .await
+        .unwrap()
+        .unwrap();
+
+        let resources = connected.resources.unwrap();
+        assert_eq!(resources.len(), 1);
+        assert_eq!(resources[0].name, "notes");
+        let prompts = connected.prompts.unwrap();
+        assert_eq!(prompts.len(), 1);
+        assert_eq!(prompts[0].name, "summarize");
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_http_transport_failures_trip_circuit_breaker() {
+        use crate::mcp_gateway::error::error_code;
+
+        let server = http_backend_server(wiremock::ResponseTemplate::new(503)).await;
+        let url = server.uri();
+
+        tokio::task::spawn_blocking(move || {
+            let mut manager = test_manager();
+            insert_http_backend(&mut manager, 1, "remote", &url);
+            manager.set_circuit_breaker(CircuitBreakerConfig {
+                failure_threshold: 2,
+                cooldown: Duration::from_secs(60),
+            });
+
+            for _ in 0..2 {
+                let err = manager
+                    .call_tool_on_mcp("remote", "ping", serde_json::json!({}))
+                    .unwrap_err();
+                assert!(err.to_string().contains("HTTP error 503"), "{}", err);
+            }
+            let err = manager
+                .call_tool_on_mcp("remote", "ping", serde_json::json!({}))
+                .unwrap_err();
+            assert_eq!(error_code(&err), GatewayErrorCode::Quarantined);
+        })
+        .await
+        .unwrap();
+
+        let calls = server
+            .received_requests()
+            .await
+            .unwrap()
+            .iter()
+            .filter(|r| String::from_utf8_lossy(&r.body).contains("tools/call"))
+            .count();
+        assert_eq!(calls, 2);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_fallback_serves_call_when_http_primary_fails() {
+        use std::sync::atomic::{AtomicU32, Ordering};
+
+        let server = http_backend_server(wiremock::ResponseTemplate::new(502)).await;
+        let url = server.uri();
+
+        let fallback_calls = tokio::task::spawn_blocking(move || {
+            let fallback_calls = Arc::new(AtomicU32::new(0));
+            let mut manager = test_manager();
+            insert_http_backend(&mut manager, 1, "remote", &url);
+            insert_connected(&mut manager, 2, "local", vec![test_tool("ping", "Ping")]);
+            manager.backends.get_mut(&2).unwrap().client = Some(Box::new(CountingClient {
+                calls: fallback_calls.clone(),
+            }));
+            manager
+                .set_backend_options(
+                    1,
+                    BackendOptions {
+                        fallbacks: vec![2],
+                        ..Default::default()
+                    },
+                )
+                .unwrap();
+
+            let result = manager
+                .call_tool_on_mcp("remote", "ping", serde_json::json!({"q": "rust"}))
+                .unwrap();
+            assert!(!result.is_error);
+            fallback_calls.load(Ordering::SeqCst)
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(fallback_calls, 1);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_error_codes_for_each_failure_path() {
+        use crate::mcp_gateway::error::error_code;
+        use wiremock::ResponseTemplate;
+
+        // Failing backends are real HTTP clients, so the codes come from the
+        // errors the client actually returns
+        let slow =
+            http_backend_server(ResponseTemplate::new(200).set_delay(Duration::from_secs(3))).await;
+        let throttled = http_backend_server(ResponseTemplate::new(429)).await;
+        let broken = http_backend_server(ResponseTemplate::new(500)).await;
+        let failing = [
+            ("slow", slow.uri(), GatewayErrorCode::Timeout),
+            ("throttled", throttled.uri(), GatewayErrorCode::RateLimited),
+            ("broken", broken.uri(), GatewayErrorCode::BackendError),
+        ];
+
+        tokio::task::spawn_blocking(move || {
+            let mut manager = test_manager();
+            let code = |result: Result<ToolCallResult>| error_code(&result.unwrap_err());
+            let mut schema_tool = test_tool("read", "Read a file");
+            schema_tool.input_schema = Some(serde_json::json!({
+                "type": "object",
+                "properties": {"path": {"type": "string"}},
+            }));
+            insert_connected(
+                &mut manager,
+                1,
+                "fs",
+                vec![schema_tool, test_tool("delete", "Delete")],
+            );
+            manager
+                .set_backend_options(
+                    1,
+                    BackendOptions {
+                        denied_tools: vec!["delete".to_string()],
+                        validate_arguments: true,
+                        ..Default::default()
+                    },
+                )
+                .unwrap();
+
+            assert_eq!(
+                code(manager.call_tool_on_mcp("missing", "read", serde_json::json!({}))),
+                GatewayErrorCode::BackendNotFound
+            );
+            assert_eq!(
+                code(manager.call_tool_on_mcp("fs", "delete", serde_json::json!({}))),
+                GatewayErrorCode::ToolNotExposed
+            );
+            assert_eq!(
+                error_code(&manager.describe_tool("fs", "write").unwrap_err()),
+                GatewayErrorCode::ToolNotExposed
+            );
+            assert_eq!(
+                code(manager.call_tool_on_mcp("fs", "read", serde_json::json!({"path": 7}))),
+                GatewayErrorCode::InvalidArgs
+            );
+
+            for (id, (name, url, expected)) in (2..).zip(failing) {
+                insert_http_backend(&mut manager, id, name, &url);
+                assert_eq!(
+                    code(manager.call_tool_on_mcp(name, "ping", serde_json::json!({}))),
+                    expected,
+                    "{}",
+                    name
+                );
+            }
+
+            manager.set_circuit_breaker(CircuitBreakerConfig {
+                failure_threshold: 1,
+                cooldown: Duration::from_secs(60),
+            });
+            let _ = manager.call_tool_on_mcp("broken", "ping", serde_json::json!({}));
+            assert_eq!(
+                code(manager.call_tool_on_mcp("broken", "ping", serde_json::json!({}))),
+                GatewayErrorCode::Quarantined
+            );
+        })
+        .await
+        .unwrap();
+    }
+
+    #[test]
+    fn test_circuit_breaker_quarantines_then_recovers() {
+        use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+
+        let mut manager = test_manager();
+        let failing = Arc::new(AtomicBool::new(true));
+        let calls = Arc::new(AtomicU32::new(0));
+        insert_connected(&mut manager, 1, "flaky", vec![test_tool("ping", "Ping")]);
+        manager.backends.get_mut(&1).unwrap().client = Some(Box::new(FlakyClient {
+            failing: failing.clone(),
+            calls: calls.clone(),
+        }));
+        manager.set_circuit_breaker(CircuitBreakerConfig {
+            failure_threshold: 2,
+            cooldown: Duration::from_millis(50),
+        });
+
+        for _ in 0..2 {
+            let err = manager
+                .call_tool_on_mcp("flaky", "ping", serde_json::json!({}))
+                .unwrap_err();
+            assert!(err.to_string().contains("connection reset"));
+        }
+
+        // Tripped: fails fast without reaching the backend
+        let err = manager
+            .call_tool_on_mcp("flaky", "ping", serde_json::json!({}))
+            .unwrap_err();
+        assert!(err.to_string().contains("Backend quarantined"));
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+
+        // After the cooldown a successful probe closes the breaker
+        std::thread::sleep(Duration::from_millis(60));
+        failing.store(false, Ordering::SeqCst);
+        assert!(manager
+            .call_tool_on_mcp("flaky", "ping", serde_json::json!({}))
+            .is_ok());
+        assert!(manager
+            .call_tool_on_mcp("flaky", "ping", serde_json::json!({}))
+            .is_ok());
+        assert_eq!(calls.load(Ordering::SeqCst), 4);
+    }
+
+    #[test]
+    fn test_fallback_serves_call_when_primary_fails() {
+        use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+
+        let mut manager = test_manager();
+        let primary_calls = Arc::new(AtomicU32::new(0));
+        let fallback_calls = Arc::new(AtomicU32::new(0));
+        insert_connected(&mut manager, 1, "search", vec![test_tool("ping", "Ping")]);
+        insert_connected(
+            &mut manager,
+            2,
+            "search-backup",
+            vec![test_tool("ping", "Ping")],
+        );
+        manager.backends.get_mut(&1).unwrap().client = Some(Box::new(FlakyClient {
+            failing: Arc::new(AtomicBool::new(true)),
+            calls: primary_calls.clone(),
+        }));
+        manager.backends.get_mut(&2).unwrap().client = Some(Box::new(CountingClient {
+            calls: fallback_calls.clone(),
+        }));
+        manager.set_circuit_breaker(CircuitBreakerConfig {
+            failure_threshold: 1,
+            cooldown: Duration::from_secs(60),
+        });
+        manager
+            .set_backend_options(
+                1,
+                BackendOptions {
+                    fallbacks: vec![2],
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+
+        // Primary errors: the fallback serves the call
+        let result = manager
+            .call_tool_on_mcp("search", "ping", serde_json::json!({"q": "rust"}))
+            .unwrap();
+        assert!(
+            matches!(&result.content[0], ToolContent::Text { text } if text == r#"{"q":"rust"}"#)
+        );
+
+        // Primary is now quarantined: served by the fallback without trying it
+        assert!(manager
+            .call_tool_on_mcp("search", "ping", serde_json::json!({}))
+            .is_ok());
+        assert_eq!(primary_calls.load(Ordering::SeqCst), 1);
+        assert_eq!(fallback_calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn test_validate_arguments_rejects_missing_required_field() {
+        let mut manager = test_manager();
+        let mut tool = test_tool("add", "Add numbers");
+        tool.input_schema = Some(serde_json::json!({
+            "type": "object",
+            "properties": { "a": { "type": "number" }, "b": { "type": "number" } },
+            "required": ["a", "b"]
+        }));
+        let calls = Arc::new(std::sync::atomic::AtomicU32::new(0));
+        insert_connected(&mut manager, 1, "math", vec![tool]);
+        manager.backends.get_mut(&1).unwrap().client = Some(Box::new(CountingClient {
+            calls: calls.clone(),
+        }));
+
+        // Off by default: the call is forwarded as-is
+        assert!(manager
+            .call_tool_on_mcp("math", "add", serde_json::json!({"a": 1}))
+            .is_ok());
+
+        manager
+            .set_backend_options(
+                1,
+                BackendOptions {
+                    validate_arguments: true,
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+        let err = manager
+            .call_tool_on_mcp("math", "add", serde_json::json!({"a": 1}))
+            .unwrap_err();
+
+        assert_eq!(
+            err.to_string(),
+            "invalid arguments: $.b: required property is missing"
+        );
+        assert_eq!(calls.load(std::sync::atomic::Ordering::SeqCst), 1);
+        assert!(manager
+            .call_tool_on_mcp("math", "add", serde_json::json!({"a": 1, "b": 2}))
+            .is_ok());
+    }
+
+    #[test]
+    fn test_validate_arguments_after_arg_transform() {
+        let mut manager = test_manager();
+        let mut tool = test_tool("find", "Find");
+        tool.input_schema = Some(serde_json::json!({
+            "type": "object",
+            "properties": { "max_results": { "type": "integer" } },
+            "required": ["max_results"]
+        }));
+        let calls = Arc::new(std::sync::atomic::AtomicU32::new(0));
+        insert_connected(&mut manager, 1, "search", vec![tool]);
+        manager.backends.get_mut(&1).unwrap().client = Some(Box::new(CountingClient {
+            calls: calls.clone(),
+        }));
+        manager
+            .set_backend_options(
+                1,
+                serde_json::from_value(serde_json::json!({
+                    "argTransform": {"maxResults": "max_results"},
+                    "validateArguments": true
+                }))
+                .unwrap(),
+            )
+            .unwrap();
+
+        // The agent's key is renamed to the required backend key before validation
+        assert!(manager
+            .call_tool_on_mcp("search", "find", serde_json::json!({"maxResults": 5}))
+            .is_ok());
+        let batch = manager.call_tools_batch(&[BatchCall {
+            mcp_name: "search".to_string(),
+            tool_name: "find".to_string(),
+            arguments: serde_json::json!({"maxResults": 5}),
+        }]);
+        assert!(batch[0].is_ok());
+
+        let err = manager
+            .call_tool_on_mcp("search", "find", serde_json::json!({}))
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "invalid arguments: $.max_results: required property is missing"
+        );
+        assert_eq!(calls.load(std::sync::atomic::Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn test_retry_policy_backoff_doubles_up_to_cap() {
+        let retry = RetryPolicy {
+            max_attempts: 5,
+            initial_backoff_ms: 100,
+            max_backoff_ms: 300,
+        };
+        assert_eq!(retry.backoff_for_attempt(1), Duration::from_millis(100));
+        assert_eq!(retry.backoff_for_attempt(2), Duration::from_millis(200));
+        assert_eq!(retry.backoff_for_attempt(3), Duration::from_millis(300));
+        assert_eq!(retry.backoff_for_attempt(40), Duration::from_millis(300));
+    }
+
+    /// Connector that always succeeds with a MockClient
+    struct MockConnector;
+
+    impl BackendConnector for MockConnector {
+        fn connect(&self, mcp: &Mcp, _options: &BackendOptions) -> Result<ConnectedBackend> {
+            Ok(ConnectedBackend {
+                client: Box::new(MockClient),
+                server_info: McpServerInfo {
+                    name: mcp.name.clone(),
+                    version: None,
+                },
+                capabilities: McpServerCapabilities::default(),
+                tools: vec![test_tool("ping", "Ping")],
+                resources: None,
+                prompts: None,
+            })
+        }
+    }
+
+    /// Connector that refuses MCPs named "broken" and otherwise acts like MockConnector
+    struct PartialConnector;
+
+    impl BackendConnector for PartialConnector {
+        fn connect(&self, mcp: &Mcp, options: &BackendOptions) -> Result<ConnectedBackend> {
+            if mcp.name == "broken" {
+                return Err(anyhow!("connection refused"));
+            }
+            MockConnector.connect(mcp, options)
+        }
+    }
+
+    /// Connector whose backends declare resources and logging support
+    struct CapableConnector;
+
+    impl BackendConnector for CapableConnector {
+        fn connect(&self, mcp: &Mcp, options: &BackendOptions) -> Result<ConnectedBackend> {
+            let mut connected = MockConnector.connect(mcp, options)?;
+            connected.server_info.version = Some("1.4.0".to_string());
+            connected.capabilities = McpServerCapabilities {
+                protocol_version: Some("2025-03-26".to_string()),
+                tools: true,
+                resources: true,
+                prompts: false,
+                logging: true,
+            };
+            Ok(connected)
+        }
+    }
+
+    /// Connector that records the init params each backend is connected with
+    struct InitParamsConnector {
+        received: Arc<Mutex<Vec<Option<serde_json::Value>>>>,
+    }
+
+    impl BackendConnector for InitParamsConnector {
+        fn connect(&self, mcp: &Mcp, options: &BackendOptions) -> Result<ConnectedBackend> {
+            self.received
+                .lock()
+                .unwrap()
+                .push(options.init_params.clone());
+            MockConnector.connect(mcp, options)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_init_params_forwarded_to_backend() {
+        let received = Arc::new(Mutex::new(Vec::new()));
+        let mut manager = test_manager();
+        manager.set_connector(Box::new(InitParamsConnector {
+            received: received.clone(),
+        }));
+        let registration: BackendRegistration = serde_json::from_value(serde_json::json!({
+            "name": "files",
+            "command": "npx files-server",
+            "options": { "initParams": { "workspacePath": "/work/app" } }
+        }))
+        .unwrap();
+        manager.register_backend(registration).unwrap();
+
+        manager.connect_backend_lazy("files").await.unwrap();
+
+        assert_eq!(
+            *received.lock().unwrap(),
+            vec![Some(serde_json::json!({ "workspacePath": "/work/app" }))]
+        );
+
+        let invalid = serde_json::from_value::<BackendOptions>(serde_json::json!({
+            "initParams": ["not", "an", "object"]
+        }));
+        assert!(invalid
+            .unwrap_err()
+            .to_string()
+            .contains("initParams must be a JSON object"));
+    }
+
+    /// Connector that records the connection pool each backend is given
+    struct PoolConnector {
+        pools: Arc<Mutex<Vec<Option<Arc<HttpClientPool>>>>>,
+    }
+
+    impl BackendConnector for PoolConnector {
+        fn connect(&self, mcp: &Mcp, options: &BackendOptions) -> Result<ConnectedBackend> {
+            self.pools.lock().unwrap().push(options.http_pool.clone());
+            MockConnector.connect(mcp, options)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_http_pool_kept_across_reconnects() {
+        let pools = Arc::new(Mutex::new(Vec::new()));
+        let mut manager = test_manager();
+        manager.set_connector(Box::new(PoolConnector {
+            pools: pools.clone(),
+        }));
+        let registration: BackendRegistration = serde_json::from_value(serde_json::json!({
+            "name": "remote",
+            "type": "http",
+            "url": "https://mcp.example.com/mcp"
+        }))
+        .unwrap();
+        let mcp_id = manager.register_backend(registration).unwrap();
+
+        manager.connect_backend_lazy("remote").await.unwrap();
+        manager.restart_backend(mcp_id).await.unwrap();
+
+        let pools = pools.lock().unwrap();
+        assert_eq!(pools.len(), 2);
+        assert!(Arc::ptr_eq(
+            pools[0].as_ref().unwrap(),
+            pools[1].as_ref().unwrap()
+        ));
+    }
+
+    /// Backend client that sends each call over a connection from its pool,
+    /// holding it until another call is in flight (or a second has passed)
+    struct PooledClient {
+        pool: Arc<HttpClientPool>,
+        in_flight: Arc<std::sync::atomic::AtomicUsize>,
+        max_in_flight: Arc<std::sync::atomic::AtomicUsize>,
+    }
+
+    impl BackendClient for PooledClient {
+        fn call_tool(
+            &mut self,
+            name: &str,
+            arguments: serde_json::Value,
+        ) -> Result<ToolCallResult> {
+            use std::sync::atomic::Ordering;
+
+            let _conn = self.pool.checkout()?;
+            let now = self.in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+            self.max_in_flight.fetch_max(now, Ordering::SeqCst);
+            let deadline = Instant::now() + Duration::from_secs(1);
+            while self.in_flight.load(Ordering::SeqCst) < 2 && Instant::now() < deadline {
+                std::thread::sleep(Duration::from_millis(5));
+            }
+            self.max_in_flight
+                .fetch_max(self.in_flight.load(Ordering::SeqCst), Ordering::SeqCst);
+            self.in_flight.fetch_sub(1, Ordering::SeqCst);
+            MockClient.call_tool(name, arguments)
+        }
+    }
+
+    /// Connector whose backends call through the HTTP pool the gateway gives them
+    struct PooledConnector {
+        in_flight: Arc<std::sync::atomic::AtomicUsize>,
+        max_in_flight: Arc<std::sync::atomic::AtomicUsize>,
+    }
+
+    impl BackendConnector for PooledConnector {
+        fn connect(&self, mcp: &Mcp, options: &BackendOptions) -> Result<ConnectedBackend> {
+            let mut connected = MockConnector.connect(mcp, options)?;
+            connected.client = Box::new(PooledClient {
+                pool: options.http_pool.clone().expect("HTTP backends get a pool"),
+                in_flight: self.in_flight.clone(),
+                max_in_flight: self.max_in_flight.clone(),
+            });
+            Ok(connected)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_gateway_calls_reuse_pooled_connections_concurrently() {
+        let max_in_flight = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let mut manager = test_manager();
+        manager.set_connector(Box::new(PooledConnector {
+            in_flight: Arc::new(std::sync::atomic::AtomicUsize::new(0)),
+            max_in_flight: max_in_flight.clone(),
+        }));
+        let mut ids = Vec::new();
+        for name in ["alpha", "beta"] {
+            let registration: BackendRegistration = serde_json::from_value(serde_json::json!({
+                "name": name,
+                "type": "http",
+                "url": format!("https://{}.example.com/mcp", name)
+            }))
+            .unwrap();
+            ids.push(manager.register_backend(registration).unwrap());
+            manager.connect_backend_lazy(name).await.unwrap();
+        }
+
+        let calls: Vec<BatchCall> = ["alpha", "beta", "alpha", "beta", "alpha"]
+            .iter()
+            .map(|name| BatchCall {
+                mcp_name: name.to_string(),
+                tool_name: "ping".to_string(),
+                arguments: serde_json::json!({}),
+            })
+            .collect();
+        let results = manager.call_tools_batch(&calls);
+        assert!(results.iter().all(|r| r.is_ok()));
+        manager
+            .call_tool_on_mcp("alpha", "ping", serde_json::json!({}))
+            .unwrap();
+
+        // The two backends' calls overlapped, yet each backend opened a
+        // single connection and reused it for every call
+        assert_eq!(max_in_flight.load(std::sync::atomic::Ordering::SeqCst), 2);
+        for id in ids {
+            assert_eq!(manager.http_pool(id).open_connections(), 1);
+        }
+    }
+
+    /// Backend client that records the log levels it is sent
+    struct LogLevelClient {
+        levels: Arc<Mutex<Vec<String>>>,
+    }
+
+    impl BackendClient for LogLevelClient {
+        fn call_tool(
+            &mut self,
+            name: &str,
+            arguments: serde_json::Value,
+        ) -> Result<ToolCallResult> {
+            MockClient.call_tool(name, arguments)
+        }
+
+        fn set_log_level(&mut self, level: &str) -> Result<()> {
+            self.levels.lock().unwrap().push(level.to_string());
+            Ok(())
+        }
+    }
+
+    /// Connector that records the env each stdio backend would be spawned
+    /// with, and whose backends declare logging support
+    struct LogLevelConnector {
+        envs: Arc<Mutex<Vec<Option<HashMap<String, String>>>>>,
+        levels: Arc<Mutex<Vec<String>>>,
+    }
+
+    impl BackendConnector for LogLevelConnector {
+        fn connect(&self, mcp: &Mcp, options: &BackendOptions) -> Result<ConnectedBackend> {
+            self.envs.lock().unwrap().push(stdio_env(mcp, options));
+            let mut connected = MockConnector.connect(mcp, options)?;
+            connected.client = Box::new(LogLevelClient {
+                levels: self.levels.clone(),
+            });
+            connected.capabilities.logging = true;
+            Ok(connected)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_log_level_forwarded_to_backend() {
+        let envs = Arc::new(Mutex::new(Vec::new()));
+        let levels = Arc::new(Mutex::new(Vec::new()));
+        let mut manager = test_manager();
+        manager.set_connector(Box::new(LogLevelConnector {
+            envs: envs.clone(),
+            levels: levels.clone(),
+        }));
+        let registration: BackendRegistration = serde_json::from_value(serde_json::json!({
+            "name": "chatty",
+            "command": "npx chatty-server",
+            "env": { "API_KEY": "secret" },
+            "options": { "logLevel": "warn" }
+        }))
+        .unwrap();
+        let mcp_id = manager.register_backend(registration).unwrap();
+
+        manager.connect_backend_lazy("chatty").await.unwrap();
+
+        let env = envs.lock().unwrap()[0].clone().unwrap();
+        assert_eq!(env.get("RUST_LOG").map(String::as_str), Some("warn"));
+        assert_eq!(env.get("API_KEY").map(String::as_str), Some("secret"));
+        assert_eq!(*levels.lock().unwrap(), vec!["warning"]);
+
+        assert!(manager.set_backend_log_level(mcp_id, Some("trace".to_string())));
+        assert_eq!(*levels.lock().unwrap(), vec!["warning", "debug"]);
+        assert_eq!(
+            manager.backend_options(mcp_id).log_level.as_deref(),
+            Some("trace")
+        );
+    }
+
+    #[test]
+    fn test_set_log_level_noop_without_logging_support() {
+        let mut manager = test_manager();
+        insert_connected(&mut manager, 1, "quiet", vec![test_tool("ping", "Ping")]);
+
+        assert!(!manager.set_backend_log_level(1, Some("error".to_string())));
+        assert!(!manager.set_backend_log_level(99, Some("error".to_string())));
+        assert_eq!(
+            manager.backend_options(1).log_level.as_deref(),
+            Some("error")
+        );
+    }
+
+    /// MockConnector that counts connection attempts
+    struct CountingConnector {
+        connects: Arc<std::sync::atomic::AtomicU32>,
+    }
+
+    impl BackendConnector for CountingConnector {
+        fn connect(&self, mcp: &Mcp, options: &BackendOptions) -> Result<ConnectedBackend> {
+            self.connects
+                .fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            MockConnector.connect(mcp, options)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_probe_tool_count_uses_cached_count_without_connecting() {
+        use std::sync::atomic::Ordering;
+
+        let connects = Arc::new(std::sync::atomic::AtomicU32::new(0));
+        let mut manager = test_manager();
+        manager.set_connector(Box::new(CountingConnector {
+            connects: connects.clone(),
+        }));
+        manager.register_backend(registration("search")).unwrap();
+
+        // Never connected: unknown, and probing does not connect
+        assert_eq!(manager.probe_tool_count("search"), None);
+        assert_eq!(connects.load(Ordering::SeqCst), 0);
+
+        manager.connect_backend_lazy("search").await.unwrap();
+        assert_eq!(manager.probe_tool_count("search"), Some(1));
+
+        // After eviction the count from the last connection is reused
+        manager.set_idle_timeout(Some(Duration::ZERO));
+        assert_eq!(manager.evict_idle_backends(), vec!["search".to_string()]);
+        assert_eq!(manager.probe_tool_count("search"), Some(1));
+        assert!(manager.get_backend_tools("search").is_none());
+        assert_eq!(connects.load(Ordering::SeqCst), 1);
+
+        assert_eq!(manager.probe_tool_count("missing"), None);
+    }
+
+    #[tokio::test]
+    async fn test_backend_info_reports_declared_capabilities() {
+        let mut manager = test_manager();
+        manager.set_connector(Box::new(CapableConnector));
+        manager.register_backend(registration("search")).unwrap();
+
+        let info = manager.backend_info("search").await.unwrap();
+
+        assert_eq!(info.status, BackendStatus::Connected);
+        assert_eq!(info.tool_count, 1);
+        let server_info = info.server_info.unwrap();
+        assert_eq!(server_info.name, "search");
+        assert_eq!(server_info.version.as_deref(), Some("1.4.0"));
+        let capabilities = info.capabilities.unwrap();
+        assert_eq!(capabilities.protocol_version.as_deref(), Some("2025-03-26"));
+        assert!(capabilities.tools && capabilities.resources && capabilities.logging);
+        assert!(!capabilities.prompts);
+
+        assert!(manager.backend_info("missing").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_export_catalog_lists_tools_and_failed_backends() {
+        let mut manager = test_manager();
+        manager.set_connector(Box::new(PartialConnector));
+        manager.register_backend(registration("search")).unwrap();
+        manager.register_backend(registration("broken")).unwrap();
+
+        let catalog = manager.export_catalog().await;
+
+        assert_eq!(
+            catalog,
+            serde_json::json!({
+                "backends": [
+                    { "mcpName": "broken", "error": "connection refused" },
+                    {
+                        "mcpName": "search",
+                        "tools": [
+                            { "name": "ping", "description": "Ping", "inputSchema": null }
+                        ]
+                    }
+                ]
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_backend_alias_accepted_in_place_of_name() {
+        let mut manager = test_manager();
+        manager.set_connector(Box::new(MockConnector));
+        let mut aliased = registration("uuid-1234");
+        aliased.options.alias = Some("search".to_string());
+        let id = manager.register_backend(aliased).unwrap();
+
+        let tools = manager.connect_backend_lazy("search").await.unwrap();
+        assert_eq!(tools[0].name, "ping");
+        assert!(manager
+            .call_tool_on_mcp("search", "ping", serde_json::json!({}))
+            .is_ok());
+        assert!(manager
+            .call_tool_on_mcp("uuid-1234", "ping", serde_json::json!({}))
+            .is_ok());
+        assert!(manager.tool_index.contains_key("search__ping"));
+        assert!(manager
+            .call_tool("search__ping", serde_json::json!({}))
+            .is_ok());
+        assert_eq!(
+            manager.get_available_mcps()[0].alias.as_deref(),
+            Some("search")
+        );
+
+        // Aliases may not clash with other aliases or names
+        let mut clash = registration("other");
+        clash.options.alias = Some("search".to_string());
+        assert!(manager
+            .register_backend(clash)
+            .unwrap_err()
+            .to_string()
+            .contains("already used by backend 'uuid-1234'"));
+        assert!(manager.register_backend(registration("search")).is_err());
+        let other = manager.register_backend(registration("other")).unwrap();
+        let taken_name = BackendOptions {
+            alias: Some("uuid-1234".to_string()),
+            ..Default::default()
+        };
+        assert!(manager.set_backend_options(other, taken_name).is_err());
+        assert_ne!(id, other);
+    }
+
+    #[tokio::test]
+    async fn test_idle_sweeper_evicts_unused_backend_but_keeps_warmup() {
+        let mut manager = test_manager();
+        insert_connected(&mut manager, 1, "idle", vec![test_tool("a", "A")]);
+        insert_connected(&mut manager, 2, "warm", vec![test_tool("b", "B")]);
+        manager
+            .set_backend_options(
+                2,
+                BackendOptions {
+                    warmup: true,
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+        manager.set_idle_timeout(Some(Duration::from_millis(10)));
+
+        let manager = Arc::new(tokio::sync::Mutex::new(manager));
+        let sweeper = tokio::spawn(run_idle_sweeper(manager.clone(), Duration::from_millis(5)));
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        sweeper.abort();
+
+        let manager = manager.lock().await;
+        assert!(manager.get_backend_tools("idle").is_none());
+        assert!(manager.get_backend_tools("warm").is_some());
+    }
+
+    #[tokio::test]
+    async fn test_evicted_backend_reconnects_on_next_call() {
+        let mut manager = test_manager();
+        manager.set_connector(Box::new(MockConnector));
+        manager.register_backend(registration("lazy")).unwrap();
+        manager.connect_backend_lazy("lazy").await.unwrap();
+
+        manager.set_idle_timeout(Some(Duration::ZERO));
+        assert_eq!(manager.evict_idle_backends(), vec!["lazy".to_string()]);
+        assert!(manager.get_backend_tools("lazy").is_none());
+
+        let result = manager
+            .call_tool_on_mcp("lazy", "ping", serde_json::json!({}))
+            .unwrap();
+        assert!(result.success);
+        assert!(manager.get_backend_tools("lazy").is_some());
+    }
+
+    #[test]
+    fn test_eviction_closes_client() {
+        let closes = Arc::new(std::sync::atomic::AtomicU32::new(0));
+        let mut manager = test_manager();
+        insert_connected(&mut manager, 1, "idle", vec![test_tool("a", "A")]);
+        manager.backends.get_mut(&1).unwrap().client = Some(Box::new(ClosingClient {
+            closes: closes.clone(),
+        }));
+
+        manager.set_idle_timeout(Some(Duration::ZERO));
+        assert_eq!(manager.evict_idle_backends(), vec!["idle".to_string()]);
+
+        assert_eq!(closes.load(std::sync::atomic::Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn test_no_eviction_without_idle_timeout() {
+        let mut manager = test_manager();
+        insert_connected(&mut manager, 1, "idle", vec![test_tool("a", "A")]);
+
+        assert!(manager.evict_idle_backends().is_empty());
+        assert!(manager.get_backend_tools("idle").is_some());
+    }
+
+    fn batch_call(mcp_name: &str, tool_name: &str) -> BatchCall {
+        BatchCall {
+            mcp_name: mcp_name.to_string(),
+            tool_name: tool_name.to_string(),
+            arguments: serde_json::json!({}),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_invoke_tool_cancelled_in_flight() {
+        let cancellations = Arc::new(std::sync::atomic::AtomicU32::new(0));
+        let mut manager = test_manager();
+        insert_connected(&mut manager, 1, "slow", vec![test_tool("crawl", "Crawl")]);
+        manager.backends.get_mut(&1).unwrap().client = Some(Box::new(SlowClient {
+            cancellations: Arc::clone(&cancellations),
+        }));
+        let manager = Arc::new(tokio::sync::Mutex::new(manager));
+
+        let cancel = CancellationToken::new();
+        let call = tokio::spawn(invoke_tool(
+            Arc::clone(&manager),
+            None,
+            "slow".to_string(),
+            "crawl".to_string(),
+            serde_json::json!({}),
+            cancel.clone(),
+        ));
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        let start = Instant::now();
+        cancel.cancel();
+
+        let err = call.await.unwrap().unwrap_err();
+        assert!(err.is::<RequestCancelled>());
+        assert!(start.elapsed() < Duration::from_secs(1));
+
+        // The backend was told to stop
+        assert_eq!(cancellations.load(std::sync::atomic::Ordering::SeqCst), 1);
+    }
+
+    /// Backend client that answers with text and an image
+    struct MixedContentClient;
+
+    impl BackendClient for MixedContentClient {
+        fn call_tool(
+            &mut self,
+            _name: &str,
+            _arguments: serde_json::Value,
+        ) -> Result<ToolCallResult> {
+            Ok(ToolCallResult {
+                success: true,
+                content: vec![
+                    ToolContent::Text {
+                        text: "Here is the chart".to_string(),
+                    },
+                    ToolContent::Image {
+                        data: "iVBORw0KGgo=".to_string(),
+                        mime_type: "image/png".to_string(),
+                    },
+                ],
+                is_error: false,
+                error: None,
+                execution_time_ms: 0,
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_invoke_tool_keeps_mixed_content() {
+        let mut manager = test_manager();
+        insert_connected(&mut manager, 1, "charts", vec![test_tool("plot", "Plot")]);
+        manager.backends.get_mut(&1).unwrap().client = Some(Box::new(MixedContentClient));
+        let manager = Arc::new(tokio::sync::Mutex::new(manager));
+
+        let result = invoke_tool(
+            manager,
+            None,
+            "charts".to_string(),
+            "plot".to_string(),
+            serde_json::json!({}),
+            CancellationToken::new(),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(result.content.len(), 2);
+        assert_eq!(result.content[0].as_text(), Some("Here is the chart"));
+        assert!(matches!(
+            &result.content[1],
+            ToolContent::Image { data, mime_type }
+                if data == "iVBORw0KGgo=" && mime_type == "image/png"
+        ));
+        assert_eq!(result.text(), "Here is the chart");
+    }
+
+    #[tokio::test]
+    async fn test_invoke_batch_across_two_backends_keeps_order() {
+        let mut manager = test_manager();
+        insert_connected(&mut manager, 1, "alpha", vec![test_tool("a1", "A1")]);
+        insert_connected(&mut manager, 2, "beta", vec![test_tool("b1", "B1")]);
+        let manager = tokio::sync::Mutex::new(manager);
+
+        let results = invoke_batch(
+            &manager,
+            vec![
+                batch_call("beta", "b1"),
+                batch_call("alpha", "a1"),
+                batch_call("missing", "x"),
+                batch_call("alpha", "a2"),
+            ],
+        )
+        .await;
+
+        assert_eq!(results.len(), 4);
+        let text = |i: usize| -> String {
+            results[i].as_ref().unwrap()["content"][0]["text"]
+                .as_str()
+                .unwrap()
+                .to_string()
+        };
+        assert_eq!(text(0), "called b1");
+        assert_eq!(text(1), "called a1");
+        assert!(results[2]
+            .as_ref()
+            .unwrap_err()
+            .to_string()
+            .contains("not connected"));
+        assert_eq!(text(3), "called a2");
+    }
+
+    #[test]
+    fn test_call_tools_batch_serves_cacheable_calls_from_cache() {
+        let mut manager = test_manager();
+        let calls = counting_backend(
+            &mut manager,
+            BackendOptions {
+                cacheable: true,
+                ..Default::default()
+            },
+        );
+
+        let batch = vec![batch_call("math", "add")];
+        assert!(manager.call_tools_batch(&batch)[0].is_ok());
+        assert!(manager.call_tools_batch(&batch)[0].is_ok());
+
+        assert_eq!(calls.load(std::sync::atomic::Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn test_denied_tool_rejected_when_invoked_directly() {
+        let mut manager = test_manager();
+        insert_connected(
+            &mut manager,
+            1,
+            "fs",
+            vec![test_tool("read", "Read"), test_tool("delete", "Delete")],
+        );
+        manager
+            .set_backend_options(
+                1,
+                BackendOptions {
+                    denied_tools: vec!["delete".to_string()],
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+
+        let err = manager
+            .call_tool_on_mcp("fs", "delete", serde_json::json!({}))
+            .unwrap_err();
+        assert!(err.to_string().contains("Permission denied"));
+        assert!(manager
+            .call_tool_on_mcp("fs", "read", serde_json::json!({}))
+            .is_ok());
+
+        let batch = manager.call_tools_batch(&[batch_call("fs", "delete")]);
+        assert!(batch[0].is_err());
+    }
+
+    #[test]
+    fn test_allowlist_rejects_unlisted_tools() {
+        let options = BackendOptions {
+            allowed_tools: vec!["read".to_string(), "delete".to_string()],
+            denied_tools: vec!["delete".to_string()],
+            ..Default::default()
+        };
+
+        assert!(options.is_tool_allowed("read"));
+        assert!(!options.is_tool_allowed("write"));
+        assert!(!options.is_tool_allowed("delete"));
+        assert!(BackendOptions::default().is_tool_allowed("anything"));
+    }
+
+    #[tokio::test]
+    async fn test_denied_tools_hidden_from_listing() {
+        let mut manager = test_manager();
+        manager.set_connector(Box::new(MockConnector));
+        let mut registration = registration("lazy");
+        registration.options.denied_tools = vec!["ping".to_string()];
+        manager.register_backend(registration).unwrap();
+
+        let tools = manager.connect_backend_lazy("lazy").await.unwrap();
+
+        assert!(tools.is_empty());
+        assert!(manager
+            .call_tool_on_mcp("lazy", "ping", serde_json::json!({}))
+            .is_err());
+    }
+
+    #[test]
+    fn test_switching_profiles_changes_exposed_tools() {
+        let mut manager = test_manager();
+        insert_connected(
+            &mut manager,
+            1,
+            "docs",
+            vec![test_tool("search_docs", "Search docs")],
+        );
+        insert_connected(
+            &mut manager,
+            2,
+            "web",
+            vec![
+                test_tool("fetch_url", "Fetch a URL"),
+                test_tool("search_web", "Search the web"),
+            ],
+        );
+        manager
+            .define_profile(
+                "research",
+                ToolProfile {
+                    backends: vec!["web".to_string()],
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+        manager
+            .define_profile(
+                "writing",
+                ToolProfile {
+                    tools: vec!["docs/search_docs".to_string(), "web/fetch_url".to_string()],
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+
+        let exposed = |manager: &GatewayBackendManager| -> Vec<String> {
+            manager
+                .list_tools_by_backend()
+                .iter()
+                .flat_map(|l| l.items.iter().map(|t| format!("{}/{}", l.mcp_name, t.name)))
+                .collect()
+        };
+        assert_eq!(exposed(&manager).len(), 3);
+
+        manager.apply_session_profile(Some("research"));
+        assert_eq!(exposed(&manager), vec!["web/fetch_url", "web/search_web"]);
+        assert!(manager
+            .call_tool_on_mcp("docs", "search_docs", serde_json::json!({}))
+            .is_err());
+        assert!(manager
+            .call_tool_on_mcp("web", "search_web", serde_json::json!({}))
+            .is_ok());
+
+        manager.apply_session_profile(Some("writing"));
+        assert_eq!(exposed(&manager), vec!["docs/search_docs", "web/fetch_url"]);
+        assert!(manager.search_tools("search_web").is_empty());
+        assert!(manager
+            .call_tool_on_mcp("web", "search_web", serde_json::json!({}))
+            .is_err());
+        assert!(manager
+            .call_tool_on_mcp("docs", "search_docs", serde_json::json!({}))
+            .is_ok());
+
+        manager.apply_session_profile(Some("missing"));
+        assert_eq!(manager.active_profile, None);
+        assert_eq!(exposed(&manager).len(), 3);
+        manager.apply_session_profile(Some("writing"));
+        manager.apply_session_profile(None);
+        assert_eq!(exposed(&manager).len(), 3);
+    }
+
+    #[test]
+    fn test_profiles_are_saved_and_reloaded() {
+        let db = Arc::new(Mutex::new(Database::in_memory().unwrap()));
+        let mut manager = GatewayBackendManager::new(db.clone());
+        let research = ToolProfile {
+            backends: vec!["web".to_string()],
+            ..Default::default()
+        };
+        manager
+            .define_profile("research", research.clone())
+            .unwrap();
+
+        let mut restarted = GatewayBackendManager::new(db.clone());
+        restarted.apply_session_profile(Some("research"));
+        assert_eq!(restarted.active_profile, None);
+        restarted.load_available_mcps().unwrap();
+        assert_eq!(restarted.profile_names(), vec!["research"]);
+        assert_eq!(restarted.profiles.get("research"), Some(&research));
+        restarted.apply_session_profile(Some("research"));
+        assert_eq!(restarted.active_profile.as_deref(), Some("research"));
+
+        db.lock()
+            .unwrap()
+            .set_setting(TOOL_PROFILES_SETTING, "not json")
+            .unwrap();
+        restarted.load_profiles().unwrap();
+        assert!(restarted.profile_names().is_empty());
+    }
+
+    #[test]
+    fn test_backend_options_are_saved_and_reloaded() {
+        let db = Arc::new(Mutex::new(Database::in_memory().unwrap()));
+        let mut manager = GatewayBackendManager::new(db.clone());
+        manager.set_allow_backend_registration(true);
+        manager
+            .set_backend_options(
+                7,
+                BackendOptions {
+                    priority: 3,
+                    denied_tools: vec!["delete_repo".to_string()],
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+        let runtime_id = manager
+            .register_backend(registration("local-server"))
+            .unwrap();
+        manager
+            .set_backend_options(
+                runtime_id,
+                BackendOptions {
+                    priority: 9,
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+
+        let mut restarted = GatewayBackendManager::new(db.clone());
+        assert_eq!(restarted.backend_options(7).priority, 0);
+        restarted.load_available_mcps().unwrap();
+        let options = restarted.backend_options(7);
+        assert_eq!(options.priority, 3);
+        assert!(!options.is_tool_allowed("delete_repo"));
+        // Runtime backends are never saved
+        assert_eq!(restarted.backend_options(runtime_id).priority, 0);
+
+        // Reloading keeps the options of runtime backends in memory
+        manager.load_available_mcps().unwrap();
+        assert_eq!(manager.backend_options(runtime_id).priority, 9);
+        assert_eq!(manager.backend_options(7).priority, 3);
+    }
+
+    #[test]
+    fn test_invalid_saved_backend_options_are_skipped() {
+        let db = Arc::new(Mutex::new(Database::in_memory().unwrap()));
+        db.lock()
+            .unwrap()
+            .set_setting(
+                BACKEND_OPTIONS_SETTING,
+                r#"{"1": {"priority": 2, "initParams": "/workspace"}, "2": {"priority": 5}}"#,
+            )
+            .unwrap();
+
+        let mut manager = GatewayBackendManager::new(db);
+        manager.load_available_mcps().unwrap();
+        let rejected = manager.backend_options(1);
+        assert_eq!(rejected.priority, 0);
+        assert!(rejected.init_params.is_none());
+        assert_eq!(manager.backend_options(2).priority, 5);
+    }
+
+    #[tokio::test]
+    async fn test_each_session_keeps_its_own_profile() {
+        let mut manager = test_manager();
+        insert_connected(
+            &mut manager,
+            1,
+            "docs",
+            vec![test_tool("search_docs", "Search docs")],
+        );
+        insert_connected(
+            &mut manager,
+            2,
+            "web",
+            vec![test_tool("fetch_url", "Fetch")],
+        );
+        manager
+            .define_profile(
+                "research",
+                ToolProfile {
+                    backends: vec!["web".to_string()],
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+        let manager = tokio::sync::Mutex::new(manager);
+
+        let exposed = |manager: &GatewayBackendManager| -> Vec<String> {
+            manager
+                .list_tools_by_backend()
+                .iter()
+                .filter(|l| !l.items.is_empty())
+                .map(|l| l.mcp_name.clone())
+                .collect()
+        };
+
+        let researching = lock_for_session(&manager, Some("research")).await;
+        assert_eq!(exposed(&researching), vec!["web"]);
+        drop(researching);
+
+        let other = lock_for_session(&manager, None).await;
+        assert_eq!(exposed(&other).len(), 2);
+        drop(other);
+
+        let results = invoke_batch(
+            &manager,
+            Some("research"),
+            vec![BatchCall {
+                mcp_name: "docs".to_string(),
+                tool_name: "search_docs".to_string(),
+                arguments: serde_json::json!({}),
+            }],
+        )
+        .await;
+        assert!(results[0].is_err());
+
+        // A removed profile falls back to the full catalog
+        let stale = lock_for_session(&manager, Some("deleted")).await;
+        assert_eq!(exposed(&stale).len(), 2);
+    }
+
+    /// Manager with one connected HTTP backend (keepalive every 30s) counting pings
+    fn keepalive_manager(pings: &Arc<std::sync::atomic::AtomicU32>) -> GatewayBackendManager {
+        let mut manager = test_manager();
+        insert_connected(&mut manager, 1, "remote", vec![test_tool("query", "Query")]);
+        let backend = manager.backends.get_mut(&1).unwrap();
+        backend.mcp.mcp_type = "http".to_string();
+        backend.client = Some(Box::new(PingClient {
+            pings: Arc::clone(pings),
+        }));
+        manager
+            .set_backend_options(
+                1,
+                BackendOptions {
+                    keepalive_interval_secs: Some(30),
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+        manager
+    }
+
+    #[test]
+    fn test_keepalive_pings_at_configured_interval() {
+        let pings = Arc::new(std::sync::atomic::AtomicU32::new(0));
+        let mut manager = keepalive_manager(&pings);
+        let start = manager.backends[&1].last_used;
+        let at = |secs| start + Duration::from_secs(secs);
+        let count = || pings.load(std::sync::atomic::Ordering::SeqCst);
+
+        assert!(manager.send_keepalives(at(10)).is_empty());
+        assert_eq!(manager.send_keepalives(at(30)), vec!["remote"]);
+        assert!(manager.send_keepalives(at(45)).is_empty());
+        assert_eq!(manager.send_keepalives(at(60)), vec!["remote"]);
+        assert_eq!(count(), 2);
+
+        // A call resets the interval
+        manager.backends.get_mut(&1).unwrap().last_used = at(80);
+        assert!(manager.send_keepalives(at(100)).is_empty());
+        assert_eq!(manager.send_keepalives(at(110)), vec!["remote"]);
+        assert_eq!(count(), 3);
+    }
+
+    #[test]
+    fn test_keepalive_disabled_when_idle_eviction_comes_first() {
+        let pings = Arc::new(std::sync::atomic::AtomicU32::new(0));
+        let mut manager = keepalive_manager(&pings);
+        manager.set_idle_timeout(Some(Duration::from_secs(20)));
+        let start = manager.backends[&1].last_used;
+
+        assert!(manager
+            .send_keepalives(start + Duration::from_secs(60))
+            .is_empty());
+        assert_eq!(pings.load(std::sync::atomic::Ordering::SeqCst), 0);
+    }
+
+    #[test]
+    fn test_usage_stats_count_calls_per_tool() {
+        let mut manager = test_manager();
+        insert_connected(
+            &mut manager,
+            1,
+            "web",
+            vec![
+                test_tool("fetch_url", "Fetch"),
+                test_tool("search_web", "Search"),
+            ],
+        );
+        insert_connected(
+            &mut manager,
+            2,
+            "docs",
+            vec![test_tool("search_docs", "Search")],
+        );
+
+        for _ in 0..2 {
+            manager
+                .call_tool_on_mcp("web", "fetch_url", serde_json::json!({}))
+                .unwrap();
+        }
+        manager.call_tools_batch(&[BatchCall {
+            mcp_name: "docs".to_string(),
+            tool_name: "search_docs".to_string(),
+            arguments: serde_json::json!({}),
+        }]);
+
+        let stats = manager.usage_stats();
+        let usage = |mcp: &str, tool: &str| {
+            stats
+                .iter()
+                .find(|u| u.mcp_name == mcp && u.tool_name == tool)
+                .unwrap()
+                .clone()
+        };
+        assert_eq!(stats.len(), 3);
+        assert_eq!(usage("web", "fetch_url").count, 2);
+        assert!(usage("web", "fetch_url").last_used.is_some());
+        assert_eq!(usage("docs", "search_docs").count, 1);
+        assert_eq!(usage("web", "search_web").count, 0);
+        assert_eq!(usage("web", "search_web").last_used, None);
+    }
+
+    #[test]
+    fn test_usage_skips_rejected_and_unknown_calls() {
+        let mut manager = test_manager();
+        insert_connected(
+            &mut manager,
+            1,
+            "web",
+            vec![test_tool("fetch_url", "Fetch")],
+        );
+        manager
+            .set_backend_options(
+                1,
+                BackendOptions {
+                    denied_tools: vec!["fetch_url".to_string()],
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+
+        assert!(manager
+            .call_tool_on_mcp("web", "fetch_url", serde_json::json!({}))
+            .is_err());
+        let _ = manager.call_tool_on_mcp("web", "made_up", serde_json::json!({}));
+        let _ = manager.call_tool_on_mcp("nowhere", "fetch_url", serde_json::json!({}));
+        manager.call_tools_batch(&[
+            BatchCall {
+                mcp_name: "web".to_string(),
+                tool_name: "fetch_url".to_string(),
+                arguments: serde_json::json!({}),
+            },
+            BatchCall {
+                mcp_name: "ghost".to_string(),
+                tool_name: "boo".to_string(),
+                arguments: serde_json::json!({}),
+            },
+        ]);
+
+        let stats = manager.usage_stats();
+        assert!(stats.iter().all(|u| u.count == 0));
+        assert!(!stats
+            .iter()
+            .any(|u| u.mcp_name == "ghost" || u.tool_name == "made_up"));
+    }
 
     #[test]
     fn test_namespace_tool() {
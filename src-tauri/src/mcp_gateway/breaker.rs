@@ -0,0 +1,167 @@
+//! Gateway Backend Circuit Breaker
+//!
+//! Quarantines a backend after repeated consecutive call failures so further
+//! calls fail fast instead of waiting on a broken server. Once the cooldown
+//! has passed, the next call is let through as a probe: success closes the
+//! breaker, failure quarantines the backend for another cooldown.
+
+use crate::mcp_gateway::error::{gateway_error, GatewayErrorCode};
+use anyhow::Result;
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+/// Default consecutive failures before a backend is quarantined
+pub const DEFAULT_FAILURE_THRESHOLD: u32 = 5;
+
+/// Default time a quarantined backend fails fast before a probe is allowed
+pub const DEFAULT_QUARANTINE_COOLDOWN: Duration = Duration::from_secs(30);
+
+/// When to quarantine a backend and for how long
+#[derive(Debug, Clone, PartialEq)]
+pub struct CircuitBreakerConfig {
+    pub failure_threshold: u32,
+    pub cooldown: Duration,
+}
+
+impl Default for CircuitBreakerConfig {
+    fn default() -> Self {
+        Self {
+            failure_threshold: DEFAULT_FAILURE_THRESHOLD,
+            cooldown: DEFAULT_QUARANTINE_COOLDOWN,
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct BreakerState {
+    consecutive_failures: u32,
+    quarantined_until: Option<Instant>,
+}
+
+/// Per-backend failure tracking, keyed by MCP name
+#[derive(Debug, Default)]
+pub struct CircuitBreaker {
+    config: CircuitBreakerConfig,
+    states: HashMap<String, BreakerState>,
+}
+
+impl CircuitBreaker {
+    #[cfg(test)]
+    pub fn new(config: CircuitBreakerConfig) -> Self {
+        Self {
+            config,
+            states: HashMap::new(),
+        }
+    }
+
+    pub fn set_config(&mut self, config: CircuitBreakerConfig) {
+        self.config = config;
+    }
+
+    /// Fail fast if the backend is quarantined and its cooldown hasn't passed
+    pub fn check(&self, mcp_name: &str) -> Result<()> {
+        let Some(until) = self
+            .states
+            .get(mcp_name)
+            .and_then(|state| state.quarantined_until)
+        else {
+            return Ok(());
+        };
+
+        let remaining = until.saturating_duration_since(Instant::now());
+        if remaining.is_zero() {
+            // Cooldown over: let this call through as a probe
+            return Ok(());
+        }
+
+        Err(gateway_error(
+            GatewayErrorCode::Quarantined,
+            format!(
+                "Backend quarantined: MCP '{}' failed {} consecutive calls (retry in {}s)",
+                mcp_name,
+                self.states[mcp_name].consecutive_failures,
+                remaining.as_secs().max(1)
+            ),
+        ))
+    }
+
+    /// Record the outcome of a call that reached the backend
+    pub fn record(&mut self, mcp_name: &str, success: bool) {
+        if success {
+            if self
+                .states
+                .remove(mcp_name)
+                .is_some_and(|s| s.quarantined_until.is_some())
+            {
+                log::info!("[Gateway] MCP '{}' recovered, leaving quarantine", mcp_name);
+            }
+            return;
+        }
+
+        let state = self.states.entry(mcp_name.to_string()).or_default();
+        state.consecutive_failures += 1;
+        if state.consecutive_failures >= self.config.failure_threshold {
+            log::warn!(
+                "[Gateway] Quarantining MCP '{}' for {:?} after {} consecutive failures",
+                mcp_name,
+                self.config.cooldown,
+                state.consecutive_failures
+            );
+            state.quarantined_until = Some(Instant::now() + self.config.cooldown);
+        }
+    }
+
+    /// Forget a backend's failures (e.g. after a manual restart)
+    pub fn reset(&mut self, mcp_name: &str) {
+        self.states.remove(mcp_name);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn breaker(cooldown: Duration) -> CircuitBreaker {
+        CircuitBreaker::new(CircuitBreakerConfig {
+            failure_threshold: 2,
+            cooldown,
+        })
+    }
+
+    #[test]
+    fn test_quarantines_after_threshold() {
+        let mut breaker = breaker(Duration::from_secs(60));
+
+        breaker.record("flaky", false);
+        assert!(breaker.check("flaky").is_ok());
+        breaker.record("flaky", false);
+
+        let err = breaker.check("flaky").unwrap_err().to_string();
+        assert!(err.contains("Backend quarantined"));
+        assert!(breaker.check("other").is_ok());
+    }
+
+    #[test]
+    fn test_success_resets_failures() {
+        let mut breaker = breaker(Duration::from_secs(60));
+
+        breaker.record("flaky", false);
+        breaker.record("flaky", true);
+        breaker.record("flaky", false);
+
+        assert!(breaker.check("flaky").is_ok());
+    }
+
+    #[test]
+    fn test_probe_allowed_after_cooldown() {
+        let mut breaker = breaker(Duration::ZERO);
+        breaker.record("flaky", false);
+        breaker.record("flaky", false);
+
+        assert!(breaker.check("flaky").is_ok());
+
+        // A failed probe quarantines again straight away
+        breaker.record("flaky", false);
+        assert!(breaker.states["flaky"].quarantined_until.is_some());
+    }
+}
@@ -0,0 +1,226 @@
+//! Gateway Tool Result Cache
+//!
+//! Caches results of tools marked cacheable (pure tools where the same
+//! arguments always give the same result). Entries are keyed by backend,
+//! tool and a hash of the arguments, and expire after a TTL. The cache holds
+//! at most `capacity` entries; when full, expired entries are purged first and
+//! then the least recently used entry is evicted.
+
+use crate::mcp_gateway::audit::hash_arguments;
+use crate::services::mcp_client::ToolCallResult;
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+/// Default time a cached result stays valid
+pub const DEFAULT_RESULT_CACHE_TTL: Duration = Duration::from_secs(300);
+
+/// Default maximum number of cached results
+pub const DEFAULT_RESULT_CACHE_CAPACITY: usize = 256;
+
+/// Cache key: (MCP name, tool name, arguments hash)
+type CacheKey = (String, String, String);
+
+#[derive(Debug)]
+struct CacheEntry {
+    stored_at: Instant,
+    /// Value of `ResultCache::clock` when the entry was last read or written
+    last_used: u64,
+    result: ToolCallResult,
+}
+
+/// TTL-based cache of tool call results, bounded with LRU eviction
+#[derive(Debug)]
+pub struct ResultCache {
+    ttl: Duration,
+    capacity: usize,
+    /// Monotonic use counter for LRU ordering
+    clock: u64,
+    entries: HashMap<CacheKey, CacheEntry>,
+}
+
+impl Default for ResultCache {
+    fn default() -> Self {
+        Self::new(DEFAULT_RESULT_CACHE_TTL)
+    }
+}
+
+impl ResultCache {
+    pub fn new(ttl: Duration) -> Self {
+        Self::with_capacity(ttl, DEFAULT_RESULT_CACHE_CAPACITY)
+    }
+
+    pub fn with_capacity(ttl: Duration, capacity: usize) -> Self {
+        Self {
+            ttl,
+            capacity,
+            clock: 0,
+            entries: HashMap::new(),
+        }
+    }
+
+    /// Change the TTL (applies to existing entries too)
+    pub fn set_ttl(&mut self, ttl: Duration) {
+        self.ttl = ttl;
+    }
+
+    fn key(mcp_name: &str, tool_name: &str, arguments: &serde_json::Value) -> CacheKey {
+        (
+            mcp_name.to_string(),
+            tool_name.to_string(),
+            hash_arguments(arguments),
+        )
+    }
+
+    /// Get a cached result if present and not expired (expired entries are dropped)
+    pub fn get(
+        &mut self,
+        mcp_name: &str,
+        tool_name: &str,
+        arguments: &serde_json::Value,
+    ) -> Option<ToolCallResult> {
+        let key = Self::key(mcp_name, tool_name, arguments);
+        self.clock += 1;
+        match self.entries.get_mut(&key) {
+            Some(entry) if entry.stored_at.elapsed() < self.ttl => {
+                entry.last_used = self.clock;
+                Some(entry.result.clone())
+            }
+            Some(_) => {
+                self.entries.remove(&key);
+                None
+            }
+            None => None,
+        }
+    }
+
+    /// Store a result, evicting expired entries and then the least recently
+    /// used one if the cache is full
+    pub fn insert(
+        &mut self,
+        mcp_name: &str,
+        tool_name: &str,
+        arguments: &serde_json::Value,
+        result: ToolCallResult,
+    ) {
+        if self.capacity == 0 {
+            return;
+        }
+        let key = Self::key(mcp_name, tool_name, arguments);
+        if !self.entries.contains_key(&key) && self.entries.len() >= self.capacity {
+            let ttl = self.ttl;
+            self.entries
+                .retain(|_, entry| entry.stored_at.elapsed() < ttl);
+            if self.entries.len() >= self.capacity {
+                let oldest = self
+                    .entries
+                    .iter()
+                    .min_by_key(|(_, entry)| entry.last_used)
+                    .map(|(key, _)| key.clone());
+                if let Some(oldest) = oldest {
+                    self.entries.remove(&oldest);
+                }
+            }
+        }
+        self.clock += 1;
+        self.entries.insert(
+            key,
+            CacheEntry {
+                stored_at: Instant::now(),
+                last_used: self.clock,
+                result,
+            },
+        );
+    }
+
+    /// Drop every cached result for a backend
+    pub fn invalidate_mcp(&mut self, mcp_name: &str) {
+        self.entries.retain(|(name, _, _), _| name != mcp_name);
+    }
+
+    /// Drop all cached results
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    #[cfg(test)]
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    #[cfg(test)]
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::mcp_client::ToolContent;
+    use serde_json::json;
+
+    fn result(text: &str) -> ToolCallResult {
+        ToolCallResult {
+            success: true,
+            content: vec![ToolContent::Text {
+                text: text.to_string(),
+            }],
+            is_error: false,
+            error: None,
+            execution_time_ms: 0,
+        }
+    }
+
+    #[test]
+    fn test_get_matches_identical_arguments_only() {
+        let mut cache = ResultCache::default();
+        cache.insert("math", "add", &json!({"a": 1, "b": 2}), result("3"));
+
+        assert!(cache.get("math", "add", &json!({"a": 1, "b": 2})).is_some());
+        assert!(cache.get("math", "add", &json!({"a": 1, "b": 3})).is_none());
+        assert!(cache
+            .get("other", "add", &json!({"a": 1, "b": 2}))
+            .is_none());
+    }
+
+    #[test]
+    fn test_expired_entries_are_dropped() {
+        let mut cache = ResultCache::new(Duration::ZERO);
+        cache.insert("math", "add", &json!({}), result("0"));
+
+        assert!(cache.get("math", "add", &json!({})).is_none());
+        assert!(cache.is_empty());
+    }
+
+    #[test]
+    fn test_full_cache_evicts_least_recently_used() {
+        let mut cache = ResultCache::with_capacity(DEFAULT_RESULT_CACHE_TTL, 2);
+        cache.insert("math", "add", &json!({"a": 1}), result("1"));
+        cache.insert("math", "add", &json!({"a": 2}), result("2"));
+
+        // Reading the first entry makes the second the least recently used
+        assert!(cache.get("math", "add", &json!({"a": 1})).is_some());
+        cache.insert("math", "add", &json!({"a": 3}), result("3"));
+
+        assert_eq!(cache.len(), 2);
+        assert!(cache.get("math", "add", &json!({"a": 1})).is_some());
+        assert!(cache.get("math", "add", &json!({"a": 2})).is_none());
+        assert!(cache.get("math", "add", &json!({"a": 3})).is_some());
+
+        // Overwriting an existing key never evicts
+        cache.insert("math", "add", &json!({"a": 3}), result("3"));
+        assert_eq!(cache.len(), 2);
+    }
+
+    #[test]
+    fn test_invalidate_mcp() {
+        let mut cache = ResultCache::default();
+        cache.insert("math", "add", &json!({}), result("0"));
+        cache.insert("text", "upper", &json!({}), result("A"));
+
+        cache.invalidate_mcp("math");
+
+        assert!(cache.get("math", "add", &json!({})).is_none());
+        assert!(cache.get("text", "upper", &json!({})).is_some());
+    }
+}
@@ -0,0 +1,130 @@
+//! Gateway Error Codes
+//!
+//! Classifies gateway failures into stable codes that are sent with every
+//! error result, so agents can branch on the kind of failure instead of
+//! parsing the message.
+
+use crate::services::http_pool::{PoolExhausted, PoolWaitTimedOut};
+use crate::services::mcp_client::{HttpStatusError, RequestTimedOut};
+use serde::Serialize;
+use serde_json::{json, Value};
+
+/// Kind of failure behind a gateway error result
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GatewayErrorCode {
+    /// No such backend, or it isn't connected
+    BackendNotFound,
+    /// The backend doesn't offer the tool, or it is hidden by the allow/deny
+    /// lists or the active profile
+    ToolNotExposed,
+    /// The backend didn't answer in time
+    Timeout,
+    /// The backend is refusing calls because of their rate
+    RateLimited,
+    /// The circuit breaker is failing calls fast after repeated failures
+    Quarantined,
+    /// The arguments don't match the tool's input schema
+    InvalidArgs,
+    /// Any other failure reported by or talking to the backend
+    BackendError,
+}
+
+/// A gateway failure with its code. Displays as the message alone, so error
+/// text reads the same as before codes were attached.
+#[derive(Debug, thiserror::Error)]
+#[error("{message}")]
+pub struct GatewayError {
+    pub code: GatewayErrorCode,
+    pub message: String,
+}
+
+/// Build a coded gateway error
+pub fn gateway_error(code: GatewayErrorCode, message: impl Into<String>) -> anyhow::Error {
+    GatewayError {
+        code,
+        message: message.into(),
+    }
+    .into()
+}
+
+/// Code of any error from a gateway call: the code it was raised with, or
+/// one derived from the client error behind it (BackendError otherwise)
+pub fn error_code(err: &anyhow::Error) -> GatewayErrorCode {
+    for cause in err.chain() {
+        if let Some(e) = cause.downcast_ref::<GatewayError>() {
+            return e.code;
+        }
+        if cause.is::<RequestTimedOut>() || cause.is::<PoolWaitTimedOut>() {
+            return GatewayErrorCode::Timeout;
+        }
+        if cause.is::<PoolExhausted>() {
+            return GatewayErrorCode::RateLimited;
+        }
+        if let Some(e) = cause.downcast_ref::<HttpStatusError>() {
+            return match e.status {
+                429 => GatewayErrorCode::RateLimited,
+                408 | 504 => GatewayErrorCode::Timeout,
+                _ => GatewayErrorCode::BackendError,
+            };
+        }
+    }
+    GatewayErrorCode::BackendError
+}
+
+/// `{"code": ..., "message": ...}` for an error result
+pub fn error_json(err: &anyhow::Error) -> Value {
+    json!({
+        "code": error_code(err),
+        "message": err.to_string(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::anyhow;
+
+    #[test]
+    fn test_error_code_from_client_errors() {
+        let timeout: anyhow::Error = RequestTimedOut { secs: 30 }.into();
+        assert_eq!(error_code(&timeout), GatewayErrorCode::Timeout);
+        assert_eq!(
+            error_code(&timeout.context("calling search")),
+            GatewayErrorCode::Timeout
+        );
+
+        let throttled: anyhow::Error = HttpStatusError {
+            status: 429,
+            body: "slow down".to_string(),
+        }
+        .into();
+        assert_eq!(error_code(&throttled), GatewayErrorCode::RateLimited);
+
+        let exhausted: anyhow::Error = PoolExhausted {
+            open: 4,
+            queued: 16,
+        }
+        .into();
+        assert_eq!(error_code(&exhausted), GatewayErrorCode::RateLimited);
+        let waited: anyhow::Error = PoolWaitTimedOut { secs: 30 }.into();
+        assert_eq!(error_code(&waited), GatewayErrorCode::Timeout);
+
+        assert_eq!(
+            error_code(&anyhow!("connection reset")),
+            GatewayErrorCode::BackendError
+        );
+    }
+
+    #[test]
+    fn test_error_json_carries_code_and_message() {
+        let err = gateway_error(
+            GatewayErrorCode::InvalidArgs,
+            "invalid arguments: missing path",
+        );
+        assert_eq!(
+            error_json(&err),
+            json!({"code": "invalid_args", "message": "invalid arguments: missing path"})
+        );
+    }
+}
@@ -4,8 +4,14 @@
 //! discovering and connecting to backend MCPs on demand. This reduces context
 //! pollution by only loading tools when explicitly requested.
 
+pub mod audit;
 pub mod backend;
+pub mod breaker;
+pub mod cache;
+pub mod error;
 pub mod server;
 pub mod tools;
+pub mod transform;
+pub mod usage;
 
 pub use server::GatewayServerState;
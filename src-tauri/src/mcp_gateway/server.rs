@@ -3,16 +3,29 @@
 //! Handles starting, stopping, and managing the Gateway HTTP server.
 
 use crate::db::Database;
-use crate::mcp_gateway::backend::{AvailableMcp, BackendInfo, GatewayBackendManager};
-use crate::mcp_gateway::tools::GatewayServer;
+use crate::mcp_gateway::audit::AuditLogConfig;
+use crate::mcp_gateway::backend::{
+    run_idle_sweeper, run_keepalive, run_warmup, AvailableMcp, BackendId, BackendInfo,
+    GatewayBackendManager, ToolProfile, IDLE_SWEEP_INTERVAL, KEEPALIVE_CHECK_INTERVAL,
+};
+use crate::mcp_gateway::breaker::{
+    CircuitBreakerConfig, DEFAULT_FAILURE_THRESHOLD, DEFAULT_QUARANTINE_COOLDOWN,
+};
+use crate::mcp_gateway::cache::DEFAULT_RESULT_CACHE_TTL;
+use crate::mcp_gateway::tools::{forward_sampling, GatewayServer, SamplingPeer};
+use crate::mcp_gateway::usage::ToolUsage;
+use crate::services::http_pool::{PoolConfig, DEFAULT_POOL_SIZE};
+use crate::services::mcp_client::{SamplingHandler, ToolCallResult};
 use axum::Router;
 use rmcp::transport::streamable_http_server::{
     session::local::LocalSessionManager, StreamableHttpService,
 };
 use serde::{Deserialize, Serialize};
 use std::net::SocketAddr;
+use std::path::PathBuf;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Mutex};
+use std::time::Duration;
 use tokio::net::TcpListener;
 use tokio::sync::oneshot;
 use tower_http::cors::{Any, CorsLayer};
@@ -22,11 +35,25 @@ pub const DEFAULT_GATEWAY_PORT: u16 = 23848;
 
 /// Gateway server configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
+#[serde(rename_all = "camelCase", default)]
 pub struct GatewayServerConfig {
     pub enabled: bool,
     pub port: u16,
     pub auto_start: bool,
+    /// Let clients add MCPs (and so launch commands) through register_backend
+    pub allow_backend_registration: bool,
+    /// File the tool-call audit log is appended to (empty disables the log)
+    pub audit_log_path: String,
+    /// How long cached results of cacheable tools stay valid
+    pub result_cache_ttl_secs: u64,
+    /// Disconnect backends unused for this long (0 disables eviction)
+    pub idle_timeout_secs: u64,
+    /// Consecutive failed calls that quarantine a backend
+    pub circuit_breaker_threshold: u32,
+    /// How long a quarantined backend is skipped
+    pub circuit_breaker_cooldown_secs: u64,
+    /// Connections open to one HTTP backend at a time
+    pub http_pool_size: usize,
 }
 
 impl Default for GatewayServerConfig {
@@ -35,6 +62,13 @@ impl Default for GatewayServerConfig {
             enabled: false,
             port: DEFAULT_GATEWAY_PORT,
             auto_start: false,
+            allow_backend_registration: false,
+            audit_log_path: String::new(),
+            result_cache_ttl_secs: DEFAULT_RESULT_CACHE_TTL.as_secs(),
+            idle_timeout_secs: 0,
+            circuit_breaker_threshold: DEFAULT_FAILURE_THRESHOLD,
+            circuit_breaker_cooldown_secs: DEFAULT_QUARANTINE_COOLDOWN.as_secs(),
+            http_pool_size: DEFAULT_POOL_SIZE,
         }
     }
 }
@@ -62,18 +96,22 @@ pub struct GatewayServerState {
     shutdown_tx: Arc<Mutex<Option<oneshot::Sender<()>>>>,
     port: Arc<Mutex<u16>>,
     pub backend_manager: Arc<tokio::sync::Mutex<GatewayBackendManager>>,
+    /// Workspace roots forwarded to backends through the MCP roots capability
+    pub roots: Arc<Mutex<Vec<PathBuf>>>,
 }
 
 impl GatewayServerState {
     pub fn with_config(config: GatewayServerConfig, db: Arc<Mutex<Database>>) -> Self {
         let port = config.port;
-        let backend_manager = GatewayBackendManager::new(db.clone());
+        let mut backend_manager = GatewayBackendManager::new(db.clone());
+        backend_manager.set_allow_backend_registration(config.allow_backend_registration);
         Self {
             is_running: Arc::new(AtomicBool::new(false)),
             config: Arc::new(Mutex::new(config)),
             shutdown_tx: Arc::new(Mutex::new(None)),
             port: Arc::new(Mutex::new(port)),
             backend_manager: Arc::new(tokio::sync::Mutex::new(backend_manager)),
+            roots: Arc::new(Mutex::new(Vec::new())),
         }
     }
 
@@ -117,19 +155,44 @@ impl GatewayServerState {
             return Err("Gateway server is already running".to_string());
         }
 
-        let port = {
-            let config = self.config.lock().map_err(|e| e.to_string())?;
-            config.port
-        };
+        let config = self.get_config()?;
+        let port = config.port;
+
+        // Apply the gateway settings before any backend connects
+        self.set_audit_log(
+            (!config.audit_log_path.is_empty())
+                .then(|| AuditLogConfig::new(PathBuf::from(&config.audit_log_path))),
+        )
+        .await;
+        self.set_result_cache_ttl(Duration::from_secs(config.result_cache_ttl_secs))
+            .await;
+        self.set_idle_timeout(
+            (config.idle_timeout_secs > 0).then(|| Duration::from_secs(config.idle_timeout_secs)),
+        )
+        .await;
+        self.set_circuit_breaker(CircuitBreakerConfig {
+            failure_threshold: config.circuit_breaker_threshold,
+            cooldown: Duration::from_secs(config.circuit_breaker_cooldown_secs),
+        })
+        .await;
+        self.set_http_pool_config(PoolConfig {
+            size: config.http_pool_size,
+            ..Default::default()
+        })
+        .await;
 
         // Load available MCPs (lazy mode - no connections yet)
         {
             let mut backend_manager = self.backend_manager.lock().await;
+            backend_manager.set_allow_backend_registration(config.allow_backend_registration);
             backend_manager
                 .load_available_mcps()
                 .map_err(|e| e.to_string())?;
         }
 
+        // Pre-connect warmup-flagged backends without blocking startup
+        schedule_warmup(self);
+
         // Try to bind to the port
         let addr = SocketAddr::from(([127, 0, 0, 1], port));
         let listener = TcpListener::bind(addr)
@@ -138,10 +201,19 @@ impl GatewayServerState {
 
         log::info!("[Gateway] Starting MCP Gateway on {}", addr);
 
+        // Forward backends' sampling requests to the latest client session
+        let sampling_peer = SamplingPeer::default();
+        set_sampling_handler(self, Some(forward_sampling(sampling_peer.clone()))).await;
+
         // Create the gateway MCP service
         let backend_manager = self.backend_manager.clone();
         let service = StreamableHttpService::new(
-            move || Ok(GatewayServer::new(backend_manager.clone())),
+            move || {
+                Ok(GatewayServer::new(
+                    backend_manager.clone(),
+                    sampling_peer.clone(),
+                ))
+            },
             LocalSessionManager::default().into(),
             Default::default(),
         );
@@ -171,6 +243,10 @@ impl GatewayServerState {
         }
         self.is_running.store(true, Ordering::SeqCst);
 
+        // Disconnect idle backends and keep HTTP sessions warm until the server stops
+        let idle_sweeper = schedule_idle_sweeper(self);
+        let keepalive = schedule_keepalive(self);
+
         // Spawn the server
         let is_running = self.is_running.clone();
         let backend_manager_shutdown = self.backend_manager.clone();
@@ -185,6 +261,8 @@ impl GatewayServerState {
             }
 
             // Shutdown backend connections
+            idle_sweeper.abort();
+            keepalive.abort();
             {
                 let mut manager = backend_manager_shutdown.lock().await;
                 manager.shutdown();
@@ -222,7 +300,7 @@ impl GatewayServerState {
         Ok(())
     }
 
-    /// Update configuration
+    /// Update configuration (everything but enabled and auto_start applies on the next start)
     pub fn update_config(&self, new_config: GatewayServerConfig) -> Result<(), String> {
         let mut config = self.config.lock().map_err(|e| e.to_string())?;
         *config = new_config;
@@ -235,6 +313,36 @@ impl GatewayServerState {
         Ok(config.clone())
     }
 
+    /// Enable (or disable with None) the tool-call audit log
+    pub async fn set_audit_log(&self, config: Option<AuditLogConfig>) {
+        let mut backend_manager = self.backend_manager.lock().await;
+        backend_manager.set_audit_log(config);
+    }
+
+    /// Set how long cached results of cacheable tools stay valid
+    pub async fn set_result_cache_ttl(&self, ttl: std::time::Duration) {
+        let mut backend_manager = self.backend_manager.lock().await;
+        backend_manager.set_result_cache_ttl(ttl);
+    }
+
+    /// Set how long a backend may go unused before it is disconnected (None disables)
+    pub async fn set_idle_timeout(&self, timeout: Option<std::time::Duration>) {
+        let mut backend_manager = self.backend_manager.lock().await;
+        backend_manager.set_idle_timeout(timeout);
+    }
+
+    /// Set when a backend with repeated failed calls is quarantined, and for how long
+    pub async fn set_circuit_breaker(&self, config: CircuitBreakerConfig) {
+        let mut backend_manager = self.backend_manager.lock().await;
+        backend_manager.set_circuit_breaker(config);
+    }
+
+    /// Set the size and timeouts of HTTP backends' connection pools
+    pub async fn set_http_pool_config(&self, config: PoolConfig) {
+        let mut backend_manager = self.backend_manager.lock().await;
+        backend_manager.set_http_pool_config(config);
+    }
+
     /// Restart a specific backend
     pub async fn restart_backend(&self, mcp_id: i64) -> Result<BackendInfo, String> {
         let mut backend_manager = self.backend_manager.lock().await;
@@ -255,6 +363,130 @@ impl GatewayServerState {
     }
 }
 
+/// Connect warmup-flagged backends on a background task.
+/// Returns immediately; failures are retried and logged by the task.
+pub fn schedule_warmup(state: &GatewayServerState) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(run_warmup(state.backend_manager.clone()))
+}
+
+/// Periodically ping HTTP backends configured with a keepalive interval.
+/// The task runs until aborted.
+pub fn schedule_keepalive(state: &GatewayServerState) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(run_keepalive(
+        state.backend_manager.clone(),
+        KEEPALIVE_CHECK_INTERVAL,
+    ))
+}
+
+/// Periodically disconnect backends idle for longer than the manager's idle timeout.
+/// The task runs until aborted.
+pub fn schedule_idle_sweeper(state: &GatewayServerState) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(run_idle_sweeper(
+        state.backend_manager.clone(),
+        IDLE_SWEEP_INTERVAL,
+    ))
+}
+
+/// Every tool exposed through the gateway, with descriptions and schemas.
+/// Connects backends that aren't connected yet; see GatewayBackendManager::export_catalog.
+pub async fn export_catalog(state: &GatewayServerState) -> serde_json::Value {
+    let mut backend_manager = state.backend_manager.lock().await;
+    backend_manager.export_catalog().await
+}
+
+/// Set the workspace roots offered to backends when they connect.
+/// Backends that are already connected keep their previous roots.
+pub async fn set_roots(state: &GatewayServerState, roots: Vec<PathBuf>) {
+    *state.roots.lock().unwrap() = roots.clone();
+    state.backend_manager.lock().await.set_roots(roots);
+}
+
+/// Register the callback that runs model completions for backends' sampling
+/// requests, or refuse them with None. Backends that are already connected
+/// keep their previous handler.
+pub async fn set_sampling_handler(state: &GatewayServerState, handler: Option<SamplingHandler>) {
+    state
+        .backend_manager
+        .lock()
+        .await
+        .set_sampling_handler(handler);
+}
+
+/// Set a backend's log level (RUST_LOG-style, None to stop overriding it).
+/// Returns whether a connected backend applied it at once; otherwise it takes
+/// effect on the next connection, if the backend supports it at all.
+pub async fn set_backend_log_level(
+    state: &GatewayServerState,
+    mcp_id: i64,
+    level: Option<String>,
+) -> bool {
+    state
+        .backend_manager
+        .lock()
+        .await
+        .set_backend_log_level(mcp_id, level)
+}
+
+/// Status and declared capabilities of one backend, connecting it if needed.
+/// See GatewayBackendManager::backend_info.
+pub async fn backend_info(
+    state: &GatewayServerState,
+    backend_id: &str,
+) -> anyhow::Result<BackendInfo> {
+    let mut backend_manager = state.backend_manager.lock().await;
+    backend_manager.backend_info(backend_id).await
+}
+
+/// Ids of backends that are connected right now, without connecting or
+/// pinging any
+pub async fn list_connected_backends(state: &GatewayServerState) -> Vec<BackendId> {
+    state.backend_manager.lock().await.connected_backend_ids()
+}
+
+/// Names of connected backends offering `tool_name`, highest priority first.
+/// See GatewayBackendManager::find_backends_with_tool.
+pub async fn find_backends_with_tool(state: &GatewayServerState, tool_name: &str) -> Vec<String> {
+    state
+        .backend_manager
+        .lock()
+        .await
+        .find_backends_with_tool(tool_name)
+}
+
+/// Call `tool_name` on whichever connected backend offers it, preferring the
+/// highest priority. Fails if no connected backend offers the tool.
+pub async fn invoke_any(
+    state: &GatewayServerState,
+    tool_name: &str,
+    arguments: serde_json::Value,
+) -> anyhow::Result<ToolCallResult> {
+    state
+        .backend_manager
+        .lock()
+        .await
+        .call_tool_on_any(tool_name, arguments)
+}
+
+/// Per-tool invocation counts and last call times, for spotting unused backends.
+/// See GatewayBackendManager::usage_stats.
+pub async fn usage_stats(state: &GatewayServerState) -> Vec<ToolUsage> {
+    state.backend_manager.lock().await.usage_stats()
+}
+
+/// Define (or replace) a tool profile, saved so it survives restarts.
+/// Sessions choose a profile for themselves with the use_profile meta-tool.
+pub async fn define_profile(
+    state: &GatewayServerState,
+    name: &str,
+    profile: ToolProfile,
+) -> anyhow::Result<()> {
+    state
+        .backend_manager
+        .lock()
+        .await
+        .define_profile(name, profile)
+}
+
 /// Generate an MCP entry for the Gateway server to add to the library as a system MCP
 pub fn generate_gateway_mcp_entry(port: u16) -> crate::db::models::CreateMcpRequest {
     crate::db::models::CreateMcpRequest {
@@ -274,6 +506,302 @@ pub fn generate_gateway_mcp_entry(port: u16) -> crate::db::models::CreateMcpRequ
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::db::models::Mcp;
+    use crate::mcp_gateway::backend::{
+        BackendClient, BackendConnector, BackendOptions, BackendRegistration, BackendStatus,
+        ConnectedBackend, RetryPolicy,
+    };
+    use crate::services::mcp_client::{
+        sampling_reply, McpServerCapabilities, McpServerInfo, McpTool, ToolCallResult, ToolContent,
+        SAMPLING_METHOD,
+    };
+    use std::sync::atomic::AtomicU32;
+
+    struct NoopClient;
+
+    impl BackendClient for NoopClient {
+        fn call_tool(
+            &mut self,
+            _name: &str,
+            _arguments: serde_json::Value,
+        ) -> anyhow::Result<ToolCallResult> {
+            Err(anyhow::anyhow!("not implemented"))
+        }
+    }
+
+    /// Connector that fails the first `failures` attempts, then connects
+    struct FlakyConnector {
+        failures: u32,
+        attempts: Arc<AtomicU32>,
+    }
+
+    impl BackendConnector for FlakyConnector {
+        fn connect(
+            &self,
+            mcp: &Mcp,
+            _options: &BackendOptions,
+        ) -> anyhow::Result<ConnectedBackend> {
+            let attempt = self.attempts.fetch_add(1, Ordering::SeqCst) + 1;
+            if attempt <= self.failures {
+                return Err(anyhow::anyhow!("connection refused"));
+            }
+            Ok(ConnectedBackend {
+                client: Box::new(NoopClient),
+                server_info: McpServerInfo {
+                    name: mcp.name.clone(),
+                    version: None,
+                },
+                capabilities: McpServerCapabilities::default(),
+                tools: Vec::new(),
+                resources: None,
+                prompts: None,
+            })
+        }
+    }
+
+    async fn warmup_state(failures: u32, warmup: bool) -> (GatewayServerState, Arc<AtomicU32>) {
+        let db = Arc::new(Mutex::new(Database::in_memory().unwrap()));
+        let state = GatewayServerState::with_config(GatewayServerConfig::default(), db);
+        let attempts = Arc::new(AtomicU32::new(0));
+        {
+            let mut manager = state.backend_manager.lock().await;
+            manager.set_connector(Box::new(FlakyConnector {
+                failures,
+                attempts: attempts.clone(),
+            }));
+            let mut registration: BackendRegistration = serde_json::from_value(
+                serde_json::json!({"name": "warm", "command": "npx warm-server"}),
+            )
+            .unwrap();
+            registration.options = BackendOptions {
+                warmup,
+                retry: RetryPolicy {
+                    max_attempts: 3,
+                    initial_backoff_ms: 1,
+                    max_backoff_ms: 1,
+                },
+                ..Default::default()
+            };
+            manager.set_allow_backend_registration(true);
+            manager.register_backend(registration).unwrap();
+        }
+        (state, attempts)
+    }
+
+    async fn backend_status(state: &GatewayServerState) -> Option<BackendStatus> {
+        let manager = state.backend_manager.lock().await;
+        manager
+            .get_backends_info()
+            .into_iter()
+            .find(|b| b.mcp_name == "warm")
+            .map(|b| b.status)
+    }
+
+    #[tokio::test]
+    async fn test_schedule_warmup_connects_flagged_backend() {
+        let (state, attempts) = warmup_state(0, true).await;
+
+        schedule_warmup(&state).await.unwrap();
+
+        assert_eq!(backend_status(&state).await, Some(BackendStatus::Connected));
+        assert_eq!(attempts.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_schedule_warmup_retries_failures() {
+        let (state, attempts) = warmup_state(2, true).await;
+
+        schedule_warmup(&state).await.unwrap();
+
+        assert_eq!(backend_status(&state).await, Some(BackendStatus::Connected));
+        assert_eq!(attempts.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn test_schedule_warmup_skips_unflagged_backend() {
+        let (state, attempts) = warmup_state(0, false).await;
+
+        schedule_warmup(&state).await.unwrap();
+
+        assert_eq!(backend_status(&state).await, None);
+        assert_eq!(attempts.load(Ordering::SeqCst), 0);
+    }
+
+    /// Connector that records the roots each backend is offered
+    struct RootsConnector {
+        offered: Arc<Mutex<Vec<Vec<PathBuf>>>>,
+    }
+
+    impl BackendConnector for RootsConnector {
+        fn connect(&self, mcp: &Mcp, options: &BackendOptions) -> anyhow::Result<ConnectedBackend> {
+            self.offered.lock().unwrap().push(options.roots.clone());
+            FlakyConnector {
+                failures: 0,
+                attempts: Arc::new(AtomicU32::new(0)),
+            }
+            .connect(mcp, options)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_set_roots_forwards_roots_to_backends() {
+        let db = Arc::new(Mutex::new(Database::in_memory().unwrap()));
+        let state = GatewayServerState::with_config(GatewayServerConfig::default(), db);
+        let offered = Arc::new(Mutex::new(Vec::new()));
+        let roots = vec![PathBuf::from("/work/app"), PathBuf::from("/work/lib")];
+
+        set_roots(&state, roots.clone()).await;
+        {
+            let mut manager = state.backend_manager.lock().await;
+            manager.set_connector(Box::new(RootsConnector {
+                offered: offered.clone(),
+            }));
+            let registration: BackendRegistration = serde_json::from_value(
+                serde_json::json!({"name": "files", "command": "npx files-server"}),
+            )
+            .unwrap();
+            manager.set_allow_backend_registration(true);
+            manager.register_backend(registration).unwrap();
+            manager.connect_backend_lazy("files").await.unwrap();
+        }
+
+        assert_eq!(*state.roots.lock().unwrap(), roots);
+        assert_eq!(*offered.lock().unwrap(), vec![roots]);
+    }
+
+    #[tokio::test]
+    async fn test_list_connected_backends_skips_unconnected() {
+        let db = Arc::new(Mutex::new(Database::in_memory().unwrap()));
+        let state = GatewayServerState::with_config(GatewayServerConfig::default(), db);
+        let connected_id = {
+            let mut manager = state.backend_manager.lock().await;
+            manager.set_connector(Box::new(FlakyConnector {
+                failures: 0,
+                attempts: Arc::new(AtomicU32::new(0)),
+            }));
+            manager.set_allow_backend_registration(true);
+            let mut ids = Vec::new();
+            for name in ["files", "search"] {
+                let registration: BackendRegistration = serde_json::from_value(
+                    serde_json::json!({"name": name, "command": format!("npx {}-server", name)}),
+                )
+                .unwrap();
+                ids.push(manager.register_backend(registration).unwrap());
+            }
+            manager.connect_backend_lazy("files").await.unwrap();
+            ids[0]
+        };
+
+        assert_eq!(list_connected_backends(&state).await, vec![connected_id]);
+    }
+
+    /// Backend that asks the gateway for a completion on every tool call and
+    /// returns the JSON-RPC reply it got as the tool's text
+    struct SamplingClient {
+        sampling: Option<SamplingHandler>,
+    }
+
+    impl BackendClient for SamplingClient {
+        fn call_tool(
+            &mut self,
+            _name: &str,
+            arguments: serde_json::Value,
+        ) -> anyhow::Result<ToolCallResult> {
+            let request = serde_json::json!({
+                "jsonrpc": "2.0",
+                "id": "s1",
+                "method": SAMPLING_METHOD,
+                "params": {"messages": [{"role": "user", "content": {"type": "text", "text": arguments["prompt"]}}]},
+            });
+            let reply = sampling_reply(&request, self.sampling.as_ref()).unwrap();
+            Ok(ToolCallResult {
+                success: true,
+                content: vec![ToolContent::Text {
+                    text: reply.to_string(),
+                }],
+                is_error: false,
+                error: None,
+                execution_time_ms: 0,
+            })
+        }
+    }
+
+    struct SamplingConnector;
+
+    impl BackendConnector for SamplingConnector {
+        fn connect(&self, mcp: &Mcp, options: &BackendOptions) -> anyhow::Result<ConnectedBackend> {
+            Ok(ConnectedBackend {
+                client: Box::new(SamplingClient {
+                    sampling: options.sampling.clone(),
+                }),
+                server_info: McpServerInfo {
+                    name: mcp.name.clone(),
+                    version: None,
+                },
+                capabilities: McpServerCapabilities::default(),
+                tools: vec![McpTool {
+                    name: "summarize".to_string(),
+                    description: None,
+                    input_schema: None,
+                }],
+                resources: None,
+                prompts: None,
+            })
+        }
+    }
+
+    async fn sampling_reply_text(handler: Option<SamplingHandler>) -> serde_json::Value {
+        let db = Arc::new(Mutex::new(Database::in_memory().unwrap()));
+        let state = GatewayServerState::with_config(GatewayServerConfig::default(), db);
+        set_sampling_handler(&state, handler).await;
+
+        let mut manager = state.backend_manager.lock().await;
+        manager.set_connector(Box::new(SamplingConnector));
+        let registration: BackendRegistration = serde_json::from_value(
+            serde_json::json!({"name": "writer", "command": "npx writer-server"}),
+        )
+        .unwrap();
+        manager.set_allow_backend_registration(true);
+        manager.register_backend(registration).unwrap();
+        manager.connect_backend_lazy("writer").await.unwrap();
+
+        let result = manager
+            .call_tool_on_mcp("writer", "summarize", serde_json::json!({"prompt": "Hi"}))
+            .unwrap();
+        match &result.content[0] {
+            ToolContent::Text { text } => serde_json::from_str(text).unwrap(),
+            other => panic!("unexpected content {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_backend_sampling_request_reaches_handler() {
+        let handler = SamplingHandler::new(|params| {
+            let prompt = params["messages"][0]["content"]["text"].as_str().unwrap();
+            Ok(serde_json::json!({
+                "role": "assistant",
+                "content": {"type": "text", "text": format!("Summary of {}", prompt)},
+                "model": "stub",
+            }))
+        });
+
+        let reply = sampling_reply_text(Some(handler)).await;
+
+        assert_eq!(reply["id"], "s1");
+        assert_eq!(reply["result"]["content"]["text"], "Summary of Hi");
+        assert_eq!(reply["result"]["model"], "stub");
+    }
+
+    #[tokio::test]
+    async fn test_backend_sampling_request_refused_without_handler() {
+        let reply = sampling_reply_text(None).await;
+
+        assert!(reply.get("result").is_none());
+        assert!(reply["error"]["message"]
+            .as_str()
+            .unwrap()
+            .starts_with("Sampling not supported"));
+    }
 
     #[test]
     fn test_default_config() {
@@ -281,6 +809,25 @@ mod tests {
         assert!(!config.enabled);
         assert_eq!(config.port, DEFAULT_GATEWAY_PORT);
         assert!(!config.auto_start);
+        assert!(config.audit_log_path.is_empty());
+        assert_eq!(config.idle_timeout_secs, 0);
+    }
+
+    #[test]
+    fn test_config_without_tuning_fields_uses_defaults() {
+        let config: GatewayServerConfig = serde_json::from_value(serde_json::json!({
+            "enabled": true,
+            "port": 4000,
+            "autoStart": false
+        }))
+        .unwrap();
+        assert_eq!(config.port, 4000);
+        assert!(!config.allow_backend_registration);
+        assert_eq!(
+            config.result_cache_ttl_secs,
+            DEFAULT_RESULT_CACHE_TTL.as_secs()
+        );
+        assert_eq!(config.http_pool_size, DEFAULT_POOL_SIZE);
     }
 
     #[test]
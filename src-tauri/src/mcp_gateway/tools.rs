@@ -1,23 +1,37 @@
 //! MCP Gateway Server Handler
 //!
 //! Implements the rmcp ServerHandler trait with lazy-loading meta-tools.
-//! Instead of exposing all backend tools upfront, exposes 3 meta-tools:
+//! Instead of exposing all backend tools upfront, exposes meta-tools:
 //! - list_available_mcps: Discover available MCP servers
 //! - load_mcp_tools: Connect to an MCP and get its tools
 //! - call_mcp_tool: Execute a tool on a specific MCP
+//! - call_mcp_tools_batch: Execute several tools at once, across MCPs
+//! - search_tools: Search tools of connected MCPs
+//! - describe_tool: Full definition of one tool (listings may shorten descriptions)
+//! - export_catalog: Every tool of every MCP, for documentation
+//! - backend_info: Server version and declared capabilities of one MCP
+//! - list_tools / list_resources / list_prompts: Capabilities of connected MCPs, per MCP
+//! - register_backend: Add an MCP to the gateway at runtime
+//! - use_profile: Limit this session to one of the saved tool profiles
 
-use crate::mcp_gateway::backend::GatewayBackendManager;
+use crate::mcp_gateway::backend::{
+    invoke_batch, invoke_tool, lock_for_session, BackendRegistration, BatchCall,
+    GatewayBackendManager,
+};
+use crate::mcp_gateway::error::{error_json, gateway_error, GatewayErrorCode};
+use crate::services::mcp_client::{CancellationToken, SamplingHandler};
 use rmcp::{
     model::{
         CallToolRequestParam, CallToolResult, Content, ListToolsResult, PaginatedRequestParam,
         ServerCapabilities, ServerInfo, Tool,
     },
-    service::RequestContext,
+    service::{Peer, RequestContext},
     ErrorData, RoleServer, ServerHandler,
 };
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
 use std::sync::Arc;
+use std::time::Duration;
 
 /// Arguments for load_mcp_tools meta-tool
 #[derive(Debug, Deserialize)]
@@ -34,6 +48,38 @@ struct CallMcpToolArgs {
     arguments: Value,
 }
 
+/// Arguments for call_mcp_tools_batch meta-tool
+#[derive(Debug, Deserialize)]
+struct CallMcpToolsBatchArgs {
+    calls: Vec<BatchCall>,
+}
+
+/// Arguments for use_profile meta-tool
+#[derive(Debug, Deserialize)]
+struct UseProfileArgs {
+    #[serde(default)]
+    name: Option<String>,
+}
+
+/// Arguments for search_tools meta-tool
+#[derive(Debug, Deserialize)]
+struct SearchToolsArgs {
+    query: String,
+}
+
+/// Arguments for describe_tool meta-tool
+#[derive(Debug, Deserialize)]
+struct DescribeToolArgs {
+    mcp_name: String,
+    tool_name: String,
+}
+
+/// Arguments for backend_info meta-tool
+#[derive(Debug, Deserialize)]
+struct BackendInfoArgs {
+    mcp_name: String,
+}
+
 /// Tool information returned by load_mcp_tools
 #[derive(Debug, Serialize)]
 struct ToolInfo {
@@ -43,11 +89,259 @@ struct ToolInfo {
     input_schema: Option<Value>,
 }
 
+/// Build a meta-tool definition with an object input schema
+fn meta_tool(
+    name: &'static str,
+    description: &'static str,
+    properties: Value,
+    required: Value,
+) -> Tool {
+    Tool {
+        name: name.into(),
+        title: None,
+        description: Some(description.into()),
+        input_schema: Arc::new(serde_json::Map::from_iter([
+            ("type".to_string(), json!("object")),
+            ("properties".to_string(), properties),
+            ("required".to_string(), required),
+        ])),
+        output_schema: None,
+        annotations: None,
+        icons: None,
+        meta: None,
+    }
+}
+
+/// All meta-tools exposed by the gateway
+fn meta_tools() -> Vec<Tool> {
+    vec![
+        meta_tool(
+            "list_available_mcps",
+            "List all MCP servers available through this gateway. \
+            Call this first to discover what MCPs you can use.",
+            json!({}),
+            json!([]),
+        ),
+        meta_tool(
+            "load_mcp_tools",
+            "Load and return all tools from a specific MCP server. \
+            The MCP will be connected if not already. \
+            Call this after list_available_mcps to see what tools an MCP offers.",
+            json!({
+                "mcp_name": {
+                    "type": "string",
+                    "description": "Name of the MCP to load tools from"
+                }
+            }),
+            json!(["mcp_name"]),
+        ),
+        meta_tool(
+            "call_mcp_tool",
+            "Execute a tool on a specific MCP server. \
+            The MCP must be connected first via load_mcp_tools.",
+            json!({
+                "mcp_name": {
+                    "type": "string",
+                    "description": "Name of the MCP containing the tool"
+                },
+                "tool_name": {
+                    "type": "string",
+                    "description": "Name of the tool to call"
+                },
+                "arguments": {
+                    "type": "object",
+                    "description": "Arguments to pass to the tool",
+                    "default": {}
+                }
+            }),
+            json!(["mcp_name", "tool_name"]),
+        ),
+        meta_tool(
+            "call_mcp_tools_batch",
+            "Execute several tools in one request. Calls to different MCPs run concurrently; \
+            results are returned in request order and a failed call does not affect the others. \
+            Each MCP must be connected first via load_mcp_tools.",
+            json!({
+                "calls": {
+                    "type": "array",
+                    "items": {
+                        "type": "object",
+                        "properties": {
+                            "mcp_name": { "type": "string" },
+                            "tool_name": { "type": "string" },
+                            "arguments": { "type": "object", "default": {} }
+                        },
+                        "required": ["mcp_name", "tool_name"]
+                    }
+                }
+            }),
+            json!(["calls"]),
+        ),
+        meta_tool(
+            "search_tools",
+            "Search tools of connected MCP servers by name or description. \
+            Results from higher-priority MCPs rank first when matches tie.",
+            json!({
+                "query": {
+                    "type": "string",
+                    "description": "Text to match against tool names and descriptions"
+                }
+            }),
+            json!(["query"]),
+        ),
+        meta_tool(
+            "describe_tool",
+            "Get the full definition of one tool on a connected MCP server, \
+            including its complete description and input schema.",
+            json!({
+                "mcp_name": {
+                    "type": "string",
+                    "description": "Name of the MCP containing the tool"
+                },
+                "tool_name": {
+                    "type": "string",
+                    "description": "Name of the tool to describe"
+                }
+            }),
+            json!(["mcp_name", "tool_name"]),
+        ),
+        meta_tool(
+            "export_catalog",
+            "Export every tool of every MCP server behind this gateway with its \
+            description and input schema, sorted by MCP and tool name. \
+            Connects MCPs that are not connected yet.",
+            json!({}),
+            json!([]),
+        ),
+        meta_tool(
+            "backend_info",
+            "Get the status, server name and version, and declared capabilities \
+            (protocol version, tools, resources, prompts, logging) of one MCP server. \
+            Connects the MCP if it is not connected yet.",
+            json!({
+                "mcp_name": {
+                    "type": "string",
+                    "description": "Name of the MCP to inspect"
+                }
+            }),
+            json!(["mcp_name"]),
+        ),
+        meta_tool(
+            "list_tools",
+            "List the tools of every connected MCP server, grouped by MCP.",
+            json!({}),
+            json!([]),
+        ),
+        meta_tool(
+            "list_resources",
+            "List the resources of every connected MCP server, grouped by MCP. \
+            MCPs that do not support resources are omitted.",
+            json!({}),
+            json!([]),
+        ),
+        meta_tool(
+            "list_prompts",
+            "List the prompts of every connected MCP server, grouped by MCP. \
+            MCPs that do not support prompts are omitted.",
+            json!({}),
+            json!([]),
+        ),
+        meta_tool(
+            "register_backend",
+            "Register a new MCP server with the gateway at runtime without connecting to it. \
+            Returns the assigned id; the MCP connects on first use like any other. \
+            Fails unless backend registration is allowed in the gateway settings.",
+            json!({
+                "name": {
+                    "type": "string",
+                    "description": "Unique name for the MCP"
+                },
+                "type": {
+                    "type": "string",
+                    "enum": ["stdio", "sse", "http"],
+                    "default": "stdio"
+                },
+                "command": {
+                    "type": "string",
+                    "description": "Command to launch a stdio MCP"
+                },
+                "args": {
+                    "type": "array",
+                    "items": { "type": "string" }
+                },
+                "url": {
+                    "type": "string",
+                    "description": "URL of an sse/http MCP"
+                },
+                "env": {
+                    "type": "object",
+                    "additionalProperties": { "type": "string" }
+                },
+                "description": { "type": "string" }
+            }),
+            json!(["name"]),
+        ),
+        meta_tool(
+            "use_profile",
+            "Limit this session to the MCPs and tools of a saved tool profile. \
+            Omit name to see every MCP again. Other sessions are not affected.",
+            json!({
+                "name": {
+                    "type": "string",
+                    "description": "Name of the profile to use"
+                }
+            }),
+            json!([]),
+        ),
+    ]
+}
+
+/// Error result whose text is `{"error": {"code": ..., "message": ...}}`,
+/// so callers can branch on the code
+fn error_result(err: &anyhow::Error) -> CallToolResult {
+    let body = json!({ "error": error_json(err) });
+    CallToolResult::error(vec![Content::text(
+        serde_json::to_string_pretty(&body).unwrap_or_else(|_| format!("Error: {}", err)),
+    )])
+}
+
+/// How long a backend's sampling request waits for the client's answer
+const SAMPLING_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// Client that answers backends' sampling requests, shared by all sessions:
+/// the one that most recently called a meta-tool
+pub type SamplingPeer = Arc<std::sync::Mutex<Option<Peer<RoleServer>>>>;
+
+/// Sampling handler that forwards backends' requests to the client in `peer`.
+/// Must be created inside the runtime the gateway is served from.
+pub fn forward_sampling(peer: SamplingPeer) -> SamplingHandler {
+    let runtime = tokio::runtime::Handle::current();
+    SamplingHandler::new(move |params| {
+        let client = peer
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+            .ok_or_else(|| anyhow::anyhow!("No client is connected to answer sampling requests"))?;
+        let params = serde_json::from_value(params.clone())?;
+        let (tx, rx) = std::sync::mpsc::channel();
+        runtime.spawn(async move {
+            let _ = tx.send(client.create_message(params).await);
+        });
+        let result = rx
+            .recv_timeout(SAMPLING_TIMEOUT)
+            .map_err(|_| anyhow::anyhow!("The client did not answer the sampling request"))??;
+        Ok(serde_json::to_value(result)?)
+    })
+}
+
 /// The Gateway MCP Server handler
 ///
 /// Uses lazy-loading meta-tools instead of exposing all backend tools upfront.
 pub struct GatewayServer {
     backend_manager: Arc<tokio::sync::Mutex<GatewayBackendManager>>,
+    /// Tool profile chosen by this session with use_profile
+    profile: Arc<std::sync::Mutex<Option<String>>>,
+    sampling_peer: SamplingPeer,
 }
 
 impl std::fmt::Debug for GatewayServer {
@@ -60,13 +354,39 @@ impl Clone for GatewayServer {
     fn clone(&self) -> Self {
         Self {
             backend_manager: self.backend_manager.clone(),
+            profile: self.profile.clone(),
+            sampling_peer: self.sampling_peer.clone(),
         }
     }
 }
 
 impl GatewayServer {
-    pub fn new(backend_manager: Arc<tokio::sync::Mutex<GatewayBackendManager>>) -> Self {
-        Self { backend_manager }
+    pub fn new(
+        backend_manager: Arc<tokio::sync::Mutex<GatewayBackendManager>>,
+        sampling_peer: SamplingPeer,
+    ) -> Self {
+        Self {
+            backend_manager,
+            profile: Arc::new(std::sync::Mutex::new(None)),
+            sampling_peer,
+        }
+    }
+
+    /// Let backends' sampling requests reach this session's client
+    fn remember_client(&self, peer: &Peer<RoleServer>) {
+        *self.sampling_peer.lock().unwrap_or_else(|e| e.into_inner()) = Some(peer.clone());
+    }
+
+    fn session_profile(&self) -> Option<String> {
+        self.profile
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    /// Lock the backend manager with this session's tool profile applied
+    async fn manager(&self) -> tokio::sync::MutexGuard<'_, GatewayBackendManager> {
+        lock_for_session(&self.backend_manager, self.session_profile().as_deref()).await
     }
 }
 
@@ -74,10 +394,18 @@ impl ServerHandler for GatewayServer {
     fn get_info(&self) -> ServerInfo {
         ServerInfo {
             instructions: Some(
-                "MCP Gateway with lazy-loading. Use these 3 meta-tools:\n\
+                "MCP Gateway with lazy-loading. Use these meta-tools:\n\
                 1. list_available_mcps - Discover available MCP servers\n\
                 2. load_mcp_tools - Connect to an MCP and get its tools\n\
-                3. call_mcp_tool - Execute a tool on a specific MCP\n\n\
+                3. call_mcp_tool - Execute a tool on a specific MCP\n\
+                4. call_mcp_tools_batch - Execute several tools at once, across MCPs\n\
+                5. search_tools - Search tools of connected MCPs by name or description\n\
+                6. describe_tool - Full definition of one tool on a connected MCP\n\
+                7. export_catalog - Every tool of every MCP, for documentation\n\
+                8. backend_info - Server version and declared capabilities of one MCP\n\
+                9. list_tools, list_resources, list_prompts - Capabilities of connected MCPs\n\
+                10. register_backend - Add an MCP to the gateway at runtime\n\
+                11. use_profile - Limit this session to a saved tool profile\n\n\
                 Flow: First call list_available_mcps to see what's available, \
                 then call load_mcp_tools to connect and see tools, \
                 then call call_mcp_tool to execute tools."
@@ -94,91 +422,12 @@ impl ServerHandler for GatewayServer {
         _context: RequestContext<RoleServer>,
     ) -> impl std::future::Future<Output = Result<ListToolsResult, ErrorData>> + Send + '_ {
         async move {
-            // Return only the 3 meta-tools
-            let meta_tools = vec![
-                Tool {
-                    name: "list_available_mcps".into(),
-                    title: None,
-                    description: Some(
-                        "List all MCP servers available through this gateway. \
-                        Call this first to discover what MCPs you can use."
-                            .into(),
-                    ),
-                    input_schema: Arc::new(serde_json::Map::from_iter([
-                        ("type".to_string(), json!("object")),
-                        ("properties".to_string(), json!({})),
-                        ("required".to_string(), json!([])),
-                    ])),
-                    output_schema: None,
-                    annotations: None,
-                    icons: None,
-                    meta: None,
-                },
-                Tool {
-                    name: "load_mcp_tools".into(),
-                    title: None,
-                    description: Some(
-                        "Load and return all tools from a specific MCP server. \
-                        The MCP will be connected if not already. \
-                        Call this after list_available_mcps to see what tools an MCP offers."
-                            .into(),
-                    ),
-                    input_schema: Arc::new(serde_json::Map::from_iter([
-                        ("type".to_string(), json!("object")),
-                        (
-                            "properties".to_string(),
-                            json!({
-                                "mcp_name": {
-                                    "type": "string",
-                                    "description": "Name of the MCP to load tools from"
-                                }
-                            }),
-                        ),
-                        ("required".to_string(), json!(["mcp_name"])),
-                    ])),
-                    output_schema: None,
-                    annotations: None,
-                    icons: None,
-                    meta: None,
-                },
-                Tool {
-                    name: "call_mcp_tool".into(),
-                    title: None,
-                    description: Some(
-                        "Execute a tool on a specific MCP server. \
-                        The MCP must be connected first via load_mcp_tools."
-                            .into(),
-                    ),
-                    input_schema: Arc::new(serde_json::Map::from_iter([
-                        ("type".to_string(), json!("object")),
-                        (
-                            "properties".to_string(),
-                            json!({
-                                "mcp_name": {
-                                    "type": "string",
-                                    "description": "Name of the MCP containing the tool"
-                                },
-                                "tool_name": {
-                                    "type": "string",
-                                    "description": "Name of the tool to call"
-                                },
-                                "arguments": {
-                                    "type": "object",
-                                    "description": "Arguments to pass to the tool",
-                                    "default": {}
-                                }
-                            }),
-                        ),
-                        ("required".to_string(), json!(["mcp_name", "tool_name"])),
-                    ])),
-                    output_schema: None,
-                    annotations: None,
-                    icons: None,
-                    meta: None,
-                },
-            ];
+            let meta_tools = meta_tools();
 
-            log::info!("[Gateway] Listing 3 meta-tools (lazy mode)");
+            log::info!(
+                "[Gateway] Listing {} meta-tools (lazy mode)",
+                meta_tools.len()
+            );
 
             Ok(ListToolsResult {
                 tools: meta_tools,
@@ -191,17 +440,18 @@ impl ServerHandler for GatewayServer {
     fn call_tool(
         &self,
         request: CallToolRequestParam,
-        _context: RequestContext<RoleServer>,
+        context: RequestContext<RoleServer>,
     ) -> impl std::future::Future<Output = Result<CallToolResult, ErrorData>> + Send + '_ {
         async move {
             let tool_name = request.name.as_ref();
             let arguments = request.arguments.unwrap_or_default();
 
             log::info!("[Gateway] Meta-tool call: {}", tool_name);
+            self.remember_client(&context.peer);
 
             match tool_name {
                 "list_available_mcps" => {
-                    let backend_manager = self.backend_manager.lock().await;
+                    let backend_manager = self.manager().await;
                     let mcps = backend_manager.get_available_mcps();
                     let result = serde_json::to_string_pretty(&mcps)
                         .unwrap_or_else(|e| format!("Error serializing MCPs: {}", e));
@@ -215,7 +465,7 @@ impl ServerHandler for GatewayServer {
                         ErrorData::invalid_params(format!("Invalid arguments: {}", e), None)
                     })?;
 
-                    let mut backend_manager = self.backend_manager.lock().await;
+                    let mut backend_manager = self.manager().await;
                     match backend_manager.connect_backend_lazy(&args.mcp_name).await {
                         Ok(tools) => {
                             let tool_infos: Vec<ToolInfo> = tools
@@ -232,10 +482,7 @@ impl ServerHandler for GatewayServer {
                         }
                         Err(e) => {
                             log::error!("[Gateway] Failed to load MCP tools: {}", e);
-                            Ok(CallToolResult::error(vec![Content::text(format!(
-                                "Error: {}",
-                                e
-                            ))]))
+                            Ok(error_result(&e))
                         }
                     }
                 }
@@ -247,12 +494,27 @@ impl ServerHandler for GatewayServer {
                             ErrorData::invalid_params(format!("Invalid arguments: {}", e), None)
                         })?;
 
-                    let mut backend_manager = self.backend_manager.lock().await;
-                    match backend_manager.call_tool_on_mcp(
-                        &args.mcp_name,
-                        &args.tool_name,
+                    // Abort the backend call when the client cancels its request
+                    let cancel = CancellationToken::new();
+                    let watcher = {
+                        let cancel = cancel.clone();
+                        let ct = context.ct.clone();
+                        tokio::spawn(async move {
+                            ct.cancelled().await;
+                            cancel.cancel();
+                        })
+                    };
+                    let result = invoke_tool(
+                        self.backend_manager.clone(),
+                        self.session_profile(),
+                        args.mcp_name,
+                        args.tool_name,
                         args.arguments,
-                    ) {
+                        cancel,
+                    )
+                    .await;
+                    watcher.abort();
+                    match result {
                         Ok(result) => {
                             // Convert our ToolCallResult to rmcp's CallToolResult
                             let content: Vec<Content> = result
@@ -277,6 +539,18 @@ impl ServerHandler for GatewayServer {
                                             uri,
                                         ))
                                     }
+                                    // Forwarded as an embedded resource without
+                                    // contents; the client reads it by uri
+                                    crate::services::mcp_client::ToolContent::ResourceLink {
+                                        uri,
+                                        ..
+                                    } => {
+                                        use rmcp::model::ResourceContents;
+                                        Content::resource(ResourceContents::text(
+                                            String::new(),
+                                            uri,
+                                        ))
+                                    }
                                 })
                                 .collect();
 
@@ -288,22 +562,216 @@ impl ServerHandler for GatewayServer {
                         }
                         Err(e) => {
                             log::error!("[Gateway] Tool call failed: {}", e);
-                            Ok(CallToolResult::error(vec![Content::text(format!(
-                                "Error: {}",
-                                e
-                            ))]))
+                            Ok(error_result(&e))
                         }
                     }
                 }
 
+                "call_mcp_tools_batch" => {
+                    let args: CallMcpToolsBatchArgs =
+                        serde_json::from_value(Value::Object(arguments)).map_err(|e| {
+                            log::error!("[Gateway] Invalid call_mcp_tools_batch arguments: {}", e);
+                            ErrorData::invalid_params(format!("Invalid arguments: {}", e), None)
+                        })?;
+
+                    let calls = args.calls.clone();
+                    let results = invoke_batch(
+                        &self.backend_manager,
+                        self.session_profile().as_deref(),
+                        args.calls,
+                    )
+                    .await;
+                    let entries: Vec<Value> = calls
+                        .iter()
+                        .zip(results)
+                        .map(|(call, result)| match result {
+                            Ok(value) => json!({
+                                "mcpName": call.mcp_name,
+                                "toolName": call.tool_name,
+                                "result": value,
+                            }),
+                            Err(e) => json!({
+                                "mcpName": call.mcp_name,
+                                "toolName": call.tool_name,
+                                "error": error_json(&e),
+                            }),
+                        })
+                        .collect();
+                    let result = serde_json::to_string_pretty(&entries)
+                        .unwrap_or_else(|e| format!("Error serializing results: {}", e));
+                    Ok(CallToolResult::success(vec![Content::text(result)]))
+                }
+
+                "search_tools" => {
+                    let args: SearchToolsArgs = serde_json::from_value(Value::Object(arguments))
+                        .map_err(|e| {
+                            log::error!("[Gateway] Invalid search_tools arguments: {}", e);
+                            ErrorData::invalid_params(format!("Invalid arguments: {}", e), None)
+                        })?;
+
+                    let backend_manager = self.manager().await;
+                    let results = backend_manager.search_tools(&args.query);
+                    let result = serde_json::to_string_pretty(&results)
+                        .unwrap_or_else(|e| format!("Error serializing results: {}", e));
+                    Ok(CallToolResult::success(vec![Content::text(result)]))
+                }
+
+                "describe_tool" => {
+                    let args: DescribeToolArgs = serde_json::from_value(Value::Object(arguments))
+                        .map_err(|e| {
+                        log::error!("[Gateway] Invalid describe_tool arguments: {}", e);
+                        ErrorData::invalid_params(format!("Invalid arguments: {}", e), None)
+                    })?;
+
+                    let backend_manager = self.manager().await;
+                    match backend_manager.describe_tool(&args.mcp_name, &args.tool_name) {
+                        Ok(tool) => {
+                            let info = ToolInfo {
+                                name: tool.name,
+                                description: tool.description,
+                                input_schema: tool.input_schema,
+                            };
+                            let result = serde_json::to_string_pretty(&info)
+                                .unwrap_or_else(|e| format!("Error serializing tool: {}", e));
+                            Ok(CallToolResult::success(vec![Content::text(result)]))
+                        }
+                        Err(e) => Ok(error_result(&e)),
+                    }
+                }
+
+                "export_catalog" => {
+                    let mut backend_manager = self.manager().await;
+                    let catalog = backend_manager.export_catalog().await;
+                    let result = serde_json::to_string_pretty(&catalog)
+                        .unwrap_or_else(|e| format!("Error serializing catalog: {}", e));
+                    Ok(CallToolResult::success(vec![Content::text(result)]))
+                }
+
+                "backend_info" => {
+                    let args: BackendInfoArgs = serde_json::from_value(Value::Object(arguments))
+                        .map_err(|e| {
+                            log::error!("[Gateway] Invalid backend_info arguments: {}", e);
+                            ErrorData::invalid_params(format!("Invalid arguments: {}", e), None)
+                        })?;
+
+                    let mut backend_manager = self.manager().await;
+                    match backend_manager.backend_info(&args.mcp_name).await {
+                        Ok(info) => {
+                            let result = serde_json::to_string_pretty(&info)
+                                .unwrap_or_else(|e| format!("Error serializing info: {}", e));
+                            Ok(CallToolResult::success(vec![Content::text(result)]))
+                        }
+                        Err(e) => Ok(error_result(&e)),
+                    }
+                }
+
+                "list_tools" | "list_resources" | "list_prompts" => {
+                    let backend_manager = self.manager().await;
+                    let serialized = match tool_name {
+                        "list_tools" => {
+                            serde_json::to_string_pretty(&backend_manager.list_tools_by_backend())
+                        }
+                        "list_resources" => serde_json::to_string_pretty(
+                            &backend_manager.list_resources_by_backend(),
+                        ),
+                        _ => {
+                            serde_json::to_string_pretty(&backend_manager.list_prompts_by_backend())
+                        }
+                    };
+                    let result =
+                        serialized.unwrap_or_else(|e| format!("Error serializing listing: {}", e));
+                    Ok(CallToolResult::success(vec![Content::text(result)]))
+                }
+
+                "register_backend" => {
+                    let registration: BackendRegistration =
+                        serde_json::from_value(Value::Object(arguments)).map_err(|e| {
+                            log::error!("[Gateway] Invalid register_backend arguments: {}", e);
+                            ErrorData::invalid_params(format!("Invalid arguments: {}", e), None)
+                        })?;
+                    let name = registration.name.clone();
+
+                    let mut backend_manager = self.manager().await;
+                    match backend_manager.register_backend(registration) {
+                        Ok(id) => {
+                            let result = json!({ "id": id, "name": name, "connected": false });
+                            Ok(CallToolResult::success(vec![Content::text(
+                                serde_json::to_string_pretty(&result)
+                                    .unwrap_or_else(|e| format!("Error serializing result: {}", e)),
+                            )]))
+                        }
+                        Err(e) => {
+                            log::error!("[Gateway] Failed to register backend: {}", e);
+                            Ok(error_result(&e))
+                        }
+                    }
+                }
+
+                "use_profile" => {
+                    let args: UseProfileArgs = serde_json::from_value(Value::Object(arguments))
+                        .map_err(|e| {
+                            log::error!("[Gateway] Invalid use_profile arguments: {}", e);
+                            ErrorData::invalid_params(format!("Invalid arguments: {}", e), None)
+                        })?;
+                    let name = args.name.filter(|n| !n.trim().is_empty());
+
+                    let profiles = self.manager().await.profile_names();
+                    if let Some(ref name) = name {
+                        if !profiles.contains(name) {
+                            return Ok(error_result(&anyhow::anyhow!(
+                                "Unknown profile '{}'. Saved profiles: {}",
+                                name,
+                                profiles.join(", ")
+                            )));
+                        }
+                    }
+                    *self.profile.lock().unwrap_or_else(|e| e.into_inner()) = name.clone();
+                    log::info!("[Gateway] Session tool profile set to {:?}", name);
+
+                    let result = json!({ "profile": name, "profiles": profiles });
+                    Ok(CallToolResult::success(vec![Content::text(
+                        serde_json::to_string_pretty(&result)
+                            .unwrap_or_else(|e| format!("Error serializing result: {}", e)),
+                    )]))
+                }
+
                 _ => {
                     log::warn!("[Gateway] Unknown meta-tool: {}", tool_name);
-                    Ok(CallToolResult::error(vec![Content::text(format!(
-                        "Unknown tool: {}. Available meta-tools: list_available_mcps, load_mcp_tools, call_mcp_tool",
-                        tool_name
-                    ))]))
+                    Ok(error_result(&gateway_error(
+                        GatewayErrorCode::ToolNotExposed,
+                        format!(
+                            "Unknown tool: {}. Available meta-tools: list_available_mcps, load_mcp_tools, call_mcp_tool, call_mcp_tools_batch, search_tools, describe_tool, export_catalog, backend_info, list_tools, list_resources, list_prompts, register_backend, use_profile",
+                            tool_name
+                        ),
+                    )))
                 }
             }
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_meta_tools_have_unique_names_and_object_schemas() {
+        let tools = meta_tools();
+        let mut names: Vec<&str> = tools.iter().map(|t| t.name.as_ref()).collect();
+        names.sort();
+        names.dedup();
+        assert_eq!(names.len(), tools.len());
+        assert!(names.contains(&"register_backend"));
+        assert!(names.contains(&"list_resources"));
+        assert!(names.contains(&"list_prompts"));
+        assert!(names.contains(&"call_mcp_tools_batch"));
+        assert!(names.contains(&"describe_tool"));
+        assert!(names.contains(&"export_catalog"));
+        assert!(names.contains(&"backend_info"));
+        assert!(names.contains(&"use_profile"));
+
+        for tool in &tools {
+            assert_eq!(tool.input_schema.get("type"), Some(&json!("object")));
+        }
+    }
+}
@@ -0,0 +1,146 @@
+//! Gateway Argument Transforms
+//!
+//! Per-backend key renames for backends whose argument naming differs from
+//! what agents send (e.g. camelCase in, snake_case out). Outgoing arguments
+//! are renamed with the map; JSON text results are renamed back with its
+//! inverse. Keys without a mapping pass through unchanged.
+
+use crate::services::mcp_client::{ToolCallResult, ToolContent};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Declarative rename map: agent-facing key -> backend key
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct ArgTransform {
+    renames: HashMap<String, String>,
+}
+
+impl ArgTransform {
+    /// Transform renaming the keys of `renames` to their values (for testing)
+    #[cfg(test)]
+    pub fn new(renames: HashMap<String, String>) -> Self {
+        Self { renames }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.renames.is_empty()
+    }
+
+    /// Rename keys in outgoing tool arguments (nested objects included)
+    pub fn apply_to_arguments(&self, arguments: serde_json::Value) -> serde_json::Value {
+        if self.is_empty() {
+            return arguments;
+        }
+        rename_keys(arguments, &|key| self.renames.get(key).cloned())
+    }
+
+    /// Rename keys back in JSON text content of a tool result.
+    /// Text that isn't JSON, or has no renamed keys, is left as-is.
+    pub fn apply_inverse_to_result(&self, mut result: ToolCallResult) -> ToolCallResult {
+        if self.is_empty() {
+            return result;
+        }
+
+        let inverse: HashMap<&str, &str> = self
+            .renames
+            .iter()
+            .map(|(from, to)| (to.as_str(), from.as_str()))
+            .collect();
+        let lookup = |key: &str| inverse.get(key).map(|k| k.to_string());
+
+        for content in &mut result.content {
+            let ToolContent::Text { text } = content else {
+                continue;
+            };
+            let Ok(value) = serde_json::from_str::<serde_json::Value>(text) else {
+                continue;
+            };
+            if !has_mapped_key(&value, &lookup) {
+                continue;
+            }
+            if let Ok(renamed) = serde_json::to_string(&rename_keys(value, &lookup)) {
+                *text = renamed;
+            }
+        }
+        result
+    }
+}
+
+fn rename_keys(
+    value: serde_json::Value,
+    lookup: &dyn Fn(&str) -> Option<String>,
+) -> serde_json::Value {
+    match value {
+        serde_json::Value::Object(map) => map
+            .into_iter()
+            .map(|(key, v)| (lookup(&key).unwrap_or(key), rename_keys(v, lookup)))
+            .collect::<serde_json::Map<_, _>>()
+            .into(),
+        serde_json::Value::Array(items) => items
+            .into_iter()
+            .map(|v| rename_keys(v, lookup))
+            .collect::<Vec<_>>()
+            .into(),
+        other => other,
+    }
+}
+
+fn has_mapped_key(value: &serde_json::Value, lookup: &dyn Fn(&str) -> Option<String>) -> bool {
+    match value {
+        serde_json::Value::Object(map) => map
+            .iter()
+            .any(|(key, v)| lookup(key).is_some() || has_mapped_key(v, lookup)),
+        serde_json::Value::Array(items) => items.iter().any(|v| has_mapped_key(v, lookup)),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn transform() -> ArgTransform {
+        ArgTransform::new(HashMap::from([(
+            "maxResults".to_string(),
+            "max_results".to_string(),
+        )]))
+    }
+
+    fn text_result(text: &str) -> ToolCallResult {
+        ToolCallResult {
+            success: true,
+            content: vec![ToolContent::Text {
+                text: text.to_string(),
+            }],
+            is_error: false,
+            error: None,
+            execution_time_ms: 0,
+        }
+    }
+
+    #[test]
+    fn test_renames_outgoing_arguments() {
+        let args = transform().apply_to_arguments(json!({"maxResults": 5, "query": "rust"}));
+        assert_eq!(args, json!({"max_results": 5, "query": "rust"}));
+    }
+
+    #[test]
+    fn test_inverse_applies_to_json_results_only() {
+        let result =
+            transform().apply_inverse_to_result(text_result(r#"{"max_results":5,"items":[]}"#));
+        let ToolContent::Text { text } = &result.content[0] else {
+            panic!("expected text content");
+        };
+        assert_eq!(
+            serde_json::from_str::<serde_json::Value>(text).unwrap(),
+            json!({"maxResults": 5, "items": []})
+        );
+
+        let plain = transform().apply_inverse_to_result(text_result("max_results: 5"));
+        assert!(
+            matches!(&plain.content[0], ToolContent::Text { text } if text == "max_results: 5")
+        );
+    }
+}
@@ -0,0 +1,83 @@
+//! Gateway Tool Usage
+//!
+//! Counts tool invocations per backend and remembers when each tool was last
+//! called, so unused backends can be spotted and pruned.
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+
+/// Invocation count and last call time of one tool
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ToolUsage {
+    pub mcp_name: String,
+    pub tool_name: String,
+    pub count: u64,
+    /// RFC 3339 time of the last call (None if never called)
+    pub last_used: Option<String>,
+}
+
+/// Per-tool invocation counters, keyed by (MCP name, tool name)
+#[derive(Debug, Default)]
+pub struct UsageTracker {
+    entries: HashMap<(String, String), (u64, DateTime<Utc>)>,
+}
+
+impl UsageTracker {
+    /// Count one invocation of a tool at `at`
+    pub fn record(&mut self, mcp_name: &str, tool_name: &str, at: DateTime<Utc>) {
+        let entry = self
+            .entries
+            .entry((mcp_name.to_string(), tool_name.to_string()))
+            .or_insert((0, at));
+        entry.0 += 1;
+        entry.1 = entry.1.max(at);
+    }
+
+    /// Usage of every recorded tool plus every `known` (MCP name, tool name)
+    /// pair, which reads zero if never called. Sorted by MCP then tool name.
+    pub fn stats<'a>(&self, known: impl IntoIterator<Item = (&'a str, &'a str)>) -> Vec<ToolUsage> {
+        let mut stats: BTreeMap<(String, String), Option<(u64, DateTime<Utc>)>> = known
+            .into_iter()
+            .map(|(mcp, tool)| ((mcp.to_string(), tool.to_string()), None))
+            .collect();
+        for (key, entry) in &self.entries {
+            stats.insert(key.clone(), Some(*entry));
+        }
+
+        stats
+            .into_iter()
+            .map(|((mcp_name, tool_name), entry)| ToolUsage {
+                mcp_name,
+                tool_name,
+                count: entry.map_or(0, |(count, _)| count),
+                last_used: entry.map(|(_, at)| at.to_rfc3339()),
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stats_include_known_tools_with_zero_count() {
+        let mut tracker = UsageTracker::default();
+        let first = Utc::now();
+        let later = first + chrono::Duration::seconds(5);
+        tracker.record("web", "fetch", first);
+        tracker.record("web", "fetch", later);
+
+        let stats = tracker.stats([("web", "fetch"), ("web", "search")]);
+
+        assert_eq!(stats.len(), 2);
+        assert_eq!(stats[0].tool_name, "fetch");
+        assert_eq!(stats[0].count, 2);
+        assert_eq!(stats[0].last_used, Some(later.to_rfc3339()));
+        assert_eq!(stats[1].tool_name, "search");
+        assert_eq!(stats[1].count, 0);
+        assert_eq!(stats[1].last_used, None);
+    }
+}
@@ -620,7 +620,9 @@ impl ToolManagerServer {
             model: params.model,
             permission_mode: params.permission_mode,
             skills: None,
+            color: None,
             tags: params.tags,
+            env: None,
         };
 
         let db = self.get_db()?;
@@ -0,0 +1,475 @@
+//! Agent format compatibility
+//!
+//! Reports which parts of a sub-agent are lost or rewritten when it is written
+//! in another tool's format, so users can check before exporting, and converts
+//! whole agent directories between formats.
+
+use crate::db::models::SubAgent;
+use crate::services::subagent_import::AgentFormat;
+use crate::services::subagent_writer::{
+    is_opencode_tool, known_opencode_tools, load_subagent_file, load_subagent_file_opencode,
+    opencode_permissions_for_mode, write_subagent_with_formatter, AgentFormatter, ClaudeFormatter,
+    OpenCodeFormatter, OPENCODE_EXTRA_FIELDS,
+};
+use crate::utils::paths::ClaudeLayout;
+use anyhow::{anyhow, Result};
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+
+/// What happens to a field when an agent is written in the target format
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum CompatChange {
+    /// Not written at all
+    Dropped,
+    /// Written in a different form
+    Transformed,
+    /// Written unchanged, but the target tool may not accept the value
+    Warning,
+}
+
+/// One field affected by a format conversion
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CompatIssue {
+    /// SubAgent field name (e.g. `permission_mode`)
+    pub field: String,
+    pub change: CompatChange,
+    pub detail: String,
+}
+
+/// Fields of an agent that won't survive a write to `format` unchanged
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CompatReport {
+    pub format: AgentFormat,
+    pub issues: Vec<CompatIssue>,
+}
+
+impl CompatReport {
+    /// True when the agent is written without losing or rewriting anything
+    /// (warnings don't count)
+    #[cfg(test)]
+    pub fn is_lossless(&self) -> bool {
+        self.issues
+            .iter()
+            .all(|i| i.change == CompatChange::Warning)
+    }
+
+    /// Issues for one field
+    #[cfg(test)]
+    pub fn for_field(&self, field: &str) -> Vec<&CompatIssue> {
+        self.issues.iter().filter(|i| i.field == field).collect()
+    }
+
+    fn push(&mut self, field: &str, change: CompatChange, detail: String) {
+        self.issues.push(CompatIssue {
+            field: field.to_string(),
+            change,
+            detail,
+        });
+    }
+}
+
+/// List the fields of `subagent` that are dropped or transformed when it is
+/// written in `format` with the default generation options
+pub fn compatibility_report(subagent: &SubAgent, format: AgentFormat) -> CompatReport {
+    let mut report = CompatReport {
+        format,
+        issues: Vec::new(),
+    };
+    match format {
+        AgentFormat::Claude => claude_issues(subagent, &mut report),
+        AgentFormat::OpenCode => opencode_issues(subagent, &mut report),
+    }
+    report
+}
+
+fn claude_issues(subagent: &SubAgent, report: &mut CompatReport) {
+    use CompatChange::{Dropped, Warning};
+
+    for field in OPENCODE_EXTRA_FIELDS {
+        if subagent.extra.contains_key(field) {
+            report.push(
+                field,
+                Dropped,
+                format!("Claude Code agents have no `{}` equivalent", field),
+            );
+        }
+    }
+
+    if let Some(model) = subagent.model.as_deref().filter(|m| m.contains('/')) {
+        report.push(
+            "model",
+            Warning,
+            format!(
+                "model '{}' has a provider prefix; Claude Code expects names like \
+                 'sonnet' or 'claude-sonnet-4-20250514'",
+                model
+            ),
+        );
+    }
+}
+
+fn opencode_issues(subagent: &SubAgent, report: &mut CompatReport) {
+    use CompatChange::{Dropped, Transformed, Warning};
+
+    report.push(
+        "name",
+        Transformed,
+        "OpenCode has no name field; the name is kept only as the file name".to_string(),
+    );
+
+    if let Some(tools) = subagent.tools.as_ref().filter(|t| !t.is_empty()) {
+        let known = known_opencode_tools();
+        let (kept, dropped): (Vec<&String>, Vec<&String>) =
+            tools.iter().partition(|t| is_opencode_tool(t, &known));
+        for tool in dropped {
+            report.push(
+                "tools",
+                Dropped,
+                format!("Tool '{}' has no OpenCode equivalent", tool),
+            );
+        }
+        if !kept.is_empty() {
+            report.push(
+                "tools",
+                Transformed,
+                "Written as a `tools:` map of OpenCode tool names (e.g. LS becomes list)"
+                    .to_string(),
+            );
+        }
+    }
+
+    if let Some(model) = subagent
+        .model
+        .as_deref()
+        .filter(|m| !m.is_empty() && !m.contains('/'))
+    {
+        report.push(
+            "model",
+            Warning,
+            format!(
+                "model '{}' has no provider prefix; OpenCode expects ids like \
+                 'anthropic/claude-sonnet-4-20250514'",
+                model
+            ),
+        );
+    }
+
+    if let Some(mode) = subagent
+        .permission_mode
+        .as_deref()
+        .filter(|m| !m.is_empty())
+    {
+        match opencode_permissions_for_mode(mode) {
+            Some([]) => {}
+            Some(entries) => {
+                let permission = entries
+                    .iter()
+                    .map(|(axis, action)| format!("{}: {}", axis, action))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                report.push(
+                    "permission_mode",
+                    Transformed,
+                    format!(
+                        "permissionMode '{}' becomes a permission object ({})",
+                        mode, permission
+                    ),
+                );
+            }
+            None => report.push(
+                "permission_mode",
+                Dropped,
+                format!("permissionMode '{}' has no OpenCode equivalent", mode),
+            ),
+        }
+    }
+
+    let unsupported = [
+        (
+            "skills",
+            subagent.skills.as_ref().is_some_and(|v| !v.is_empty()),
+        ),
+        (
+            "color",
+            subagent.color.as_ref().is_some_and(|v| !v.is_empty()),
+        ),
+        (
+            "tags",
+            subagent.tags.as_ref().is_some_and(|v| !v.is_empty()),
+        ),
+        ("env", subagent.env.as_ref().is_some_and(|v| !v.is_empty())),
+        (
+            "mcp_servers",
+            subagent.mcp_servers.as_ref().is_some_and(|v| !v.is_empty()),
+        ),
+        ("enabled", !subagent.enabled),
+    ];
+    for (field, set) in unsupported {
+        if set {
+            report.push(
+                field,
+                Dropped,
+                format!("OpenCode agents have no `{}` equivalent", field),
+            );
+        }
+    }
+}
+
+/// An agent written by convert_directory
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConvertedAgent {
+    pub name: String,
+    pub path: PathBuf,
+    /// Fields dropped or transformed on the way (see compatibility_report)
+    pub warnings: Vec<String>,
+}
+
+/// An agent file convert_directory couldn't read or write
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConvertFailure {
+    pub path: PathBuf,
+    pub error: String,
+}
+
+/// Outcome of converting a directory of agents
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConvertReport {
+    pub converted: Vec<ConvertedAgent>,
+    pub failed: Vec<ConvertFailure>,
+}
+
+/// Directory holding a format's agent files under a base path
+/// (the project for Claude, the `.opencode` directory for OpenCode)
+fn agents_dir(base_path: &Path, format: AgentFormat) -> PathBuf {
+    match format {
+        AgentFormat::Claude => ClaudeLayout::default().agents_dir(base_path),
+        AgentFormat::OpenCode => base_path.join("agent"),
+    }
+}
+
+/// Formatter writing and parsing agents in `format`
+pub fn formatter(format: AgentFormat) -> Box<dyn AgentFormatter> {
+    match format {
+        AgentFormat::Claude => Box::new(ClaudeFormatter::default()),
+        AgentFormat::OpenCode => Box::new(OpenCodeFormatter::default()),
+    }
+}
+
+fn load_agent(path: &Path, format: AgentFormat) -> Result<SubAgent> {
+    match format {
+        AgentFormat::Claude => load_subagent_file(path),
+        AgentFormat::OpenCode => load_subagent_file_opencode(path),
+    }
+}
+
+/// Convert every agent under `from_base` (in `from_fmt`) to `to_fmt` under
+/// `to_base`, e.g. a project's `.claude/agents` into `.opencode/agent`.
+///
+/// An agent that can't be read or written is recorded in `failed` and the
+/// rest are still converted. Each converted agent lists the compatibility
+/// warnings for the target format. Fails only if the source directory is missing.
+pub fn convert_directory(
+    from_base: &Path,
+    to_base: &Path,
+    from_fmt: AgentFormat,
+    to_fmt: AgentFormat,
+) -> Result<ConvertReport> {
+    let source_dir = agents_dir(from_base, from_fmt);
+    if !source_dir.is_dir() {
+        return Err(anyhow!("Not a directory: {}", source_dir.display()));
+    }
+
+    let mut files: Vec<PathBuf> = std::fs::read_dir(&source_dir)?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| path.is_file() && path.extension().is_some_and(|ext| ext == "md"))
+        .collect();
+    files.sort();
+
+    let target = formatter(to_fmt);
+    let mut report = ConvertReport::default();
+    for path in files {
+        let converted = load_agent(&path, from_fmt).and_then(|agent| {
+            write_subagent_with_formatter(to_base, &agent, target.as_ref())?;
+            Ok(agent)
+        });
+        match converted {
+            Ok(agent) => report.converted.push(ConvertedAgent {
+                path: target.file_path(to_base, &agent.name),
+                warnings: compatibility_report(&agent, to_fmt)
+                    .issues
+                    .into_iter()
+                    .map(|issue| format!("{}: {}", issue.field, issue.detail))
+                    .collect(),
+                name: agent.name,
+            }),
+            Err(e) => {
+                log::warn!("[AgentCompat] Can't convert {}: {}", path.display(), e);
+                report.failed.push(ConvertFailure {
+                    path,
+                    error: e.to_string(),
+                });
+            }
+        }
+    }
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::models::sample_subagent;
+    use tempfile::TempDir;
+
+    fn agent() -> SubAgent {
+        SubAgent {
+            content: "Review it.".to_string(),
+            tools: Some(vec!["Read".to_string(), "TodoWrite".to_string()]),
+            model: Some("sonnet".to_string()),
+            permission_mode: Some("acceptEdits".to_string()),
+            skills: Some(vec!["lint".to_string()]),
+            ..sample_subagent()
+        }
+    }
+
+    #[test]
+    fn test_compatibility_report_opencode_lists_permission_transformation() {
+        let report = compatibility_report(&agent(), AgentFormat::OpenCode);
+
+        let permission = report.for_field("permission_mode");
+        assert_eq!(permission.len(), 1);
+        assert_eq!(permission[0].change, CompatChange::Transformed);
+        assert!(permission[0].detail.contains("edit: allow"));
+
+        let skills = report.for_field("skills");
+        assert_eq!(skills[0].change, CompatChange::Dropped);
+        assert!(report
+            .for_field("tools")
+            .iter()
+            .any(|i| i.change == CompatChange::Dropped && i.detail.contains("TodoWrite")));
+        let model = report.for_field("model");
+        assert_eq!(model[0].change, CompatChange::Warning);
+        assert!(model[0].detail.contains("'sonnet' has no provider prefix"));
+        assert!(!report.is_lossless());
+    }
+
+    #[test]
+    fn test_compatibility_report_claude_is_lossless() {
+        assert!(compatibility_report(&agent(), AgentFormat::Claude).is_lossless());
+    }
+
+    #[test]
+    fn test_convert_directory_claude_to_opencode() {
+        let temp_dir = TempDir::new().unwrap();
+        let project = temp_dir.path();
+        let agents = project.join(".claude").join("agents");
+        std::fs::create_dir_all(&agents).unwrap();
+        std::fs::write(
+            agents.join("reviewer.md"),
+            "---\nname: reviewer\ndescription: Reviews code\ntools: Read, TodoWrite\npermissionMode: plan\n---\n\nReview it.",
+        )
+        .unwrap();
+        std::fs::write(
+            agents.join("writer.md"),
+            "---\nname: writer\ndescription: Writes docs\n---\n\nWrite it.",
+        )
+        .unwrap();
+        std::fs::write(
+            agents.join("broken.md"),
+            "---\nname: broken\n\nNever closed.",
+        )
+        .unwrap();
+
+        let opencode = project.join(".opencode");
+        let report = convert_directory(
+            project,
+            &opencode,
+            AgentFormat::Claude,
+            AgentFormat::OpenCode,
+        )
+        .unwrap();
+
+        let names: Vec<&str> = report.converted.iter().map(|a| a.name.as_str()).collect();
+        assert_eq!(names, ["reviewer", "writer"]);
+        assert_eq!(report.failed.len(), 1);
+        assert_eq!(report.failed[0].path, agents.join("broken.md"));
+
+        let reviewer = &report.converted[0];
+        assert_eq!(reviewer.path, opencode.join("agent").join("reviewer.md"));
+        let written = std::fs::read_to_string(&reviewer.path).unwrap();
+        assert!(written.contains("description: \"Reviews code\""));
+        assert!(written.contains("tools:\n  read: true\n"));
+        assert!(written.contains("permission:\n  bash: deny\n  edit: deny\n"));
+        assert!(written.ends_with("Review it."));
+        assert!(reviewer
+            .warnings
+            .iter()
+            .any(|w| w.starts_with("tools:") && w.contains("TodoWrite")));
+        assert!(reviewer
+            .warnings
+            .iter()
+            .any(|w| w.starts_with("permission_mode:")));
+        assert!(opencode.join("agent").join("writer.md").is_file());
+
+        // The converted files read back as the same agents
+        let back = load_subagent_file_opencode(&reviewer.path).unwrap();
+        assert_eq!(back.name, "reviewer");
+        assert_eq!(back.description, "Reviews code");
+    }
+
+    #[test]
+    fn test_convert_directory_opencode_to_claude() {
+        let temp_dir = TempDir::new().unwrap();
+        let opencode = temp_dir.path().join(".opencode");
+        std::fs::create_dir_all(opencode.join("agent")).unwrap();
+        std::fs::write(
+            opencode.join("agent").join("reviewer.md"),
+            "---\ndescription: \"Reviews code\"\nmode: subagent\nmodel: anthropic/claude-sonnet-4-20250514\ntemperature: 0.2\ntools:\n  read: true\npermission:\n  edit: deny\n---\n\nReview it.",
+        )
+        .unwrap();
+
+        let project = temp_dir.path().join("project");
+        let report = convert_directory(
+            &opencode,
+            &project,
+            AgentFormat::OpenCode,
+            AgentFormat::Claude,
+        )
+        .unwrap();
+
+        assert!(report.failed.is_empty());
+        let reviewer = &report.converted[0];
+        assert_eq!(reviewer.path, project.join(".claude/agents/reviewer.md"));
+        for field in ["permission", "mode", "temperature", "model"] {
+            assert!(
+                reviewer
+                    .warnings
+                    .iter()
+                    .any(|w| w.starts_with(&format!("{}:", field))),
+                "no warning for {}",
+                field
+            );
+        }
+
+        let back = load_subagent_file(&reviewer.path).unwrap();
+        assert_eq!(back.description, "Reviews code");
+        assert_eq!(back.tools, Some(vec!["Read".to_string()]));
+    }
+
+    #[test]
+    fn test_convert_directory_missing_source_errors() {
+        let temp_dir = TempDir::new().unwrap();
+        assert!(convert_directory(
+            temp_dir.path(),
+            &temp_dir.path().join("out"),
+            AgentFormat::Claude,
+            AgentFormat::OpenCode,
+        )
+        .is_err());
+    }
+}
@@ -0,0 +1,157 @@
+//! Agent diffs
+//!
+//! Compares two versions of a sub-agent field by field for "what changed"
+//! views. List fields are compared as sets; the id and timestamps are ignored.
+
+use crate::db::models::SubAgent;
+use serde::Serialize;
+use std::collections::BTreeSet;
+
+/// How one field differs between two agents
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase", tag = "kind")]
+pub enum Change {
+    /// A single value changed (None when unset)
+    Value {
+        old: Option<String>,
+        new: Option<String>,
+    },
+    /// Items added to or removed from a list field, in sorted order
+    Set {
+        added: Vec<String>,
+        removed: Vec<String>,
+    },
+}
+
+/// One differing field
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FieldChange {
+    /// SubAgent field name (e.g. `permission_mode`)
+    pub field: String,
+    pub change: Change,
+}
+
+/// Fields that differ from `a` to `b`, in SubAgent field order. Unset and
+/// empty values compare equal, as do lists holding the same items.
+pub fn diff_subagents(a: &SubAgent, b: &SubAgent) -> Vec<FieldChange> {
+    let mut changes = Vec::new();
+
+    let mut value = |field: &str, old: Option<String>, new: Option<String>| {
+        let old = old.filter(|v| !v.is_empty());
+        let new = new.filter(|v| !v.is_empty());
+        if old != new {
+            changes.push(FieldChange {
+                field: field.to_string(),
+                change: Change::Value { old, new },
+            });
+        }
+    };
+    value("name", Some(a.name.clone()), Some(b.name.clone()));
+    value(
+        "description",
+        Some(a.description.clone()),
+        Some(b.description.clone()),
+    );
+    value("content", Some(a.content.clone()), Some(b.content.clone()));
+    value("model", a.model.clone(), b.model.clone());
+    value(
+        "permission_mode",
+        a.permission_mode.clone(),
+        b.permission_mode.clone(),
+    );
+    value("color", a.color.clone(), b.color.clone());
+    value("source", Some(a.source.clone()), Some(b.source.clone()));
+    value("source_path", a.source_path.clone(), b.source_path.clone());
+    value(
+        "is_favorite",
+        Some(a.is_favorite.to_string()),
+        Some(b.is_favorite.to_string()),
+    );
+    value(
+        "enabled",
+        Some(a.enabled.to_string()),
+        Some(b.enabled.to_string()),
+    );
+    value("extends", a.extends.clone(), b.extends.clone());
+    if a.extra != b.extra {
+        let json = |agent: &SubAgent| {
+            (!agent.extra.is_empty())
+                .then(|| serde_json::to_string(&agent.extra).unwrap_or_default())
+        };
+        value("extra", json(a), json(b));
+    }
+
+    let lists = [
+        ("tools", &a.tools, &b.tools),
+        ("skills", &a.skills, &b.skills),
+        ("tags", &a.tags, &b.tags),
+        ("env", &a.env, &b.env),
+        ("mcp_servers", &a.mcp_servers, &b.mcp_servers),
+    ];
+    for (field, old, new) in lists {
+        let set = |items: &Option<Vec<String>>| -> BTreeSet<String> {
+            items.iter().flatten().cloned().collect()
+        };
+        let (old, new) = (set(old), set(new));
+        if old != new {
+            changes.push(FieldChange {
+                field: field.to_string(),
+                change: Change::Set {
+                    added: new.difference(&old).cloned().collect(),
+                    removed: old.difference(&new).cloned().collect(),
+                },
+            });
+        }
+    }
+
+    changes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::models::sample_subagent;
+
+    fn agent(content: &str, tools: &[&str]) -> SubAgent {
+        SubAgent {
+            content: content.to_string(),
+            tools: Some(tools.iter().map(|t| t.to_string()).collect()),
+            model: Some("sonnet".to_string()),
+            tags: Some(vec![]),
+            ..sample_subagent()
+        }
+    }
+
+    #[test]
+    fn test_diff_reports_content_and_tool_changes() {
+        let old = agent("You review code.", &["Read", "Grep", "Glob"]);
+        let mut new = agent("You review code carefully.", &["Glob", "Read", "Bash"]);
+        new.id = 7;
+        new.tags = None;
+        new.updated_at = "2024-06-01".to_string();
+
+        let changes = diff_subagents(&old, &new);
+
+        assert_eq!(
+            changes,
+            vec![
+                FieldChange {
+                    field: "content".to_string(),
+                    change: Change::Value {
+                        old: Some("You review code.".to_string()),
+                        new: Some("You review code carefully.".to_string()),
+                    },
+                },
+                FieldChange {
+                    field: "tools".to_string(),
+                    change: Change::Set {
+                        added: vec!["Bash".to_string()],
+                        removed: vec!["Grep".to_string()],
+                    },
+                },
+            ]
+        );
+        assert!(diff_subagents(&old, &old).is_empty());
+    }
+}
@@ -0,0 +1,180 @@
+//! `.agentignore` Rules
+//!
+//! Gitignore-style exclusion for directory imports. Each `.agentignore`
+//! applies to its own directory and everything below it; patterns are
+//! matched against paths relative to that directory. Supported syntax:
+//! `#` comments, `!` negation, trailing `/` for directories only, leading or
+//! inner `/` to anchor, and the `*`, `?`, `[...]` and `**` wildcards.
+//! As in git, the last matching pattern wins.
+
+use anyhow::Result;
+use regex::Regex;
+use std::path::{Path, PathBuf};
+
+/// Name of the ignore file honored in imported directories
+pub const AGENT_IGNORE_FILE: &str = ".agentignore";
+
+#[derive(Debug)]
+struct IgnoreRule {
+    /// Directory holding the ignore file the rule came from
+    base: PathBuf,
+    regex: Regex,
+    negated: bool,
+    dir_only: bool,
+}
+
+/// Rules from every `.agentignore` loaded so far
+#[derive(Debug, Default)]
+pub struct IgnoreRules {
+    rules: Vec<IgnoreRule>,
+}
+
+impl IgnoreRules {
+    /// Load `{dir}/.agentignore` if present. Rules are kept in load order, so
+    /// a directory must be loaded after its parents for their rules to be
+    /// overridden correctly (as a top-down walk does).
+    pub fn load_dir(&mut self, dir: &Path) -> Result<()> {
+        let path = dir.join(AGENT_IGNORE_FILE);
+        if !path.is_file() {
+            return Ok(());
+        }
+        let content = std::fs::read_to_string(&path)?;
+        self.add_patterns(dir, &content);
+        Ok(())
+    }
+
+    /// Add rules from ignore-file `content` applying below `base`
+    pub fn add_patterns(&mut self, base: &Path, content: &str) {
+        for line in content.lines() {
+            let line = line.trim_end();
+            if line.trim().is_empty() || line.starts_with('#') {
+                continue;
+            }
+            match parse_rule(base, line) {
+                Some(rule) => self.rules.push(rule),
+                None => log::warn!("[AgentIgnore] Ignoring invalid pattern '{}'", line),
+            }
+        }
+    }
+
+    /// Whether `path` is excluded by the rules of its ancestor directories
+    pub fn is_ignored(&self, path: &Path, is_dir: bool) -> bool {
+        let mut ignored = false;
+        for rule in &self.rules {
+            if rule.dir_only && !is_dir {
+                continue;
+            }
+            let Ok(relative) = path.strip_prefix(&rule.base) else {
+                continue;
+            };
+            let relative = relative
+                .components()
+                .map(|c| c.as_os_str().to_string_lossy())
+                .collect::<Vec<_>>()
+                .join("/");
+            if !relative.is_empty() && rule.regex.is_match(&relative) {
+                ignored = !rule.negated;
+            }
+        }
+        ignored
+    }
+}
+
+fn parse_rule(base: &Path, line: &str) -> Option<IgnoreRule> {
+    let (negated, pattern) = match line.strip_prefix('!') {
+        Some(rest) => (true, rest),
+        None => (false, line.strip_prefix('\\').unwrap_or(line)),
+    };
+    let (dir_only, pattern) = match pattern.strip_suffix('/') {
+        Some(rest) => (true, rest),
+        None => (false, pattern),
+    };
+    // A slash anywhere but the end anchors the pattern to the ignore file's directory
+    let anchored = pattern.contains('/');
+    let pattern = pattern.strip_prefix('/').unwrap_or(pattern);
+    if pattern.is_empty() {
+        return None;
+    }
+
+    let prefix = if anchored { "^" } else { "^(?:.*/)?" };
+    let regex = Regex::new(&format!("{}{}$", prefix, glob_to_regex(pattern))).ok()?;
+    Some(IgnoreRule {
+        base: base.to_path_buf(),
+        regex,
+        negated,
+        dir_only,
+    })
+}
+
+/// Translate a gitignore glob to a regex body
+fn glob_to_regex(glob: &str) -> String {
+    let chars: Vec<char> = glob.chars().collect();
+    let mut regex = String::new();
+    let mut i = 0;
+    while i < chars.len() {
+        match chars[i] {
+            '*' if chars.get(i + 1) == Some(&'*') => {
+                if chars.get(i + 2) == Some(&'/') {
+                    // `**/` matches zero or more directories
+                    regex.push_str("(?:.*/)?");
+                    i += 3;
+                } else {
+                    regex.push_str(".*");
+                    i += 2;
+                }
+                continue;
+            }
+            '*' => regex.push_str("[^/]*"),
+            '?' => regex.push_str("[^/]"),
+            '[' => match chars[i..].iter().position(|&c| c == ']') {
+                Some(len) if len > 1 => {
+                    let class: String = chars[i + 1..i + len].iter().collect();
+                    let class = class
+                        .strip_prefix('!')
+                        .map_or(class.clone(), |rest| format!("^{}", rest));
+                    regex.push('[');
+                    regex.push_str(&class.replace('\\', "\\\\"));
+                    regex.push(']');
+                    i += len + 1;
+                    continue;
+                }
+                _ => regex.push_str("\\["),
+            },
+            c => regex.push_str(&regex::escape(&c.to_string())),
+        }
+        i += 1;
+    }
+    regex
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rules(content: &str) -> IgnoreRules {
+        let mut rules = IgnoreRules::default();
+        rules.add_patterns(Path::new("/lib"), content);
+        rules
+    }
+
+    #[test]
+    fn test_patterns_match_like_gitignore() {
+        let rules =
+            rules("# drafts\n*.draft.md\n/top.md\ndrafts/\ndocs/**/old-*.md\n!keep.draft.md\n");
+        let ignored = |path: &str, is_dir| rules.is_ignored(&Path::new("/lib").join(path), is_dir);
+
+        assert!(ignored("a.draft.md", false));
+        assert!(ignored("team/b.draft.md", false));
+        assert!(!ignored("keep.draft.md", false));
+        assert!(ignored("top.md", false));
+        assert!(!ignored("team/top.md", false));
+        assert!(ignored("drafts", true));
+        assert!(ignored("team/drafts", true));
+        assert!(!ignored("drafts", false));
+        assert!(ignored("docs/old-a.md", false));
+        assert!(ignored("docs/x/y/old-b.md", false));
+        assert!(!ignored("reviewer.md", false));
+        // Paths outside the ignore file's directory are unaffected
+        assert!(!rules.is_ignored(Path::new("/other/top.md"), false));
+    }
+}
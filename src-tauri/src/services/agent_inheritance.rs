@@ -0,0 +1,141 @@
+//! Agent inheritance
+//!
+//! Resolves `extends` chains at generation time so a derived agent carries its
+//! base agent's content, tools and skills without copy-paste. The expanded agent
+//! no longer extends anything and can be written like any other.
+
+use crate::db::models::SubAgent;
+use anyhow::{anyhow, Result};
+
+/// Expand an agent's `extends` chain, resolving parents by name with `resolver`.
+///
+/// Tools and skills are the union of the whole chain (base agent's entries
+/// first), and each parent's content is prepended to its child's. An agent
+/// without a tools list takes its parent's. Every other field comes from
+/// `agent`. Unknown parents and cycles are errors.
+pub fn expand_inheritance<F>(agent: &SubAgent, resolver: F) -> Result<SubAgent>
+where
+    F: Fn(&str) -> Option<SubAgent>,
+{
+    // Chain from the agent up to its root base agent
+    let mut chain = vec![agent.clone()];
+    while let Some(parent_name) = chain.last().and_then(|a| a.extends.clone()) {
+        if let Some(start) = chain.iter().position(|a| a.name == parent_name) {
+            let cycle: Vec<&str> = chain[start..]
+                .iter()
+                .map(|a| a.name.as_str())
+                .chain(std::iter::once(parent_name.as_str()))
+                .collect();
+            return Err(anyhow!("Agent inheritance cycle: {}", cycle.join(" -> ")));
+        }
+
+        let child = &chain[chain.len() - 1].name;
+        let parent = resolver(&parent_name)
+            .ok_or_else(|| anyhow!("Agent '{}' extends unknown agent '{}'", child, parent_name))?;
+        chain.push(parent);
+    }
+
+    let mut chain = chain.into_iter().rev();
+    let root = chain.next().unwrap_or_else(|| agent.clone());
+    let mut expanded = chain.fold(root, |parent, child| merge(&parent, child));
+    expanded.extends = None;
+    Ok(expanded)
+}
+
+/// Apply one level of inheritance: `child` with `parent` merged in
+fn merge(parent: &SubAgent, child: SubAgent) -> SubAgent {
+    let content = if parent.content.trim().is_empty() {
+        child.content
+    } else if child.content.trim().is_empty() {
+        parent.content.clone()
+    } else {
+        format!("{}\n\n{}", parent.content.trim_end(), child.content)
+    };
+
+    SubAgent {
+        content,
+        tools: union(&parent.tools, &child.tools),
+        skills: union(&parent.skills, &child.skills),
+        ..child
+    }
+}
+
+fn union(parent: &Option<Vec<String>>, child: &Option<Vec<String>>) -> Option<Vec<String>> {
+    if parent.is_none() && child.is_none() {
+        return None;
+    }
+
+    let mut merged: Vec<String> = Vec::new();
+    for item in parent.iter().chain(child.iter()).flatten() {
+        if !merged.contains(item) {
+            merged.push(item.clone());
+        }
+    }
+    Some(merged)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::models::sample_subagent;
+    use std::collections::HashMap;
+
+    fn agent(name: &str, extends: Option<&str>, content: &str, tools: &[&str]) -> SubAgent {
+        SubAgent {
+            name: name.to_string(),
+            description: format!("The {} agent", name),
+            content: content.to_string(),
+            tools: Some(tools.iter().map(|t| t.to_string()).collect()),
+            extends: extends.map(str::to_string),
+            ..sample_subagent()
+        }
+    }
+
+    fn library(agents: Vec<SubAgent>) -> HashMap<String, SubAgent> {
+        agents.into_iter().map(|a| (a.name.clone(), a)).collect()
+    }
+
+    #[test]
+    fn test_expand_single_level() {
+        let mut base = agent("base", None, "Follow the style guide.", &["Read", "Grep"]);
+        base.skills = Some(vec!["lint".to_string()]);
+        let mut reviewer = agent(
+            "reviewer",
+            Some("base"),
+            "Review the diff.",
+            &["Grep", "Bash"],
+        );
+        reviewer.model = Some("sonnet".to_string());
+        let agents = library(vec![base]);
+
+        let expanded = expand_inheritance(&reviewer, |name| agents.get(name).cloned()).unwrap();
+
+        assert_eq!(expanded.name, "reviewer");
+        assert_eq!(expanded.extends, None);
+        assert_eq!(
+            expanded.content,
+            "Follow the style guide.\n\nReview the diff."
+        );
+        assert_eq!(expanded.tools.unwrap(), ["Read", "Grep", "Bash"]);
+        assert_eq!(expanded.skills.unwrap(), ["lint"]);
+        assert_eq!(expanded.model.as_deref(), Some("sonnet"));
+    }
+
+    #[test]
+    fn test_expand_detects_cycles() {
+        let agents = library(vec![
+            agent("a", Some("b"), "A", &[]),
+            agent("b", Some("c"), "B", &[]),
+            agent("c", Some("a"), "C", &[]),
+        ]);
+
+        let err = expand_inheritance(&agents["a"], |name| agents.get(name).cloned()).unwrap_err();
+        assert_eq!(err.to_string(), "Agent inheritance cycle: a -> b -> c -> a");
+
+        let orphan = agent("orphan", Some("missing"), "", &[]);
+        assert!(expand_inheritance(&orphan, |_| None)
+            .unwrap_err()
+            .to_string()
+            .contains("unknown agent 'missing'"));
+    }
+}
@@ -0,0 +1,148 @@
+//! Agent content linting
+//!
+//! Flags common mistakes in an agent's markdown (an empty system prompt, a
+//! template placeholder that was never filled in, an oversized prompt) before
+//! it is saved. Works on raw content, so it applies to every agent format.
+
+use crate::services::frontmatter::split_frontmatter;
+use regex::Regex;
+use serde::Serialize;
+
+/// System prompts longer than this many characters are flagged
+pub const PROMPT_SIZE_BUDGET: usize = 20_000;
+
+/// The kind of mistake a lint reports
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum LintKind {
+    /// The frontmatter block is opened but never closed
+    MalformedFrontmatter,
+    /// The body after the frontmatter is blank
+    EmptyPrompt,
+    /// A `{{placeholder}}` was left in the prompt
+    UnexpandedPlaceholder,
+    /// The prompt is longer than PROMPT_SIZE_BUDGET
+    PromptTooLarge,
+}
+
+/// One finding, positioned at a 1-based line and column of the content
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Lint {
+    pub kind: LintKind,
+    pub message: String,
+    pub line: usize,
+    pub column: usize,
+}
+
+/// Lint an agent's markdown, returning findings in content order.
+///
+/// Positions refer to the content with a leading BOM removed and CRLF line
+/// endings normalized to LF.
+pub fn lint_subagent_content(content: &str) -> Vec<Lint> {
+    let normalized = content.strip_prefix('\u{feff}').unwrap_or(content);
+    let normalized = normalized.replace("\r\n", "\n");
+
+    let body = match split_frontmatter(&normalized) {
+        Ok((_, body)) => body,
+        Err(e) => {
+            return vec![Lint {
+                kind: LintKind::MalformedFrontmatter,
+                message: e.to_string(),
+                line: 1,
+                column: 1,
+            }]
+        }
+    };
+    // The body is a suffix of the normalized content
+    let body_offset = normalized.len() - body.len();
+    let position = |offset: usize| line_and_column(&normalized, body_offset + offset);
+
+    let mut lints = Vec::new();
+    if body.trim().is_empty() {
+        let (line, column) = position(0);
+        lints.push(Lint {
+            kind: LintKind::EmptyPrompt,
+            message: "The system prompt is empty".to_string(),
+            line,
+            column,
+        });
+        return lints;
+    }
+
+    let placeholder = Regex::new(r"\{\{\s*[^{}\s]*\s*\}\}").unwrap();
+    for found in placeholder.find_iter(&body) {
+        let (line, column) = position(found.start());
+        lints.push(Lint {
+            kind: LintKind::UnexpandedPlaceholder,
+            message: format!("Unexpanded placeholder {}", found.as_str()),
+            line,
+            column,
+        });
+    }
+
+    let chars = body.chars().count();
+    if chars > PROMPT_SIZE_BUDGET {
+        let (line, column) = position(0);
+        lints.push(Lint {
+            kind: LintKind::PromptTooLarge,
+            message: format!(
+                "The system prompt is {} characters, over the budget of {}",
+                chars, PROMPT_SIZE_BUDGET
+            ),
+            line,
+            column,
+        });
+    }
+
+    lints
+}
+
+/// 1-based line and column (in characters) of a byte offset
+fn line_and_column(content: &str, offset: usize) -> (usize, usize) {
+    let before = &content[..offset];
+    let line = before.matches('\n').count() + 1;
+    let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+    (line, before[line_start..].chars().count() + 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unexpanded_placeholder_is_positioned() {
+        let content =
+            "---\nname: reviewer\n---\n\nYou review code.\nFocus on {{ language }} files.\n";
+
+        let lints = lint_subagent_content(content);
+
+        assert_eq!(
+            lints,
+            vec![Lint {
+                kind: LintKind::UnexpandedPlaceholder,
+                message: "Unexpanded placeholder {{ language }}".to_string(),
+                line: 6,
+                column: 10,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_empty_prompt() {
+        let lints = lint_subagent_content("---\r\nname: blank\r\n---\r\n\r\n  \r\n");
+
+        assert_eq!(lints.len(), 1);
+        assert_eq!(lints[0].kind, LintKind::EmptyPrompt);
+
+        let oversized = format!(
+            "---\nname: big\n---\n{}",
+            "a".repeat(PROMPT_SIZE_BUDGET + 1)
+        );
+        assert_eq!(
+            lint_subagent_content(&oversized)[0].kind,
+            LintKind::PromptTooLarge
+        );
+        assert!(lint_subagent_content("Plain prompt, no frontmatter").is_empty());
+    }
+}
@@ -0,0 +1,207 @@
+//! Plain-text agent listing
+//!
+//! Renders sub-agents as a fixed-width table for scripting (greppable, no JSON).
+
+use crate::db::models::SubAgent;
+use directories::BaseDirs;
+use std::path::{Path, PathBuf};
+
+/// Default maximum width of the description column
+pub const DEFAULT_DESCRIPTION_WIDTH: usize = 50;
+
+/// Options for format_agents_table_with_options
+#[derive(Debug, Clone)]
+pub struct AgentTableOptions {
+    /// Descriptions longer than this many characters are truncated with an ellipsis
+    pub description_width: usize,
+    /// Home directory used to tell global agents from project agents
+    pub home_dir: Option<PathBuf>,
+}
+
+impl Default for AgentTableOptions {
+    fn default() -> Self {
+        Self {
+            description_width: DEFAULT_DESCRIPTION_WIDTH,
+            home_dir: BaseDirs::new().map(|d| d.home_dir().to_path_buf()),
+        }
+    }
+}
+
+/// Where an agent lives: only in the library, in the user's global config, or in a project
+fn agent_scope(agent: &SubAgent, home_dir: Option<&Path>) -> &'static str {
+    let Some(source_path) = agent.source_path.as_deref() else {
+        return "library";
+    };
+
+    let path = Path::new(source_path);
+    let is_global = home_dir.is_some_and(|home| {
+        path.starts_with(home.join(".claude"))
+            || path.starts_with(home.join(".config").join("opencode"))
+    });
+
+    if is_global {
+        "global"
+    } else {
+        "project"
+    }
+}
+
+/// Truncate to at most `width` characters, ending with an ellipsis when shortened
+fn truncate_with_ellipsis(text: &str, width: usize) -> String {
+    if text.chars().count() <= width {
+        return text.to_string();
+    }
+    if width == 0 {
+        return String::new();
+    }
+
+    let mut truncated: String = text.chars().take(width - 1).collect();
+    truncated.push('…');
+    truncated
+}
+
+/// Format agents as a fixed-width table using default options
+pub fn format_agents_table(agents: &[SubAgent]) -> String {
+    format_agents_table_with_options(agents, &AgentTableOptions::default())
+}
+
+/// Format agents as a fixed-width table: NAME, MODEL, TOOLS, SCOPE, DESCRIPTION.
+///
+/// Agents without a tools list inherit every tool, shown as `all`.
+pub fn format_agents_table_with_options(
+    agents: &[SubAgent],
+    options: &AgentTableOptions,
+) -> String {
+    let header = ["NAME", "MODEL", "TOOLS", "SCOPE", "DESCRIPTION"];
+
+    let rows: Vec<[String; 5]> = agents
+        .iter()
+        .map(|agent| {
+            [
+                agent.name.clone(),
+                agent
+                    .model
+                    .clone()
+                    .filter(|m| !m.is_empty())
+                    .unwrap_or_else(|| "-".to_string()),
+                agent
+                    .tools
+                    .as_ref()
+                    .map(|t| t.len().to_string())
+                    .unwrap_or_else(|| "all".to_string()),
+                agent_scope(agent, options.home_dir.as_deref()).to_string(),
+                truncate_with_ellipsis(agent.description.trim(), options.description_width),
+            ]
+        })
+        .collect();
+
+    // The last column is never padded, so only the first four need widths
+    let mut widths = [0usize; 4];
+    for (i, width) in widths.iter_mut().enumerate() {
+        *width = rows
+            .iter()
+            .map(|row| row[i].chars().count())
+            .chain(std::iter::once(header[i].len()))
+            .max()
+            .unwrap_or_default();
+    }
+
+    let format_row = |cells: [&str; 5]| -> String {
+        let mut line = String::new();
+        for (i, width) in widths.iter().enumerate() {
+            line.push_str(&format!("{:<width$}  ", cells[i], width = width));
+        }
+        line.push_str(cells[4]);
+        line.trim_end().to_string()
+    };
+
+    let mut table = format_row(header);
+    table.push('\n');
+    for row in &rows {
+        table.push_str(&format_row([
+            row[0].as_str(),
+            row[1].as_str(),
+            row[2].as_str(),
+            row[3].as_str(),
+            row[4].as_str(),
+        ]));
+        table.push('\n');
+    }
+
+    table
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::models::sample_subagent;
+
+    fn agent(name: &str, description: &str, source_path: Option<&str>) -> SubAgent {
+        SubAgent {
+            name: name.to_string(),
+            description: description.to_string(),
+            source_path: source_path.map(str::to_string),
+            ..sample_subagent()
+        }
+    }
+
+    fn options(description_width: usize) -> AgentTableOptions {
+        AgentTableOptions {
+            description_width,
+            home_dir: Some(PathBuf::from("/home/dev")),
+        }
+    }
+
+    #[test]
+    fn test_format_agents_table_aligns_columns() {
+        let mut reviewer = agent(
+            "code-reviewer",
+            "Reviews code",
+            Some("/home/dev/.claude/agents/code-reviewer.md"),
+        );
+        reviewer.model = Some("sonnet".to_string());
+        reviewer.tools = Some(vec!["Read".to_string(), "Grep".to_string()]);
+        let planner = agent(
+            "planner",
+            "Plans work",
+            Some("/work/app/.claude/agents/planner.md"),
+        );
+        let draft = agent("draft", "Not installed", None);
+
+        let table = format_agents_table_with_options(&[reviewer, planner, draft], &options(50));
+        let lines: Vec<&str> = table.lines().collect();
+
+        assert_eq!(
+            lines,
+            vec![
+                "NAME           MODEL   TOOLS  SCOPE    DESCRIPTION",
+                "code-reviewer  sonnet  2      global   Reviews code",
+                "planner        -       all    project  Plans work",
+                "draft          -       all    library  Not installed",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_format_agents_table_truncates_long_descriptions() {
+        let long = agent(
+            "writer",
+            "Writes long-form documentation for every module",
+            None,
+        );
+
+        let table = format_agents_table_with_options(&[long], &options(12));
+        let row = table.lines().nth(1).unwrap();
+
+        assert!(row.ends_with("Writes long…"));
+        assert_eq!(row.split("  ").last().unwrap().chars().count(), 12);
+    }
+
+    #[test]
+    fn test_truncate_with_ellipsis() {
+        assert_eq!(truncate_with_ellipsis("short", 10), "short");
+        assert_eq!(truncate_with_ellipsis("exactly10!", 10), "exactly10!");
+        assert_eq!(truncate_with_ellipsis("abcdef", 4), "abc…");
+        assert_eq!(truncate_with_ellipsis("abcdef", 0), "");
+    }
+}
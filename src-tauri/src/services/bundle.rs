@@ -0,0 +1,426 @@
+//! Agent/Skill Bundle Export
+//!
+//! Packs sub-agents and skills into a single zip with a JSON manifest.
+//! Team packs additionally carry a redacted copy of `settings.local.json`.
+//! Bundles are deterministic: entries are sorted by name and written with a
+//! fixed timestamp, so exporting the same set twice yields identical bytes.
+
+use crate::db::models::{Skill, SubAgent};
+use crate::services::skill_writer::generate_skill_markdown;
+use crate::services::subagent_writer::generate_subagent_markdown;
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::io::{Seek, Write};
+use std::path::Path;
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, DateTime, ZipWriter};
+
+/// Name of the manifest file at the root of a bundle
+pub const MANIFEST_FILE: &str = "manifest.json";
+
+/// Current bundle format version
+pub const BUNDLE_FORMAT_VERSION: u32 = 1;
+
+/// Path of the sanitized settings file inside a team pack
+pub const SETTINGS_FILE: &str = "settings.local.json";
+
+/// Replacement for values stripped from exported settings
+pub const REDACTED: &str = "[REDACTED]";
+
+/// Key fragments that mark a settings value as secret (matched case-insensitively)
+const SECRET_KEY_MARKERS: &[&str] = &[
+    "token",
+    "secret",
+    "password",
+    "apikey",
+    "api_key",
+    "credential",
+    "auth",
+];
+
+/// A single agent or skill listed in the manifest
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BundleEntry {
+    pub name: String,
+    /// Path of the entry's file inside the zip
+    pub path: String,
+}
+
+/// Bundle manifest (manifest.json)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BundleManifest {
+    pub version: u32,
+    pub agents: Vec<BundleEntry>,
+    pub skills: Vec<BundleEntry>,
+    /// Path of the sanitized settings file, present in team packs only
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub settings: Option<String>,
+}
+
+fn agent_entry_path(name: &str) -> String {
+    format!("agents/{}.md", name)
+}
+
+fn skill_entry_path(name: &str) -> String {
+    format!("skills/{}/SKILL.md", name)
+}
+
+/// Sort by name and reject duplicates (they would collide inside the zip)
+fn sorted_by_name<'a, T>(
+    items: &'a [T],
+    name: impl Fn(&T) -> &str,
+    kind: &str,
+) -> Result<Vec<&'a T>> {
+    let mut sorted: Vec<&T> = items.iter().collect();
+    sorted.sort_by(|a, b| name(a).cmp(name(b)));
+
+    if let Some(pair) = sorted
+        .windows(2)
+        .find(|pair| name(pair[0]) == name(pair[1]))
+    {
+        return Err(anyhow!(
+            "Duplicate {} name in bundle: {}",
+            kind,
+            name(pair[0])
+        ));
+    }
+
+    Ok(sorted)
+}
+
+/// Build the manifest for a set of agents and skills, sorted by name
+pub fn build_manifest(agents: &[SubAgent], skills: &[Skill]) -> Result<BundleManifest> {
+    let agents = sorted_by_name(agents, |a| a.name.as_str(), "agent")?;
+    let skills = sorted_by_name(skills, |s| s.name.as_str(), "skill")?;
+
+    Ok(BundleManifest {
+        version: BUNDLE_FORMAT_VERSION,
+        agents: agents
+            .iter()
+            .map(|a| BundleEntry {
+                name: a.name.clone(),
+                path: agent_entry_path(&a.name),
+            })
+            .collect(),
+        skills: skills
+            .iter()
+            .map(|s| BundleEntry {
+                name: s.name.clone(),
+                path: skill_entry_path(&s.name),
+            })
+            .collect(),
+        settings: None,
+    })
+}
+
+fn is_secret_key(key: &str) -> bool {
+    let key = key.to_lowercase();
+    SECRET_KEY_MARKERS.iter().any(|marker| key.contains(marker))
+}
+
+/// Strip secrets from a settings value before it leaves the machine.
+///
+/// Every `env` value is redacted (keys are kept so teammates know what to
+/// set), as is any non-empty value under a secret-looking key. Permissions
+/// and other settings pass through unchanged.
+pub fn redact_settings(settings: &serde_json::Value) -> serde_json::Value {
+    match settings {
+        serde_json::Value::Object(map) => map
+            .iter()
+            .map(|(key, value)| {
+                let value = if key == "env" {
+                    match value {
+                        serde_json::Value::Object(env) => env
+                            .keys()
+                            .map(|k| (k.clone(), serde_json::Value::from(REDACTED)))
+                            .collect::<serde_json::Map<_, _>>()
+                            .into(),
+                        other => redact_settings(other),
+                    }
+                } else if is_secret_key(key) && !value.is_null() {
+                    serde_json::Value::from(REDACTED)
+                } else {
+                    redact_settings(value)
+                };
+                (key.clone(), value)
+            })
+            .collect::<serde_json::Map<_, _>>()
+            .into(),
+        serde_json::Value::Array(items) => items.iter().map(redact_settings).collect(),
+        other => other.clone(),
+    }
+}
+
+/// Export agents and skills to a zip bundle at `out`.
+///
+/// The manifest is written first, followed by agents and then skills in
+/// manifest order.
+pub fn export_bundle(agents: &[SubAgent], skills: &[Skill], out: &Path) -> Result<()> {
+    export_bundle_to_writer(agents, skills, create_output(out)?)
+}
+
+/// Export agents and skills as a zip bundle into any seekable sink (an
+/// in-memory buffer, a socket-backed spool, ...), laid out like export_bundle.
+pub fn export_bundle_to_writer<W: Write + Seek>(
+    agents: &[SubAgent],
+    skills: &[Skill],
+    w: W,
+) -> Result<()> {
+    write_bundle(agents, skills, None, w)
+}
+
+/// Export agents and skills together with the settings they rely on.
+///
+/// `settings` is the parsed `settings.local.json`; it is passed through
+/// redact_settings and stored as `settings.local.json` right after the
+/// manifest, which records its path.
+pub fn export_team_pack(
+    agents: &[SubAgent],
+    skills: &[Skill],
+    settings: serde_json::Value,
+    out: &Path,
+) -> Result<()> {
+    write_bundle(
+        agents,
+        skills,
+        Some(&redact_settings(&settings)),
+        create_output(out)?,
+    )
+}
+
+/// Create the bundle file at `out`, along with its parent directories
+fn create_output(out: &Path) -> Result<std::fs::File> {
+    if let Some(parent) = out.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    Ok(std::fs::File::create(out)?)
+}
+
+fn write_bundle<W: Write + Seek>(
+    agents: &[SubAgent],
+    skills: &[Skill],
+    settings: Option<&serde_json::Value>,
+    w: W,
+) -> Result<()> {
+    let mut manifest = build_manifest(agents, skills)?;
+    if settings.is_some() {
+        manifest.settings = Some(SETTINGS_FILE.to_string());
+    }
+
+    let mut zip = ZipWriter::new(w);
+
+    // Fixed timestamp and no compression keep the output byte-for-byte stable
+    let options = SimpleFileOptions::default()
+        .compression_method(CompressionMethod::Stored)
+        .last_modified_time(DateTime::default());
+
+    zip.start_file(MANIFEST_FILE, options)?;
+    zip.write_all(serde_json::to_string_pretty(&manifest)?.as_bytes())?;
+
+    if let Some(settings) = settings {
+        zip.start_file(SETTINGS_FILE, options)?;
+        zip.write_all(serde_json::to_string_pretty(settings)?.as_bytes())?;
+    }
+
+    for entry in &manifest.agents {
+        let agent = agents
+            .iter()
+            .find(|a| a.name == entry.name)
+            .ok_or_else(|| anyhow!("Agent '{}' missing from export", entry.name))?;
+        zip.start_file(entry.path.as_str(), options)?;
+        zip.write_all(generate_subagent_markdown(agent).as_bytes())?;
+    }
+
+    for entry in &manifest.skills {
+        let skill = skills
+            .iter()
+            .find(|s| s.name == entry.name)
+            .ok_or_else(|| anyhow!("Skill '{}' missing from export", entry.name))?;
+        zip.start_file(entry.path.as_str(), options)?;
+        zip.write_all(generate_skill_markdown(skill).as_bytes())?;
+    }
+
+    zip.finish()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::models::sample_subagent;
+    use std::io::Read;
+    use tempfile::TempDir;
+
+    fn agent(name: &str) -> SubAgent {
+        SubAgent {
+            name: name.to_string(),
+            description: format!("The {} agent", name),
+            content: "Do the work.".to_string(),
+            ..sample_subagent()
+        }
+    }
+
+    fn skill(name: &str) -> Skill {
+        Skill {
+            id: 1,
+            name: name.to_string(),
+            description: Some(format!("The {} skill", name)),
+            content: "Follow the steps.".to_string(),
+            allowed_tools: None,
+            model: None,
+            disable_model_invocation: false,
+            tags: None,
+            source: "manual".to_string(),
+            source_path: None,
+            is_favorite: false,
+            created_at: "2024-01-01".to_string(),
+            updated_at: "2024-01-01".to_string(),
+            when_to_use: None,
+            examples: None,
+            reference_files: Vec::new(),
+            shared_assets: Vec::new(),
+        }
+    }
+
+    fn read_entry(path: &Path, name: &str) -> Vec<u8> {
+        let mut archive = zip::ZipArchive::new(std::fs::File::open(path).unwrap()).unwrap();
+        let mut file = archive.by_name(name).unwrap();
+        let mut buf = Vec::new();
+        file.read_to_end(&mut buf).unwrap();
+        buf
+    }
+
+    #[test]
+    fn test_build_manifest_sorts_by_name() {
+        let manifest = build_manifest(
+            &[agent("zeta"), agent("alpha")],
+            &[skill("lint"), skill("build")],
+        )
+        .unwrap();
+
+        let agents: Vec<&str> = manifest.agents.iter().map(|e| e.name.as_str()).collect();
+        assert_eq!(agents, vec!["alpha", "zeta"]);
+        assert_eq!(manifest.agents[0].path, "agents/alpha.md");
+        assert_eq!(manifest.skills[0].path, "skills/build/SKILL.md");
+    }
+
+    #[test]
+    fn test_build_manifest_rejects_duplicate_names() {
+        let result = build_manifest(&[agent("alpha"), agent("alpha")], &[]);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_export_twice_yields_identical_manifests() {
+        let temp_dir = TempDir::new().unwrap();
+        let first = temp_dir.path().join("first.zip");
+        let second = temp_dir.path().join("second.zip");
+
+        export_bundle(&[agent("zeta"), agent("alpha")], &[skill("lint")], &first).unwrap();
+        // Same set, different input order
+        export_bundle(&[agent("alpha"), agent("zeta")], &[skill("lint")], &second).unwrap();
+
+        assert_eq!(
+            read_entry(&first, MANIFEST_FILE),
+            read_entry(&second, MANIFEST_FILE)
+        );
+        assert_eq!(
+            std::fs::read(&first).unwrap(),
+            std::fs::read(&second).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_export_zip_entries_follow_manifest_order() {
+        let temp_dir = TempDir::new().unwrap();
+        let out = temp_dir.path().join("bundle.zip");
+
+        export_bundle(
+            &[agent("zeta"), agent("alpha")],
+            &[skill("lint"), skill("build")],
+            &out,
+        )
+        .unwrap();
+
+        let mut archive = zip::ZipArchive::new(std::fs::File::open(&out).unwrap()).unwrap();
+        let names: Vec<String> = (0..archive.len())
+            .map(|i| archive.by_index(i).unwrap().name().to_string())
+            .collect();
+        assert_eq!(
+            names,
+            vec![
+                MANIFEST_FILE,
+                "agents/alpha.md",
+                "agents/zeta.md",
+                "skills/build/SKILL.md",
+                "skills/lint/SKILL.md",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_export_bundle_to_in_memory_writer() {
+        let mut buffer = std::io::Cursor::new(Vec::new());
+
+        export_bundle_to_writer(&[agent("alpha")], &[skill("lint")], &mut buffer).unwrap();
+
+        let mut archive = zip::ZipArchive::new(std::io::Cursor::new(buffer.get_ref())).unwrap();
+        let manifest: BundleManifest = {
+            let mut file = archive.by_name(MANIFEST_FILE).unwrap();
+            let mut buf = Vec::new();
+            file.read_to_end(&mut buf).unwrap();
+            serde_json::from_slice(&buf).unwrap()
+        };
+        assert_eq!(
+            manifest,
+            build_manifest(&[agent("alpha")], &[skill("lint")]).unwrap()
+        );
+
+        let mut agent_md = String::new();
+        archive
+            .by_name("agents/alpha.md")
+            .unwrap()
+            .read_to_string(&mut agent_md)
+            .unwrap();
+        assert_eq!(agent_md, generate_subagent_markdown(&agent("alpha")));
+        assert!(archive.by_name("skills/lint/SKILL.md").is_ok());
+
+        // Same bytes as the path-based export
+        let temp_dir = TempDir::new().unwrap();
+        let out = temp_dir.path().join("bundle.zip");
+        export_bundle(&[agent("alpha")], &[skill("lint")], &out).unwrap();
+        assert_eq!(std::fs::read(&out).unwrap(), buffer.into_inner());
+    }
+
+    #[test]
+    fn test_export_team_pack_includes_redacted_settings() {
+        let temp_dir = TempDir::new().unwrap();
+        let out = temp_dir.path().join("team.zip");
+        let settings = serde_json::json!({
+            "permissions": {"allow": ["Bash(git:*)", "Read"], "deny": []},
+            "env": {"GITHUB_TOKEN": "ghp_abc123", "LOG_LEVEL": "debug"},
+            "apiKeyHelper": "/usr/local/bin/get-key"
+        });
+
+        export_team_pack(&[agent("alpha")], &[skill("lint")], settings, &out).unwrap();
+
+        let manifest: BundleManifest =
+            serde_json::from_slice(&read_entry(&out, MANIFEST_FILE)).unwrap();
+        assert_eq!(manifest.settings.as_deref(), Some(SETTINGS_FILE));
+
+        let raw = String::from_utf8(read_entry(&out, SETTINGS_FILE)).unwrap();
+        assert!(!raw.contains("ghp_abc123"));
+        assert!(!raw.contains("get-key"));
+        let exported: serde_json::Value = serde_json::from_str(&raw).unwrap();
+        assert_eq!(
+            exported,
+            serde_json::json!({
+                "permissions": {"allow": ["Bash(git:*)", "Read"], "deny": []},
+                "env": {"GITHUB_TOKEN": REDACTED, "LOG_LEVEL": REDACTED},
+                "apiKeyHelper": REDACTED
+            })
+        );
+    }
+}
@@ -0,0 +1,342 @@
+//! Claude config doctor
+//!
+//! Audits a Claude config directory (`~/.claude` or a project's `.claude`) and
+//! reports everything that looks wrong in one pass: malformed frontmatter,
+//! skills whose name doesn't match their directory, agents referencing unknown
+//! tools, duplicate names, and files that can't be read.
+
+use crate::services::scanner::{parse_agent_tools, parse_frontmatter};
+use crate::services::subagent_writer::{check_frontmatter, safe_filename};
+use anyhow::Result;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+
+/// Kind of problem found by audit_claude_config
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum FindingCategory {
+    MalformedFrontmatter,
+    NameMismatch,
+    UnknownTool,
+    DuplicateName,
+    Unreadable,
+}
+
+/// One problem in one file
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AuditFinding {
+    pub category: FindingCategory,
+    pub path: PathBuf,
+    pub message: String,
+}
+
+/// Findings of audit_claude_config, ordered by category then path
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AuditReport {
+    pub findings: Vec<AuditFinding>,
+}
+
+impl AuditReport {
+    #[cfg(test)]
+    pub fn is_clean(&self) -> bool {
+        self.findings.is_empty()
+    }
+
+    /// Findings of one category
+    #[cfg(test)]
+    pub fn by_category(&self, category: FindingCategory) -> Vec<&AuditFinding> {
+        self.findings
+            .iter()
+            .filter(|f| f.category == category)
+            .collect()
+    }
+
+    fn push(&mut self, category: FindingCategory, path: &Path, message: String) {
+        self.findings.push(AuditFinding {
+            category,
+            path: path.to_path_buf(),
+            message,
+        });
+    }
+}
+
+/// Audit the agents (`agents/*.md`) and skills (`skills/{name}/SKILL.md`) in
+/// a Claude config directory.
+///
+/// Agent tools are checked against `known_tools`; MCP tools (`mcp__...`) are
+/// always accepted and argument patterns (`Bash(git:*)`) are ignored.
+/// Missing `agents` or `skills` directories are not findings.
+pub fn audit_claude_config(base_path: &Path, known_tools: &[&str]) -> Result<AuditReport> {
+    let mut report = AuditReport::default();
+    audit_agents(&base_path.join("agents"), known_tools, &mut report)?;
+    audit_skills(&base_path.join("skills"), &mut report)?;
+    report
+        .findings
+        .sort_by(|a, b| (a.category, &a.path).cmp(&(b.category, &b.path)));
+    Ok(report)
+}
+
+/// Sorted entries of a directory, or none if it doesn't exist
+fn sorted_entries(dir: &Path) -> Result<Vec<PathBuf>> {
+    if !dir.is_dir() {
+        return Ok(Vec::new());
+    }
+    let mut paths = std::fs::read_dir(dir)?
+        .map(|entry| entry.map(|e| e.path()))
+        .collect::<std::io::Result<Vec<_>>>()?;
+    paths.sort();
+    Ok(paths)
+}
+
+/// Frontmatter of a file, or a finding explaining why it can't be read
+fn read_frontmatter(
+    path: &Path,
+    report: &mut AuditReport,
+) -> Result<Option<HashMap<String, String>>> {
+    let content = match std::fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(e) => {
+            report.push(
+                FindingCategory::Unreadable,
+                path,
+                format!("Could not read file: {}", e),
+            );
+            return Ok(None);
+        }
+    };
+    if !content.starts_with("---") {
+        report.push(
+            FindingCategory::MalformedFrontmatter,
+            path,
+            "No frontmatter block".to_string(),
+        );
+        return Ok(None);
+    }
+    if let Err(e) = check_frontmatter(&content) {
+        report.push(FindingCategory::MalformedFrontmatter, path, e.to_string());
+        return Ok(None);
+    }
+    Ok(Some(parse_frontmatter(&content).0))
+}
+
+fn report_duplicates(names: BTreeMap<String, Vec<PathBuf>>, kind: &str, report: &mut AuditReport) {
+    for (name, paths) in names.into_iter().filter(|(_, paths)| paths.len() > 1) {
+        for path in &paths {
+            report.push(
+                FindingCategory::DuplicateName,
+                path,
+                format!("{} name '{}' is used by {} files", kind, name, paths.len()),
+            );
+        }
+    }
+}
+
+fn audit_agents(agents_dir: &Path, known_tools: &[&str], report: &mut AuditReport) -> Result<()> {
+    let mut names: BTreeMap<String, Vec<PathBuf>> = BTreeMap::new();
+
+    for path in sorted_entries(agents_dir)? {
+        if path.extension().is_none_or(|e| e != "md") {
+            continue;
+        }
+        let Some(frontmatter) = read_frontmatter(&path, report)? else {
+            continue;
+        };
+
+        let stem = path
+            .file_stem()
+            .map(|s| s.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let name = match frontmatter.get("name") {
+            Some(name) => {
+                // Names that aren't valid file names are stored encoded
+                if *name != stem && safe_filename(name) != stem {
+                    report.push(
+                        FindingCategory::NameMismatch,
+                        &path,
+                        format!("Agent name '{}' does not match file name '{}'", name, stem),
+                    );
+                }
+                name.clone()
+            }
+            None => {
+                report.push(
+                    FindingCategory::MalformedFrontmatter,
+                    &path,
+                    "Missing required field 'name'".to_string(),
+                );
+                stem
+            }
+        };
+        names.entry(name).or_default().push(path.clone());
+
+        let tools = frontmatter
+            .get("tools")
+            .map(|t| parse_agent_tools(t.as_str()))
+            .unwrap_or_default();
+        for tool in tools {
+            let base = tool.split('(').next().unwrap_or_default().trim();
+            if !base.starts_with("mcp__") && !known_tools.contains(&base) {
+                report.push(
+                    FindingCategory::UnknownTool,
+                    &path,
+                    format!("Unknown tool '{}'", tool),
+                );
+            }
+        }
+    }
+
+    report_duplicates(names, "Agent", report);
+    Ok(())
+}
+
+fn audit_skills(skills_dir: &Path, report: &mut AuditReport) -> Result<()> {
+    let mut names: BTreeMap<String, Vec<PathBuf>> = BTreeMap::new();
+
+    for dir in sorted_entries(skills_dir)? {
+        let skill_file = dir.join("SKILL.md");
+        if !dir.is_dir() || !skill_file.is_file() {
+            continue;
+        }
+        let Some(frontmatter) = read_frontmatter(&skill_file, report)? else {
+            continue;
+        };
+
+        let dir_name = dir
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let name = frontmatter
+            .get("name")
+            .cloned()
+            .unwrap_or_else(|| dir_name.clone());
+        if name != dir_name {
+            report.push(
+                FindingCategory::NameMismatch,
+                &skill_file,
+                format!(
+                    "Skill name '{}' does not match directory '{}'",
+                    name, dir_name
+                ),
+            );
+        }
+        names.entry(name).or_default().push(skill_file);
+    }
+
+    report_duplicates(names, "Skill", report);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    const KNOWN_TOOLS: &[&str] = &["Read", "Grep", "Glob", "Bash", "Edit", "Write"];
+
+    fn write(base: &Path, relative: &str, content: &str) -> PathBuf {
+        let path = base.join(relative);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, content).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_audit_reports_each_issue() {
+        let temp_dir = TempDir::new().unwrap();
+        let base = temp_dir.path();
+        write(
+            base,
+            "agents/reviewer.md",
+            "---\nname: reviewer\ndescription: Reviews\ntools: Read, Bash(git:*), mcp__github__search\n---\n\nReview.",
+        );
+        let broken = write(
+            base,
+            "agents/broken.md",
+            "---\nname: broken\n\nNo closing delimiter.",
+        );
+        let unknown = write(
+            base,
+            "agents/searcher.md",
+            "---\nname: searcher\ndescription: Searches\ntools: Grep, Telepathy\n---\n\nSearch.",
+        );
+        let copy = write(
+            base,
+            "agents/searcher-copy.md",
+            "---\nname: searcher\ndescription: Searches again\n---\n\nSearch.",
+        );
+        // Stored under its encoded file name, which is not a mismatch
+        write(
+            base,
+            "agents/team%2Flead.md",
+            "---\nname: team/lead\ndescription: Leads\n---\n\nLead.",
+        );
+        write(
+            base,
+            "skills/lint/SKILL.md",
+            "---\nname: lint\ndescription: Lint\n---\n\nLint.",
+        );
+        let renamed = write(
+            base,
+            "skills/fmt/SKILL.md",
+            "---\nname: format\ndescription: Format\n---\n\nFormat.",
+        );
+
+        let report = audit_claude_config(base, KNOWN_TOOLS).unwrap();
+
+        let malformed = report.by_category(FindingCategory::MalformedFrontmatter);
+        assert_eq!(malformed.len(), 1);
+        assert_eq!(malformed[0].path, broken);
+
+        let mismatches = report.by_category(FindingCategory::NameMismatch);
+        let mismatch_paths: Vec<&PathBuf> = mismatches.iter().map(|f| &f.path).collect();
+        assert_eq!(mismatch_paths, vec![&copy, &renamed]);
+
+        let unknown_tools = report.by_category(FindingCategory::UnknownTool);
+        assert_eq!(unknown_tools.len(), 1);
+        assert_eq!(unknown_tools[0].path, unknown);
+        assert_eq!(unknown_tools[0].message, "Unknown tool 'Telepathy'");
+
+        let duplicates = report.by_category(FindingCategory::DuplicateName);
+        let duplicate_paths: Vec<&PathBuf> = duplicates.iter().map(|f| &f.path).collect();
+        assert_eq!(duplicate_paths, vec![&copy, &unknown]);
+        assert!(duplicates[0].message.contains("'searcher'"));
+
+        assert_eq!(report.findings.len(), 6);
+    }
+
+    #[test]
+    fn test_audit_continues_past_unreadable_files() {
+        let temp_dir = TempDir::new().unwrap();
+        let base = temp_dir.path();
+        let binary = base.join("agents/binary.md");
+        fs::create_dir_all(binary.parent().unwrap()).unwrap();
+        fs::write(&binary, [0xff, 0xfe, 0x00, 0x80]).unwrap();
+        let unknown = write(
+            base,
+            "agents/searcher.md",
+            "---\nname: searcher\ndescription: Searches\ntools: Telepathy\n---\n\nSearch.",
+        );
+
+        let report = audit_claude_config(base, KNOWN_TOOLS).unwrap();
+
+        let unreadable = report.by_category(FindingCategory::Unreadable);
+        assert_eq!(unreadable.len(), 1);
+        assert_eq!(unreadable[0].path, binary);
+        assert_eq!(
+            report.by_category(FindingCategory::UnknownTool)[0].path,
+            unknown
+        );
+    }
+
+    #[test]
+    fn test_audit_clean_and_missing_directories() {
+        let temp_dir = TempDir::new().unwrap();
+        assert!(audit_claude_config(temp_dir.path(), KNOWN_TOOLS)
+            .unwrap()
+            .is_clean());
+    }
+}
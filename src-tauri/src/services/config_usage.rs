@@ -0,0 +1,203 @@
+//! Config disk usage
+//!
+//! Counts and sizes the agents, skills and trashed agents under a base path
+//! (the home directory or a project) for the storage panel.
+
+use crate::services::scanner::list_skills;
+use crate::services::subagent_writer::trash_dir;
+use crate::utils::paths::ClaudeLayout;
+use anyhow::Result;
+use serde::Serialize;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+/// Number of items and bytes on disk for one category
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UsageTotals {
+    pub count: usize,
+    pub bytes: u64,
+}
+
+/// Disk usage of a Claude config, by category
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UsageReport {
+    /// Agent files (`agents/*.md`), with their sidecars in `bytes`
+    pub agents: UsageTotals,
+    /// Skills, with their references, assets, scripts and the shared assets in `bytes`
+    pub skills: UsageTotals,
+    /// Trashed agents (`agents/.trash/`)
+    pub trash: UsageTotals,
+}
+
+impl UsageReport {
+    #[cfg(test)]
+    pub fn total_bytes(&self) -> u64 {
+        self.agents.bytes + self.skills.bytes + self.trash.bytes
+    }
+}
+
+/// Compute disk usage for the agents and skills under
+/// {base_path}/.claude/. Symlinks are followed and a target reachable through
+/// several links is counted once; symlink loops are skipped. Skills are
+/// counted the way the scanner finds them: nested skills only when
+/// `recursive_skills` (see scanner::recursive_skill_discovery) is set.
+pub fn config_usage(base_path: &Path, recursive_skills: bool) -> Result<UsageReport> {
+    let layout = ClaudeLayout::default();
+    let agents_dir = layout.agents_dir(base_path);
+    let skills_dir = layout.skills_dir(base_path);
+    let trash = trash_dir(base_path);
+
+    // Shared so a file linked from two categories is only counted once
+    let mut seen = HashSet::new();
+
+    let agents = UsageTotals {
+        count: count_markdown_files(&agents_dir)?,
+        bytes: directory_bytes(&agents_dir, Some(&trash), &mut seen),
+    };
+    let skills = UsageTotals {
+        count: if skills_dir.is_dir() {
+            list_skills(&skills_dir, recursive_skills)?.len()
+        } else {
+            0
+        },
+        bytes: directory_bytes(&skills_dir, None, &mut seen),
+    };
+    let trash = UsageTotals {
+        count: count_markdown_files(&trash)?,
+        bytes: directory_bytes(&trash, None, &mut seen),
+    };
+
+    Ok(UsageReport {
+        agents,
+        skills,
+        trash,
+    })
+}
+
+/// Number of `.md` files directly in `dir` (0 if it doesn't exist)
+fn count_markdown_files(dir: &Path) -> Result<usize> {
+    if !dir.is_dir() {
+        return Ok(0);
+    }
+    let mut count = 0;
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_file() && path.extension().is_some_and(|e| e == "md") {
+            count += 1;
+        }
+    }
+    Ok(count)
+}
+
+/// Total size of the files under `dir`, skipping `exclude` and any file
+/// already in `seen` (by canonical path)
+fn directory_bytes(dir: &Path, exclude: Option<&Path>, seen: &mut HashSet<PathBuf>) -> u64 {
+    if !dir.is_dir() {
+        return 0;
+    }
+
+    let mut bytes = 0;
+    let walker = WalkDir::new(dir)
+        .follow_links(true)
+        .into_iter()
+        .filter_entry(|e| exclude.is_none_or(|excluded| e.path() != excluded));
+    for entry in walker {
+        // Loops and broken links surface as errors; they have no size to count
+        let entry = match entry {
+            Ok(entry) => entry,
+            Err(e) => {
+                log::warn!("[ConfigUsage] Skipping {}", e);
+                continue;
+            }
+        };
+        if !entry.file_type().is_file() {
+            continue;
+        }
+        let Ok(canonical) = entry.path().canonicalize() else {
+            continue;
+        };
+        if !seen.insert(canonical) {
+            continue;
+        }
+        bytes += entry.metadata().map(|m| m.len()).unwrap_or(0);
+    }
+    bytes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn write(base: &Path, relative: &str, content: &str) -> PathBuf {
+        let path = base.join(relative);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, content).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_config_usage_counts_seeded_directory() {
+        let temp_dir = TempDir::new().unwrap();
+        let base = temp_dir.path();
+        write(base, ".claude/agents/reviewer.md", "0123456789"); // 10
+        write(base, ".claude/agents/reviewer.meta.json", "{}"); // 2
+        write(base, ".claude/agents/planner.md", "01234"); // 5
+        write(
+            base,
+            ".claude/agents/.trash/20260101000000000-old.md",
+            "0123",
+        ); // 4
+        write(
+            base,
+            ".claude/skills/lint/SKILL.md",
+            "---\nname: lint\n---\n",
+        ); // 19
+        let asset = write(base, ".claude/skills/lint/scripts/run.sh", "echo lint"); // 9
+        write(base, ".claude/skills/_shared/common.sh", "echo hi"); // 7
+
+        #[cfg(unix)]
+        {
+            // A second link to the same asset and a directory loop add nothing
+            std::os::unix::fs::symlink(&asset, base.join(".claude/skills/lint/run-link.sh"))
+                .unwrap();
+            std::os::unix::fs::symlink(
+                base.join(".claude/skills/lint"),
+                base.join(".claude/skills/lint/scripts/loop"),
+            )
+            .unwrap();
+        }
+
+        let report = config_usage(base, false).unwrap();
+
+        assert_eq!(
+            report.agents,
+            UsageTotals {
+                count: 2,
+                bytes: 17
+            }
+        );
+        assert_eq!(
+            report.skills,
+            UsageTotals {
+                count: 1,
+                bytes: 35
+            }
+        );
+        assert_eq!(report.trash, UsageTotals { count: 1, bytes: 4 });
+        assert_eq!(report.total_bytes(), 56);
+    }
+
+    #[test]
+    fn test_config_usage_empty_base() {
+        let temp_dir = TempDir::new().unwrap();
+        assert_eq!(
+            config_usage(temp_dir.path(), false).unwrap(),
+            UsageReport::default()
+        );
+    }
+}
@@ -0,0 +1,285 @@
+//! Markdown Frontmatter
+//!
+//! Shared splitting and parsing of the `---` delimited YAML block at the top
+//! of agent and skill files, so both writers agree on BOM, CRLF and
+//! delimiter handling.
+
+use anyhow::{anyhow, Result};
+use std::collections::HashMap;
+
+/// The frontmatter text between the `---` delimiters (LF line endings)
+pub type RawYaml = String;
+
+/// The markdown after the frontmatter block
+pub type Body = String;
+
+/// Top-level `key: value` pairs of a frontmatter block
+pub type FrontmatterMap = HashMap<String, String>;
+
+const DELIMITER: &str = "---";
+
+/// Split markdown into its frontmatter block and body.
+///
+/// A leading byte order mark is ignored and CRLF line endings are normalized
+/// to LF. Content that doesn't open with a `---` line has no frontmatter and
+/// is returned whole as the body; an opened block that is never closed is an
+/// error. Leading blank lines of the body are dropped.
+pub fn split_frontmatter(content: &str) -> Result<(RawYaml, Body)> {
+    let content = content.strip_prefix('\u{feff}').unwrap_or(content);
+    let content = content.replace("\r\n", "\n");
+
+    let Some(rest) = content
+        .strip_prefix(DELIMITER)
+        .and_then(|rest| rest.trim_start_matches([' ', '\t']).strip_prefix('\n'))
+    else {
+        return Ok((RawYaml::new(), content));
+    };
+
+    let mut offset = 0;
+    for line in rest.split_inclusive('\n') {
+        if line.trim_end() == DELIMITER {
+            let raw = rest[..offset].trim_end_matches('\n').to_string();
+            let body = rest[offset + line.len()..].trim_start().to_string();
+            return Ok((raw, body));
+        }
+        offset += line.len();
+    }
+    Err(anyhow!(
+        "Frontmatter block opened on line 1 is never closed"
+    ))
+}
+
+/// Parse the top-level `key: value` pairs of a frontmatter block.
+///
+/// Indented lines (nested maps, sequence items, block scalars) and comments
+/// are skipped, as are keys with an empty value. A top-level line that is not
+/// a `key: value` pair is an error.
+pub fn parse_yaml_map(raw: &str) -> Result<FrontmatterMap> {
+    let (map, mut problems) = parse_yaml_map_lenient(raw);
+    if problems.is_empty() {
+        Ok(map)
+    } else {
+        Err(anyhow!(problems.remove(0)))
+    }
+}
+
+/// Like parse_yaml_map, but top-level lines that aren't `key: value` pairs are
+/// skipped and described in the returned list instead of failing the parse
+pub fn parse_yaml_map_lenient(raw: &str) -> (FrontmatterMap, Vec<String>) {
+    let mut map = FrontmatterMap::new();
+    let mut problems = Vec::new();
+    for (index, line) in raw.lines().enumerate() {
+        if line.trim().is_empty()
+            || line.starts_with([' ', '\t'])
+            || line.starts_with('#')
+            || line.starts_with("- ")
+        {
+            continue;
+        }
+        let Some((key, value)) = line.split_once(':') else {
+            problems.push(format!(
+                "Frontmatter line {} is not a `key: value` pair: {}",
+                index + 2,
+                line
+            ));
+            continue;
+        };
+        let (key, value) = (key.trim(), value.trim());
+        if !key.is_empty() && !value.is_empty() {
+            map.insert(key.to_string(), value.to_string());
+        }
+    }
+    (map, problems)
+}
+
+/// Items of a block sequence in a frontmatter block (`key:` then `  - item` lines)
+pub fn parse_yaml_sequence(raw: &str, key: &str) -> Vec<String> {
+    let header = format!("{}:", key);
+    let mut items = Vec::new();
+    let mut in_sequence = false;
+    for line in raw.lines() {
+        if !line.starts_with([' ', '\t']) {
+            in_sequence = line.trim_end() == header;
+            continue;
+        }
+        if in_sequence {
+            if let Some(item) = line.trim().strip_prefix("- ") {
+                items.push(unquote_yaml_string(item.trim()));
+            }
+        }
+    }
+    items
+}
+
+/// Text of a top-level value written either inline (`key: text`) or as a
+/// literal block scalar (`key: |` followed by indented lines). Block lines
+/// keep their line breaks; the common indentation is removed.
+pub fn parse_yaml_block_scalar(raw: &str, key: &str) -> Option<String> {
+    let prefix = format!("{}:", key);
+    let mut lines = raw.lines();
+    let value = lines
+        .by_ref()
+        .find_map(|line| line.strip_prefix(prefix.as_str()))?
+        .trim();
+
+    if !value.starts_with('|') && !value.starts_with('>') {
+        return Some(unquote_yaml_string(value)).filter(|v| !v.is_empty());
+    }
+
+    let block: Vec<&str> = lines
+        .take_while(|line| line.is_empty() || line.starts_with([' ', '\t']))
+        .collect();
+    let indent = block
+        .iter()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| line.len() - line.trim_start().len())
+        .min()
+        .unwrap_or_default();
+    let text = block
+        .iter()
+        .map(|line| line.get(indent..).unwrap_or_default())
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    Some(text.trim_end().to_string()).filter(|t| !t.is_empty())
+}
+
+/// A double-quoted YAML scalar holding `value`, safe to write as a value or
+/// sequence item whatever characters it contains
+pub fn quote_yaml_string(value: &str) -> String {
+    let mut quoted = String::from("\"");
+    for c in value.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\t' => quoted.push_str("\\t"),
+            '\r' => quoted.push_str("\\r"),
+            _ => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+/// The text of a scalar, undoing single or double quoting. Plain scalars are
+/// returned as is.
+fn unquote_yaml_string(value: &str) -> String {
+    if let Some(inner) = value.strip_prefix('\'').and_then(|v| v.strip_suffix('\'')) {
+        return inner.replace("''", "'");
+    }
+    let Some(inner) = value.strip_prefix('"').and_then(|v| v.strip_suffix('"')) else {
+        return value.to_string();
+    };
+
+    let mut text = String::new();
+    let mut chars = inner.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            text.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => text.push('\n'),
+            Some('t') => text.push('\t'),
+            Some('r') => text.push('\r'),
+            Some(other) => text.push(other),
+            None => text.push('\\'),
+        }
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_frontmatter_well_formed() {
+        let (raw, body) =
+            split_frontmatter("---\nname: reviewer\ndescription: Reviews\n---\n\nBody text.\n")
+                .unwrap();
+        assert_eq!(raw, "name: reviewer\ndescription: Reviews");
+        assert_eq!(body, "Body text.\n");
+
+        let map = parse_yaml_map(&raw).unwrap();
+        assert_eq!(map.get("name").map(String::as_str), Some("reviewer"));
+        assert_eq!(map.get("description").map(String::as_str), Some("Reviews"));
+    }
+
+    #[test]
+    fn test_split_frontmatter_strips_bom_and_crlf() {
+        let (raw, body) =
+            split_frontmatter("\u{feff}---\r\nname: a\r\n---\r\n\r\nLine one.\r\nLine two.")
+                .unwrap();
+        assert_eq!(raw, "name: a");
+        assert_eq!(body, "Line one.\nLine two.");
+    }
+
+    #[test]
+    fn test_split_frontmatter_missing_close() {
+        assert!(split_frontmatter("---\nname: broken\n\nNever closed.").is_err());
+    }
+
+    #[test]
+    fn test_split_frontmatter_without_frontmatter() {
+        let (raw, body) = split_frontmatter("Just a prompt.\n---\nnot: yaml").unwrap();
+        assert!(raw.is_empty());
+        assert_eq!(body, "Just a prompt.\n---\nnot: yaml");
+
+        // `----` is a horizontal rule, not a delimiter
+        let (raw, _) = split_frontmatter("----\nname: a\n---\n").unwrap();
+        assert!(raw.is_empty());
+    }
+
+    #[test]
+    fn test_parse_yaml_map_skips_nested_values() {
+        let raw =
+            "description: Reviewer\ntools:\n  read: true\n# comment\nenv:\n  - A=1\nmodel: sonnet";
+        let map = parse_yaml_map(raw).unwrap();
+        assert_eq!(map.len(), 2);
+        assert!(!map.contains_key("read"));
+        assert_eq!(parse_yaml_sequence(raw, "env"), ["A=1"]);
+        assert!(parse_yaml_map("name: a\nnot a pair").is_err());
+
+        let (map, problems) = parse_yaml_map_lenient("name: a\nnot a pair\nmodel: b");
+        assert_eq!(map.len(), 2);
+        assert_eq!(
+            problems,
+            ["Frontmatter line 3 is not a `key: value` pair: not a pair"]
+        );
+    }
+
+    #[test]
+    fn test_quoted_sequence_items_round_trip() {
+        let items = [
+            "key: value",
+            "# not a comment",
+            "say \"hi\" \\ bye",
+            "- dash",
+        ];
+        let mut raw = String::from("examples:\n");
+        for item in items {
+            raw.push_str(&format!("  - {}\n", quote_yaml_string(item)));
+        }
+        raw.push_str("plain:\n  - 'it''s'\n  - bare");
+
+        assert_eq!(parse_yaml_sequence(&raw, "examples"), items);
+        assert_eq!(parse_yaml_sequence(&raw, "plain"), ["it's", "bare"]);
+    }
+
+    #[test]
+    fn test_parse_yaml_block_scalar() {
+        let raw =
+            "when-to-use: |\n  First line.\n\n    Indented.\nmodel: sonnet\ninline: \"quoted\"";
+        assert_eq!(
+            parse_yaml_block_scalar(raw, "when-to-use").as_deref(),
+            Some("First line.\n\n  Indented.")
+        );
+        assert_eq!(
+            parse_yaml_block_scalar(raw, "inline").as_deref(),
+            Some("quoted")
+        );
+        assert_eq!(parse_yaml_block_scalar(raw, "missing"), None);
+    }
+}
@@ -0,0 +1,314 @@
+//! HTTP Connection Pool
+//!
+//! Keeps a bounded set of open HTTP connections per MCP server so calls reuse
+//! them instead of dialing the server each time. When every connection is
+//! busy, callers wait in a bounded queue; once the queue is full (or the wait
+//! runs out) the call fails rather than piling up.
+
+use anyhow::Result;
+use std::ops::Deref;
+use std::sync::{Condvar, Mutex};
+use std::time::{Duration, Instant};
+
+/// Default number of connections open to one backend at a time
+pub const DEFAULT_POOL_SIZE: usize = 4;
+
+/// Default time an unused connection stays open
+pub const DEFAULT_POOL_IDLE_TIMEOUT: Duration = Duration::from_secs(90);
+
+/// Default number of calls that may wait for a busy backend's connection
+pub const DEFAULT_POOL_MAX_QUEUED: usize = 16;
+
+/// Default time a queued call waits for a connection
+pub const DEFAULT_POOL_QUEUE_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Every connection is busy and the wait queue is full
+#[derive(Debug, thiserror::Error)]
+#[error("Connection pool exhausted: {open} connections busy and {queued} calls queued")]
+pub struct PoolExhausted {
+    pub open: usize,
+    pub queued: usize,
+}
+
+/// A queued call gave up waiting for a connection
+#[derive(Debug, thiserror::Error)]
+#[error("Timed out after {secs}s waiting for a pooled connection")]
+pub struct PoolWaitTimedOut {
+    pub secs: u64,
+}
+
+/// Size and timeouts of each backend's connection pool
+#[derive(Debug, Clone, PartialEq)]
+pub struct PoolConfig {
+    /// Connections open to one backend at a time (at least 1)
+    pub size: usize,
+    /// Close connections unused for this long
+    pub idle_timeout: Duration,
+    /// Calls allowed to wait when every connection is busy
+    pub max_queued: usize,
+    /// How long a queued call waits before giving up
+    pub queue_timeout: Duration,
+}
+
+impl Default for PoolConfig {
+    fn default() -> Self {
+        Self {
+            size: DEFAULT_POOL_SIZE,
+            idle_timeout: DEFAULT_POOL_IDLE_TIMEOUT,
+            max_queued: DEFAULT_POOL_MAX_QUEUED,
+            queue_timeout: DEFAULT_POOL_QUEUE_TIMEOUT,
+        }
+    }
+}
+
+struct PoolState<C> {
+    /// Connections not checked out, with when they were returned
+    idle: Vec<(C, Instant)>,
+    /// Connections open, idle or checked out
+    open: usize,
+    /// Calls waiting for a connection
+    queued: usize,
+}
+
+/// A bounded pool of connections made by `connect`
+pub struct ConnectionPool<C> {
+    config: PoolConfig,
+    connect: Box<dyn Fn() -> Result<C> + Send + Sync>,
+    state: Mutex<PoolState<C>>,
+    returned: Condvar,
+}
+
+impl<C> std::fmt::Debug for ConnectionPool<C> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ConnectionPool")
+            .field("config", &self.config)
+            .finish()
+    }
+}
+
+impl<C> ConnectionPool<C> {
+    pub fn new(
+        config: PoolConfig,
+        connect: impl Fn() -> Result<C> + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            config,
+            connect: Box::new(connect),
+            state: Mutex::new(PoolState {
+                idle: Vec::new(),
+                open: 0,
+                queued: 0,
+            }),
+            returned: Condvar::new(),
+        }
+    }
+
+    /// Take a connection, reusing the most recently returned one if any.
+    /// Opens a new one while below the pool size; otherwise waits in the
+    /// queue for one to be returned. Fails with PoolExhausted if the queue is
+    /// full and PoolWaitTimedOut if the wait runs out.
+    pub fn checkout(&self) -> Result<Pooled<'_, C>> {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        let deadline = Instant::now() + self.config.queue_timeout;
+        let mut queued = false;
+
+        loop {
+            let now = Instant::now();
+            let before = state.idle.len();
+            state.idle.retain(|(_, since)| {
+                now.saturating_duration_since(*since) < self.config.idle_timeout
+            });
+            state.open -= before - state.idle.len();
+
+            if let Some((conn, _)) = state.idle.pop() {
+                if queued {
+                    state.queued -= 1;
+                }
+                return Ok(self.pooled(conn));
+            }
+
+            if state.open < self.config.size.max(1) {
+                if queued {
+                    state.queued -= 1;
+                }
+                state.open += 1;
+                drop(state);
+                return match (self.connect)() {
+                    Ok(conn) => Ok(self.pooled(conn)),
+                    Err(e) => {
+                        self.release_slot();
+                        Err(e)
+                    }
+                };
+            }
+
+            if !queued {
+                if state.queued >= self.config.max_queued {
+                    return Err(PoolExhausted {
+                        open: state.open,
+                        queued: state.queued,
+                    }
+                    .into());
+                }
+                state.queued += 1;
+                queued = true;
+            }
+
+            let remaining = deadline.saturating_duration_since(now);
+            if remaining.is_zero() {
+                state.queued -= 1;
+                return Err(PoolWaitTimedOut {
+                    secs: self.config.queue_timeout.as_secs(),
+                }
+                .into());
+            }
+            state = self
+                .returned
+                .wait_timeout(state, remaining)
+                .unwrap_or_else(|e| e.into_inner())
+                .0;
+        }
+    }
+
+    /// Connections currently open, idle or checked out (for testing)
+    #[cfg(test)]
+    pub fn open_connections(&self) -> usize {
+        self.state.lock().unwrap_or_else(|e| e.into_inner()).open
+    }
+
+    fn pooled(&self, conn: C) -> Pooled<'_, C> {
+        Pooled {
+            pool: self,
+            conn: Some(conn),
+        }
+    }
+
+    /// Give back the slot of a connection that failed to open
+    fn release_slot(&self) {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        state.open -= 1;
+        self.returned.notify_one();
+    }
+
+    fn checkin(&self, conn: C) {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        state.idle.push((conn, Instant::now()));
+        self.returned.notify_one();
+    }
+}
+
+/// A checked-out connection, returned to its pool when dropped
+pub struct Pooled<'a, C> {
+    pool: &'a ConnectionPool<C>,
+    conn: Option<C>,
+}
+
+impl<C> Deref for Pooled<'_, C> {
+    type Target = C;
+
+    fn deref(&self) -> &C {
+        self.conn.as_ref().expect("pooled connection taken")
+    }
+}
+
+impl<C> Drop for Pooled<'_, C> {
+    fn drop(&mut self) {
+        if let Some(conn) = self.conn.take() {
+            self.pool.checkin(conn);
+        }
+    }
+}
+
+/// Pool of HTTP clients for one backend. Each client keeps at most one
+/// keep-alive connection, so the pool size bounds the open connections.
+pub type HttpClientPool = ConnectionPool<reqwest::blocking::Client>;
+
+/// Create an HTTP client pool with the given config
+pub fn http_client_pool(config: PoolConfig) -> HttpClientPool {
+    let idle_timeout = config.idle_timeout;
+    ConnectionPool::new(config, move || {
+        Ok(reqwest::blocking::Client::builder()
+            .pool_max_idle_per_host(1)
+            .pool_idle_timeout(idle_timeout)
+            .build()?)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    /// Pool of numbered connections that counts how many were opened
+    fn counting_pool(config: PoolConfig) -> (ConnectionPool<usize>, Arc<AtomicUsize>) {
+        let opened = Arc::new(AtomicUsize::new(0));
+        let counter = opened.clone();
+        let pool = ConnectionPool::new(config, move || Ok(counter.fetch_add(1, Ordering::SeqCst)));
+        (pool, opened)
+    }
+
+    #[test]
+    fn test_connections_reused_across_calls() {
+        let (pool, opened) = counting_pool(PoolConfig::default());
+
+        for _ in 0..5 {
+            let conn = pool.checkout().unwrap();
+            assert_eq!(*conn, 0);
+        }
+
+        assert_eq!(opened.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn test_idle_connections_are_replaced() {
+        let (pool, opened) = counting_pool(PoolConfig {
+            idle_timeout: Duration::ZERO,
+            ..PoolConfig::default()
+        });
+
+        drop(pool.checkout().unwrap());
+        assert_eq!(*pool.checkout().unwrap(), 1);
+        assert_eq!(opened.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn test_exhausted_pool_queues_with_bound() {
+        let (pool, _) = counting_pool(PoolConfig {
+            size: 1,
+            max_queued: 1,
+            queue_timeout: Duration::from_secs(5),
+            ..PoolConfig::default()
+        });
+        let pool = Arc::new(pool);
+
+        let busy = pool.checkout().unwrap();
+        let waiter = {
+            let pool = pool.clone();
+            std::thread::spawn(move || *pool.checkout().unwrap())
+        };
+        while pool.state.lock().unwrap().queued == 0 {
+            std::thread::sleep(Duration::from_millis(5));
+        }
+
+        let err = pool.checkout().err().unwrap();
+        assert!(err.is::<PoolExhausted>());
+
+        drop(busy);
+        assert_eq!(waiter.join().unwrap(), 0);
+    }
+
+    #[test]
+    fn test_queued_call_times_out() {
+        let (pool, _) = counting_pool(PoolConfig {
+            size: 1,
+            queue_timeout: Duration::from_millis(20),
+            ..PoolConfig::default()
+        });
+
+        let _busy = pool.checkout().unwrap();
+        let err = pool.checkout().err().unwrap();
+
+        assert!(err.is::<PoolWaitTimedOut>());
+    }
+}
@@ -0,0 +1,218 @@
+//! JSON Schema Validation
+//!
+//! Checks a JSON value against a schema so mistakes come back as a clear path
+//! and reason. Used by the gateway for tool arguments (against the tool's
+//! cached input schema) and for agent data before it is deserialized. Covers
+//! the JSON Schema subset MCP servers use in practice: `type`, `required`,
+//! `properties`, `items` and `enum`, plus `minLength` when asked for (see
+//! SchemaChecks). Other keywords are ignored.
+
+use serde_json::Value;
+
+/// Keywords checked in addition to the always-on subset
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SchemaChecks {
+    /// Enforce `minLength` on strings
+    pub min_length: bool,
+}
+
+/// Validate `value` against `schema`, returning `<path>: <reason>` for the
+/// first violation. Paths start at `$` (e.g. `$.filters.limit`, `$.ids[2]`).
+pub fn validate_json(schema: &Value, value: &Value, checks: SchemaChecks) -> Result<(), String> {
+    validate_at("$", schema, value, checks)
+}
+
+fn validate_at(
+    path: &str,
+    schema: &Value,
+    value: &Value,
+    checks: SchemaChecks,
+) -> Result<(), String> {
+    let Some(schema) = schema.as_object() else {
+        return Ok(());
+    };
+
+    if let Some(expected) = schema.get("type") {
+        let matches = match expected {
+            Value::String(t) => type_matches(t, value),
+            Value::Array(types) => types
+                .iter()
+                .filter_map(Value::as_str)
+                .any(|t| type_matches(t, value)),
+            _ => true,
+        };
+        if !matches {
+            return Err(format!(
+                "{}: expected {}, got {}",
+                path,
+                type_label(expected),
+                json_type(value)
+            ));
+        }
+    }
+
+    if let Some(allowed) = schema.get("enum").and_then(Value::as_array) {
+        if !allowed.contains(value) {
+            return Err(format!(
+                "{}: must be one of {}",
+                path,
+                Value::from(allowed.clone())
+            ));
+        }
+    }
+
+    if let (true, Value::String(text), Some(min)) = (
+        checks.min_length,
+        value,
+        schema.get("minLength").and_then(Value::as_u64),
+    ) {
+        if (text.chars().count() as u64) < min {
+            return Err(format!(
+                "{}: must be at least {} character{} long",
+                path,
+                min,
+                if min == 1 { "" } else { "s" }
+            ));
+        }
+    }
+
+    if let Value::Object(object) = value {
+        for required in schema
+            .get("required")
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+            .filter_map(Value::as_str)
+        {
+            if !object.contains_key(required) {
+                return Err(format!(
+                    "{}.{}: required property is missing",
+                    path, required
+                ));
+            }
+        }
+
+        if let Some(properties) = schema.get("properties").and_then(Value::as_object) {
+            for (key, property_schema) in properties {
+                if let Some(property) = object.get(key) {
+                    validate_at(
+                        &format!("{}.{}", path, key),
+                        property_schema,
+                        property,
+                        checks,
+                    )?;
+                }
+            }
+        }
+    }
+
+    if let (Value::Array(items), Some(item_schema)) = (value, schema.get("items")) {
+        for (index, item) in items.iter().enumerate() {
+            validate_at(&format!("{}[{}]", path, index), item_schema, item, checks)?;
+        }
+    }
+
+    Ok(())
+}
+
+fn type_matches(expected: &str, value: &Value) -> bool {
+    match expected {
+        "object" => value.is_object(),
+        "array" => value.is_array(),
+        "string" => value.is_string(),
+        "boolean" => value.is_boolean(),
+        "null" => value.is_null(),
+        "number" => value.is_number(),
+        "integer" => value.is_i64() || value.is_u64(),
+        _ => true,
+    }
+}
+
+fn type_label(expected: &Value) -> String {
+    match expected {
+        Value::Array(types) => types
+            .iter()
+            .filter_map(Value::as_str)
+            .collect::<Vec<_>>()
+            .join(" or "),
+        other => other.as_str().unwrap_or_default().to_string(),
+    }
+}
+
+fn json_type(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn validate_arguments(schema: &Value, arguments: &Value) -> Result<(), String> {
+        validate_json(schema, arguments, SchemaChecks::default())
+    }
+
+    fn search_schema() -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "query": { "type": "string" },
+                "limit": { "type": "integer" },
+                "filters": {
+                    "type": "object",
+                    "properties": { "sort": { "enum": ["asc", "desc"] } }
+                },
+                "ids": { "type": "array", "items": { "type": "integer" } }
+            },
+            "required": ["query"]
+        })
+    }
+
+    #[test]
+    fn test_valid_arguments_pass() {
+        let args = json!({"query": "rust", "limit": 5, "ids": [1, 2], "extra": true});
+        assert!(validate_arguments(&search_schema(), &args).is_ok());
+    }
+
+    #[test]
+    fn test_violations_report_path_and_reason() {
+        let schema = search_schema();
+
+        assert_eq!(
+            validate_arguments(&schema, &json!({"limit": 5})).unwrap_err(),
+            "$.query: required property is missing"
+        );
+        assert_eq!(
+            validate_arguments(&schema, &json!({"query": "a", "limit": "5"})).unwrap_err(),
+            "$.limit: expected integer, got string"
+        );
+        assert_eq!(
+            validate_arguments(&schema, &json!({"query": "a", "ids": [1, "x"]})).unwrap_err(),
+            "$.ids[1]: expected integer, got string"
+        );
+        assert!(
+            validate_arguments(&schema, &json!({"query": "a", "filters": {"sort": "up"}}))
+                .unwrap_err()
+                .starts_with("$.filters.sort: must be one of")
+        );
+    }
+
+    #[test]
+    fn test_min_length_only_when_asked_for() {
+        let schema = json!({"type": "string", "minLength": 2});
+        let checks = SchemaChecks { min_length: true };
+        assert!(validate_json(&schema, &json!("ab"), checks).is_ok());
+        assert_eq!(
+            validate_json(&schema, &json!("é"), checks).unwrap_err(),
+            "$: must be at least 2 characters long"
+        );
+        assert!(validate_arguments(&schema, &json!("é")).is_ok());
+    }
+}
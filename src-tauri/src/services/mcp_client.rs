@@ -3,6 +3,7 @@
 //! Implements the MCP protocol to connect to servers, perform handshake,
 //! and retrieve available tools.
 
+use crate::services::http_pool::HttpClientPool;
 use anyhow::{anyhow, Result};
 use futures::StreamExt;
 use log::{error, info};
@@ -10,8 +11,10 @@ use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
 use std::collections::HashMap;
 use std::io::{BufRead, BufReader, Write};
-use std::process::{Child, Command, Stdio};
-use std::sync::atomic::{AtomicU64, Ordering};
+use std::path::PathBuf;
+use std::process::{Child, ChildStdin, Command, Stdio};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
 use tokio::sync::mpsc;
 use tokio::time::timeout;
@@ -23,6 +26,151 @@ fn next_request_id() -> u64 {
     REQUEST_ID.fetch_add(1, Ordering::SeqCst)
 }
 
+/// A request got no response within the client's timeout
+#[derive(Debug, thiserror::Error)]
+#[error("Timeout waiting for response ({secs}s)")]
+pub struct RequestTimedOut {
+    pub secs: u64,
+}
+
+/// An HTTP server answered with a non-success status (other than 401)
+#[derive(Debug, thiserror::Error)]
+#[error("HTTP error {status}: {body}")]
+pub struct HttpStatusError {
+    pub status: u16,
+    pub body: String,
+}
+
+/// The server answered a request with a JSON-RPC error
+#[derive(Debug, thiserror::Error)]
+#[error("MCP error: {message}")]
+pub struct McpErrorResponse {
+    pub code: i64,
+    pub message: String,
+}
+
+// ============================================================================
+// Cancellation
+// ============================================================================
+
+/// Error returned by a request that was cancelled through a CancellationToken
+#[derive(Debug, thiserror::Error)]
+#[error("Request was cancelled")]
+pub struct RequestCancelled;
+
+type CancelHook = Box<dyn FnOnce() + Send>;
+
+#[derive(Default)]
+struct CancelState {
+    cancelled: AtomicBool,
+    notify: tokio::sync::Notify,
+    /// Run once on cancellation, e.g. to tell the server to stop working
+    hook: Mutex<Option<CancelHook>>,
+}
+
+/// Cancels an in-flight request. Clones share state: cancelling one cancels all.
+#[derive(Clone, Default)]
+pub struct CancellationToken {
+    state: Arc<CancelState>,
+}
+
+impl std::fmt::Debug for CancellationToken {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CancellationToken")
+            .field("cancelled", &self.is_cancelled())
+            .finish()
+    }
+}
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Cancel the request, running its hook if one is registered
+    pub fn cancel(&self) {
+        if self.state.cancelled.swap(true, Ordering::SeqCst) {
+            return;
+        }
+        let hook = self.state.hook.lock().ok().and_then(|mut h| h.take());
+        if let Some(hook) = hook {
+            hook();
+        }
+        self.state.notify.notify_waiters();
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.state.cancelled.load(Ordering::SeqCst)
+    }
+
+    /// Resolve once the token is cancelled
+    pub async fn cancelled(&self) {
+        loop {
+            let notified = self.state.notify.notified();
+            if self.is_cancelled() {
+                return;
+            }
+            notified.await;
+        }
+    }
+
+    /// Register the hook run on cancellation (replacing any previous one).
+    /// Runs it right away if the token is already cancelled.
+    pub fn on_cancel(&self, hook: impl FnOnce() + Send + 'static) {
+        let Ok(mut slot) = self.state.hook.lock() else {
+            return;
+        };
+        if self.is_cancelled() {
+            drop(slot);
+            hook();
+        } else {
+            *slot = Some(Box::new(hook));
+        }
+    }
+
+    /// Drop the registered hook once its request has finished
+    pub fn clear_on_cancel(&self) {
+        if let Ok(mut slot) = self.state.hook.lock() {
+            slot.take();
+        }
+    }
+}
+
+// ============================================================================
+// Logging
+// ============================================================================
+
+/// Method a client uses to set the minimum level of a server's log messages
+const LOGGING_SET_LEVEL_METHOD: &str = "logging/setLevel";
+
+// ============================================================================
+// Sampling
+// ============================================================================
+
+/// Method a server uses to ask the client for a model completion
+pub const SAMPLING_METHOD: &str = "sampling/createMessage";
+
+/// Runs a model completion for a server's sampling request: receives the
+/// request params and returns the `CreateMessageResult`
+#[derive(Clone)]
+pub struct SamplingHandler(Arc<dyn Fn(&Value) -> Result<Value> + Send + Sync>);
+
+impl std::fmt::Debug for SamplingHandler {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("SamplingHandler")
+    }
+}
+
+impl SamplingHandler {
+    pub fn new(handler: impl Fn(&Value) -> Result<Value> + Send + Sync + 'static) -> Self {
+        Self(Arc::new(handler))
+    }
+
+    pub fn handle(&self, params: &Value) -> Result<Value> {
+        (self.0)(params)
+    }
+}
+
 // ============================================================================
 // MCP Protocol Types
 // ============================================================================
@@ -37,6 +185,37 @@ pub struct McpTool {
     pub input_schema: Option<Value>,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct McpResource {
+    pub uri: String,
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub mime_type: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct McpPromptArgument {
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub required: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct McpPrompt {
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub arguments: Vec<McpPromptArgument>,
+}
+
 // ============================================================================
 // Tool Execution Types
 // ============================================================================
@@ -48,15 +227,64 @@ pub enum ToolContent {
     #[serde(rename = "text")]
     Text { text: String },
     #[serde(rename = "image")]
-    Image { data: String, mime_type: String },
+    Image {
+        data: String,
+        #[serde(rename = "mimeType")]
+        mime_type: String,
+    },
+    /// An embedded resource. Servers nest it under `resource`; it is
+    /// flattened when a tool result is parsed.
     #[serde(rename = "resource")]
     Resource {
         uri: String,
-        #[serde(default)]
+        #[serde(default, rename = "mimeType")]
         mime_type: Option<String>,
         #[serde(default)]
         text: Option<String>,
     },
+    /// A link to a resource the client can read separately
+    #[serde(rename = "resource_link")]
+    ResourceLink {
+        uri: String,
+        #[serde(default)]
+        name: Option<String>,
+        #[serde(default)]
+        description: Option<String>,
+        #[serde(default, rename = "mimeType")]
+        mime_type: Option<String>,
+    },
+}
+
+impl ToolContent {
+    /// The text of a text part (None for images and resources)
+    #[cfg(test)]
+    pub fn as_text(&self) -> Option<&str> {
+        match self {
+            ToolContent::Text { text } => Some(text),
+            _ => None,
+        }
+    }
+
+    /// Parse one item of a tool result's `content` array, flattening an
+    /// embedded resource. None for content types the gateway doesn't know.
+    fn from_json(item: &Value) -> Option<Self> {
+        let mut item = item.clone();
+        if let Some(object) = item.as_object_mut() {
+            if let Some(Value::Object(resource)) = object.remove("resource") {
+                object.extend(resource);
+            }
+        }
+        match serde_json::from_value(item) {
+            Ok(content) => Some(content),
+            Err(e) => {
+                log::warn!(
+                    "[MCP Client] Skipping unsupported tool result content: {}",
+                    e
+                );
+                None
+            }
+        }
+    }
 }
 
 /// Result of executing a tool via MCP
@@ -72,6 +300,19 @@ pub struct ToolCallResult {
     pub execution_time_ms: u64,
 }
 
+impl ToolCallResult {
+    /// The text parts of the result joined by newlines, for callers that
+    /// can only show text. Images and resources are left out.
+    #[cfg(test)]
+    pub fn text(&self) -> String {
+        self.content
+            .iter()
+            .filter_map(ToolContent::as_text)
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct McpServerInfo {
@@ -80,6 +321,44 @@ pub struct McpServerInfo {
     pub version: Option<String>,
 }
 
+/// Protocol version and capability flags a server declares in its initialize result
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct McpServerCapabilities {
+    #[serde(default)]
+    pub protocol_version: Option<String>,
+    #[serde(default)]
+    pub tools: bool,
+    #[serde(default)]
+    pub resources: bool,
+    #[serde(default)]
+    pub prompts: bool,
+    #[serde(default)]
+    pub logging: bool,
+}
+
+impl McpServerCapabilities {
+    /// Read the capabilities from an initialize result
+    pub fn from_initialize_result(result: &Value) -> Self {
+        let declares = |key: &str| {
+            result
+                .get("capabilities")
+                .and_then(|c| c.get(key))
+                .is_some()
+        };
+        Self {
+            protocol_version: result
+                .get("protocolVersion")
+                .and_then(|v| v.as_str())
+                .map(|s| s.to_string()),
+            tools: declares("tools"),
+            resources: declares("resources"),
+            prompts: declares("prompts"),
+            logging: declares("logging"),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct McpTestResult {
@@ -158,13 +437,21 @@ struct JsonRpcResponse {
 
 #[derive(Debug, Deserialize)]
 struct JsonRpcError {
-    #[allow(dead_code)]
     code: i64,
     message: String,
     #[allow(dead_code)]
     data: Option<Value>,
 }
 
+impl From<JsonRpcError> for McpErrorResponse {
+    fn from(error: JsonRpcError) -> Self {
+        Self {
+            code: error.code,
+            message: error.message,
+        }
+    }
+}
+
 // ============================================================================
 // STDIO MCP Client
 // ============================================================================
@@ -172,8 +459,17 @@ struct JsonRpcError {
 /// Client for communicating with stdio-based MCP servers
 pub struct StdioMcpClient {
     child: Child,
+    /// Shared so a cancellation can be written while a call waits for its response
+    stdin: Arc<Mutex<ChildStdin>>,
     timeout: Duration,
+    /// Workspace roots answered to the server's `roots/list` requests
+    roots: Vec<PathBuf>,
+    /// Answers the server's sampling requests (refused if None)
+    sampling: Option<SamplingHandler>,
+    /// Extra parameters merged into the initialize request
+    init_params: Option<Value>,
     server_info: Option<McpServerInfo>,
+    capabilities: McpServerCapabilities,
     tools: Vec<McpTool>,
     resources_supported: bool,
     prompts_supported: bool,
@@ -233,18 +529,27 @@ impl StdioMcpClient {
             cmd.creation_flags(0x08000000); // CREATE_NO_WINDOW
         }
 
-        let child = cmd.spawn().map_err(|e| {
+        let mut child = cmd.spawn().map_err(|e| {
             anyhow!(
                 "Failed to spawn MCP process '{}': {}. Make sure the command is installed and in PATH.",
                 command,
                 e
             )
         })?;
+        let stdin = child
+            .stdin
+            .take()
+            .ok_or_else(|| anyhow!("Failed to get stdin"))?;
 
         Ok(Self {
             child,
+            stdin: Arc::new(Mutex::new(stdin)),
             timeout: Duration::from_secs(timeout_secs),
+            roots: Vec::new(),
+            sampling: None,
+            init_params: None,
             server_info: None,
+            capabilities: McpServerCapabilities::default(),
             tools: vec![],
             resources_supported: false,
             prompts_supported: false,
@@ -257,8 +562,27 @@ impl StdioMcpClient {
         args: &[String],
         env: Option<&HashMap<String, String>>,
         timeout_secs: u64,
+    ) -> Result<Self> {
+        Self::spawn_with_init(command, args, env, timeout_secs, &[], None, None)
+    }
+
+    /// Spawn and initialize an MCP client that offers the server `roots`
+    /// through the roots capability (none are offered if empty), answers its
+    /// sampling requests with `sampling` (refused if None) and merges
+    /// `init_params` into the initialize request
+    pub fn spawn_with_init(
+        command: &str,
+        args: &[String],
+        env: Option<&HashMap<String, String>>,
+        timeout_secs: u64,
+        roots: &[PathBuf],
+        sampling: Option<&SamplingHandler>,
+        init_params: Option<&Value>,
     ) -> Result<Self> {
         let mut client = Self::spawn_process(command, args, env, timeout_secs)?;
+        client.roots = roots.to_vec();
+        client.sampling = sampling.cloned();
+        client.init_params = init_params.cloned();
         client.initialize()?;
         Ok(client)
     }
@@ -266,14 +590,24 @@ impl StdioMcpClient {
     /// Perform MCP protocol handshake
     fn initialize(&mut self) -> Result<()> {
         info!("[MCP Client] Sending initialize request...");
-        let init_params = json!({
+        let mut client_capabilities = json!({});
+        if !self.roots.is_empty() {
+            client_capabilities["roots"] = json!({ "listChanged": false });
+        }
+        if self.sampling.is_some() {
+            client_capabilities["sampling"] = json!({});
+        }
+        let mut init_params = json!({
             "protocolVersion": "2024-11-05",
-            "capabilities": {},
+            "capabilities": client_capabilities,
             "clientInfo": {
                 "name": "claude-code-tool-manager",
                 "version": env!("CARGO_PKG_VERSION")
             }
         });
+        if let Some(extra) = &self.init_params {
+            merge_init_params(&mut init_params, extra);
+        }
 
         let init_result = self.send_request("initialize", Some(init_params))?;
 
@@ -294,6 +628,7 @@ impl StdioMcpClient {
             None
         };
 
+        self.capabilities = McpServerCapabilities::from_initialize_result(&init_result);
         let capabilities = init_result.get("capabilities");
         self.resources_supported = capabilities.and_then(|c| c.get("resources")).is_some();
         self.prompts_supported = capabilities.and_then(|c| c.get("prompts")).is_some();
@@ -324,6 +659,11 @@ impl StdioMcpClient {
         self.server_info.as_ref()
     }
 
+    /// Get the capabilities declared in the initialize handshake
+    pub fn capabilities(&self) -> &McpServerCapabilities {
+        &self.capabilities
+    }
+
     /// Get available tools
     pub fn tools(&self) -> &[McpTool] {
         &self.tools
@@ -339,6 +679,41 @@ impl StdioMcpClient {
         self.prompts_supported
     }
 
+    /// Ask the server to only send log messages at `level` (an MCP logging
+    /// level such as "info" or "warning") or above
+    pub fn set_log_level(&mut self, level: &str) -> Result<()> {
+        self.send_request(LOGGING_SET_LEVEL_METHOD, Some(json!({ "level": level })))?;
+        Ok(())
+    }
+
+    /// List resources exposed by the server (empty if resources are unsupported)
+    pub fn list_resources(&mut self) -> Result<Vec<McpResource>> {
+        if !self.resources_supported {
+            return Ok(vec![]);
+        }
+
+        info!("[MCP Client] Requesting resources list...");
+        let result = self.send_request("resources/list", Some(json!({})))?;
+        Ok(result
+            .get("resources")
+            .and_then(|r| serde_json::from_value(r.clone()).ok())
+            .unwrap_or_default())
+    }
+
+    /// List prompts exposed by the server (empty if prompts are unsupported)
+    pub fn list_prompts(&mut self) -> Result<Vec<McpPrompt>> {
+        if !self.prompts_supported {
+            return Ok(vec![]);
+        }
+
+        info!("[MCP Client] Requesting prompts list...");
+        let result = self.send_request("prompts/list", Some(json!({})))?;
+        Ok(result
+            .get("prompts")
+            .and_then(|p| serde_json::from_value(p.clone()).ok())
+            .unwrap_or_default())
+    }
+
     /// Call a tool with the given arguments
     pub fn call_tool(&mut self, name: &str, arguments: Value) -> Result<ToolCallResult> {
         info!(
@@ -355,24 +730,80 @@ impl StdioMcpClient {
         let result = self.send_request("tools/call", Some(params));
         let elapsed = start.elapsed().as_millis() as u64;
 
-        match result {
+        Self::tool_call_outcome(result, elapsed)
+    }
+
+    /// Call a tool, giving up when `cancel` fires. On cancellation the server
+    /// is sent `notifications/cancelled` for the request right away; the call
+    /// returns RequestCancelled once the server writes its next message (or
+    /// the timeout elapses).
+    pub fn call_tool_cancellable(
+        &mut self,
+        name: &str,
+        arguments: Value,
+        cancel: &CancellationToken,
+    ) -> Result<ToolCallResult> {
+        if cancel.is_cancelled() {
+            return Err(RequestCancelled.into());
+        }
+
+        let params = json!({
+            "name": name,
+            "arguments": arguments
+        });
+
+        let start = Instant::now();
+        let id = self.write_request("tools/call", Some(params))?;
+        let stdin = Arc::clone(&self.stdin);
+        cancel.on_cancel(move || {
+            let notification = json!({
+                "jsonrpc": "2.0",
+                "method": "notifications/cancelled",
+                "params": { "requestId": id, "reason": "Cancelled by the client" }
+            });
+            info!("[MCP Client] Cancelling request {}", id);
+            if let Err(e) = write_stdin_line(&stdin, &notification.to_string()) {
+                error!("[MCP Client] Failed to send cancellation: {}", e);
+            }
+        });
+        let result = self.read_response(id, Some(cancel));
+        cancel.clear_on_cancel();
+        let elapsed = start.elapsed().as_millis() as u64;
+
+        if cancel.is_cancelled() {
+            return Err(RequestCancelled.into());
+        }
+        Self::tool_call_outcome(result, elapsed)
+    }
+
+    /// Turn the response to a tools/call request into the call's result. A
+    /// JSON-RPC error answer means the server rejected the call and becomes
+    /// an error result; transport failures, timeouts and HTTP errors stay
+    /// errors, so callers can tell a failing tool from a failing backend.
+    fn tool_call_outcome(response: Result<Value>, elapsed: u64) -> Result<ToolCallResult> {
+        match response {
             Ok(response) => Self::parse_tool_result(response, elapsed),
-            Err(e) => Ok(ToolCallResult {
-                success: false,
-                content: vec![],
-                is_error: true,
-                error: Some(e.to_string()),
-                execution_time_ms: elapsed,
-            }),
+            Err(e) => match e.downcast::<McpErrorResponse>() {
+                Ok(rejected) => Ok(ToolCallResult {
+                    success: false,
+                    content: vec![],
+                    is_error: true,
+                    error: Some(rejected.to_string()),
+                    execution_time_ms: elapsed,
+                }),
+                Err(e) => Err(e),
+            },
         }
     }
 
     /// Parse the result of a tool call
     fn parse_tool_result(result: Value, elapsed: u64) -> Result<ToolCallResult> {
-        // Parse content array from result
+        // Parse content array from result, item by item so one unknown
+        // content type doesn't lose the rest
         let content: Vec<ToolContent> = result
             .get("content")
-            .and_then(|c| serde_json::from_value(c.clone()).ok())
+            .and_then(Value::as_array)
+            .map(|items| items.iter().filter_map(ToolContent::from_json).collect())
             .unwrap_or_default();
 
         let is_error = result
@@ -390,12 +821,12 @@ impl StdioMcpClient {
     }
 
     fn send_request(&mut self, method: &str, params: Option<Value>) -> Result<Value> {
-        let stdin = self
-            .child
-            .stdin
-            .as_mut()
-            .ok_or_else(|| anyhow!("Failed to get stdin"))?;
+        let id = self.write_request(method, params)?;
+        self.read_response(id, None)
+    }
 
+    /// Write a request without waiting for its response, returning its id
+    fn write_request(&mut self, method: &str, params: Option<Value>) -> Result<u64> {
         let id = next_request_id();
         let request = JsonRpcRequest {
             jsonrpc: "2.0",
@@ -407,19 +838,11 @@ impl StdioMcpClient {
         let request_str = serde_json::to_string(&request)?;
         info!("[MCP Client] Sending request: {}", request_str);
 
-        writeln!(stdin, "{}", request_str)?;
-        stdin.flush()?;
-
-        self.read_response(id)
+        write_stdin_line(&self.stdin, &request_str)?;
+        Ok(id)
     }
 
     fn send_notification(&mut self, method: &str) -> Result<()> {
-        let stdin = self
-            .child
-            .stdin
-            .as_mut()
-            .ok_or_else(|| anyhow!("Failed to get stdin"))?;
-
         let notification = JsonRpcNotification {
             jsonrpc: "2.0",
             method: method.to_string(),
@@ -428,13 +851,14 @@ impl StdioMcpClient {
         let notification_str = serde_json::to_string(&notification)?;
         info!("[MCP Client] Sending notification: {}", notification_str);
 
-        writeln!(stdin, "{}", notification_str)?;
-        stdin.flush()?;
-
-        Ok(())
+        write_stdin_line(&self.stdin, &notification_str)
     }
 
-    fn read_response(&mut self, expected_id: u64) -> Result<Value> {
+    fn read_response(
+        &mut self,
+        expected_id: u64,
+        cancel: Option<&CancellationToken>,
+    ) -> Result<Value> {
         let stdout = self
             .child
             .stdout
@@ -445,11 +869,14 @@ impl StdioMcpClient {
         let start = Instant::now();
 
         loop {
+            if cancel.is_some_and(|c| c.is_cancelled()) {
+                return Err(RequestCancelled.into());
+            }
             if start.elapsed() > self.timeout {
-                return Err(anyhow!(
-                    "Timeout waiting for response ({}s)",
-                    self.timeout.as_secs()
-                ));
+                return Err(RequestTimedOut {
+                    secs: self.timeout.as_secs(),
+                }
+                .into());
             }
 
             let mut line = String::new();
@@ -484,6 +911,16 @@ impl StdioMcpClient {
 
                     info!("[MCP Client] Received: {}", line);
 
+                    // Answer the server's roots/list and sampling requests while waiting
+                    if let Ok(message) = serde_json::from_str::<Value>(line) {
+                        let reply = roots_list_reply(&message, &self.roots)
+                            .or_else(|| sampling_reply(&message, self.sampling.as_ref()));
+                        if let Some(reply) = reply {
+                            write_stdin_line(&self.stdin, &reply.to_string())?;
+                            continue;
+                        }
+                    }
+
                     // Try to parse as JSON-RPC response
                     match serde_json::from_str::<JsonRpcResponse>(line) {
                         Ok(response) => {
@@ -491,7 +928,7 @@ impl StdioMcpClient {
                             if let Some(id) = response.id {
                                 if id == expected_id {
                                     if let Some(error) = response.error {
-                                        return Err(anyhow!("MCP error: {}", error.message));
+                                        return Err(McpErrorResponse::from(error).into());
                                     }
                                     return response
                                         .result
@@ -517,6 +954,191 @@ impl StdioMcpClient {
     }
 }
 
+/// Write one JSON-RPC message line to a stdio server
+fn write_stdin_line(stdin: &Mutex<ChildStdin>, line: &str) -> Result<()> {
+    let mut stdin = stdin.lock().map_err(|_| anyhow!("Failed to get stdin"))?;
+    writeln!(stdin, "{}", line)?;
+    stdin.flush()?;
+    Ok(())
+}
+
+/// Merge backend-specific `extra` parameters into initialize request params.
+/// Objects are merged key by key; any other value replaces the default.
+fn merge_init_params(params: &mut Value, extra: &Value) {
+    match (params, extra) {
+        (Value::Object(params), Value::Object(extra)) => {
+            for (key, value) in extra {
+                match params.get_mut(key) {
+                    Some(existing) => merge_init_params(existing, value),
+                    None => {
+                        params.insert(key.clone(), value.clone());
+                    }
+                }
+            }
+        }
+        (params, extra) => *params = extra.clone(),
+    }
+}
+
+/// Root entries (`file://` URI and directory name) for a roots/list result
+fn roots_list_result(roots: &[PathBuf]) -> Value {
+    let roots: Vec<Value> = roots
+        .iter()
+        .map(|root| {
+            let name = root
+                .file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_else(|| root.display().to_string());
+            json!({
+                "uri": format!("file://{}", root.display()),
+                "name": name,
+            })
+        })
+        .collect();
+    json!({ "roots": roots })
+}
+
+/// JSON-RPC reply to `message` if it is a server's roots/list request
+fn roots_list_reply(message: &Value, roots: &[PathBuf]) -> Option<Value> {
+    if message.get("method").and_then(|m| m.as_str()) != Some("roots/list") {
+        return None;
+    }
+    let id = message.get("id")?;
+    Some(json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "result": roots_list_result(roots),
+    }))
+}
+
+/// JSON-RPC reply to `message` if it is a server's sampling request: the
+/// handler's result, or an error if it fails or no handler is registered
+pub(crate) fn sampling_reply(message: &Value, handler: Option<&SamplingHandler>) -> Option<Value> {
+    if message.get("method").and_then(|m| m.as_str()) != Some(SAMPLING_METHOD) {
+        return None;
+    }
+    let id = message.get("id")?;
+    let Some(handler) = handler else {
+        return Some(json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "error": {
+                "code": -32601,
+                "message": "Sampling not supported: no sampling handler is registered with the gateway",
+            },
+        }));
+    };
+
+    let params = message.get("params").cloned().unwrap_or_else(|| json!({}));
+    Some(match handler.handle(&params) {
+        Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+        Err(e) => json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "error": { "code": -32603, "message": format!("Sampling failed: {}", e) },
+        }),
+    })
+}
+
+// ============================================================================
+// Token Refresh (for HTTP servers using short-lived tokens)
+// ============================================================================
+
+/// Where a fresh access token comes from
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum TokenSource {
+    /// Run a command; its trimmed stdout is the token
+    Command {
+        command: String,
+        #[serde(default)]
+        args: Vec<String>,
+    },
+    /// POST to an endpoint; the token is read from `access_token`/`token` in a
+    /// JSON response, or the plain-text body otherwise
+    Endpoint { url: String },
+}
+
+/// How to re-fetch an access token after the server answers 401
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RefreshConfig {
+    pub source: TokenSource,
+    /// Header the token is sent in
+    #[serde(default = "default_token_header")]
+    pub header: String,
+    /// Prefix placed before the token in the header value
+    #[serde(default = "default_token_prefix")]
+    pub prefix: String,
+}
+
+fn default_token_header() -> String {
+    "Authorization".to_string()
+}
+
+fn default_token_prefix() -> String {
+    "Bearer ".to_string()
+}
+
+impl RefreshConfig {
+    /// Fetch a new token from the configured source
+    fn fetch_token(&self, client: &reqwest::blocking::Client) -> Result<String> {
+        let token = match &self.source {
+            TokenSource::Command { command, args } => {
+                let output = Command::new(command)
+                    .args(args)
+                    .output()
+                    .map_err(|e| anyhow!("Failed to run '{}': {}", command, e))?;
+                if !output.status.success() {
+                    return Err(anyhow!(
+                        "'{}' exited with {}: {}",
+                        command,
+                        output.status,
+                        String::from_utf8_lossy(&output.stderr).trim()
+                    ));
+                }
+                String::from_utf8_lossy(&output.stdout).trim().to_string()
+            }
+            TokenSource::Endpoint { url } => {
+                let response = client
+                    .post(url)
+                    .send()
+                    .map_err(|e| anyhow!("Request to {} failed: {}", url, e))?;
+                let status = response.status();
+                let body = response.text().unwrap_or_default();
+                if !status.is_success() {
+                    return Err(anyhow!("{} returned {}: {}", url, status, body));
+                }
+                parse_token_response(&body)
+            }
+        };
+
+        if token.is_empty() {
+            return Err(anyhow!("Token source returned an empty token"));
+        }
+        Ok(token)
+    }
+}
+
+/// Extract a token from a token endpoint response body
+fn parse_token_response(body: &str) -> String {
+    serde_json::from_str::<Value>(body)
+        .ok()
+        .and_then(|json| {
+            ["access_token", "token"]
+                .iter()
+                .find_map(|key| json.get(key).and_then(|v| v.as_str()).map(str::to_string))
+        })
+        .unwrap_or_else(|| body.trim().to_string())
+}
+
+/// The server rejected the request's credentials (HTTP 401)
+#[derive(Debug, thiserror::Error)]
+#[error("HTTP error 401 Unauthorized: {body}")]
+struct UnauthorizedError {
+    body: String,
+}
+
 // ============================================================================
 // HTTP MCP Client (for persistent sessions)
 // ============================================================================
@@ -524,10 +1146,21 @@ impl StdioMcpClient {
 /// Client for communicating with HTTP-based MCP servers
 pub struct HttpMcpClient {
     client: reqwest::blocking::Client,
+    /// Clients shared across connections to the same backend; requests use
+    /// one of these instead of `client` when set
+    pool: Option<Arc<HttpClientPool>>,
+    timeout: Duration,
     url: String,
     session_id: Option<String>,
     headers: Option<HashMap<String, String>>,
+    /// How to get a new token when the server answers 401
+    token_refresh: Option<RefreshConfig>,
+    /// Most recently fetched token (sent instead of any static header of the same name)
+    token: Option<String>,
+    /// Extra parameters merged into the initialize request
+    init_params: Option<Value>,
     server_info: Option<McpServerInfo>,
+    capabilities: McpServerCapabilities,
     tools: Vec<McpTool>,
     resources_supported: bool,
     prompts_supported: bool,
@@ -539,19 +1172,61 @@ impl HttpMcpClient {
         url: &str,
         headers: Option<&HashMap<String, String>>,
         timeout_secs: u64,
+    ) -> Result<Self> {
+        Self::connect_with_refresh(url, headers, timeout_secs, None)
+    }
+
+    /// Connect to an HTTP MCP server, refreshing the access token and retrying
+    /// once whenever a request is rejected with 401
+    pub fn connect_with_refresh(
+        url: &str,
+        headers: Option<&HashMap<String, String>>,
+        timeout_secs: u64,
+        token_refresh: Option<RefreshConfig>,
+    ) -> Result<Self> {
+        Self::connect_with_init(url, headers, timeout_secs, token_refresh, None)
+    }
+
+    /// connect_with_refresh, merging `init_params` into the initialize request
+    pub fn connect_with_init(
+        url: &str,
+        headers: Option<&HashMap<String, String>>,
+        timeout_secs: u64,
+        token_refresh: Option<RefreshConfig>,
+        init_params: Option<&Value>,
+    ) -> Result<Self> {
+        Self::connect_with_pool(url, headers, timeout_secs, token_refresh, init_params, None)
+    }
+
+    /// connect_with_init, sending every request through a client checked out
+    /// of `pool` so connections are reused across calls and reconnects
+    pub fn connect_with_pool(
+        url: &str,
+        headers: Option<&HashMap<String, String>>,
+        timeout_secs: u64,
+        token_refresh: Option<RefreshConfig>,
+        init_params: Option<&Value>,
+        pool: Option<Arc<HttpClientPool>>,
     ) -> Result<Self> {
         info!("[HTTP MCP Client] Connecting to: {}", url);
 
+        let timeout = Duration::from_secs(timeout_secs);
         let client = reqwest::blocking::Client::builder()
-            .timeout(Duration::from_secs(timeout_secs))
+            .timeout(timeout)
             .build()?;
 
         let mut instance = Self {
             client,
+            pool,
+            timeout,
             url: url.to_string(),
             session_id: None,
             headers: headers.cloned(),
+            token_refresh,
+            token: None,
+            init_params: init_params.cloned(),
             server_info: None,
+            capabilities: McpServerCapabilities::default(),
             tools: vec![],
             resources_supported: false,
             prompts_supported: false,
@@ -564,7 +1239,7 @@ impl HttpMcpClient {
     fn initialize(&mut self) -> Result<()> {
         info!("[HTTP MCP Client] Sending initialize request...");
 
-        let init_request = json!({
+        let mut init_request = json!({
             "jsonrpc": "2.0",
             "id": next_request_id(),
             "method": "initialize",
@@ -577,6 +1252,9 @@ impl HttpMcpClient {
                 }
             }
         });
+        if let Some(extra) = &self.init_params {
+            merge_init_params(&mut init_request["params"], extra);
+        }
 
         let response = self.send_request(&init_request)?;
 
@@ -602,6 +1280,7 @@ impl HttpMcpClient {
         }
 
         // Parse capabilities
+        self.capabilities = McpServerCapabilities::from_initialize_result(&response.body);
         if let Some(caps) = response.body.get("capabilities") {
             self.resources_supported = caps.get("resources").is_some();
             self.prompts_supported = caps.get("prompts").is_some();
@@ -635,6 +1314,11 @@ impl HttpMcpClient {
         self.server_info.as_ref()
     }
 
+    /// Get the capabilities declared in the initialize handshake
+    pub fn capabilities(&self) -> &McpServerCapabilities {
+        &self.capabilities
+    }
+
     /// Get available tools
     pub fn tools(&self) -> &[McpTool] {
         &self.tools
@@ -671,51 +1355,188 @@ impl HttpMcpClient {
         let result = self.send_request(&request);
         let elapsed = start.elapsed().as_millis() as u64;
 
-        match result {
-            Ok(response) => StdioMcpClient::parse_tool_result(response.body, elapsed),
-            Err(e) => Ok(ToolCallResult {
-                success: false,
-                content: vec![],
-                is_error: true,
-                error: Some(e.to_string()),
-                execution_time_ms: elapsed,
-            }),
+        StdioMcpClient::tool_call_outcome(result.map(|response| response.body), elapsed)
+    }
+
+    /// List resources exposed by the server (empty if resources are unsupported)
+    pub fn list_resources(&mut self) -> Result<Vec<McpResource>> {
+        if !self.resources_supported {
+            return Ok(vec![]);
         }
+
+        info!("[HTTP MCP Client] Requesting resources list...");
+        let request = json!({
+            "jsonrpc": "2.0",
+            "id": next_request_id(),
+            "method": "resources/list",
+            "params": {}
+        });
+        let response = self.send_request(&request)?;
+        Ok(response
+            .body
+            .get("resources")
+            .and_then(|r| serde_json::from_value(r.clone()).ok())
+            .unwrap_or_default())
     }
 
-    fn send_request(&self, request: &Value) -> Result<HttpResponse> {
-        let body = serde_json::to_string(request)?;
-        info!(
-            "[HTTP MCP Client] Sending: {}",
-            &body[..body.len().min(200)]
-        );
+    /// List prompts exposed by the server (empty if prompts are unsupported)
+    pub fn list_prompts(&mut self) -> Result<Vec<McpPrompt>> {
+        if !self.prompts_supported {
+            return Ok(vec![]);
+        }
 
-        let mut builder = self
-            .client
-            .post(&self.url)
-            .header("Content-Type", "application/json")
-            .header("Accept", "application/json, text/event-stream")
-            .body(body);
+        info!("[HTTP MCP Client] Requesting prompts list...");
+        let request = json!({
+            "jsonrpc": "2.0",
+            "id": next_request_id(),
+            "method": "prompts/list",
+            "params": {}
+        });
+        let response = self.send_request(&request)?;
+        Ok(response
+            .body
+            .get("prompts")
+            .and_then(|p| serde_json::from_value(p.clone()).ok())
+            .unwrap_or_default())
+    }
+
+    /// Send an MCP `ping`, keeping the server's session (and the connection) alive
+    pub fn ping(&mut self) -> Result<()> {
+        let request = json!({
+            "jsonrpc": "2.0",
+            "id": next_request_id(),
+            "method": "ping"
+        });
+        self.send_request(&request)?;
+        Ok(())
+    }
 
+    /// Ask the server to only send log messages at `level` or above
+    pub fn set_log_level(&mut self, level: &str) -> Result<()> {
+        let request = json!({
+            "jsonrpc": "2.0",
+            "id": next_request_id(),
+            "method": LOGGING_SET_LEVEL_METHOD,
+            "params": { "level": level }
+        });
+        self.send_request(&request)?;
+        Ok(())
+    }
+
+    /// Send a request, refreshing the token and retrying once on 401
+    fn send_request(&mut self, request: &Value) -> Result<HttpResponse> {
+        match self.send_request_once(request) {
+            Err(e) if e.is::<UnauthorizedError>() && self.token_refresh.is_some() => {
+                info!("[HTTP MCP Client] Got 401, refreshing token");
+                self.refresh_token()?;
+                self.send_request_once(request)
+            }
+            result => result,
+        }
+    }
+
+    fn refresh_token(&mut self) -> Result<()> {
+        let config = self
+            .token_refresh
+            .as_ref()
+            .ok_or_else(|| anyhow!("No token refresh configured"))?;
+        let token = config
+            .fetch_token(&self.client)
+            .map_err(|e| anyhow!("Token refresh failed: {}", e))?;
+        self.token = Some(token);
+        Ok(())
+    }
+
+    /// Add session, static and token headers to a request
+    fn apply_headers(
+        &self,
+        mut builder: reqwest::blocking::RequestBuilder,
+    ) -> reqwest::blocking::RequestBuilder {
         if let Some(sid) = &self.session_id {
             builder = builder.header("mcp-session-id", sid);
         }
 
+        let token_header =
+            self.token_refresh
+                .as_ref()
+                .zip(self.token.as_ref())
+                .map(|(config, token)| {
+                    (
+                        config.header.as_str(),
+                        format!("{}{}", config.prefix, token),
+                    )
+                });
+
         if let Some(hdrs) = &self.headers {
             for (key, value) in hdrs {
+                if token_header
+                    .as_ref()
+                    .is_some_and(|(name, _)| name.eq_ignore_ascii_case(key))
+                {
+                    continue;
+                }
                 builder = builder.header(key, value);
             }
         }
 
-        let response = builder
+        if let Some((name, value)) = token_header {
+            builder = builder.header(name, value);
+        }
+
+        builder
+    }
+
+    fn send_request_once(&self, request: &Value) -> Result<HttpResponse> {
+        let body = serde_json::to_string(request)?;
+        info!(
+            "[HTTP MCP Client] Sending: {}",
+            &body[..body.len().min(200)]
+        );
+
+        self.with_client(|client| self.post_request(client, body))?
+    }
+
+    /// POST a request body with `client` and read the JSON-RPC response
+    fn post_request(
+        &self,
+        client: &reqwest::blocking::Client,
+        body: String,
+    ) -> Result<HttpResponse> {
+        let builder = client
+            .post(&self.url)
+            .timeout(self.timeout)
+            .header("Content-Type", "application/json")
+            .header("Accept", "application/json, text/event-stream")
+            .body(body);
+
+        let response = self
+            .apply_headers(builder)
             .send()
-            .map_err(|e| anyhow!("HTTP request failed: {}", e))?;
+            .map_err(|e| -> anyhow::Error {
+                if e.is_timeout() {
+                    RequestTimedOut {
+                        secs: self.timeout.as_secs(),
+                    }
+                    .into()
+                } else {
+                    anyhow!("HTTP request failed: {}", e)
+                }
+            })?;
         let headers = response.headers().clone();
         let status = response.status();
 
+        if status == reqwest::StatusCode::UNAUTHORIZED {
+            let body = response.text().unwrap_or_default();
+            return Err(UnauthorizedError { body }.into());
+        }
+
         if !status.is_success() {
             let body = response.text().unwrap_or_default();
-            return Err(anyhow!("HTTP error {}: {}", status, body));
+            return Err(HttpStatusError {
+                status: status.as_u16(),
+                body,
+            }
+            .into());
         }
 
         let content_type = headers
@@ -737,7 +1558,7 @@ impl HttpMcpClient {
         };
 
         if let Some(error) = json_response.error {
-            return Err(anyhow!("MCP error: {}", error.message));
+            return Err(McpErrorResponse::from(error).into());
         }
 
         Ok(HttpResponse {
@@ -754,24 +1575,24 @@ impl HttpMcpClient {
 
         let body = serde_json::to_string(&notification)?;
 
-        let mut builder = self
-            .client
-            .post(&self.url)
-            .header("Content-Type", "application/json")
-            .body(body);
-
-        if let Some(sid) = &self.session_id {
-            builder = builder.header("mcp-session-id", sid);
-        }
+        self.with_client(|client| {
+            let builder = client
+                .post(&self.url)
+                .timeout(self.timeout)
+                .header("Content-Type", "application/json")
+                .body(body);
+            let _ = self.apply_headers(builder).send();
+        })
+    }
 
-        if let Some(hdrs) = &self.headers {
-            for (key, value) in hdrs {
-                builder = builder.header(key, value);
-            }
+    /// Run `f` with a client checked out of the backend's pool, or the
+    /// client's own one if it has no pool. The pooled client is held until
+    /// `f` returns, so its connection is busy while the response is read.
+    fn with_client<T>(&self, f: impl FnOnce(&reqwest::blocking::Client) -> T) -> Result<T> {
+        match &self.pool {
+            Some(pool) => Ok(f(&pool.checkout()?)),
+            None => Ok(f(&self.client)),
         }
-
-        let _ = builder.send();
-        Ok(())
     }
 
     /// Close the client (HTTP clients don't need explicit cleanup)
@@ -1126,7 +1947,7 @@ impl SseMcpClient {
             serde_json::from_str(&text).map_err(|e| anyhow!("Invalid JSON response: {}", e))?;
 
         if let Some(error) = json_response.error {
-            return Err(anyhow!("MCP error: {}", error.message));
+            return Err(McpErrorResponse::from(error).into());
         }
 
         Ok(json_response.result.unwrap_or(Value::Null))
@@ -2781,6 +3602,129 @@ mod tests {
     // McpServerInfo tests
     // =========================================================================
 
+    #[test]
+    fn test_merge_init_params_merges_objects_and_replaces_values() {
+        let mut params = json!({
+            "protocolVersion": "2024-11-05",
+            "capabilities": {"roots": {"listChanged": false}},
+        });
+
+        merge_init_params(
+            &mut params,
+            &json!({
+                "capabilities": {"experimental": {"workspace": true}},
+                "workspacePath": "/work/app",
+                "protocolVersion": "2025-03-26",
+            }),
+        );
+
+        assert_eq!(
+            params,
+            json!({
+                "protocolVersion": "2025-03-26",
+                "capabilities": {
+                    "roots": {"listChanged": false},
+                    "experimental": {"workspace": true},
+                },
+                "workspacePath": "/work/app",
+            })
+        );
+    }
+
+    #[test]
+    fn test_roots_list_reply_answers_only_roots_requests() {
+        let roots = vec![PathBuf::from("/work/app")];
+
+        let reply = roots_list_reply(
+            &json!({"jsonrpc": "2.0", "id": "r1", "method": "roots/list"}),
+            &roots,
+        )
+        .unwrap();
+        assert_eq!(
+            reply,
+            json!({
+                "jsonrpc": "2.0",
+                "id": "r1",
+                "result": {"roots": [{"uri": "file:///work/app", "name": "app"}]}
+            })
+        );
+
+        let response = json!({"jsonrpc": "2.0", "id": 3, "result": {}});
+        assert!(roots_list_reply(&response, &roots).is_none());
+    }
+
+    #[test]
+    fn test_parse_tool_result_keeps_every_content_type() {
+        let result = json!({
+            "content": [
+                {"type": "text", "text": "Rendered chart"},
+                {"type": "image", "data": "iVBORw0KGgo=", "mimeType": "image/png"},
+                {"type": "resource", "resource": {"uri": "file:///data.csv", "mimeType": "text/csv", "text": "a,b"}},
+                {"type": "resource_link", "uri": "file:///report.pdf", "name": "report"},
+                {"type": "hologram", "frames": 3}
+            ]
+        });
+
+        let parsed = StdioMcpClient::parse_tool_result(result, 5).unwrap();
+
+        assert_eq!(parsed.content.len(), 4);
+        assert!(matches!(
+            &parsed.content[1],
+            ToolContent::Image { mime_type, .. } if mime_type == "image/png"
+        ));
+        assert!(matches!(
+            &parsed.content[2],
+            ToolContent::Resource { uri, text: Some(text), .. }
+                if uri == "file:///data.csv" && text == "a,b"
+        ));
+        assert!(matches!(
+            &parsed.content[3],
+            ToolContent::ResourceLink { name: Some(name), .. } if name == "report"
+        ));
+        assert_eq!(parsed.text(), "Rendered chart");
+        assert_eq!(
+            serde_json::to_value(&parsed.content[1]).unwrap(),
+            json!({"type": "image", "data": "iVBORw0KGgo=", "mimeType": "image/png"})
+        );
+    }
+
+    #[test]
+    fn test_sampling_reply_uses_handler_or_refuses() {
+        let request = json!({
+            "jsonrpc": "2.0",
+            "id": 7,
+            "method": SAMPLING_METHOD,
+            "params": {"messages": [], "maxTokens": 10},
+        });
+
+        let refused = sampling_reply(&request, None).unwrap();
+        assert_eq!(refused["id"], 7);
+        assert_eq!(refused["error"]["code"], -32601);
+        assert!(refused["error"]["message"]
+            .as_str()
+            .unwrap()
+            .contains("Sampling not supported"));
+
+        let handler = SamplingHandler::new(|params| {
+            Ok(json!({"role": "assistant", "maxTokens": params["maxTokens"]}))
+        });
+        let reply = sampling_reply(&request, Some(&handler)).unwrap();
+        assert_eq!(
+            reply["result"],
+            json!({"role": "assistant", "maxTokens": 10})
+        );
+
+        let failing = SamplingHandler::new(|_| Err(anyhow!("model unavailable")));
+        let reply = sampling_reply(&request, Some(&failing)).unwrap();
+        assert_eq!(
+            reply["error"]["message"],
+            "Sampling failed: model unavailable"
+        );
+
+        let response = json!({"jsonrpc": "2.0", "id": 3, "result": {}});
+        assert!(sampling_reply(&response, Some(&handler)).is_none());
+    }
+
     #[test]
     fn test_mcp_server_info_deserialization() {
         let json = r#"{"name": "test-server", "version": "2.0.0"}"#;
@@ -2835,6 +3779,32 @@ mod tests {
         assert!(json.contains("\"description\":\"A test tool\""));
     }
 
+    // =========================================================================
+    // McpResource / McpPrompt tests
+    // =========================================================================
+
+    #[test]
+    fn test_mcp_resource_deserialization() {
+        let json = r#"{"uri": "file:///readme.md", "name": "readme", "mimeType": "text/markdown"}"#;
+        let resource: McpResource = serde_json::from_str(json).unwrap();
+        assert_eq!(resource.uri, "file:///readme.md");
+        assert_eq!(resource.mime_type, Some("text/markdown".to_string()));
+        assert!(resource.description.is_none());
+    }
+
+    #[test]
+    fn test_mcp_prompt_deserialization() {
+        let json = r#"{
+            "name": "review",
+            "arguments": [{"name": "code", "required": true}, {"name": "style"}]
+        }"#;
+        let prompt: McpPrompt = serde_json::from_str(json).unwrap();
+        assert_eq!(prompt.name, "review");
+        assert_eq!(prompt.arguments.len(), 2);
+        assert!(prompt.arguments[0].required);
+        assert!(!prompt.arguments[1].required);
+    }
+
     // =========================================================================
     // parse_sse_response tests
     // =========================================================================
@@ -3224,4 +4194,228 @@ data: "result":{}}
         assert_eq!(tools[1].description, None);
         assert!(tools[2].input_schema.is_some());
     }
+
+    // =========================================================================
+    // Token refresh tests
+    // =========================================================================
+
+    mod token_refresh {
+        use super::*;
+        use wiremock::matchers::{body_string_contains, method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        fn rpc_result(result: Value) -> ResponseTemplate {
+            ResponseTemplate::new(200)
+                .set_body_json(json!({"jsonrpc": "2.0", "id": 1, "result": result}))
+        }
+
+        /// MCP server whose first tools/call is rejected with 401
+        async fn expiring_token_server() -> MockServer {
+            let server = MockServer::start().await;
+
+            Mock::given(method("POST"))
+                .and(path("/mcp"))
+                .and(body_string_contains("tools/call"))
+                .respond_with(ResponseTemplate::new(401).set_body_string("token expired"))
+                .up_to_n_times(1)
+                .with_priority(1)
+                .mount(&server)
+                .await;
+            Mock::given(method("POST"))
+                .and(path("/mcp"))
+                .and(body_string_contains("tools/call"))
+                .respond_with(rpc_result(json!({
+                    "content": [{"type": "text", "text": "ok"}]
+                })))
+                .with_priority(2)
+                .mount(&server)
+                .await;
+            Mock::given(method("POST"))
+                .and(path("/mcp"))
+                .respond_with(rpc_result(json!({
+                    "serverInfo": {"name": "auth-test"},
+                    "capabilities": {},
+                    "tools": []
+                })))
+                .with_priority(3)
+                .mount(&server)
+                .await;
+
+            server
+        }
+
+        fn endpoint_refresh(server: &MockServer) -> RefreshConfig {
+            RefreshConfig {
+                source: TokenSource::Endpoint {
+                    url: format!("{}/token", server.uri()),
+                },
+                header: default_token_header(),
+                prefix: default_token_prefix(),
+            }
+        }
+
+        #[test]
+        fn test_parse_token_response() {
+            assert_eq!(parse_token_response(r#"{"access_token": "abc"}"#), "abc");
+            assert_eq!(parse_token_response(r#"{"token": "def"}"#), "def");
+            assert_eq!(parse_token_response("  plain-token\n"), "plain-token");
+        }
+
+        #[test]
+        fn test_refresh_config_deserialization_defaults() {
+            let config: RefreshConfig = serde_json::from_value(json!({
+                "source": {"type": "command", "command": "get-token"}
+            }))
+            .unwrap();
+
+            assert_eq!(config.header, "Authorization");
+            assert_eq!(config.prefix, "Bearer ");
+            assert_eq!(
+                config.source,
+                TokenSource::Command {
+                    command: "get-token".to_string(),
+                    args: vec![],
+                }
+            );
+        }
+
+        #[tokio::test(flavor = "multi_thread")]
+        async fn test_call_retries_once_after_refreshing_on_401() {
+            let server = expiring_token_server().await;
+            Mock::given(method("POST"))
+                .and(path("/token"))
+                .respond_with(
+                    ResponseTemplate::new(200).set_body_json(json!({"access_token": "fresh"})),
+                )
+                .expect(1)
+                .mount(&server)
+                .await;
+
+            let url = format!("{}/mcp", server.uri());
+            let refresh = endpoint_refresh(&server);
+            let result = tokio::task::spawn_blocking(move || {
+                let mut client =
+                    HttpMcpClient::connect_with_refresh(&url, None, 5, Some(refresh)).unwrap();
+                client.call_tool("echo", json!({})).unwrap()
+            })
+            .await
+            .unwrap();
+
+            assert!(result.success, "unexpected error: {:?}", result.error);
+
+            let requests = server.received_requests().await.unwrap();
+            let retried_call = requests.last().unwrap();
+            assert_eq!(
+                retried_call.headers.get("authorization").unwrap(),
+                "Bearer fresh"
+            );
+        }
+
+        #[tokio::test(flavor = "multi_thread")]
+        async fn test_refresh_failure_fails_the_call() {
+            let server = expiring_token_server().await;
+            Mock::given(method("POST"))
+                .and(path("/token"))
+                .respond_with(ResponseTemplate::new(500))
+                .mount(&server)
+                .await;
+
+            let url = format!("{}/mcp", server.uri());
+            let refresh = endpoint_refresh(&server);
+            let err = tokio::task::spawn_blocking(move || {
+                let mut client =
+                    HttpMcpClient::connect_with_refresh(&url, None, 5, Some(refresh)).unwrap();
+                client.call_tool("echo", json!({})).unwrap_err()
+            })
+            .await
+            .unwrap();
+
+            assert!(err.to_string().contains("Token refresh failed"));
+        }
+    }
+
+    // =========================================================================
+    // Tool call failure tests
+    // =========================================================================
+
+    mod call_failures {
+        use super::*;
+        use wiremock::matchers::{body_string_contains, method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        /// MCP server that answers tools/call with `call_response`
+        async fn server_answering_calls(call_response: ResponseTemplate) -> MockServer {
+            let server = MockServer::start().await;
+            Mock::given(method("POST"))
+                .and(path("/mcp"))
+                .and(body_string_contains("tools/call"))
+                .respond_with(call_response)
+                .with_priority(1)
+                .mount(&server)
+                .await;
+            Mock::given(method("POST"))
+                .and(path("/mcp"))
+                .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                    "jsonrpc": "2.0",
+                    "id": 1,
+                    "result": {"serverInfo": {"name": "calls"}, "capabilities": {}, "tools": []}
+                })))
+                .with_priority(2)
+                .mount(&server)
+                .await;
+            server
+        }
+
+        /// Connect to `server` and call a tool
+        async fn call_echo(server: &MockServer) -> Result<ToolCallResult> {
+            let url = format!("{}/mcp", server.uri());
+            tokio::task::spawn_blocking(move || {
+                HttpMcpClient::connect(&url, None, 1)?.call_tool("echo", json!({}))
+            })
+            .await
+            .unwrap()
+        }
+
+        #[tokio::test(flavor = "multi_thread")]
+        async fn test_http_status_failure_is_an_error() {
+            let server =
+                server_answering_calls(ResponseTemplate::new(429).set_body_string("slow down"))
+                    .await;
+
+            let err = call_echo(&server).await.unwrap_err();
+
+            let status = err.downcast_ref::<HttpStatusError>().unwrap();
+            assert_eq!(status.status, 429);
+        }
+
+        #[tokio::test(flavor = "multi_thread")]
+        async fn test_http_timeout_is_an_error() {
+            let server = server_answering_calls(
+                ResponseTemplate::new(200).set_delay(Duration::from_secs(3)),
+            )
+            .await;
+
+            let err = call_echo(&server).await.unwrap_err();
+
+            assert!(err.is::<RequestTimedOut>(), "unexpected error: {}", err);
+        }
+
+        #[tokio::test(flavor = "multi_thread")]
+        async fn test_rpc_error_answer_is_an_error_result() {
+            let server = server_answering_calls(ResponseTemplate::new(200).set_body_json(json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "error": {"code": -32602, "message": "Unknown tool: echo"}
+            })))
+            .await;
+
+            let result = call_echo(&server).await.unwrap();
+
+            assert!(result.is_error);
+            assert_eq!(
+                result.error.as_deref(),
+                Some("MCP error: Unknown tool: echo")
+            );
+        }
+    }
 }
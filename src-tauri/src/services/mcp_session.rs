@@ -137,14 +137,28 @@ impl McpSession {
     }
 
     fn call_tool(&mut self, name: &str, arguments: Value) -> Result<ToolCallResult> {
+        let start = Instant::now();
+        // Stdio and HTTP clients fail calls whose transport failed; sessions
+        // show those as error results, like the other clients do
+        let transport_failure = |e: anyhow::Error| ToolCallResult {
+            success: false,
+            content: vec![],
+            is_error: true,
+            error: Some(e.to_string()),
+            execution_time_ms: start.elapsed().as_millis() as u64,
+        };
         match self {
             McpSession::Stdio(s) => {
                 s.last_used_at = Instant::now();
-                s.client.call_tool(name, arguments)
+                Ok(s.client
+                    .call_tool(name, arguments)
+                    .unwrap_or_else(transport_failure))
             }
             McpSession::Http(s) => {
                 s.last_used_at = Instant::now();
-                s.client.call_tool(name, arguments)
+                Ok(s.client
+                    .call_tool(name, arguments)
+                    .unwrap_or_else(transport_failure))
             }
             McpSession::Sse(s) => {
                 s.last_used_at = Instant::now();
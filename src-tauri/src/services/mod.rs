@@ -1,14 +1,26 @@
+pub mod agent_compat;
+pub mod agent_diff;
+pub mod agent_ignore;
+pub mod agent_inheritance;
+pub mod agent_lint;
+pub mod agent_table;
+pub mod bundle;
 pub mod claude_json;
 pub mod codex_config;
 pub mod command_writer;
+pub mod config_doctor;
 pub mod config_parser;
+pub mod config_usage;
 pub mod config_writer;
 pub mod copilot_config;
 pub mod cursor_config;
 pub mod debug_logger;
+pub mod frontmatter;
 pub mod gemini_config;
 pub mod github_client;
 pub mod hook_writer;
+pub mod http_pool;
+pub mod json_schema;
 pub mod mcp_client;
 pub mod mcp_registry;
 pub mod mcp_session;
@@ -20,4 +32,5 @@ pub mod skill_writer;
 pub mod sound_player;
 pub mod statusline_gallery;
 pub mod statusline_writer;
+pub mod subagent_import;
 pub mod subagent_writer;
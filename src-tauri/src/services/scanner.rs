@@ -4,6 +4,10 @@ use crate::services::codex_config;
 use crate::services::config_parser;
 use crate::services::copilot_config;
 use crate::services::cursor_config;
+use crate::services::frontmatter::{
+    parse_yaml_block_scalar, parse_yaml_map_lenient, parse_yaml_sequence, split_frontmatter,
+    FrontmatterMap,
+};
 use crate::services::gemini_config;
 use crate::services::opencode_config;
 use crate::utils::codex_paths::get_codex_paths;
@@ -15,7 +19,7 @@ use crate::utils::paths::{get_claude_paths, normalize_path};
 use anyhow::Result;
 use rusqlite::params;
 use std::collections::HashSet;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use tauri::Manager;
 use walkdir::WalkDir;
 
@@ -490,31 +494,35 @@ pub fn scan_global_commands(db: &Database) -> Result<usize> {
     Ok(count)
 }
 
+/// Setting that makes skill scans descend into category folders
+pub const RECURSIVE_SKILLS_SETTING: &str = "recursive_skill_discovery";
+
+/// Whether skill scans recurse into nested folders (off unless the user
+/// opted in)
+pub fn recursive_skill_discovery(db: &Database) -> bool {
+    db.get_setting(RECURSIVE_SKILLS_SETTING)
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(false)
+}
+
 /// Scan global skills from ~/.claude/skills/ directories
 pub fn scan_global_skills(db: &Database) -> Result<usize> {
     let paths = get_claude_paths()?;
     let mut count = 0;
 
     if paths.skills_dir.exists() {
-        for entry in std::fs::read_dir(&paths.skills_dir)? {
-            let entry = entry?;
-            let path = entry.path();
-
-            // Only process directories (each skill is a directory with SKILL.md)
-            if path.is_dir() {
-                if let Some((skill, files)) = parse_agent_skill_dir(&path) {
-                    // Use get_or_create_skill to insert into skills table
-                    let source_path = path.to_string_lossy().to_string();
-                    let (skill_id, was_created) = get_or_create_skill(db, &skill, &source_path)?;
-
-                    // Insert skill files if this is a new skill
-                    if was_created {
-                        if !files.is_empty() {
-                            let _ = insert_skill_files(db, skill_id, &files);
-                        }
-                        count += 1;
-                    }
+        let recursive = recursive_skill_discovery(db);
+        for DiscoveredSkill { path, skill, files } in list_skills(&paths.skills_dir, recursive)? {
+            // Use get_or_create_skill to insert into skills table
+            let source_path = path.to_string_lossy().to_string();
+            let (skill_id, was_created) = get_or_create_skill(db, &skill, &source_path)?;
+
+            // Insert skill files if this is a new skill
+            if was_created {
+                if !files.is_empty() {
+                    let _ = insert_skill_files(db, skill_id, &files);
                 }
+                count += 1;
             }
         }
     }
@@ -569,10 +577,20 @@ pub fn scan_global_agents(db: &Database) -> Result<usize> {
                         } else {
                             Some(serde_json::to_string(&agent.tags).unwrap())
                         };
+                        let mcp_servers_json = if agent.mcp_servers.is_empty() {
+                            None
+                        } else {
+                            Some(serde_json::to_string(&agent.mcp_servers).unwrap())
+                        };
+                        let env_json = if agent.env.is_empty() {
+                            None
+                        } else {
+                            Some(serde_json::to_string(&agent.env).unwrap())
+                        };
 
                         let result = db.conn().execute(
-                            "INSERT INTO subagents (name, description, content, tools, model, permission_mode, skills, tags, source, source_path)
-                             VALUES (?, ?, ?, ?, ?, ?, ?, ?, 'auto-detected', ?)",
+                            "INSERT INTO subagents (name, description, content, tools, model, permission_mode, skills, tags, source, source_path, enabled, mcp_servers, color, env)
+                             VALUES (?, ?, ?, ?, ?, ?, ?, ?, 'auto-detected', ?, ?, ?, ?, ?)",
                             params![
                                 agent.name,
                                 agent.description,
@@ -582,7 +600,11 @@ pub fn scan_global_agents(db: &Database) -> Result<usize> {
                                 agent.permission_mode,
                                 skills_json,
                                 tags_json,
-                                source_path
+                                source_path,
+                                agent.enabled as i32,
+                                mcp_servers_json,
+                                agent.color,
+                                env_json
                             ],
                         );
 
@@ -610,6 +632,10 @@ pub(crate) struct ParsedSkill {
     pub(crate) model: Option<String>,
     pub(crate) disable_model_invocation: bool,
     pub(crate) tags: Vec<String>,
+    pub(crate) when_to_use: Option<String>,
+    pub(crate) examples: Vec<String>,
+    /// Assets under `skills/_shared/` the skill links to (paths relative to it)
+    pub(crate) shared_assets: Vec<String>,
 }
 
 /// Parsed skill file data (references, assets, scripts)
@@ -631,6 +657,14 @@ pub(crate) struct ParsedAgent {
     pub(crate) permission_mode: Option<String>,
     pub(crate) skills: Vec<String>,
     pub(crate) tags: Vec<String>,
+    /// False for agents marked `disabled: true`
+    pub(crate) enabled: bool,
+    /// MCP servers the agent is scoped to (`mcpServers:` sequence)
+    pub(crate) mcp_servers: Vec<String>,
+    /// UI color (`color:`)
+    pub(crate) color: Option<String>,
+    /// Environment variables the agent expects (`env:` sequence)
+    pub(crate) env: Vec<String>,
 }
 
 /// Parse a skill markdown file
@@ -669,12 +703,7 @@ pub(crate) fn parse_skill_file(path: &Path) -> Option<ParsedSkill> {
         .unwrap_or(false);
     let tags = frontmatter
         .get("tags")
-        .map(|t| {
-            t.split(',')
-                .map(|s| s.trim().to_string())
-                .filter(|s| !s.is_empty())
-                .collect()
-        })
+        .map(|t| parse_string_list(t.as_str()))
         .unwrap_or_default();
 
     Some(ParsedSkill {
@@ -687,9 +716,128 @@ pub(crate) fn parse_skill_file(path: &Path) -> Option<ParsedSkill> {
         model,
         disable_model_invocation,
         tags,
+        when_to_use: parse_frontmatter_block_scalar(&content, "when-to-use"),
+        examples: parse_frontmatter_sequence(&content, "examples"),
+        shared_assets: Vec::new(),
     })
 }
 
+/// Directory under `skills/` holding assets shared by several skills
+/// (not a skill itself)
+pub(crate) const SHARED_SKILL_DIR: &str = "_shared";
+
+/// Paths of `skills/_shared/` assets linked from a skill body as
+/// `](../_shared/...)` (one `../` per level of a nested skill's name), in
+/// order of first appearance
+fn shared_asset_links(body: &str) -> Vec<String> {
+    let shared = format!("{}/", SHARED_SKILL_DIR);
+    let mut links: Vec<String> = Vec::new();
+    for (start, _) in body.match_indices("](../") {
+        let mut rest = &body[start + 2..];
+        while let Some(up) = rest.strip_prefix("../") {
+            rest = up;
+        }
+        let Some(rest) = rest.strip_prefix(&shared) else {
+            continue;
+        };
+        if let Some(end) = rest.find(')') {
+            let link = rest[..end].to_string();
+            if !link.is_empty() && !links.contains(&link) {
+                links.push(link);
+            }
+        }
+    }
+    links
+}
+
+/// A skill found by list_skills
+#[derive(Debug)]
+pub(crate) struct DiscoveredSkill {
+    /// Skill directory, or the `.md` file for a legacy single-file skill
+    pub(crate) path: PathBuf,
+    pub(crate) skill: ParsedSkill,
+    pub(crate) files: Vec<ParsedSkillFile>,
+}
+
+/// List the skills in a skills directory (each skill is a directory with SKILL.md),
+/// sorted by name.
+///
+/// Discovery is flat by default. With `recursive`, skills nested under category
+/// folders (`skills/web/fetch/SKILL.md`) are found too and named by their path
+/// relative to the skills directory (`web/fetch`). Directories that contain a
+/// SKILL.md are not searched further, and hidden directories are skipped.
+///
+/// Legacy single-file skills (`skills/{name}.md`) are listed too, unless a
+/// `skills/{name}/SKILL.md` directory of the same name exists. The shared
+/// asset directory (`skills/_shared/`) is never treated as a skill.
+pub(crate) fn list_skills(skills_dir: &Path, recursive: bool) -> Result<Vec<DiscoveredSkill>> {
+    let mut skills = Vec::new();
+    collect_skill_dirs(skills_dir, skills_dir, recursive, &mut skills)?;
+    skills.sort_by(|a, b| a.skill.name.cmp(&b.skill.name));
+    Ok(skills)
+}
+
+fn collect_skill_dirs(
+    skills_dir: &Path,
+    dir: &Path,
+    recursive: bool,
+    skills: &mut Vec<DiscoveredSkill>,
+) -> Result<()> {
+    let relative_name = |path: &Path| {
+        path.strip_prefix(skills_dir).ok().map(|relative| {
+            relative
+                .components()
+                .map(|c| c.as_os_str().to_string_lossy())
+                .collect::<Vec<_>>()
+                .join("/")
+        })
+    };
+
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        let hidden = path
+            .file_name()
+            .is_some_and(|n| n.to_string_lossy().starts_with('.'));
+        let shared = dir == skills_dir && path.file_name().is_some_and(|n| n == SHARED_SKILL_DIR);
+        if hidden || shared {
+            continue;
+        }
+
+        if path.is_file() {
+            let is_markdown = path.extension().is_some_and(|e| e == "md");
+            let shadowed = path.with_extension("").join("SKILL.md").exists();
+            if !is_markdown || shadowed {
+                continue;
+            }
+            if let Some(mut skill) = parse_skill_file(&path) {
+                skill.skill_type = "skill".to_string();
+                if let Some(name) = relative_name(&path.with_extension("")) {
+                    skill.name = name;
+                }
+                skills.push(DiscoveredSkill {
+                    path,
+                    skill,
+                    files: Vec::new(),
+                });
+            }
+            continue;
+        }
+        if !path.is_dir() {
+            continue;
+        }
+
+        if let Some((mut skill, files)) = parse_agent_skill_dir(&path) {
+            if let Some(name) = relative_name(&path) {
+                skill.name = name;
+            }
+            skills.push(DiscoveredSkill { path, skill, files });
+        } else if recursive {
+            collect_skill_dirs(skills_dir, &path, recursive, skills)?;
+        }
+    }
+    Ok(())
+}
+
 /// Parse an agent skill directory (e.g., .claude/skills/my-skill/)
 /// Returns the skill from SKILL.md and any files from references/assets/scripts subdirs
 fn parse_agent_skill_dir(skill_dir: &Path) -> Option<(ParsedSkill, Vec<ParsedSkillFile>)> {
@@ -724,12 +872,7 @@ fn parse_agent_skill_dir(skill_dir: &Path) -> Option<(ParsedSkill, Vec<ParsedSki
         .unwrap_or(false);
     let tags = frontmatter
         .get("tags")
-        .map(|t| {
-            t.split(',')
-                .map(|s| s.trim().to_string())
-                .filter(|s| !s.is_empty())
-                .collect()
-        })
+        .map(|t| parse_string_list(t.as_str()))
         .unwrap_or_default();
 
     let skill = ParsedSkill {
@@ -742,6 +885,9 @@ fn parse_agent_skill_dir(skill_dir: &Path) -> Option<(ParsedSkill, Vec<ParsedSki
         model,
         disable_model_invocation,
         tags,
+        when_to_use: parse_frontmatter_block_scalar(&content, "when-to-use"),
+        examples: parse_frontmatter_sequence(&content, "examples"),
+        shared_assets: shared_asset_links(&body),
     };
 
     // Scan subdirectories for files
@@ -800,31 +946,20 @@ pub(crate) fn parse_agent_file(path: &Path) -> Option<ParsedAgent> {
         .cloned();
     let tools = frontmatter
         .get("tools")
-        .map(|t| {
-            t.split(',')
-                .map(|s| s.trim().to_string())
-                .filter(|s| !s.is_empty())
-                .collect()
-        })
+        .map(|t| parse_agent_tools(t.as_str()))
         .unwrap_or_default();
     let skills = frontmatter
         .get("skills")
-        .map(|t| {
-            t.split(',')
-                .map(|s| s.trim().to_string())
-                .filter(|s| !s.is_empty())
-                .collect()
-        })
+        .map(|t| parse_string_list(t.as_str()))
         .unwrap_or_default();
     let tags = frontmatter
         .get("tags")
-        .map(|t| {
-            t.split(',')
-                .map(|s| s.trim().to_string())
-                .filter(|s| !s.is_empty())
-                .collect()
-        })
+        .map(|t| parse_string_list(t.as_str()))
         .unwrap_or_default();
+    let enabled = frontmatter.get("disabled").map(|v| v.trim()) != Some("true");
+    let mcp_servers = parse_frontmatter_sequence(&content, "mcpServers");
+    let color = frontmatter.get("color").cloned().filter(|c| !c.is_empty());
+    let env = parse_frontmatter_sequence(&content, "env");
 
     Some(ParsedAgent {
         name: file_name,
@@ -835,39 +970,68 @@ pub(crate) fn parse_agent_file(path: &Path) -> Option<ParsedAgent> {
         permission_mode,
         skills,
         tags,
+        enabled,
+        mcp_servers,
+        color,
+        env,
     })
 }
 
-/// Parse YAML-like frontmatter from markdown content
-pub(crate) fn parse_frontmatter(
-    content: &str,
-) -> (std::collections::HashMap<String, String>, String) {
-    let mut frontmatter = std::collections::HashMap::new();
-
-    if content.starts_with("---") {
-        // Find the closing ---
-        if let Some(end_pos) = content[3..].find("\n---") {
-            let fm_content = &content[3..end_pos + 3];
-            let body = content[end_pos + 7..].trim_start().to_string();
-
-            // Parse simple key: value pairs
-            for line in fm_content.lines() {
-                let line = line.trim();
-                if let Some(colon_pos) = line.find(':') {
-                    let key = line[..colon_pos].trim().to_string();
-                    let value = line[colon_pos + 1..].trim().to_string();
-                    if !key.is_empty() && !value.is_empty() {
-                        frontmatter.insert(key, value);
-                    }
-                }
-            }
+/// `tools:` value granting an agent every tool, same as omitting the line
+pub(crate) const ALL_TOOLS_WILDCARD: &str = "*";
+
+/// Parse an agent's `tools:` value. The wildcard (`*` or `"*"`) yields an
+/// empty list: the agent inherits all tools.
+pub(crate) fn parse_agent_tools(value: &str) -> Vec<String> {
+    if value.trim().trim_matches(['"', '\'']) == ALL_TOOLS_WILDCARD {
+        return Vec::new();
+    }
+    parse_string_list(value)
+}
 
-            return (frontmatter, body);
+/// Parse a comma-separated frontmatter list (tools, skills, tags).
+/// Tolerates hand-written separators like `Read,Grep`, `Read ,  Grep` and trailing commas.
+pub(crate) fn parse_string_list(value: &str) -> Vec<String> {
+    value
+        .split(',')
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+        .collect()
+}
+
+/// Top-level `key: value` pairs of a markdown file's frontmatter, and its
+/// body, as split and parsed by the frontmatter module. Content without a
+/// closed frontmatter block is returned whole as the body. Top-level lines
+/// that aren't `key: value` pairs are skipped with a warning.
+pub(crate) fn parse_frontmatter(content: &str) -> (FrontmatterMap, String) {
+    match split_frontmatter(content) {
+        Ok((raw, body)) => {
+            let (frontmatter, problems) = parse_yaml_map_lenient(&raw);
+            for problem in problems {
+                log::warn!("[Scanner] Skipping malformed frontmatter line: {}", problem);
+            }
+            (frontmatter, body)
         }
+        Err(_) => (FrontmatterMap::new(), content.to_string()),
     }
+}
 
-    // No frontmatter, return content as-is
-    (frontmatter, content.to_string())
+/// Lines of the frontmatter block, without the `---` delimiters
+fn frontmatter_block(content: &str) -> String {
+    split_frontmatter(content)
+        .map(|(raw, _)| raw)
+        .unwrap_or_default()
+}
+
+/// Items of a YAML block sequence in the frontmatter (`key:` then `  - item` lines)
+pub(crate) fn parse_frontmatter_sequence(content: &str, key: &str) -> Vec<String> {
+    parse_yaml_sequence(&frontmatter_block(content), key)
+}
+
+/// Text of a frontmatter value written inline or as a block scalar (see
+/// frontmatter::parse_yaml_block_scalar)
+pub(crate) fn parse_frontmatter_block_scalar(content: &str, key: &str) -> Option<String> {
+    parse_yaml_block_scalar(&frontmatter_block(content), key)
 }
 
 /// Scan project-level commands from .claude/commands/ and assign to project
@@ -900,28 +1064,21 @@ fn scan_project_commands(db: &Database, project_id: i64, commands_dir: &Path) ->
 fn scan_project_skills(db: &Database, project_id: i64, skills_dir: &Path) -> Result<usize> {
     let mut count = 0;
 
-    for entry in std::fs::read_dir(skills_dir)? {
-        let entry = entry?;
-        let path = entry.path();
+    let recursive = recursive_skill_discovery(db);
+    for DiscoveredSkill { path, skill, files } in list_skills(skills_dir, recursive)? {
+        // Get or create the skill in the library
+        let source_path = path.to_string_lossy().to_string();
+        let (skill_id, was_created) = get_or_create_skill(db, &skill, &source_path)?;
 
-        // Only process directories (each skill is a directory with SKILL.md)
-        if path.is_dir() {
-            if let Some((skill, files)) = parse_agent_skill_dir(&path) {
-                // Get or create the skill in the library
-                let source_path = path.to_string_lossy().to_string();
-                let (skill_id, was_created) = get_or_create_skill(db, &skill, &source_path)?;
-
-                // Insert skill files if this is a new skill
-                if was_created && !files.is_empty() {
-                    let _ = insert_skill_files(db, skill_id, &files);
-                }
+        // Insert skill files if this is a new skill
+        if was_created && !files.is_empty() {
+            let _ = insert_skill_files(db, skill_id, &files);
+        }
 
-                // Assign skill to project if not already assigned
-                assign_skill_to_project(db, project_id, skill_id)?;
+        // Assign skill to project if not already assigned
+        assign_skill_to_project(db, project_id, skill_id)?;
 
-                count += 1;
-            }
-        }
+        count += 1;
     }
 
     Ok(count)
@@ -1065,10 +1222,20 @@ fn get_or_create_agent(db: &Database, agent: &ParsedAgent, source_path: &str) ->
     } else {
         Some(serde_json::to_string(&agent.tags).unwrap())
     };
+    let mcp_servers_json = if agent.mcp_servers.is_empty() {
+        None
+    } else {
+        Some(serde_json::to_string(&agent.mcp_servers).unwrap())
+    };
+    let env_json = if agent.env.is_empty() {
+        None
+    } else {
+        Some(serde_json::to_string(&agent.env).unwrap())
+    };
 
     db.conn().execute(
-        "INSERT INTO subagents (name, description, content, tools, model, permission_mode, skills, tags, source, source_path)
-         VALUES (?, ?, ?, ?, ?, ?, ?, ?, 'auto-detected', ?)",
+        "INSERT INTO subagents (name, description, content, tools, model, permission_mode, skills, tags, source, source_path, enabled, mcp_servers, color, env)
+         VALUES (?, ?, ?, ?, ?, ?, ?, ?, 'auto-detected', ?, ?, ?, ?, ?)",
         params![
             agent.name,
             agent.description,
@@ -1078,7 +1245,11 @@ fn get_or_create_agent(db: &Database, agent: &ParsedAgent, source_path: &str) ->
             agent.permission_mode,
             skills_json,
             tags_json,
-            source_path
+            source_path,
+            agent.enabled as i32,
+            mcp_servers_json,
+            agent.color,
+            env_json
         ],
     )?;
 
@@ -1635,10 +1806,20 @@ pub fn scan_opencode_global_agents(db: &Database) -> Result<usize> {
                     } else {
                         Some(serde_json::to_string(&agent.tags).unwrap())
                     };
+                    let mcp_servers_json = if agent.mcp_servers.is_empty() {
+                        None
+                    } else {
+                        Some(serde_json::to_string(&agent.mcp_servers).unwrap())
+                    };
+                    let env_json = if agent.env.is_empty() {
+                        None
+                    } else {
+                        Some(serde_json::to_string(&agent.env).unwrap())
+                    };
 
                     let result = db.conn().execute(
-                        "INSERT INTO subagents (name, description, content, tools, model, permission_mode, skills, tags, source, source_path)
-                         VALUES (?, ?, ?, ?, ?, ?, ?, ?, 'opencode', ?)",
+                        "INSERT INTO subagents (name, description, content, tools, model, permission_mode, skills, tags, source, source_path, enabled, mcp_servers, color, env)
+                         VALUES (?, ?, ?, ?, ?, ?, ?, ?, 'opencode', ?, ?, ?, ?, ?)",
                         params![
                             agent.name,
                             agent.description,
@@ -1648,7 +1829,11 @@ pub fn scan_opencode_global_agents(db: &Database) -> Result<usize> {
                             agent.permission_mode,
                             skills_json,
                             tags_json,
-                            source_path
+                            source_path,
+                            agent.enabled as i32,
+                            mcp_servers_json,
+                            agent.color,
+                            env_json
                         ],
                     );
 
@@ -2101,6 +2286,27 @@ Body"#;
         assert_eq!(body, "Body");
     }
 
+    #[test]
+    fn test_parse_frontmatter_skips_malformed_line() {
+        let content = r#"---
+name: test
+stray
+description: Still parsed
+tools: Read, Grep
+model: sonnet
+---
+Body"#;
+
+        let (fm, body) = parse_frontmatter(content);
+
+        assert_eq!(fm.get("name"), Some(&"test".to_string()));
+        assert_eq!(fm.get("description"), Some(&"Still parsed".to_string()));
+        assert_eq!(fm.get("tools"), Some(&"Read, Grep".to_string()));
+        assert_eq!(fm.get("model"), Some(&"sonnet".to_string()));
+        assert_eq!(fm.len(), 4);
+        assert_eq!(body, "Body");
+    }
+
     #[test]
     fn test_parse_frontmatter_with_comma_separated_values() {
         let content = r#"---
@@ -2128,6 +2334,58 @@ Body"#;
         assert_eq!(fm.get("url"), Some(&"https://example.com:8080".to_string()));
     }
 
+    #[test]
+    fn test_parse_frontmatter_matches_writer_handling() {
+        let content =
+            "\u{feff}---\r\nname: test\r\nhooks:\r\n  model: nested\r\n---\r\n\r\nLine 1\r\nLine 2";
+
+        let (fm, body) = parse_frontmatter(content);
+
+        assert_eq!(fm.get("name"), Some(&"test".to_string()));
+        // Nested keys are not flattened into the top level
+        assert!(fm.get("model").is_none());
+        assert_eq!(body, "Line 1\nLine 2");
+    }
+
+    // =========================================================================
+    // parse_string_list tests
+    // =========================================================================
+
+    #[test]
+    fn test_parse_string_list_separator_variations() {
+        for input in [
+            "Read,Grep",
+            "Read, Grep",
+            "Read ,  Grep",
+            "Read, Grep,",
+            " Read,,Grep ",
+        ] {
+            assert_eq!(
+                parse_string_list(input),
+                vec!["Read", "Grep"],
+                "input: {:?}",
+                input
+            );
+        }
+    }
+
+    #[test]
+    fn test_parse_string_list_empty() {
+        assert!(parse_string_list("").is_empty());
+        assert!(parse_string_list(" , ").is_empty());
+    }
+
+    #[test]
+    fn test_parse_agent_file_irregular_tool_separators() {
+        let temp_dir = TempDir::new().unwrap();
+        let agent_path = temp_dir.path().join("irregular.md");
+
+        fs::write(&agent_path, "---\ntools: Read ,  Grep,Glob,\n---\nContent").unwrap();
+
+        let agent = parse_agent_file(&agent_path).unwrap();
+        assert_eq!(agent.tools, vec!["Read", "Grep", "Glob"]);
+    }
+
     // =========================================================================
     // parse_skill_file tests
     // =========================================================================
@@ -2241,6 +2499,89 @@ Content."#,
         assert_eq!(skill.allowed_tools, Some("Bash".to_string()));
     }
 
+    #[test]
+    fn test_list_skills_nested_layout() {
+        let temp_dir = TempDir::new().unwrap();
+        let skills_dir = temp_dir.path().join("skills");
+        for dir in ["lint", "web/fetch", "web/scrape", "web/scrape/references"] {
+            fs::create_dir_all(skills_dir.join(dir)).unwrap();
+        }
+        fs::write(
+            skills_dir.join("lint/SKILL.md"),
+            "---\ndescription: Lint\n---\nLint.",
+        )
+        .unwrap();
+        fs::write(
+            skills_dir.join("web/fetch/SKILL.md"),
+            "---\ndescription: Fetch\n---\nFetch.",
+        )
+        .unwrap();
+        fs::write(
+            skills_dir.join("web/scrape/SKILL.md"),
+            "---\ndescription: Scrape\n---\nScrape.",
+        )
+        .unwrap();
+        fs::write(
+            skills_dir.join("web/scrape/references/SKILL.md"),
+            "not a skill",
+        )
+        .unwrap();
+
+        let flat = list_skills(&skills_dir, false).unwrap();
+        let flat_names: Vec<&str> = flat.iter().map(|s| s.skill.name.as_str()).collect();
+        assert_eq!(flat_names, vec!["lint"]);
+
+        let nested = list_skills(&skills_dir, true).unwrap();
+        let names: Vec<&str> = nested.iter().map(|s| s.skill.name.as_str()).collect();
+        assert_eq!(names, vec!["lint", "web/fetch", "web/scrape"]);
+        assert_eq!(nested[1].path, skills_dir.join("web/fetch"));
+        assert_eq!(nested[1].skill.description.as_deref(), Some("Fetch"));
+    }
+
+    #[test]
+    fn test_list_skills_includes_legacy_single_file_skills() {
+        let temp_dir = TempDir::new().unwrap();
+        let skills_dir = temp_dir.path().join("skills");
+        fs::create_dir_all(skills_dir.join("lint")).unwrap();
+        fs::create_dir_all(skills_dir.join("deploy")).unwrap();
+        fs::write(
+            skills_dir.join("lint/SKILL.md"),
+            "---\ndescription: Lint (directory)\n---\nLint.",
+        )
+        .unwrap();
+        fs::write(
+            skills_dir.join("lint.md"),
+            "---\ndescription: Lint (legacy)\n---\nOld lint.",
+        )
+        .unwrap();
+        fs::write(
+            skills_dir.join("deploy/SKILL.md"),
+            "---\ndescription: Deploy\n---\nDeploy.",
+        )
+        .unwrap();
+        fs::write(
+            skills_dir.join("changelog.md"),
+            "---\ndescription: Write the changelog\n---\nSummarize commits.",
+        )
+        .unwrap();
+        fs::write(skills_dir.join("notes.txt"), "not a skill").unwrap();
+
+        let skills = list_skills(&skills_dir, false).unwrap();
+        let names: Vec<&str> = skills.iter().map(|s| s.skill.name.as_str()).collect();
+        assert_eq!(names, vec!["changelog", "deploy", "lint"]);
+
+        let legacy = &skills[0];
+        assert_eq!(legacy.path, skills_dir.join("changelog.md"));
+        assert_eq!(legacy.skill.skill_type, "skill");
+        assert_eq!(legacy.skill.content, "Summarize commits.");
+
+        // The directory form wins over a loose file with the same name
+        assert_eq!(
+            skills[2].skill.description.as_deref(),
+            Some("Lint (directory)")
+        );
+    }
+
     #[test]
     fn test_parse_skill_file_nonexistent() {
         let result = parse_skill_file(Path::new("/nonexistent/path/skill.md"));
@@ -2318,6 +2659,134 @@ Content"#,
         assert_eq!(agent.permission_mode, Some("askUser".to_string()));
     }
 
+    #[test]
+    fn test_scan_project_skills_imports_legacy_and_nested_skills() {
+        let temp_dir = TempDir::new().unwrap();
+        let skills_dir = temp_dir.path().join("skills");
+        fs::create_dir_all(skills_dir.join("web/fetch")).unwrap();
+        fs::write(
+            skills_dir.join("web/fetch/SKILL.md"),
+            "---\nname: fetch\ndescription: Fetch pages\n---\nFetch",
+        )
+        .unwrap();
+        fs::write(
+            skills_dir.join("legacy.md"),
+            "---\ndescription: Old skill\n---\nLegacy body",
+        )
+        .unwrap();
+        let db = Database::in_memory().unwrap();
+        let project_id =
+            get_or_create_project(&db, "demo", &temp_dir.path().to_string_lossy()).unwrap();
+
+        // Flat by default: only the top-level skill is found
+        assert_eq!(
+            scan_project_skills(&db, project_id, &skills_dir).unwrap(),
+            1
+        );
+        assert!(!db
+            .get_all_skills()
+            .unwrap()
+            .iter()
+            .any(|s| s.name == "web/fetch"));
+
+        db.set_setting(RECURSIVE_SKILLS_SETTING, "true").unwrap();
+        assert_eq!(
+            scan_project_skills(&db, project_id, &skills_dir).unwrap(),
+            2
+        );
+
+        let skills = db.get_all_skills().unwrap();
+        let legacy = skills.iter().find(|s| s.name == "legacy").unwrap();
+        assert_eq!(legacy.content.trim(), "Legacy body");
+        assert_eq!(
+            legacy.source_path.as_deref(),
+            Some(skills_dir.join("legacy.md").to_string_lossy().as_ref())
+        );
+        assert!(skills.iter().any(|s| s.name == "web/fetch"));
+    }
+
+    #[test]
+    fn test_disabled_agent_imported_disabled() {
+        let temp_dir = TempDir::new().unwrap();
+        let agent_path = temp_dir.path().join("paused.md");
+        fs::write(
+            &agent_path,
+            "---\ndescription: Paused agent\ndisabled: true\n---\nContent",
+        )
+        .unwrap();
+        let db = Database::in_memory().unwrap();
+
+        let agent = parse_agent_file(&agent_path).unwrap();
+        assert!(!agent.enabled);
+        let id = get_or_create_agent(&db, &agent, &agent_path.to_string_lossy()).unwrap();
+
+        let stored = db.get_subagent_by_id(id).unwrap().unwrap();
+        assert!(!stored.enabled);
+    }
+
+    #[test]
+    fn test_imported_agent_keeps_mcp_servers_when_saved() {
+        let temp_dir = TempDir::new().unwrap();
+        let agent_path = temp_dir.path().join("scoped.md");
+        fs::write(
+            &agent_path,
+            "---\ndescription: Scoped agent\nmcpServers:\n  - github\n  - postgres\n---\nContent",
+        )
+        .unwrap();
+        let db = Database::in_memory().unwrap();
+
+        let agent = parse_agent_file(&agent_path).unwrap();
+        assert_eq!(agent.mcp_servers, vec!["github", "postgres"]);
+        let id = get_or_create_agent(&db, &agent, &agent_path.to_string_lossy()).unwrap();
+
+        // Editing in the UI leaves the scoping alone
+        let edit = crate::db::models::CreateSubAgentRequest {
+            name: "scoped".to_string(),
+            description: "Edited".to_string(),
+            content: "Content".to_string(),
+            tools: None,
+            model: None,
+            permission_mode: None,
+            skills: None,
+            color: None,
+            tags: None,
+            env: None,
+        };
+        crate::commands::subagents::update_subagent_in_db(&db, id, &edit).unwrap();
+        let stored = db.get_subagent_by_id(id).unwrap().unwrap();
+        assert_eq!(
+            stored.mcp_servers,
+            Some(vec!["github".to_string(), "postgres".to_string()])
+        );
+
+        let project = TempDir::new().unwrap();
+        crate::services::subagent_writer::write_project_subagent(project.path(), &stored).unwrap();
+        let saved = parse_agent_file(&project.path().join(".claude/agents/scoped.md")).unwrap();
+        assert_eq!(saved.description, "Edited");
+        assert_eq!(saved.mcp_servers, vec!["github", "postgres"]);
+    }
+
+    #[test]
+    fn test_imported_agent_keeps_color_and_env() {
+        let temp_dir = TempDir::new().unwrap();
+        let agent_path = temp_dir.path().join("painter.md");
+        fs::write(
+            &agent_path,
+            "---\ndescription: Colorful agent\ncolor: purple\nenv:\n  - GITHUB_TOKEN\n---\nContent",
+        )
+        .unwrap();
+        let db = Database::in_memory().unwrap();
+
+        let agent = parse_agent_file(&agent_path).unwrap();
+        assert_eq!(agent.color, Some("purple".to_string()));
+        assert_eq!(agent.env, vec!["GITHUB_TOKEN"]);
+        let id = get_or_create_agent(&db, &agent, &agent_path.to_string_lossy()).unwrap();
+
+        let stored = db.get_subagent_by_id(id).unwrap().unwrap();
+        assert_eq!(stored.color, Some("purple".to_string()));
+        assert_eq!(stored.env, Some(vec!["GITHUB_TOKEN".to_string()]));
+    }
+
     #[test]
     fn test_parse_agent_file_nonexistent() {
         let result = parse_agent_file(Path::new("/nonexistent/agent.md"));
@@ -1,14 +1,25 @@
-use crate::db::models::Skill;
+use crate::db::models::{Skill, SkillFile};
+use crate::services::frontmatter::{
+    parse_yaml_map, quote_yaml_string, split_frontmatter, FrontmatterMap,
+};
+use crate::services::scanner::SHARED_SKILL_DIR;
 use crate::utils::opencode_paths::get_opencode_paths;
+use crate::utils::paths::{config_scope, ClaudeLayout};
 use anyhow::Result;
 use directories::BaseDirs;
-use std::path::Path;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashSet};
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
 
 /// Generate markdown content for an agent skill (.claude/skills/name/SKILL.md)
 pub(crate) fn generate_skill_markdown(skill: &Skill) -> String {
     let mut frontmatter = String::from("---\n");
 
-    frontmatter.push_str(&format!("name: {}\n", skill.name));
+    // Nested skills live in a directory per segment; the frontmatter names the leaf
+    let name = skill.name.rsplit('/').next().unwrap_or(&skill.name);
+    frontmatter.push_str(&format!("name: {}\n", name));
 
     if let Some(ref desc) = skill.description {
         if !desc.is_empty() {
@@ -16,6 +27,31 @@ pub(crate) fn generate_skill_markdown(skill: &Skill) -> String {
         }
     }
 
+    if let Some(when_to_use) = skill
+        .when_to_use
+        .as_deref()
+        .map(str::trim_end)
+        .filter(|w| !w.trim().is_empty())
+    {
+        frontmatter.push_str("when-to-use: |\n");
+        for line in when_to_use.lines() {
+            if line.is_empty() {
+                frontmatter.push('\n');
+            } else {
+                frontmatter.push_str(&format!("  {}\n", line));
+            }
+        }
+    }
+
+    if let Some(ref examples) = skill.examples {
+        if !examples.is_empty() {
+            frontmatter.push_str("examples:\n");
+            for example in examples {
+                frontmatter.push_str(&format!("  - {}\n", quote_yaml_string(example)));
+            }
+        }
+    }
+
     if let Some(ref tools) = skill.allowed_tools {
         if !tools.is_empty() {
             frontmatter.push_str(&format!("allowed-tools: {}\n", tools.join(", ")));
@@ -36,22 +72,491 @@ pub(crate) fn generate_skill_markdown(skill: &Skill) -> String {
     format!("{}{}", frontmatter, skill.content)
 }
 
+/// A parsed `allowed-tools` entry: a tool name with an optional argument
+/// pattern, e.g. `Read` or `Bash(git:*)`. Generation always writes the
+/// original string, so this is only used for validation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AllowedTool {
+    pub tool: String,
+    /// Argument constraint inside the parentheses (`git:*` for `Bash(git:*)`)
+    pub pattern: Option<String>,
+}
+
+fn is_valid_tool_name(name: &str) -> bool {
+    name.chars().next().is_some_and(|c| c.is_ascii_alphabetic())
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+}
+
+/// Parse an `allowed-tools` entry. Returns None for entries that are neither
+/// `Tool` nor `Tool(pattern)`.
+pub fn parse_allowed_tool(entry: &str) -> Option<AllowedTool> {
+    let entry = entry.trim();
+
+    let Some(open) = entry.find('(') else {
+        return is_valid_tool_name(entry).then(|| AllowedTool {
+            tool: entry.to_string(),
+            pattern: None,
+        });
+    };
+
+    let tool = &entry[..open];
+    let pattern = entry[open + 1..].strip_suffix(')')?.trim();
+    if !is_valid_tool_name(tool) || pattern.is_empty() {
+        return None;
+    }
+
+    Some(AllowedTool {
+        tool: tool.to_string(),
+        pattern: Some(pattern.to_string()),
+    })
+}
+
+/// Size statistics for a generated SKILL.md, used for context budgeting
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ContentStats {
+    pub bytes: usize,
+    pub lines: usize,
+    /// Rough token estimate (characters / 4)
+    pub estimated_tokens: usize,
+}
+
+/// Default token budget above which validate_skill warns
+pub const DEFAULT_MAX_SKILL_TOKENS: usize = 5000;
+
+/// Options controlling skill validation
+#[derive(Debug, Clone)]
+pub struct SkillValidationOptions {
+    pub max_estimated_tokens: usize,
+}
+
+impl Default for SkillValidationOptions {
+    fn default() -> Self {
+        Self {
+            max_estimated_tokens: DEFAULT_MAX_SKILL_TOKENS,
+        }
+    }
+}
+
+/// Result of validating a skill: errors block writing, warnings are advisory
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SkillValidation {
+    pub errors: Vec<String>,
+    pub warnings: Vec<String>,
+}
+
+impl SkillValidation {
+    #[cfg(test)]
+    pub fn is_valid(&self) -> bool {
+        self.errors.is_empty()
+    }
+}
+
+/// Compute size statistics for the SKILL.md that would be written for a skill
+pub fn skill_content_stats(skill: &Skill) -> ContentStats {
+    let markdown = generate_skill_markdown(skill);
+    let chars = markdown.chars().count();
+    ContentStats {
+        bytes: markdown.len(),
+        lines: markdown.lines().count(),
+        estimated_tokens: chars.div_ceil(4),
+    }
+}
+
+/// Validate a skill before writing it
+pub fn validate_skill(skill: &Skill, options: &SkillValidationOptions) -> SkillValidation {
+    let mut validation = SkillValidation::default();
+
+    if skill.name.trim().is_empty() {
+        validation.errors.push("Skill name is required".to_string());
+    }
+
+    for entry in skill.allowed_tools.iter().flatten() {
+        if parse_allowed_tool(entry).is_none() {
+            validation.errors.push(format!(
+                "Invalid allowed-tools entry '{}' (expected Tool or Tool(pattern))",
+                entry
+            ));
+        }
+    }
+
+    let stats = skill_content_stats(skill);
+    if stats.estimated_tokens > options.max_estimated_tokens {
+        validation.warnings.push(format!(
+            "SKILL.md is ~{} tokens ({} bytes, {} lines), above the {} token budget",
+            stats.estimated_tokens, stats.bytes, stats.lines, options.max_estimated_tokens
+        ));
+    }
+
+    validation
+}
+
+/// Add a project's baseline tools to a skill's allowed-tools, keeping the
+/// skill's own entries first and dropping duplicates. Skills without
+/// allowed-tools get the baseline.
+pub fn apply_skill_tool_policy(skill: &mut Skill, baseline: &[String]) {
+    if baseline.is_empty() {
+        return;
+    }
+
+    let tools = skill.allowed_tools.get_or_insert_with(Vec::new);
+    for tool in baseline {
+        if !tools.contains(tool) {
+            tools.push(tool.clone());
+        }
+    }
+}
+
 /// Write a skill to the appropriate location
 /// Skills go to {base_path}/.claude/skills/{name}/SKILL.md
 pub fn write_skill_file(base_path: &Path, skill: &Skill) -> Result<()> {
-    let skill_dir = base_path.join(".claude").join("skills").join(&skill.name);
+    let layout = ClaudeLayout::default();
+    let path = layout.skills_dir(base_path).join(&skill.name);
+    let span = tracing::info_span!(
+        "write_skill_file",
+        skill = %skill.name,
+        scope = config_scope(base_path),
+        path = %path.display()
+    );
+    let _entered = span.enter();
+
+    let result = write_skill_file_with_layout(base_path, skill, &layout);
+    if let Err(e) = &result {
+        tracing::error!(error = %e, "failed to write skill");
+    }
+    result
+}
+
+/// Write a skill to {base_path}/{layout.skills_subdir}/{name}/SKILL.md
+pub fn write_skill_file_with_layout(
+    base_path: &Path,
+    skill: &Skill,
+    layout: &ClaudeLayout,
+) -> Result<()> {
+    write_skill_file_with_options(base_path, skill, &[], layout, &SkillWriteOptions::default())
+}
+
+/// Options controlling how a skill directory is written
+#[derive(Debug, Clone, Default)]
+pub struct SkillWriteOptions {
+    /// Remove files in the skill directory that are not part of the current skill
+    pub sync_skill_dir: bool,
+}
+
+/// Map a skill file type to its subdirectory (mirrors the scanner)
+fn skill_file_subdir(file_type: &str) -> Option<&'static str> {
+    match file_type {
+        "reference" => Some("references"),
+        "asset" => Some("assets"),
+        "script" => Some("scripts"),
+        _ => None,
+    }
+}
+
+/// Reject names that would escape their directory
+fn ensure_plain_name(name: &str, kind: &str) -> Result<()> {
+    if name.is_empty() || name == "." || name == ".." || name.contains('/') || name.contains('\\') {
+        return Err(anyhow::anyhow!("Invalid {} name: {:?}", kind, name));
+    }
+    Ok(())
+}
+
+/// Reject skill names that would escape the skills directory. Nested skills
+/// are named by their path under it (`web/fetch`, see scanner::list_skills),
+/// so `/` may separate plain segments; the shared asset directory is refused.
+fn ensure_skill_name(name: &str) -> Result<()> {
+    let valid = name
+        .split('/')
+        .all(|segment| ensure_plain_name(segment, "skill").is_ok())
+        && name.split('/').next() != Some(SHARED_SKILL_DIR);
+    if !valid {
+        return Err(anyhow::anyhow!("Invalid skill name: {:?}", name));
+    }
+    Ok(())
+}
+
+/// Reject reference paths that are absolute, escape the skill directory or replace SKILL.md
+fn ensure_relative_reference_path(path: &str) -> Result<()> {
+    let relative = Path::new(path);
+    let is_plain = !path.is_empty()
+        && relative
+            .components()
+            .all(|c| matches!(c, std::path::Component::Normal(_)));
+    if !is_plain || relative == Path::new("SKILL.md") {
+        return Err(anyhow::anyhow!("Invalid skill reference path: {:?}", path));
+    }
+    Ok(())
+}
+
+/// Paths SKILL.md links to, relative to the skill directory: its own reference
+/// files, then its shared assets (`../_shared/...`, one more `../` per level
+/// a nested skill sits below the skills directory)
+fn reference_links(skill: &Skill) -> Vec<String> {
+    let own = skill.reference_files.iter().map(|(path, _)| path.clone());
+    let up = "../".repeat(skill.name.split('/').count());
+    let shared = skill
+        .shared_assets
+        .iter()
+        .map(|(path, _)| format!("{}{}/{}", up, SHARED_SKILL_DIR, path));
+    own.chain(shared).collect()
+}
+
+/// Append a "## References" section linking each path, unless the body
+/// already has one
+fn with_references_section(markdown: String, links: &[String]) -> String {
+    if links.is_empty() || markdown.lines().any(|l| l.trim() == "## References") {
+        return markdown;
+    }
+
+    let mut out = markdown.trim_end().to_string();
+    out.push_str("\n\n## References\n\n");
+    for path in links {
+        out.push_str(&format!("- [{}]({})\n", path, path));
+    }
+    out
+}
+
+/// Write a skill and its files to {base_path}/{layout.skills_subdir}/{name}/
+///
+/// Files go to `references/`, `assets/` or `scripts/` by type, and the skill's
+/// own reference files go to their relative paths and are linked from
+/// SKILL.md. Shared assets are written once to `_shared/` next to the skill
+/// directories and linked rather than copied. With `sync_skill_dir`, anything
+/// else in the skill directory is removed; nothing outside that directory is
+/// ever removed and symlinks are not followed.
+pub fn write_skill_file_with_options(
+    base_path: &Path,
+    skill: &Skill,
+    files: &[SkillFile],
+    layout: &ClaudeLayout,
+    options: &SkillWriteOptions,
+) -> Result<()> {
+    ensure_skill_name(&skill.name)?;
+
+    // Validate everything before touching the disk, so a bad entry can't
+    // leave a half-written skill behind
+    for (path, _) in skill.reference_files.iter().chain(&skill.shared_assets) {
+        ensure_relative_reference_path(path)?;
+    }
+    let file_subdirs = files
+        .iter()
+        .map(|file| {
+            ensure_plain_name(&file.name, "skill file")?;
+            skill_file_subdir(&file.file_type)
+                .ok_or_else(|| anyhow::anyhow!("Unknown skill file type: {}", file.file_type))
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let skill_dir = layout.skills_dir(base_path).join(&skill.name);
     std::fs::create_dir_all(&skill_dir)?;
 
+    let mut expected: HashSet<PathBuf> = HashSet::new();
+
     let file_path = skill_dir.join("SKILL.md");
-    let content = generate_skill_markdown(skill);
-    std::fs::write(file_path, content)?;
+    let content = with_references_section(generate_skill_markdown(skill), &reference_links(skill));
+    std::fs::write(&file_path, content)?;
+    expected.insert(file_path);
+
+    for (path, content) in &skill.reference_files {
+        let reference_path = skill_dir.join(path);
+        if let Some(parent) = reference_path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(&reference_path, content)?;
+        expected.insert(reference_path);
+    }
+
+    let shared_dir = layout.skills_dir(base_path).join(SHARED_SKILL_DIR);
+    for (path, content) in &skill.shared_assets {
+        let asset_path = shared_dir.join(path);
+        // Skills sharing an asset usually write identical content
+        if std::fs::read(&asset_path).is_ok_and(|bytes| bytes == content.as_bytes()) {
+            continue;
+        }
+        if let Some(parent) = asset_path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(&asset_path, content)?;
+    }
+
+    for (file, subdir) in files.iter().zip(file_subdirs) {
+        let dir = skill_dir.join(subdir);
+        std::fs::create_dir_all(&dir)?;
+        let path = dir.join(&file.name);
+        std::fs::write(&path, &file.content)?;
+        expected.insert(path);
+    }
+
+    if options.sync_skill_dir {
+        remove_orphaned_files(&skill_dir, &expected)?;
+    }
+
+    Ok(())
+}
+
+/// Remove files under `skill_dir` not in `expected`, then prune empty subdirectories
+fn remove_orphaned_files(skill_dir: &Path, expected: &HashSet<PathBuf>) -> Result<()> {
+    let mut orphaned_dirs = Vec::new();
+
+    for entry in WalkDir::new(skill_dir)
+        .min_depth(1)
+        .follow_links(false)
+        .contents_first(true)
+    {
+        let entry = entry?;
+        let path = entry.path();
+        if !path.starts_with(skill_dir) {
+            continue;
+        }
+
+        if entry.file_type().is_dir() {
+            orphaned_dirs.push(path.to_path_buf());
+        } else if !expected.contains(path) {
+            log::info!("[SkillWriter] Removing orphaned skill file {:?}", path);
+            // Removes the link itself for symlinks, never the target
+            std::fs::remove_file(path)?;
+        }
+    }
+
+    // contents_first order means children come before parents
+    for dir in orphaned_dirs {
+        if std::fs::read_dir(&dir)?.next().is_none() {
+            std::fs::remove_dir(&dir)?;
+        }
+    }
 
     Ok(())
 }
 
+/// Result of checking an installed skill against the expected content
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase", tag = "status")]
+pub enum VerifyResult {
+    Match,
+    /// Content differs: a frontmatter key, `body`, or a reference file path
+    Mismatch {
+        field: String,
+    },
+    /// SKILL.md is not on disk
+    Missing,
+}
+
+fn content_hash(bytes: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(bytes))
+}
+
+/// Name the first part of SKILL.md that differs: a frontmatter key, then the body
+fn differing_skill_field(expected: &str, actual: &str) -> String {
+    // Unparseable frontmatter compares as empty, so the mismatch is still reported
+    let parse = |content: &str| -> (FrontmatterMap, String) {
+        split_frontmatter(content)
+            .and_then(|(raw, body)| Ok((parse_yaml_map(&raw)?, body)))
+            .unwrap_or_default()
+    };
+    let (expected_frontmatter, expected_body) = parse(expected);
+    let (actual_frontmatter, actual_body) = parse(actual);
+
+    let field = [
+        "name",
+        "description",
+        "allowed-tools",
+        "model",
+        "disable-model-invocation",
+    ]
+    .into_iter()
+    .find(|key| expected_frontmatter.get(*key) != actual_frontmatter.get(*key));
+
+    match field {
+        Some(key) => key.to_string(),
+        None if expected_body != actual_body => "body".to_string(),
+        None => "frontmatter".to_string(),
+    }
+}
+
+/// Verify an installed skill at {base_path}/.claude/skills/{name}/
+pub fn verify_skill(base_path: &Path, skill: &Skill) -> Result<VerifyResult> {
+    verify_skill_with_layout(base_path, skill, &ClaudeLayout::default())
+}
+
+/// Verify an installed skill by hashing its SKILL.md, reference files and
+/// shared assets against what write_skill_file would produce for `skill`
+pub fn verify_skill_with_layout(
+    base_path: &Path,
+    skill: &Skill,
+    layout: &ClaudeLayout,
+) -> Result<VerifyResult> {
+    ensure_skill_name(&skill.name)?;
+    let skill_dir = layout.skills_dir(base_path).join(&skill.name);
+
+    let skill_md = skill_dir.join("SKILL.md");
+    if !skill_md.is_file() {
+        return Ok(VerifyResult::Missing);
+    }
+
+    let expected = with_references_section(generate_skill_markdown(skill), &reference_links(skill));
+    let actual = std::fs::read_to_string(&skill_md)?;
+    if content_hash(expected.as_bytes()) != content_hash(actual.as_bytes()) {
+        return Ok(VerifyResult::Mismatch {
+            field: differing_skill_field(&expected, &actual),
+        });
+    }
+
+    for (path, content) in &skill.reference_files {
+        ensure_relative_reference_path(path)?;
+        let matches = std::fs::read(skill_dir.join(path))
+            .is_ok_and(|bytes| content_hash(&bytes) == content_hash(content.as_bytes()));
+        if !matches {
+            return Ok(VerifyResult::Mismatch {
+                field: path.clone(),
+            });
+        }
+    }
+
+    let shared_dir = layout.skills_dir(base_path).join(SHARED_SKILL_DIR);
+    for (path, content) in &skill.shared_assets {
+        ensure_relative_reference_path(path)?;
+        let matches = std::fs::read(shared_dir.join(path))
+            .is_ok_and(|bytes| content_hash(&bytes) == content_hash(content.as_bytes()));
+        if !matches {
+            return Ok(VerifyResult::Mismatch {
+                field: format!("{}/{}", SHARED_SKILL_DIR, path),
+            });
+        }
+    }
+
+    Ok(VerifyResult::Match)
+}
+
 /// Delete a skill file from the appropriate location
 pub fn delete_skill_file(base_path: &Path, skill: &Skill) -> Result<()> {
-    let skill_dir = base_path.join(".claude").join("skills").join(&skill.name);
+    let layout = ClaudeLayout::default();
+    let path = layout.skills_dir(base_path).join(&skill.name);
+    let span = tracing::info_span!(
+        "delete_skill_file",
+        skill = %skill.name,
+        scope = config_scope(base_path),
+        path = %path.display()
+    );
+    let _entered = span.enter();
+
+    let result = delete_skill_file_with_layout(base_path, skill, &layout);
+    if let Err(e) = &result {
+        tracing::error!(error = %e, "failed to delete skill");
+    }
+    result
+}
+
+/// Delete a skill directory from {base_path}/{layout.skills_subdir}/{name}/
+pub fn delete_skill_file_with_layout(
+    base_path: &Path,
+    skill: &Skill,
+    layout: &ClaudeLayout,
+) -> Result<()> {
+    ensure_skill_name(&skill.name)?;
+    let skill_dir = layout.skills_dir(base_path).join(&skill.name);
     if skill_dir.exists() {
         std::fs::remove_dir_all(skill_dir)?;
     }
@@ -59,6 +564,89 @@ pub fn delete_skill_file(base_path: &Path, skill: &Skill) -> Result<()> {
     Ok(())
 }
 
+/// Longest skill name Claude Code accepts
+const MAX_SKILL_NAME_LENGTH: usize = 64;
+
+/// Reject skill names Claude Code won't load: at most 64 lowercase letters,
+/// numbers and hyphens
+fn ensure_valid_skill_name(name: &str) -> Result<()> {
+    ensure_plain_name(name, "skill")?;
+    if name.len() > MAX_SKILL_NAME_LENGTH
+        || !name
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+    {
+        return Err(anyhow::anyhow!(
+            "Invalid skill name '{}': use at most {} lowercase letters, numbers and hyphens",
+            name,
+            MAX_SKILL_NAME_LENGTH
+        ));
+    }
+    Ok(())
+}
+
+/// Replace the top-level `name:` of a SKILL.md frontmatter block, keeping
+/// every other line (and its line ending) as is
+fn with_frontmatter_name(content: &str, name: &str) -> String {
+    let mut result = String::with_capacity(content.len());
+    let mut in_frontmatter = false;
+    for (index, line) in content.split_inclusive('\n').enumerate() {
+        let text = line.trim_end_matches(['\r', '\n']);
+        let ending = &line[text.len()..];
+        let text = if index == 0 {
+            text.strip_prefix('\u{feff}').unwrap_or(text)
+        } else {
+            text
+        };
+        if text.trim_end() == "---" {
+            in_frontmatter = index == 0;
+        } else if in_frontmatter && text.starts_with("name:") {
+            result.push_str(&format!("name: {}{}", name, ending));
+            continue;
+        }
+        result.push_str(line);
+    }
+    result
+}
+
+/// Rename a skill: move {base_path}/.claude/skills/{old_name}/ to
+/// {new_name}/ and update the `name:` in its SKILL.md to match. Fails if the
+/// new name is invalid, the skill doesn't exist or the target directory
+/// does; the move is undone if SKILL.md can't be updated.
+pub fn rename_skill(base_path: &Path, old_name: &str, new_name: &str) -> Result<()> {
+    ensure_skill_name(old_name)?;
+    ensure_valid_skill_name(new_name)?;
+
+    let skills_dir = ClaudeLayout::default().skills_dir(base_path);
+    let old_dir = skills_dir.join(old_name);
+    let new_dir = skills_dir.join(new_name);
+    if !old_dir.is_dir() {
+        return Err(anyhow::anyhow!("Skill '{}' not found", old_name));
+    }
+    if new_dir.exists() {
+        return Err(anyhow::anyhow!(
+            "Cannot rename skill '{}': '{}' already exists",
+            old_name,
+            new_name
+        ));
+    }
+
+    std::fs::rename(&old_dir, &new_dir)?;
+
+    let skill_file = new_dir.join("SKILL.md");
+    let updated = std::fs::read_to_string(&skill_file)
+        .and_then(|content| std::fs::write(&skill_file, with_frontmatter_name(&content, new_name)));
+    if let Err(e) = updated {
+        std::fs::rename(&new_dir, &old_dir)?;
+        return Err(anyhow::anyhow!(
+            "Failed to update {}: {}",
+            skill_file.display(),
+            e
+        ));
+    }
+    Ok(())
+}
+
 /// Write a skill to the global Claude config (~/.claude/)
 pub fn write_global_skill(skill: &Skill) -> Result<()> {
     let base_dirs =
@@ -89,6 +677,95 @@ pub fn delete_project_skill(project_path: &Path, skill: &Skill) -> Result<()> {
 // OpenCode Support
 // ============================================================================
 
+/// OpenCode permission for one axis: allowed outright, or only for some
+/// command patterns (bash only)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OpenCodePermission {
+    Allow,
+    Patterns(Vec<String>),
+}
+
+/// OpenCode permission axis an allowed tool grants (None for tools OpenCode
+/// doesn't gate, like Read)
+fn opencode_permission_axis(tool: &str) -> Option<&'static str> {
+    match tool {
+        "Bash" => Some("bash"),
+        "Edit" | "MultiEdit" | "Write" | "NotebookEdit" => Some("edit"),
+        "WebFetch" => Some("webfetch"),
+        _ => None,
+    }
+}
+
+/// Derive an OpenCode `permission` object from a skill's allowed tools.
+///
+/// `Bash(git:*)` becomes the bash pattern `git *`; an unrestricted `Bash`
+/// allows every command. Tools without a permission axis are ignored.
+pub fn derive_opencode_permissions(
+    allowed_tools: &[String],
+) -> BTreeMap<&'static str, OpenCodePermission> {
+    let mut permissions: BTreeMap<&'static str, OpenCodePermission> = BTreeMap::new();
+
+    for entry in allowed_tools {
+        let Some(allowed) = parse_allowed_tool(entry) else {
+            continue;
+        };
+        let Some(axis) = opencode_permission_axis(&allowed.tool) else {
+            continue;
+        };
+
+        match (allowed.pattern, permissions.get_mut(axis)) {
+            (_, Some(OpenCodePermission::Allow)) => {}
+            (Some(pattern), existing) if axis == "bash" => {
+                let pattern = match pattern.strip_suffix(":*") {
+                    Some(prefix) => format!("{} *", prefix),
+                    None => pattern,
+                };
+                match existing {
+                    Some(OpenCodePermission::Patterns(patterns)) => patterns.push(pattern),
+                    _ => {
+                        permissions.insert(axis, OpenCodePermission::Patterns(vec![pattern]));
+                    }
+                }
+            }
+            _ => {
+                permissions.insert(axis, OpenCodePermission::Allow);
+            }
+        }
+    }
+
+    permissions
+}
+
+/// Generate an OpenCode agent file for a skill: the SKILL.md frontmatter
+/// plus a `permission` block derived from allowed tools
+pub(crate) fn generate_skill_markdown_opencode(skill: &Skill) -> String {
+    let markdown = generate_skill_markdown(skill);
+    let permissions =
+        derive_opencode_permissions(skill.allowed_tools.as_deref().unwrap_or_default());
+    if permissions.is_empty() {
+        return markdown;
+    }
+
+    let mut block = String::from("permission:\n");
+    for (axis, permission) in &permissions {
+        match permission {
+            OpenCodePermission::Allow => block.push_str(&format!("  {}: allow\n", axis)),
+            OpenCodePermission::Patterns(patterns) => {
+                block.push_str(&format!("  {}:\n", axis));
+                for pattern in patterns {
+                    block.push_str(&format!("    \"{}\": allow\n", pattern));
+                }
+            }
+        }
+    }
+
+    // Insert before the closing frontmatter delimiter
+    match markdown.find("\n---\n") {
+        Some(end) => format!("{}\n{}{}", &markdown[..end], block, &markdown[end + 1..]),
+        None => markdown,
+    }
+}
+
 /// Write a skill to OpenCode's format
 /// Agent skills go to {base_path}/agent/{name}.md (OpenCode uses agent/ not skills/)
 pub fn write_skill_file_opencode(base_path: &Path, skill: &Skill) -> Result<()> {
@@ -96,7 +773,7 @@ pub fn write_skill_file_opencode(base_path: &Path, skill: &Skill) -> Result<()>
     std::fs::create_dir_all(&agent_dir)?;
 
     let file_path = agent_dir.join(format!("{}.md", skill.name));
-    let content = generate_skill_markdown(skill);
+    let content = generate_skill_markdown_opencode(skill);
     std::fs::write(file_path, content)?;
 
     Ok(())
@@ -112,6 +789,100 @@ pub fn delete_skill_file_opencode(base_path: &Path, skill: &Skill) -> Result<()>
     Ok(())
 }
 
+/// Frontmatter keys only skills have, marking a flat agent/ file as a skill
+const SKILL_ONLY_KEYS: &[&str] = &[
+    "allowed-tools",
+    "disable-model-invocation",
+    "when-to-use",
+    "examples",
+];
+
+/// Whether an agent/ file was written by the legacy write_skill_file_opencode:
+/// it has no agent `mode` and has a skill-only key. A `name` alone isn't
+/// enough, since hand-written OpenCode agents may have one too.
+fn is_legacy_opencode_skill(frontmatter: &FrontmatterMap, raw: &str) -> bool {
+    let has_key = |key: &str| {
+        raw.lines()
+            .any(|line| line.starts_with(&format!("{}:", key)))
+    };
+    !frontmatter.contains_key("mode") && SKILL_ONLY_KEYS.iter().any(|key| has_key(key))
+}
+
+/// Move skills written flat to {base_path}/agent/{name}.md by the legacy
+/// OpenCode writer into {base_path}/skill/{name}/SKILL.md. Agents in agent/
+/// are left alone, as are skills whose target already exists with other
+/// content. Legacy skills without any skill-only key can't be told apart from
+/// agents and stay where they are. Files that can't be read or moved are
+/// skipped with a warning. Returns the migrated names, sorted; running it
+/// again is a no-op.
+pub fn migrate_opencode_skills(base_path: &Path) -> Result<Vec<String>> {
+    let agent_dir = base_path.join("agent");
+    if !agent_dir.is_dir() {
+        return Ok(Vec::new());
+    }
+
+    let mut migrated = Vec::new();
+    for entry in std::fs::read_dir(&agent_dir)? {
+        let path = entry?.path();
+        if !path.is_file() || path.extension().is_none_or(|ext| ext != "md") {
+            continue;
+        }
+        match migrate_opencode_skill(base_path, &path) {
+            Ok(Some(name)) => {
+                log::info!("[SkillWriter] Migrated OpenCode skill '{}'", name);
+                migrated.push(name);
+            }
+            Ok(None) => {}
+            Err(e) => log::warn!("[SkillWriter] Skipping {:?}: {}", path, e),
+        }
+    }
+
+    migrated.sort();
+    Ok(migrated)
+}
+
+/// Migrate one agent/ file if it is a legacy skill, returning its name
+fn migrate_opencode_skill(base_path: &Path, path: &Path) -> Result<Option<String>> {
+    let content = std::fs::read_to_string(path)?;
+    let Ok((raw, _)) = split_frontmatter(&content) else {
+        return Ok(None);
+    };
+    let Ok(frontmatter) = parse_yaml_map(&raw) else {
+        return Ok(None);
+    };
+    if !is_legacy_opencode_skill(&frontmatter, &raw) {
+        return Ok(None);
+    }
+
+    let name = match frontmatter.get("name") {
+        Some(name) => name.clone(),
+        None => path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().to_string())
+            .unwrap_or_default(),
+    };
+    ensure_plain_name(&name, "skill")?;
+
+    let skill_dir = base_path.join("skill").join(&name);
+    let target = skill_dir.join("SKILL.md");
+    if target.exists() {
+        if std::fs::read_to_string(&target)? != content {
+            log::warn!(
+                "[SkillWriter] Not migrating {:?}: {:?} already exists",
+                path,
+                target
+            );
+            return Ok(None);
+        }
+        // Copied by an earlier, interrupted run
+        std::fs::remove_file(path)?;
+    } else {
+        std::fs::create_dir_all(&skill_dir)?;
+        std::fs::rename(path, &target)?;
+    }
+    Ok(Some(name))
+}
+
 /// Write a skill to the global OpenCode config (~/.config/opencode/)
 pub fn write_global_skill_opencode(skill: &Skill) -> Result<()> {
     let paths = get_opencode_paths()?;
@@ -139,6 +910,7 @@ pub fn delete_project_skill_opencode(project_path: &Path, skill: &Skill) -> Resu
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::services::scanner::list_skills;
     use tempfile::TempDir;
 
     // =========================================================================
@@ -160,6 +932,10 @@ mod tests {
             is_favorite: false,
             created_at: "2024-01-01".to_string(),
             updated_at: "2024-01-01".to_string(),
+            when_to_use: None,
+            examples: None,
+            reference_files: Vec::new(),
+            shared_assets: Vec::new(),
         }
     }
 
@@ -178,6 +954,10 @@ mod tests {
             is_favorite: false,
             created_at: "2024-01-01".to_string(),
             updated_at: "2024-01-01".to_string(),
+            when_to_use: None,
+            examples: None,
+            reference_files: Vec::new(),
+            shared_assets: Vec::new(),
         }
     }
 
@@ -185,6 +965,47 @@ mod tests {
     // generate_skill_markdown tests
     // =========================================================================
 
+    #[test]
+    fn test_when_to_use_and_examples_round_trip() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut skill = sample_skill();
+        skill.when_to_use =
+            Some("Use when reviewing a pull request.\n\nNot for drafting code.".to_string());
+        skill.examples = Some(vec![
+            "Review my latest PR".to_string(),
+            "Check this diff: any \"bugs\"?".to_string(),
+        ]);
+
+        let md = generate_skill_markdown(&skill);
+        assert!(md.contains(
+            "when-to-use: |\n  Use when reviewing a pull request.\n\n  Not for drafting code.\n"
+        ));
+        assert!(md.contains(
+            "examples:\n  - \"Review my latest PR\"\n  - \"Check this diff: any \\\"bugs\\\"?\"\n"
+        ));
+
+        write_skill_file(temp_dir.path(), &skill).unwrap();
+        let skills = list_skills(&temp_dir.path().join(".claude/skills"), false).unwrap();
+        let parsed = &skills[0].skill;
+
+        assert_eq!(parsed.when_to_use, skill.when_to_use);
+        assert_eq!(Some(parsed.examples.clone()), skill.examples);
+        assert_eq!(parsed.description.as_deref(), Some("An agent skill"));
+        assert_eq!(parsed.model.as_deref(), Some("opus"));
+    }
+
+    #[test]
+    fn test_generate_skill_markdown_omits_empty_when_to_use_and_examples() {
+        let mut skill = sample_minimal_skill();
+        skill.when_to_use = Some("  \n".to_string());
+        skill.examples = Some(Vec::new());
+
+        let md = generate_skill_markdown(&skill);
+
+        assert!(!md.contains("when-to-use"));
+        assert!(!md.contains("examples"));
+    }
+
     #[test]
     fn test_generate_skill_markdown_full() {
         let skill = sample_skill();
@@ -219,6 +1040,143 @@ mod tests {
         assert!(md.contains("name: minimal\n"));
     }
 
+    // =========================================================================
+    // apply_skill_tool_policy tests
+    // =========================================================================
+
+    #[test]
+    fn test_apply_skill_tool_policy_unions_baseline() {
+        let mut skill = sample_skill();
+        let baseline = vec!["Read".to_string(), "Bash".to_string(), "Read".to_string()];
+
+        apply_skill_tool_policy(&mut skill, &baseline);
+
+        assert_eq!(
+            skill.allowed_tools,
+            Some(vec![
+                "Bash".to_string(),
+                "Glob".to_string(),
+                "Read".to_string()
+            ])
+        );
+        assert!(generate_skill_markdown(&skill).contains("allowed-tools: Bash, Glob, Read\n"));
+    }
+
+    #[test]
+    fn test_apply_skill_tool_policy_gives_baseline_to_skill_without_tools() {
+        let mut skill = sample_minimal_skill();
+        apply_skill_tool_policy(&mut skill, &[]);
+        assert_eq!(skill.allowed_tools, None);
+
+        apply_skill_tool_policy(&mut skill, &["Read".to_string(), "Grep".to_string()]);
+        assert_eq!(
+            skill.allowed_tools,
+            Some(vec!["Read".to_string(), "Grep".to_string()])
+        );
+    }
+
+    // =========================================================================
+    // skill_content_stats / validate_skill tests
+    // =========================================================================
+
+    #[test]
+    fn test_skill_content_stats() {
+        let mut skill = sample_minimal_skill();
+        skill.content = "line one\nline two".to_string();
+        let stats = skill_content_stats(&skill);
+
+        let md = generate_skill_markdown(&skill);
+        assert_eq!(stats.bytes, md.len());
+        assert_eq!(stats.lines, md.lines().count());
+        assert_eq!(stats.estimated_tokens, md.len().div_ceil(4));
+    }
+
+    #[test]
+    fn test_validate_skill_warns_above_token_budget() {
+        let mut skill = sample_minimal_skill();
+        skill.content = "x".repeat(4000);
+        let options = SkillValidationOptions {
+            max_estimated_tokens: 500,
+        };
+
+        let validation = validate_skill(&skill, &options);
+        assert!(validation.is_valid());
+        assert_eq!(validation.warnings.len(), 1);
+        assert!(validation.warnings[0].contains("500 token budget"));
+    }
+
+    #[test]
+    fn test_validate_skill_no_warning_below_token_budget() {
+        let skill = sample_minimal_skill();
+        let options = SkillValidationOptions {
+            max_estimated_tokens: 500,
+        };
+
+        let validation = validate_skill(&skill, &options);
+        assert!(validation.is_valid());
+        assert!(validation.warnings.is_empty());
+    }
+
+    #[test]
+    fn test_validate_skill_requires_name() {
+        let mut skill = sample_minimal_skill();
+        skill.name = "  ".to_string();
+
+        let validation = validate_skill(&skill, &SkillValidationOptions::default());
+        assert!(!validation.is_valid());
+    }
+
+    #[test]
+    fn test_parse_allowed_tool_with_pattern() {
+        assert_eq!(
+            parse_allowed_tool("Bash(git:*)"),
+            Some(AllowedTool {
+                tool: "Bash".to_string(),
+                pattern: Some("git:*".to_string()),
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_allowed_tool_plain() {
+        assert_eq!(
+            parse_allowed_tool("Read"),
+            Some(AllowedTool {
+                tool: "Read".to_string(),
+                pattern: None,
+            })
+        );
+        assert!(parse_allowed_tool("mcp__github__create_issue").is_some());
+    }
+
+    #[test]
+    fn test_parse_allowed_tool_rejects_bogus_entries() {
+        for entry in ["", "Bash(git:*", "Bash()", "(git:*)", "Read Write", "Bash)"] {
+            assert_eq!(parse_allowed_tool(entry), None, "entry: {:?}", entry);
+        }
+    }
+
+    #[test]
+    fn test_validate_skill_allowed_tools() {
+        let mut skill = sample_minimal_skill();
+        skill.allowed_tools = Some(vec!["Bash(git:*)".to_string(), "Read".to_string()]);
+        assert!(validate_skill(&skill, &SkillValidationOptions::default()).is_valid());
+
+        skill.allowed_tools = Some(vec!["Bash(git:*".to_string()]);
+        let validation = validate_skill(&skill, &SkillValidationOptions::default());
+        assert!(!validation.is_valid());
+        assert!(validation.errors[0].contains("Bash(git:*"));
+    }
+
+    #[test]
+    fn test_generate_skill_markdown_preserves_tool_patterns() {
+        let mut skill = sample_minimal_skill();
+        skill.allowed_tools = Some(vec!["Bash(git:*)".to_string(), "Read".to_string()]);
+
+        let md = generate_skill_markdown(&skill);
+        assert!(md.contains("allowed-tools: Bash(git:*), Read\n"));
+    }
+
     // =========================================================================
     // write_skill_file tests (file system)
     // =========================================================================
@@ -281,6 +1239,358 @@ mod tests {
         assert!(!skill_dir.exists());
     }
 
+    #[test]
+    fn test_rename_skill_moves_directory_and_updates_name() {
+        let temp_dir = TempDir::new().unwrap();
+        let skill = sample_skill();
+        write_skill_file_with_options(
+            temp_dir.path(),
+            &skill,
+            &[sample_skill_file("reference", "guide.md")],
+            &ClaudeLayout::default(),
+            &SkillWriteOptions::default(),
+        )
+        .unwrap();
+        let skills_dir = temp_dir.path().join(".claude").join("skills");
+
+        rename_skill(temp_dir.path(), "test-agent", "code-helper").unwrap();
+
+        assert!(!skills_dir.join("test-agent").exists());
+        let new_dir = skills_dir.join("code-helper");
+        assert!(new_dir.join("references/guide.md").exists());
+        let content = std::fs::read_to_string(new_dir.join("SKILL.md")).unwrap();
+        assert!(content.contains("name: code-helper\n"));
+        assert!(!content.contains("test-agent"));
+
+        let err = rename_skill(temp_dir.path(), "code-helper", "Bad Name").unwrap_err();
+        assert!(err.to_string().contains("Invalid skill name"));
+    }
+
+    #[test]
+    fn test_rename_skill_refuses_existing_target() {
+        let temp_dir = TempDir::new().unwrap();
+        let skill = sample_skill();
+        write_skill_file(temp_dir.path(), &skill).unwrap();
+        let mut other = sample_skill();
+        other.name = "taken".to_string();
+        write_skill_file(temp_dir.path(), &other).unwrap();
+
+        let err = rename_skill(temp_dir.path(), "test-agent", "taken").unwrap_err();
+
+        assert!(err.to_string().contains("already exists"));
+        let skills_dir = temp_dir.path().join(".claude").join("skills");
+        assert!(skills_dir.join("test-agent/SKILL.md").exists());
+        let taken = std::fs::read_to_string(skills_dir.join("taken/SKILL.md")).unwrap();
+        assert!(taken.contains("name: taken"));
+    }
+
+    fn sample_skill_file(file_type: &str, name: &str) -> SkillFile {
+        SkillFile {
+            id: 1,
+            skill_id: 1,
+            file_type: file_type.to_string(),
+            name: name.to_string(),
+            content: format!("content of {}", name),
+            created_at: "2024-01-01".to_string(),
+            updated_at: "2024-01-01".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_write_skill_file_writes_files_by_type() {
+        let temp_dir = TempDir::new().unwrap();
+        let files = vec![
+            sample_skill_file("reference", "api.md"),
+            sample_skill_file("script", "run.sh"),
+        ];
+
+        write_skill_file_with_options(
+            temp_dir.path(),
+            &sample_skill(),
+            &files,
+            &ClaudeLayout::default(),
+            &SkillWriteOptions::default(),
+        )
+        .unwrap();
+
+        let skill_dir = temp_dir.path().join(".claude/skills/test-agent");
+        assert_eq!(
+            std::fs::read_to_string(skill_dir.join("references/api.md")).unwrap(),
+            "content of api.md"
+        );
+        assert!(skill_dir.join("scripts/run.sh").exists());
+    }
+
+    #[test]
+    fn test_sync_write_removes_orphaned_files() {
+        let temp_dir = TempDir::new().unwrap();
+        let skill = sample_skill();
+        let skill_dir = temp_dir.path().join(".claude/skills/test-agent");
+
+        // Seed an orphan asset, a stray file and a sibling skill
+        std::fs::create_dir_all(skill_dir.join("assets")).unwrap();
+        std::fs::create_dir_all(skill_dir.join("scripts")).unwrap();
+        std::fs::write(skill_dir.join("assets/old.png"), "old").unwrap();
+        std::fs::write(skill_dir.join("scripts/old.sh"), "old").unwrap();
+        std::fs::write(skill_dir.join("notes.txt"), "stray").unwrap();
+        let sibling = temp_dir.path().join(".claude/skills/other/SKILL.md");
+        std::fs::create_dir_all(sibling.parent().unwrap()).unwrap();
+        std::fs::write(&sibling, "other").unwrap();
+
+        let files = vec![sample_skill_file("asset", "new.png")];
+        write_skill_file_with_options(
+            temp_dir.path(),
+            &skill,
+            &files,
+            &ClaudeLayout::default(),
+            &SkillWriteOptions {
+                sync_skill_dir: true,
+            },
+        )
+        .unwrap();
+
+        assert!(skill_dir.join("SKILL.md").exists());
+        assert!(skill_dir.join("assets/new.png").exists());
+        assert!(!skill_dir.join("assets/old.png").exists());
+        assert!(!skill_dir.join("notes.txt").exists());
+        assert!(!skill_dir.join("scripts").exists());
+        assert!(sibling.exists());
+    }
+
+    #[test]
+    fn test_write_without_sync_keeps_existing_files() {
+        let temp_dir = TempDir::new().unwrap();
+        let skill_dir = temp_dir.path().join(".claude/skills/test-agent");
+        std::fs::create_dir_all(skill_dir.join("assets")).unwrap();
+        std::fs::write(skill_dir.join("assets/old.png"), "old").unwrap();
+
+        write_skill_file(temp_dir.path(), &sample_skill()).unwrap();
+
+        assert!(skill_dir.join("assets/old.png").exists());
+    }
+
+    #[test]
+    fn test_write_skill_file_writes_and_links_reference_files() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut skill = sample_skill();
+        skill.reference_files = vec![
+            ("reference/api.md".to_string(), "# API".to_string()),
+            ("FORMS.md".to_string(), "# Forms".to_string()),
+        ];
+
+        write_skill_file(temp_dir.path(), &skill).unwrap();
+
+        let skill_dir = temp_dir.path().join(".claude/skills/test-agent");
+        assert_eq!(
+            std::fs::read_to_string(skill_dir.join("reference/api.md")).unwrap(),
+            "# API"
+        );
+        assert_eq!(
+            std::fs::read_to_string(skill_dir.join("FORMS.md")).unwrap(),
+            "# Forms"
+        );
+
+        let skill_md = std::fs::read_to_string(skill_dir.join("SKILL.md")).unwrap();
+        assert!(skill_md.contains("## References"));
+        assert!(skill_md.contains("- [reference/api.md](reference/api.md)"));
+        assert!(skill_md.contains("- [FORMS.md](FORMS.md)"));
+    }
+
+    #[test]
+    fn test_write_nested_skill() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut skill = sample_skill();
+        skill.name = "web/fetch".to_string();
+        skill.shared_assets = vec![("style.md".to_string(), "# Style".to_string())];
+
+        write_skill_file(temp_dir.path(), &skill).unwrap();
+
+        let skill_md =
+            std::fs::read_to_string(temp_dir.path().join(".claude/skills/web/fetch/SKILL.md"))
+                .unwrap();
+        assert!(skill_md.contains("name: fetch\n"));
+        assert!(skill_md.contains("(../../_shared/style.md)"));
+
+        let listed = list_skills(&temp_dir.path().join(".claude/skills"), true).unwrap();
+        assert_eq!(listed.len(), 1);
+        assert_eq!(listed[0].skill.name, "web/fetch");
+        assert_eq!(listed[0].skill.shared_assets, vec!["style.md"]);
+
+        for name in ["web/../x", "_shared/x", "web//fetch", "/web"] {
+            skill.name = name.to_string();
+            assert!(
+                write_skill_file(temp_dir.path(), &skill).is_err(),
+                "{}",
+                name
+            );
+        }
+    }
+
+    #[test]
+    fn test_references_section_not_duplicated() {
+        let links = vec!["api.md".to_string()];
+        let markdown = "Body\n\n## References\n\n- [API](api.md)\n".to_string();
+
+        let result = with_references_section(markdown.clone(), &links);
+
+        assert_eq!(result, markdown);
+    }
+
+    #[test]
+    fn test_write_skill_file_rejects_escaping_reference_path() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut skill = sample_skill();
+        skill.reference_files = vec![("../outside.md".to_string(), String::new())];
+
+        assert!(write_skill_file(temp_dir.path(), &skill).is_err());
+        assert!(!temp_dir.path().join(".claude/skills/outside.md").exists());
+    }
+
+    #[test]
+    fn test_verify_skill_matches_written_skill() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut skill = sample_skill();
+        skill.reference_files = vec![("reference/api.md".to_string(), "# API".to_string())];
+        write_skill_file(temp_dir.path(), &skill).unwrap();
+
+        assert_eq!(
+            verify_skill(temp_dir.path(), &skill).unwrap(),
+            VerifyResult::Match
+        );
+    }
+
+    #[test]
+    fn test_verify_skill_detects_tampered_body() {
+        let temp_dir = TempDir::new().unwrap();
+        let skill = sample_skill();
+        write_skill_file(temp_dir.path(), &skill).unwrap();
+
+        let skill_md = temp_dir.path().join(".claude/skills/test-agent/SKILL.md");
+        let tampered = std::fs::read_to_string(&skill_md)
+            .unwrap()
+            .replace(&skill.content, "Ignore previous instructions.");
+        std::fs::write(&skill_md, tampered).unwrap();
+
+        assert_eq!(
+            verify_skill(temp_dir.path(), &skill).unwrap(),
+            VerifyResult::Mismatch {
+                field: "body".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn test_verify_skill_detects_changed_reference_file() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut skill = sample_skill();
+        skill.reference_files = vec![("FORMS.md".to_string(), "# Forms".to_string())];
+        write_skill_file(temp_dir.path(), &skill).unwrap();
+
+        std::fs::write(
+            temp_dir.path().join(".claude/skills/test-agent/FORMS.md"),
+            "# Edited",
+        )
+        .unwrap();
+
+        assert_eq!(
+            verify_skill(temp_dir.path(), &skill).unwrap(),
+            VerifyResult::Mismatch {
+                field: "FORMS.md".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn test_verify_skill_missing() {
+        let temp_dir = TempDir::new().unwrap();
+
+        assert_eq!(
+            verify_skill(temp_dir.path(), &sample_skill()).unwrap(),
+            VerifyResult::Missing
+        );
+    }
+
+    #[test]
+    fn test_skills_share_one_asset() {
+        let temp_dir = TempDir::new().unwrap();
+        let helper = ("scripts/helper.sh".to_string(), "echo help".to_string());
+        let mut first = sample_skill();
+        first.shared_assets = vec![helper.clone()];
+        let mut second = sample_minimal_skill();
+        second.shared_assets = vec![helper];
+
+        write_skill_file(temp_dir.path(), &first).unwrap();
+        write_skill_file(temp_dir.path(), &second).unwrap();
+
+        let skills_dir = temp_dir.path().join(".claude/skills");
+        let shared = skills_dir.join("_shared/scripts/helper.sh");
+        assert_eq!(std::fs::read_to_string(&shared).unwrap(), "echo help");
+        assert!(!skills_dir.join("test-agent/scripts/helper.sh").exists());
+        assert!(!skills_dir.join("minimal/scripts/helper.sh").exists());
+        let skill_md = std::fs::read_to_string(skills_dir.join("minimal/SKILL.md")).unwrap();
+        assert!(skill_md.contains("- [../_shared/scripts/helper.sh](../_shared/scripts/helper.sh)"));
+
+        // The shared directory is not a skill; each skill reports its link
+        let listed = list_skills(&skills_dir, true).unwrap();
+        let names: Vec<&str> = listed.iter().map(|s| s.skill.name.as_str()).collect();
+        assert_eq!(names, vec!["minimal", "test-agent"]);
+        for skill in &listed {
+            assert_eq!(skill.skill.shared_assets, vec!["scripts/helper.sh"]);
+        }
+
+        assert_eq!(
+            verify_skill(temp_dir.path(), &first).unwrap(),
+            VerifyResult::Match
+        );
+        std::fs::write(&shared, "echo changed").unwrap();
+        assert_eq!(
+            verify_skill(temp_dir.path(), &second).unwrap(),
+            VerifyResult::Mismatch {
+                field: "_shared/scripts/helper.sh".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn test_write_skill_file_rejects_path_traversal() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut skill = sample_skill();
+        skill.name = "..".to_string();
+        assert!(write_skill_file(temp_dir.path(), &skill).is_err());
+
+        let files = vec![sample_skill_file("asset", "../escape.txt")];
+        let result = write_skill_file_with_options(
+            temp_dir.path(),
+            &sample_skill(),
+            &files,
+            &ClaudeLayout::default(),
+            &SkillWriteOptions {
+                sync_skill_dir: true,
+            },
+        );
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_invalid_skill_file_writes_nothing() {
+        let temp_dir = TempDir::new().unwrap();
+        let files = vec![
+            sample_skill_file("reference", "api.md"),
+            sample_skill_file("bogus", "data.bin"),
+        ];
+
+        let result = write_skill_file_with_options(
+            temp_dir.path(),
+            &sample_skill(),
+            &files,
+            &ClaudeLayout::default(),
+            &SkillWriteOptions::default(),
+        );
+
+        assert!(result.is_err());
+        assert!(!temp_dir.path().join(".claude/skills/test-agent").exists());
+    }
+
     #[test]
     fn test_delete_nonexistent_skill_succeeds() {
         let temp_dir = TempDir::new().unwrap();
@@ -307,6 +1617,37 @@ mod tests {
         assert!(expected_path.exists());
     }
 
+    #[test]
+    fn test_generate_skill_markdown_opencode_derives_permissions() {
+        let mut skill = sample_skill();
+        skill.allowed_tools = Some(vec!["Bash".to_string(), "Read".to_string()]);
+
+        let md = generate_skill_markdown_opencode(&skill);
+
+        assert!(md.contains("allowed-tools: Bash, Read\n"));
+        assert!(md.contains("permission:\n  bash: allow\n---\n"));
+        assert!(!md.contains("read: allow"));
+    }
+
+    #[test]
+    fn test_derive_opencode_permissions_bash_patterns() {
+        let permissions = derive_opencode_permissions(&[
+            "Bash(git:*)".to_string(),
+            "Bash(npm test)".to_string(),
+            "Write".to_string(),
+        ]);
+
+        assert_eq!(
+            permissions.get("bash"),
+            Some(&OpenCodePermission::Patterns(vec![
+                "git *".to_string(),
+                "npm test".to_string()
+            ]))
+        );
+        assert_eq!(permissions.get("edit"), Some(&OpenCodePermission::Allow));
+        assert_eq!(permissions.len(), 2);
+    }
+
     #[test]
     fn test_delete_skill_file_opencode() {
         let temp_dir = TempDir::new().unwrap();
@@ -319,4 +1660,58 @@ mod tests {
         delete_skill_file_opencode(temp_dir.path(), &skill).unwrap();
         assert!(!file_path.exists());
     }
+
+    #[test]
+    fn test_migrate_opencode_skills_moves_flat_skill() {
+        let temp_dir = TempDir::new().unwrap();
+        let skill = sample_skill();
+        write_skill_file_opencode(temp_dir.path(), &skill).unwrap();
+        let agent_dir = temp_dir.path().join("agent");
+        let agent = "---\ndescription: \"Reviews code\"\nmode: subagent\n---\n\nYou review code.";
+        std::fs::write(agent_dir.join("reviewer.md"), agent).unwrap();
+
+        let migrated = migrate_opencode_skills(temp_dir.path()).unwrap();
+
+        assert_eq!(migrated, vec!["test-agent"]);
+        let target = temp_dir.path().join("skill/test-agent/SKILL.md");
+        assert_eq!(
+            std::fs::read_to_string(&target).unwrap(),
+            generate_skill_markdown_opencode(&skill)
+        );
+        assert!(!agent_dir.join("test-agent.md").exists());
+        assert!(agent_dir.join("reviewer.md").exists());
+
+        assert!(migrate_opencode_skills(temp_dir.path()).unwrap().is_empty());
+        assert!(target.exists());
+    }
+
+    #[test]
+    fn test_migrate_opencode_skills_leaves_agents_and_skips_bad_files() {
+        let temp_dir = TempDir::new().unwrap();
+        let agent_dir = temp_dir.path().join("agent");
+        std::fs::create_dir_all(&agent_dir).unwrap();
+        // A hand-written agent with a name and no mode is not a skill
+        let agent = "---\nname: planner\ndescription: Plans work\n---\n\nPlan.";
+        std::fs::write(agent_dir.join("planner.md"), agent).unwrap();
+        std::fs::write(agent_dir.join("binary.md"), [0xff, 0xfe, 0x00]).unwrap();
+        std::fs::write(
+            agent_dir.join("escape.md"),
+            "---\nname: ../escape\nallowed-tools: Read\n---\n\nBody",
+        )
+        .unwrap();
+        let mut skill = sample_skill();
+        skill.disable_model_invocation = false;
+        skill.allowed_tools = Some(vec!["Read".to_string()]);
+        write_skill_file_opencode(temp_dir.path(), &skill).unwrap();
+
+        let migrated = migrate_opencode_skills(temp_dir.path()).unwrap();
+
+        assert_eq!(migrated, vec!["test-agent"]);
+        assert_eq!(
+            std::fs::read_to_string(agent_dir.join("planner.md")).unwrap(),
+            agent
+        );
+        assert!(agent_dir.join("binary.md").exists());
+        assert!(agent_dir.join("escape.md").exists());
+    }
 }
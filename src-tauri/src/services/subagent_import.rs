@@ -0,0 +1,422 @@
+use crate::db::models::SubAgent;
+use crate::services::agent_ignore::IgnoreRules;
+use crate::services::scanner::parse_agent_file;
+use crate::services::subagent_writer::parse_subagent_markdown;
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+/// On-disk format an agent file was written in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AgentFormat {
+    Claude,   // .claude/agents/{name}.md
+    OpenCode, // .opencode/agent/{name}.md
+}
+
+/// A single agent file that belongs to an import group
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AgentGroupMember {
+    pub format: AgentFormat,
+    pub path: PathBuf,
+}
+
+/// Agent files from one import that describe the same logical agent
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AgentGroup {
+    /// Normalized agent name shared by all members
+    pub name: String,
+    pub members: Vec<AgentGroupMember>,
+    /// True when members disagree on description or prompt body
+    pub content_differs: bool,
+}
+
+/// Normalize an agent name for cross-format matching (case, `_` and spaces)
+pub fn normalize_agent_name(name: &str) -> String {
+    name.trim()
+        .to_lowercase()
+        .chars()
+        .map(|c| {
+            if c == '_' || c.is_whitespace() {
+                '-'
+            } else {
+                c
+            }
+        })
+        .collect()
+}
+
+/// Detect the agent format from its location (OpenCode uses singular `agent/`)
+pub fn detect_agent_format(path: &Path) -> AgentFormat {
+    let in_opencode_dir = path.components().any(|c| c.as_os_str() == ".opencode");
+    let in_agent_dir = path
+        .parent()
+        .and_then(|p| p.file_name())
+        .map(|n| n == "agent")
+        .unwrap_or(false);
+
+    if in_opencode_dir || in_agent_dir {
+        AgentFormat::OpenCode
+    } else {
+        AgentFormat::Claude
+    }
+}
+
+/// Group imported agent files by normalized name across Claude and OpenCode formats.
+///
+/// Frontmatter differs between the formats, so only the description and the
+/// prompt body are compared when flagging `content_differs`. Files that cannot
+/// be read are skipped. Groups are returned sorted by name.
+pub fn group_imported_agents(files: &[PathBuf]) -> Vec<AgentGroup> {
+    let mut groups: BTreeMap<String, (Vec<AgentGroupMember>, Vec<(String, String)>)> =
+        BTreeMap::new();
+
+    for path in files {
+        let Some(agent) = parse_agent_file(path) else {
+            continue;
+        };
+
+        let name = normalize_agent_name(&agent.name);
+        let description = agent.description.trim().trim_matches('"').to_string();
+        let body = agent.content.trim().to_string();
+
+        let entry = groups.entry(name).or_default();
+        entry.0.push(AgentGroupMember {
+            format: detect_agent_format(path),
+            path: path.clone(),
+        });
+        entry.1.push((description, body));
+    }
+
+    groups
+        .into_iter()
+        .map(|(name, (members, contents))| {
+            let content_differs = contents.windows(2).any(|pair| pair[0] != pair[1]);
+            AgentGroup {
+                name,
+                members,
+                content_differs,
+            }
+        })
+        .collect()
+}
+
+/// Import the `*.md` agents in a shared directory, such as a curated library
+/// kept in a repo subdirectory rather than a `.claude` folder.
+///
+/// Agents are tagged `source = "shared"` with `source_path` set to their file;
+/// the file stem is used when the frontmatter has no name. With `recursive`,
+/// subdirectories are scanned too (hidden directories are skipped). Paths
+/// matched by a `.agentignore` (gitignore syntax, applying to its directory's
+/// subtree) are skipped. Files that cannot be read or parsed are skipped with
+/// a warning. Results are sorted by path.
+pub fn import_from_directory(dir: &Path, recursive: bool) -> Result<Vec<SubAgent>> {
+    let mut agents = Vec::new();
+    for path in shared_agent_files(dir, recursive)? {
+        let parsed = std::fs::read_to_string(&path)
+            .map_err(anyhow::Error::from)
+            .and_then(|content| parse_shared_agent(&path, &content));
+        match parsed {
+            Ok(agent) => agents.push(agent),
+            Err(e) => log::warn!("[SubAgentImport] Skipping {}: {}", path.display(), e),
+        }
+    }
+
+    Ok(agents)
+}
+
+/// Outcome of import_changed_only
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportResult {
+    /// Agents whose file is new or changed since the last import
+    pub agents: Vec<SubAgent>,
+    /// Content hash of every agent file seen, keyed by path; pass this back
+    /// as `known_hashes` on the next sync
+    pub hashes: HashMap<String, String>,
+    /// Files skipped because their hash matched `known_hashes`
+    pub unchanged: usize,
+}
+
+/// Re-import a shared directory (recursively), parsing only files whose
+/// content hash differs from `known_hashes` (path -> hash from a previous
+/// import). Unreadable or unparseable files are skipped with a warning and
+/// left out of `hashes`, so they are retried next time.
+pub fn import_changed_only(
+    dir: &Path,
+    known_hashes: &HashMap<String, String>,
+) -> Result<ImportResult> {
+    let mut result = ImportResult::default();
+
+    for path in shared_agent_files(dir, true)? {
+        let content = match std::fs::read_to_string(&path) {
+            Ok(content) => content,
+            Err(e) => {
+                log::warn!("[SubAgentImport] Skipping {}: {}", path.display(), e);
+                continue;
+            }
+        };
+
+        let key = path.to_string_lossy().to_string();
+        let hash = content_hash(&content);
+        if known_hashes.get(&key) == Some(&hash) {
+            result.unchanged += 1;
+            result.hashes.insert(key, hash);
+            continue;
+        }
+
+        match parse_shared_agent(&path, &content) {
+            Ok(agent) => {
+                result.agents.push(agent);
+                result.hashes.insert(key, hash);
+            }
+            Err(e) => log::warn!("[SubAgentImport] Skipping {}: {}", path.display(), e),
+        }
+    }
+
+    Ok(result)
+}
+
+fn content_hash(content: &str) -> String {
+    format!("{:x}", Sha256::digest(content.as_bytes()))
+}
+
+/// `*.md` files in a shared directory, sorted by path (hidden directories and
+/// paths excluded by `.agentignore` files skipped)
+fn shared_agent_files(dir: &Path, recursive: bool) -> Result<Vec<PathBuf>> {
+    if !dir.is_dir() {
+        return Err(anyhow!("Not a directory: {}", dir.display()));
+    }
+
+    // Directories are visited before their contents, so each ignore file is
+    // loaded before the entries it applies to
+    let mut ignore = IgnoreRules::default();
+    ignore.load_dir(dir)?;
+    let walker = WalkDir::new(dir)
+        .min_depth(1)
+        .max_depth(if recursive { usize::MAX } else { 1 })
+        .follow_links(false)
+        .sort_by_file_name()
+        .into_iter()
+        .filter_entry(move |e| {
+            if e.depth() > 0 && e.file_name().to_string_lossy().starts_with('.') {
+                return false;
+            }
+            let is_dir = e.file_type().is_dir();
+            if ignore.is_ignored(e.path(), is_dir) {
+                return false;
+            }
+            if is_dir {
+                if let Err(err) = ignore.load_dir(e.path()) {
+                    log::warn!(
+                        "[SubAgentImport] Can't read ignore file in {}: {}",
+                        e.path().display(),
+                        err
+                    );
+                }
+            }
+            true
+        });
+
+    Ok(walker
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_file() && e.path().extension().is_some_and(|ext| ext == "md"))
+        .map(|e| e.into_path())
+        .collect())
+}
+
+/// Parse a shared agent file, tagging its source and defaulting the name to the file stem
+fn parse_shared_agent(path: &Path, content: &str) -> Result<SubAgent> {
+    let mut agent = parse_subagent_markdown(content)?;
+
+    if agent.name.is_empty() {
+        agent.name = path
+            .file_stem()
+            .map(|s| s.to_string_lossy().to_string())
+            .unwrap_or_default();
+    }
+    agent.source = "shared".to_string();
+    agent.source_path = Some(path.to_string_lossy().to_string());
+    Ok(agent)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn write_agent(path: &Path, content: &str) -> PathBuf {
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, content).unwrap();
+        path.to_path_buf()
+    }
+
+    #[test]
+    fn test_normalize_agent_name() {
+        assert_eq!(normalize_agent_name("Code_Reviewer"), "code-reviewer");
+        assert_eq!(normalize_agent_name(" code reviewer "), "code-reviewer");
+    }
+
+    #[test]
+    fn test_detect_agent_format() {
+        assert_eq!(
+            detect_agent_format(Path::new("/p/.claude/agents/x.md")),
+            AgentFormat::Claude
+        );
+        assert_eq!(
+            detect_agent_format(Path::new("/p/.opencode/agent/x.md")),
+            AgentFormat::OpenCode
+        );
+    }
+
+    #[test]
+    fn test_group_imported_agents_cross_format() {
+        let temp_dir = TempDir::new().unwrap();
+        let claude_dir = temp_dir.path().join(".claude").join("agents");
+        let opencode_dir = temp_dir.path().join(".opencode").join("agent");
+
+        let files = vec![
+            write_agent(
+                &claude_dir.join("reviewer.md"),
+                "---\nname: reviewer\ndescription: Reviews code\ntools: Read, Grep\n---\n\nReview it.",
+            ),
+            write_agent(
+                &opencode_dir.join("reviewer.md"),
+                "---\ndescription: \"Reviews code\"\ntools:\n  read: true\n  grep: true\n---\n\nReview it.",
+            ),
+            write_agent(
+                &claude_dir.join("planner.md"),
+                "---\nname: planner\ndescription: Plans work\n---\n\nPlan carefully.",
+            ),
+            write_agent(
+                &opencode_dir.join("Planner.md"),
+                "---\ndescription: \"Plans work\"\n---\n\nPlan quickly.",
+            ),
+        ];
+
+        let groups = group_imported_agents(&files);
+        assert_eq!(groups.len(), 2);
+
+        let planner = &groups[0];
+        assert_eq!(planner.name, "planner");
+        assert_eq!(planner.members.len(), 2);
+        assert!(planner.content_differs);
+
+        let reviewer = &groups[1];
+        assert_eq!(reviewer.name, "reviewer");
+        assert_eq!(reviewer.members[0].format, AgentFormat::Claude);
+        assert_eq!(reviewer.members[1].format, AgentFormat::OpenCode);
+        assert!(!reviewer.content_differs);
+    }
+
+    #[test]
+    fn test_import_from_directory_nested() {
+        let temp_dir = TempDir::new().unwrap();
+        let library = temp_dir.path().join("team-agents");
+        write_agent(
+            &library.join("reviewer.md"),
+            "---\nname: reviewer\ndescription: Reviews code\n---\n\nReview it.",
+        );
+        write_agent(
+            &library.join("backend").join("db-tuner.md"),
+            "---\ndescription: Tunes queries\n---\n\nTune it.",
+        );
+        write_agent(&library.join("backend").join("notes.txt"), "not an agent");
+        write_agent(
+            &library.join(".git").join("hidden.md"),
+            "---\nname: hidden\n---\n",
+        );
+
+        let top_level = import_from_directory(&library, false).unwrap();
+        assert_eq!(top_level.len(), 1);
+        assert_eq!(top_level[0].name, "reviewer");
+
+        let all = import_from_directory(&library, true).unwrap();
+        let names: Vec<&str> = all.iter().map(|a| a.name.as_str()).collect();
+        assert_eq!(names, vec!["db-tuner", "reviewer"]);
+        assert!(all.iter().all(|a| a.source == "shared"));
+        assert_eq!(
+            all[0].source_path.as_deref(),
+            Some(
+                library
+                    .join("backend")
+                    .join("db-tuner.md")
+                    .to_string_lossy()
+                    .as_ref()
+            )
+        );
+        assert_eq!(all[0].description, "Tunes queries");
+    }
+
+    #[test]
+    fn test_import_from_directory_honors_agentignore() {
+        let temp_dir = TempDir::new().unwrap();
+        let library = temp_dir.path().join("team-agents");
+        let agent = |name: &str| format!("---\nname: {}\n---\n\nBody.", name);
+        write_agent(&library.join("reviewer.md"), &agent("reviewer"));
+        write_agent(&library.join("planner.md"), &agent("planner"));
+        write_agent(&library.join("wip.md"), &agent("wip"));
+        write_agent(&library.join("drafts").join("idea.md"), &agent("idea"));
+        write_agent(
+            &library.join("backend").join("db-tuner.md"),
+            &agent("db-tuner"),
+        );
+        write_agent(
+            &library.join("backend").join("scratch.md"),
+            &agent("scratch"),
+        );
+        write_agent(
+            &library.join(".agentignore"),
+            "# not ready\nwip.md\ndrafts/\n",
+        );
+        write_agent(
+            &library.join("backend").join(".agentignore"),
+            "scratch.md\n",
+        );
+
+        let all = import_from_directory(&library, true).unwrap();
+        let names: Vec<&str> = all.iter().map(|a| a.name.as_str()).collect();
+        assert_eq!(names, vec!["db-tuner", "planner", "reviewer"]);
+    }
+
+    #[test]
+    fn test_import_from_directory_missing_dir_errors() {
+        let temp_dir = TempDir::new().unwrap();
+        assert!(import_from_directory(&temp_dir.path().join("missing"), true).is_err());
+    }
+
+    #[test]
+    fn test_import_changed_only_skips_unchanged_files() {
+        let temp_dir = TempDir::new().unwrap();
+        let library = temp_dir.path().join("team-agents");
+        write_agent(
+            &library.join("planner.md"),
+            "---\nname: planner\ndescription: Plans work\n---\n\nPlan it.",
+        );
+        write_agent(
+            &library.join("reviewer.md"),
+            "---\nname: reviewer\ndescription: Reviews code\n---\n\nReview it.",
+        );
+
+        let first = import_changed_only(&library, &HashMap::new()).unwrap();
+        assert_eq!(first.agents.len(), 2);
+        assert_eq!(first.unchanged, 0);
+
+        write_agent(
+            &library.join("reviewer.md"),
+            "---\nname: reviewer\ndescription: Reviews code carefully\n---\n\nReview it.",
+        );
+
+        let second = import_changed_only(&library, &first.hashes).unwrap();
+        assert_eq!(second.unchanged, 1);
+        assert_eq!(second.agents.len(), 1);
+        assert_eq!(second.agents[0].name, "reviewer");
+        assert_eq!(second.agents[0].description, "Reviews code carefully");
+        assert_eq!(second.hashes.len(), 2);
+    }
+}
@@ -1,20 +1,413 @@
 use crate::db::models::SubAgent;
+use crate::services::frontmatter::{parse_yaml_map, parse_yaml_sequence, split_frontmatter};
+use crate::services::json_schema::{validate_json, SchemaChecks};
+use crate::services::scanner::{parse_agent_tools, parse_string_list, ALL_TOOLS_WILDCARD};
 use crate::utils::opencode_paths::get_opencode_paths;
+use crate::utils::paths::{config_scope, ClaudeLayout};
 use anyhow::Result;
 use directories::BaseDirs;
-use std::path::Path;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::SystemTime;
+
+/// Errors from reading agent files
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum WriterError {
+    /// A `---` frontmatter block was opened but never closed
+    #[error("Malformed frontmatter: block opened on line {line} is never closed")]
+    MalformedFrontmatter { line: usize },
+    /// No agent with this name exists in the scopes searched
+    #[error("Agent '{name}' not found")]
+    NotFound { name: String },
+}
+
+/// Check that an opened frontmatter block is closed by a line that is exactly `---`
+pub fn check_frontmatter(content: &str) -> std::result::Result<(), WriterError> {
+    split_frontmatter(content)
+        .map(|_| ())
+        .map_err(|_| WriterError::MalformedFrontmatter { line: 1 })
+}
+
+/// Whether a line looks like it belongs to a frontmatter block
+/// (`key: value`, an indented continuation, or a list item)
+fn is_frontmatter_line(line: &str) -> bool {
+    if line.starts_with(' ') || line.starts_with('\t') {
+        return !line.trim().is_empty();
+    }
+    if line.starts_with("- ") {
+        return true;
+    }
+    line.split_once(':').is_some_and(|(key, _)| {
+        !key.is_empty()
+            && key
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+    })
+}
+
+/// Close an obviously-unterminated frontmatter block.
+///
+/// The block is closed after the run of frontmatter-looking lines that follows
+/// the opening `---`. Returns None if the frontmatter is already well formed or
+/// if no such lines follow the opener (nothing obvious to repair).
+pub fn try_repair_frontmatter(content: &str) -> Option<String> {
+    check_frontmatter(content).err()?;
+
+    let lines: Vec<&str> = content.lines().collect();
+    let block_len = lines[1..]
+        .iter()
+        .take_while(|line| is_frontmatter_line(line))
+        .count();
+    if block_len == 0 {
+        return None;
+    }
+
+    let close_at = 1 + block_len;
+    let mut repaired: Vec<&str> = Vec::with_capacity(lines.len() + 1);
+    repaired.extend_from_slice(&lines[..close_at]);
+    repaired.push("---");
+    repaired.extend_from_slice(&lines[close_at..]);
+
+    let mut repaired = repaired.join("\n");
+    if content.ends_with('\n') {
+        repaired.push('\n');
+    }
+    Some(repaired)
+}
+
+/// Line ending used for generated agent files
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LineEnding {
+    #[default]
+    Lf,
+    Crlf,
+}
+
+/// Most blank lines written between the frontmatter and the prompt
+pub const MAX_BLANK_LINES_AFTER_FRONTMATTER: usize = 3;
+
+/// Options controlling how agent markdown is generated
+#[derive(Debug, Clone)]
+pub struct GenerateOptions {
+    pub line_ending: LineEnding,
+    /// Write `tools: "*"` for agents without a tool list instead of omitting the line
+    pub explicit_all_tools: bool,
+    /// Sort the tools and skills lists. Off by default so lists keep the
+    /// order they were written (or imported) in.
+    pub sort_lists: bool,
+    /// Blank lines between the closing `---` and the prompt (default 1,
+    /// clamped to MAX_BLANK_LINES_AFTER_FRONTMATTER)
+    pub blank_lines_after_frontmatter: usize,
+}
+
+impl Default for GenerateOptions {
+    fn default() -> Self {
+        Self {
+            line_ending: LineEnding::default(),
+            explicit_all_tools: false,
+            sort_lists: false,
+            blank_lines_after_frontmatter: 1,
+        }
+    }
+}
+
+/// Options controlling how agent files are written to disk
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct WriteOptions {
+    /// Unix permission bits set on the agent file and its sidecar after
+    /// writing (e.g. 0o600). None keeps the umask default; ignored on non-Unix.
+    pub mode: Option<u32>,
+    /// Refuse to write agents whose model is not in `known_models`
+    pub strict: bool,
+    /// Models accepted in strict mode (aliases like `sonnet` or full model ids)
+    pub known_models: Vec<String>,
+}
+
+/// Agent colors Claude Code knows how to display
+pub const KNOWN_AGENT_COLORS: &[&str] = &[
+    "red", "blue", "green", "yellow", "purple", "orange", "pink", "cyan",
+];
+
+/// Check an agent color against the known colors.
+/// Returns a warning for unknown colors; they are still written and parsed.
+pub fn validate_subagent_color(color: &str) -> Option<String> {
+    if KNOWN_AGENT_COLORS.contains(&color.trim().to_lowercase().as_str()) {
+        None
+    } else {
+        Some(format!(
+            "Unknown agent color '{}' (expected one of: {})",
+            color,
+            KNOWN_AGENT_COLORS.join(", ")
+        ))
+    }
+}
+
+/// Check a model name against the known models. Provider-prefixed OpenCode
+/// names (`anthropic/claude-sonnet-4`) are accepted when the part after the
+/// last `/` is known. Unknown models fail with the closest known model as a
+/// suggestion, if one is close enough.
+pub fn validate_model(model: &str, known: &[String]) -> Result<()> {
+    let model = model.trim();
+    let bare = model.rsplit('/').next().unwrap_or(model);
+    if known.iter().any(|k| k == model || k == bare) {
+        return Ok(());
+    }
+
+    let suggestion = known
+        .iter()
+        .map(|k| (edit_distance(bare, k), k))
+        .filter(|(distance, k)| *distance <= (k.chars().count() / 3).max(1))
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, k)| k);
+    match suggestion {
+        Some(k) => Err(anyhow::anyhow!(
+            "Unknown model '{}'. Did you mean '{}'?",
+            model,
+            k
+        )),
+        None => Err(anyhow::anyhow!(
+            "Unknown model '{}' (expected one of: {})",
+            model,
+            known.join(", ")
+        )),
+    }
+}
+
+/// JSON schema a serialized SubAgent (camelCase keys) must match
+fn subagent_schema() -> serde_json::Value {
+    let text = serde_json::json!({ "type": "string" });
+    let optional_text = serde_json::json!({ "type": ["string", "null"] });
+    let optional_list = serde_json::json!({
+        "type": ["array", "null"],
+        "items": { "type": "string" },
+    });
+    serde_json::json!({
+        "type": "object",
+        "required": ["name", "description", "content"],
+        "properties": {
+            "name": { "type": "string", "minLength": 1 },
+            "description": text,
+            "content": text,
+            "tools": optional_list,
+            "model": optional_text,
+            "permissionMode": optional_text,
+            "skills": optional_list,
+            "color": optional_text,
+            "tags": optional_list,
+            "env": optional_list,
+            "mcpServers": optional_list,
+            "source": text,
+            "sourcePath": optional_text,
+            "isFavorite": { "type": "boolean" },
+            "enabled": { "type": "boolean" },
+            "extends": optional_text,
+            "extra": { "type": "object" },
+        },
+    })
+}
+
+/// Check that agent data from the database or UI has the shape of a SubAgent,
+/// with errors naming the offending field (e.g. `$.tools[1]: expected string,
+/// got number`) rather than serde's position-based messages
+pub fn validate_subagent_shape(value: &serde_json::Value) -> Result<()> {
+    validate_json(&subagent_schema(), value, SchemaChecks { min_length: true })
+        .map_err(|reason| anyhow::anyhow!("Invalid agent: {}", reason))
+}
+
+/// Deserialize a SubAgent after checking its shape (see validate_subagent_shape)
+pub fn subagent_from_value(value: serde_json::Value) -> Result<SubAgent> {
+    validate_subagent_shape(&value)?;
+    Ok(serde_json::from_value(value)?)
+}
+
+/// Levenshtein distance between two strings, by character
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != *cb);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+/// Rewrite newlines in generated output to the requested line ending
+pub(crate) fn apply_line_ending(text: String, line_ending: LineEnding) -> String {
+    match line_ending {
+        LineEnding::Lf => text,
+        // Normalize first so existing CRLF content doesn't become \r\r\n
+        LineEnding::Crlf => text.replace("\r\n", "\n").replace('\n', "\r\n"),
+    }
+}
+
+/// Name of the file in an agents directory that records the original names of
+/// agents whose file names had to be encoded (see safe_filename)
+const FILENAME_MAP_FILE: &str = ".filenames.json";
+
+/// Encode an agent name for use as a file stem. Bytes outside `A-Z a-z 0-9 - _ .`
+/// (slashes, spaces, emoji, `%` itself) are percent-encoded, as is a leading
+/// `.` so the file isn't hidden. Plain names are returned unchanged.
+pub fn safe_filename(name: &str) -> String {
+    let mut encoded = String::with_capacity(name.len());
+    for (i, byte) in name.bytes().enumerate() {
+        let plain = byte.is_ascii_alphanumeric() || matches!(byte, b'-' | b'_' | b'.');
+        if plain && !(i == 0 && byte == b'.') {
+            encoded.push(byte as char);
+        } else {
+            encoded.push_str(&format!("%{:02X}", byte));
+        }
+    }
+    encoded
+}
+
+/// Reverse safe_filename. Returns None for stems that aren't valid encodings.
+pub fn decode_filename(stem: &str) -> Option<String> {
+    let bytes = stem.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' {
+            let hex = stem.get(i + 1..i + 3)?;
+            decoded.push(u8::from_str_radix(hex, 16).ok()?);
+            i += 3;
+        } else {
+            decoded.push(bytes[i]);
+            i += 1;
+        }
+    }
+    String::from_utf8(decoded).ok()
+}
+
+/// File name of an agent (`{safe_filename(name)}.md`), in any format
+pub fn agent_file_name(name: &str) -> String {
+    format!("{}.md", safe_filename(name))
+}
+
+/// Path of the file holding agent `name` in `dir`: its encoded file name, or
+/// else the unencoded `{name}.md` written before names were encoded, if that
+/// exists. Names that aren't a plain file name never fall back.
+fn existing_agent_file(dir: &Path, name: &str) -> PathBuf {
+    let encoded = dir.join(agent_file_name(name));
+    if encoded.exists() {
+        return encoded;
+    }
+    let mut components = Path::new(name).components();
+    let is_plain = matches!(components.next(), Some(std::path::Component::Normal(_)))
+        && components.next().is_none()
+        && !name.contains(['/', '\\']);
+    let legacy = dir.join(format!("{}.md", name));
+    if is_plain && legacy.is_file() {
+        legacy
+    } else {
+        encoded
+    }
+}
+
+fn read_filename_map(dir: &Path) -> HashMap<String, String> {
+    std::fs::read_to_string(dir.join(FILENAME_MAP_FILE))
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+/// Record the original name of an agent whose file stem differs from it
+fn record_original_name(agent_file: &Path, name: &str) -> Result<()> {
+    let (Some(dir), Some(stem)) = (agent_file.parent(), agent_file.file_stem()) else {
+        return Ok(());
+    };
+    let stem = stem.to_string_lossy().to_string();
+    if stem == name {
+        return Ok(());
+    }
+
+    let mut map = read_filename_map(dir);
+    if map.get(&stem).map(String::as_str) == Some(name) {
+        return Ok(());
+    }
+    map.insert(stem, name.to_string());
+    write_filename_map(dir, &map)
+}
+
+/// Drop a deleted agent file's entry from the filename map, removing the map
+/// once it is empty
+fn forget_original_name(agent_file: &Path) -> Result<()> {
+    let (Some(dir), Some(stem)) = (agent_file.parent(), agent_file.file_stem()) else {
+        return Ok(());
+    };
+    let mut map = read_filename_map(dir);
+    if map.remove(stem.to_string_lossy().as_ref()).is_none() {
+        return Ok(());
+    }
+    if map.is_empty() {
+        std::fs::remove_file(dir.join(FILENAME_MAP_FILE))?;
+        return Ok(());
+    }
+    write_filename_map(dir, &map)
+}
+
+fn write_filename_map(dir: &Path, map: &HashMap<String, String>) -> Result<()> {
+    let sorted: std::collections::BTreeMap<_, _> = map.iter().collect();
+    write_atomic(
+        &dir.join(FILENAME_MAP_FILE),
+        (serde_json::to_string_pretty(&sorted)? + "\n").as_bytes(),
+    )
+}
+
+/// Original agent name for an agent file: from the stored filename map, else
+/// by decoding the file stem
+fn original_name(agent_file: &Path) -> String {
+    let stem = agent_file
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_default();
+    agent_file
+        .parent()
+        .and_then(|dir| read_filename_map(dir).remove(&stem))
+        .or_else(|| decode_filename(&stem))
+        .unwrap_or(stem)
+}
 
 /// Generate markdown content for a sub-agent (.claude/agents/name.md)
 pub(crate) fn generate_subagent_markdown(subagent: &SubAgent) -> String {
+    generate_subagent_markdown_with_options(subagent, &GenerateOptions::default())
+}
+
+/// Generate markdown content for a sub-agent with explicit generation options
+pub(crate) fn generate_subagent_markdown_with_options(
+    subagent: &SubAgent,
+    options: &GenerateOptions,
+) -> String {
+    let list = |items: &Option<Vec<String>>| -> Option<Vec<String>> {
+        let mut items = items.clone()?;
+        if options.sort_lists {
+            items.sort();
+        }
+        Some(items)
+    };
+
     let mut frontmatter = String::from("---\n");
 
     frontmatter.push_str(&format!("name: {}\n", subagent.name));
     frontmatter.push_str(&format!("description: {}\n", subagent.description));
 
-    if let Some(ref tools) = subagent.tools {
-        if !tools.is_empty() {
+    match list(&subagent.tools) {
+        Some(ref tools) if !tools.is_empty() => {
             frontmatter.push_str(&format!("tools: {}\n", tools.join(", ")));
         }
+        // Quoted: a bare `*` is not valid YAML
+        _ if options.explicit_all_tools => {
+            frontmatter.push_str(&format!("tools: \"{}\"\n", ALL_TOOLS_WILDCARD));
+        }
+        _ => {}
     }
 
     if let Some(ref model) = subagent.model {
@@ -29,40 +422,462 @@ pub(crate) fn generate_subagent_markdown(subagent: &SubAgent) -> String {
         }
     }
 
-    if let Some(ref skills) = subagent.skills {
+    if let Some(ref skills) = list(&subagent.skills) {
         if !skills.is_empty() {
             frontmatter.push_str(&format!("skills: {}\n", skills.join(", ")));
         }
     }
 
-    frontmatter.push_str("---\n\n");
-    format!("{}{}", frontmatter, subagent.content)
+    if let Some(ref color) = subagent.color {
+        if !color.is_empty() {
+            if let Some(warning) = validate_subagent_color(color) {
+                log::warn!("[SubAgentWriter] {}: {}", subagent.name, warning);
+            }
+            frontmatter.push_str(&format!("color: {}\n", color));
+        }
+    }
+
+    if !subagent.enabled {
+        frontmatter.push_str("disabled: true\n");
+    }
+
+    // Documents required environment variables; Claude Code ignores the key
+    if let Some(ref env) = subagent.env {
+        if !env.is_empty() {
+            frontmatter.push_str("env:\n");
+            for var in env {
+                frontmatter.push_str(&format!("  - {}\n", var));
+            }
+        }
+    }
+
+    if let Some(ref servers) = subagent.mcp_servers {
+        if !servers.is_empty() {
+            frontmatter.push_str("mcpServers:\n");
+            for server in servers {
+                frontmatter.push_str(&format!("  - {}\n", server));
+            }
+        }
+    }
+
+    frontmatter.push_str("---\n");
+    frontmatter.push_str(
+        &"\n".repeat(
+            options
+                .blank_lines_after_frontmatter
+                .min(MAX_BLANK_LINES_AFTER_FRONTMATTER),
+        ),
+    );
+    apply_line_ending(
+        format!("{}{}", frontmatter, subagent.content),
+        options.line_ending,
+    )
+}
+
+/// Parse Claude-format agent markdown (as produced by generate_subagent_markdown)
+/// back into a SubAgent. The result is not persisted, so `id` is 0.
+pub fn parse_subagent_markdown(content: &str) -> Result<SubAgent> {
+    check_frontmatter(content)?;
+    let (raw, body) = split_frontmatter(content)?;
+    let frontmatter = parse_yaml_map(&raw)?;
+
+    let list = |key: &str| -> Option<Vec<String>> {
+        frontmatter
+            .get(key)
+            .map(|v| parse_string_list(v.as_str()))
+            .filter(|v| !v.is_empty())
+    };
+
+    let color = frontmatter.get("color").cloned();
+    if let Some(ref c) = color {
+        if let Some(warning) = validate_subagent_color(c) {
+            log::warn!("[SubAgentWriter] {}", warning);
+        }
+    }
+
+    Ok(SubAgent {
+        id: 0,
+        name: frontmatter.get("name").cloned().unwrap_or_default(),
+        description: frontmatter.get("description").cloned().unwrap_or_default(),
+        content: body,
+        tools: frontmatter
+            .get("tools")
+            .map(|v| parse_agent_tools(v.as_str()))
+            .filter(|v| !v.is_empty()),
+        model: frontmatter.get("model").cloned(),
+        permission_mode: frontmatter
+            .get("permissionMode")
+            .or_else(|| frontmatter.get("permission_mode"))
+            .cloned(),
+        skills: list("skills"),
+        color,
+        tags: list("tags"),
+        source: "manual".to_string(),
+        source_path: None,
+        is_favorite: false,
+        created_at: String::new(),
+        updated_at: String::new(),
+        env: Some(parse_yaml_sequence(&raw, "env")).filter(|v| !v.is_empty()),
+        mcp_servers: Some(parse_yaml_sequence(&raw, "mcpServers")).filter(|v| !v.is_empty()),
+        enabled: frontmatter.get("disabled").map(|v| v.trim()) != Some("true"),
+        extends: None,
+        extra: Default::default(),
+    })
+}
+
+/// Parse a batch of Claude-format agent markdown strings (e.g. piped in by
+/// a script), one result per input in order. A malformed input only fails
+/// its own entry.
+pub fn parse_many_subagents(inputs: &[&str]) -> Vec<Result<SubAgent>> {
+    inputs
+        .iter()
+        .map(|content| parse_subagent_markdown(content))
+        .collect()
 }
 
 /// Write a sub-agent to {base_path}/.claude/agents/{name}.md
 pub fn write_subagent_file(base_path: &Path, subagent: &SubAgent) -> Result<()> {
-    let agents_dir = base_path.join(".claude").join("agents");
-    std::fs::create_dir_all(&agents_dir)?;
+    let layout = ClaudeLayout::default();
+    let path = existing_agent_file(&layout.agents_dir(base_path), &subagent.name);
+    let span = tracing::info_span!(
+        "write_subagent_file",
+        agent = %subagent.name,
+        scope = config_scope(base_path),
+        path = %path.display()
+    );
+    let _entered = span.enter();
+
+    let result = write_subagent_file_with_layout(base_path, subagent, &layout);
+    if let Err(e) = &result {
+        tracing::error!(error = %e, "failed to write sub-agent");
+    }
+    result
+}
 
-    let file_path = agents_dir.join(format!("{}.md", subagent.name));
-    let content = generate_subagent_markdown(subagent);
-    std::fs::write(file_path, content)?;
+/// Write a sub-agent to {base_path}/{layout.agents_subdir}/{name}.md
+pub fn write_subagent_file_with_layout(
+    base_path: &Path,
+    subagent: &SubAgent,
+    layout: &ClaudeLayout,
+) -> Result<()> {
+    write_subagent_file_with_options(base_path, subagent, layout, &GenerateOptions::default())
+}
 
-    Ok(())
+/// Write a sub-agent to {base_path}/{layout.agents_subdir}/{name}.md with generation options
+pub fn write_subagent_file_with_options(
+    base_path: &Path,
+    subagent: &SubAgent,
+    layout: &ClaudeLayout,
+    options: &GenerateOptions,
+) -> Result<()> {
+    let formatter = ClaudeFormatter {
+        layout: layout.clone(),
+        options: options.clone(),
+    };
+    write_subagent_with_formatter(base_path, subagent, &formatter)
+}
+
+/// Timestamp format of `updated_at`, matching SQLite's CURRENT_TIMESTAMP
+const UPDATED_AT_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+/// Write a sub-agent like write_subagent_file, returning it with `updated_at`
+/// set to the (UTC) time of the write so the database row can be kept in sync
+pub fn write_subagent_file_stamped(base_path: &Path, subagent: &SubAgent) -> Result<SubAgent> {
+    write_subagent_file(base_path, subagent)?;
+    Ok(SubAgent {
+        updated_at: chrono::Utc::now().format(UPDATED_AT_FORMAT).to_string(),
+        ..subagent.clone()
+    })
 }
 
 /// Delete a sub-agent file from {base_path}/.claude/agents/{name}.md
 pub fn delete_subagent_file(base_path: &Path, name: &str) -> Result<()> {
-    let file_path = base_path
-        .join(".claude")
-        .join("agents")
-        .join(format!("{}.md", name));
+    let layout = ClaudeLayout::default();
+    let path = existing_agent_file(&layout.agents_dir(base_path), name);
+    let span = tracing::info_span!(
+        "delete_subagent_file",
+        agent = %name,
+        scope = config_scope(base_path),
+        path = %path.display()
+    );
+    let _entered = span.enter();
+
+    let result = delete_subagent_file_with_layout(base_path, name, &layout);
+    if let Err(e) = &result {
+        tracing::error!(error = %e, "failed to delete sub-agent");
+    }
+    result
+}
+
+/// Delete a sub-agent file from {base_path}/{layout.agents_subdir}/{name}.md
+pub fn delete_subagent_file_with_layout(
+    base_path: &Path,
+    name: &str,
+    layout: &ClaudeLayout,
+) -> Result<()> {
+    let file_path = existing_agent_file(&layout.agents_dir(base_path), name);
     if file_path.exists() {
-        std::fs::remove_file(file_path)?;
+        std::fs::remove_file(&file_path)?;
+    }
+    remove_sidecar(&file_path)?;
+    forget_original_name(&file_path)
+}
+
+// ============================================================================
+// Trash
+// ============================================================================
+// Trashed agents live in {base}/.claude/agents/.trash/{timestamp}-{name}.md
+// (with their sidecar, if any) until restored or purged.
+
+/// Directory holding trashed agents
+const TRASH_DIR: &str = ".trash";
+
+/// Timestamp prefix of trashed file names (UTC, millisecond precision)
+const TRASH_TIMESTAMP_FORMAT: &str = "%Y%m%d%H%M%S%3f";
+
+pub(crate) fn trash_dir(base_path: &Path) -> PathBuf {
+    ClaudeLayout::default()
+        .agents_dir(base_path)
+        .join(TRASH_DIR)
+}
+
+/// Move a file and its sidecar (if present) to `to`
+fn move_with_sidecar(from: &Path, to: &Path) -> Result<()> {
+    std::fs::rename(from, to)?;
+    let sidecar = sidecar_path(from);
+    if sidecar.exists() {
+        std::fs::rename(&sidecar, sidecar_path(to))?;
     }
     Ok(())
 }
 
+/// Move {base_path}/.claude/agents/{name}.md (and its sidecar) into the trash
+/// instead of deleting it. Returns the trashed file name, for restore_from_trash.
+pub fn delete_subagent_trashed(base_path: &Path, name: &str) -> Result<String> {
+    let file_path = existing_agent_file(&ClaudeLayout::default().agents_dir(base_path), name);
+    if !file_path.is_file() {
+        return Err(WriterError::NotFound {
+            name: name.to_string(),
+        }
+        .into());
+    }
+
+    let trash = trash_dir(base_path);
+    std::fs::create_dir_all(&trash)?;
+    let timestamp = chrono::Utc::now().format(TRASH_TIMESTAMP_FORMAT);
+    let file_name = file_path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let trashed_name = format!("{}-{}", timestamp, file_name);
+    move_with_sidecar(&file_path, &trash.join(&trashed_name))?;
+    Ok(trashed_name)
+}
+
+/// Move a trashed agent back to {base_path}/.claude/agents/{name}.md.
+/// Fails if an agent with that name exists again, or if `trashed_name` is
+/// not a plain file name (separators, `..` and absolute paths are refused).
+/// Returns the restored path.
+pub fn restore_from_trash(base_path: &Path, trashed_name: &str) -> Result<PathBuf> {
+    let mut components = Path::new(trashed_name).components();
+    let is_plain = matches!(components.next(), Some(std::path::Component::Normal(_)))
+        && components.next().is_none()
+        && !trashed_name.contains(['/', '\\']);
+    if !is_plain {
+        return Err(anyhow::anyhow!(
+            "Invalid trashed agent name: {:?}",
+            trashed_name
+        ));
+    }
+
+    let trashed = trash_dir(base_path).join(trashed_name);
+    let name = trashed_name
+        .split_once('-')
+        .map(|(_, name)| name)
+        .filter(|_| trashed.is_file())
+        .ok_or_else(|| anyhow::anyhow!("'{}' is not in the trash", trashed_name))?;
+
+    let restored = ClaudeLayout::default().agents_dir(base_path).join(name);
+    if restored.exists() {
+        return Err(anyhow::anyhow!(
+            "Cannot restore '{}': {} already exists",
+            trashed_name,
+            restored.display()
+        ));
+    }
+    move_with_sidecar(&trashed, &restored)?;
+    Ok(restored)
+}
+
+/// Permanently delete trashed agents trashed at least `max_age` ago.
+/// Returns how many were removed.
+pub fn purge_trash(base_path: &Path, max_age: chrono::Duration) -> Result<usize> {
+    let trash = trash_dir(base_path);
+    if !trash.is_dir() {
+        return Ok(0);
+    }
+
+    let now = chrono::Utc::now().naive_utc();
+    let mut purged = 0;
+    for entry in std::fs::read_dir(&trash)? {
+        let path = entry?.path();
+        if path.extension().is_none_or(|ext| ext != "md") {
+            continue;
+        }
+        let file_name = path
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let trashed_at = file_name.split_once('-').and_then(|(timestamp, _)| {
+            chrono::NaiveDateTime::parse_from_str(timestamp, TRASH_TIMESTAMP_FORMAT).ok()
+        });
+
+        if trashed_at.is_some_and(|at| now - at >= max_age) {
+            std::fs::remove_file(&path)?;
+            remove_sidecar(&path)?;
+            purged += 1;
+        }
+    }
+    Ok(purged)
+}
+
+/// Result of upsert_subagent
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase", tag = "outcome")]
+pub enum UpsertOutcome {
+    /// No file existed; it was written
+    Created,
+    /// An existing file was overwritten
+    Written,
+    /// The file changed since it was read; nothing was written
+    Conflict {
+        #[serde(rename = "onDiskHash")]
+        on_disk_hash: String,
+    },
+}
+
+/// SHA-256 (hex) of an agent file's content, as compared by upsert_subagent
+pub fn subagent_content_hash(content: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(content))
+}
+
+/// Normalize cosmetic differences so agents with the same intent compare equal:
+/// text fields are trimmed (empty optional fields become None), list fields are
+/// trimmed, deduplicated and sorted, and content uses `\n` line endings.
+///
+/// Only for comparison and hashing; files are written from the agent as given.
+pub fn canonicalize_subagent(subagent: &SubAgent) -> SubAgent {
+    fn text(value: &Option<String>) -> Option<String> {
+        value
+            .as_deref()
+            .map(str::trim)
+            .filter(|v| !v.is_empty())
+            .map(str::to_string)
+    }
+
+    fn list(value: &Option<Vec<String>>) -> Option<Vec<String>> {
+        value.as_ref().map(|items| {
+            let mut items: Vec<String> = items
+                .iter()
+                .map(|item| item.trim().to_string())
+                .filter(|item| !item.is_empty())
+                .collect();
+            items.sort();
+            items.dedup();
+            items
+        })
+    }
+
+    let content = subagent.content.replace("\r\n", "\n").replace('\r', "\n");
+
+    SubAgent {
+        name: subagent.name.trim().to_string(),
+        description: subagent.description.trim().to_string(),
+        content: content.trim().to_string(),
+        tools: list(&subagent.tools),
+        model: text(&subagent.model),
+        permission_mode: text(&subagent.permission_mode),
+        skills: list(&subagent.skills),
+        color: text(&subagent.color),
+        tags: list(&subagent.tags),
+        env: list(&subagent.env),
+        mcp_servers: list(&subagent.mcp_servers),
+        ..subagent.clone()
+    }
+}
+
+/// Hash of an agent's canonical markdown, stable across cosmetic edits
+pub fn canonical_subagent_hash(subagent: &SubAgent) -> String {
+    let canonical = canonicalize_subagent(subagent);
+    subagent_content_hash(generate_subagent_markdown(&canonical).as_bytes())
+}
+
+/// Write a sub-agent to {base_path}/.claude/agents/{name}.md only if the file
+/// on disk still has `expected_hash` (the hash of the content last read).
+///
+/// None writes unconditionally. A missing file is always created.
+pub fn upsert_subagent(
+    base_path: &Path,
+    subagent: &SubAgent,
+    expected_hash: Option<String>,
+) -> Result<UpsertOutcome> {
+    let path = existing_agent_file(
+        &ClaudeLayout::default().agents_dir(base_path),
+        &subagent.name,
+    );
+
+    let on_disk_hash = match std::fs::read(&path) {
+        Ok(content) => Some(subagent_content_hash(&content)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+        Err(e) => return Err(e.into()),
+    };
+
+    let outcome = match (on_disk_hash, expected_hash) {
+        (None, _) => UpsertOutcome::Created,
+        (Some(on_disk), Some(expected)) if on_disk != expected => {
+            return Ok(UpsertOutcome::Conflict {
+                on_disk_hash: on_disk,
+            });
+        }
+        (Some(_), _) => UpsertOutcome::Written,
+    };
+
+    write_subagent_file(base_path, subagent)?;
+    Ok(outcome)
+}
+
+/// Write sub-agents to {base_path}/.claude/agents/, skipping any whose file
+/// (and sidecar) already has exactly the content that would be written, so
+/// unchanged files keep their mtime and don't wake file watchers.
+///
+/// Returns the names of the agents actually written.
+pub fn write_subagents_incremental(base_path: &Path, agents: &[SubAgent]) -> Result<Vec<String>> {
+    let agents_dir = ClaudeLayout::default().agents_dir(base_path);
+    let mut written = Vec::new();
+
+    for agent in agents {
+        let path = existing_agent_file(&agents_dir, &agent.name);
+        let unchanged = match std::fs::read_to_string(&path) {
+            Ok(on_disk) => {
+                on_disk == generate_subagent_markdown(agent) && {
+                    let fields = sidecar_fields(&agent.extra);
+                    fields.is_empty() || read_sidecar(&path)? == fields
+                }
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => false,
+            Err(e) => return Err(e.into()),
+        };
+        if unchanged {
+            continue;
+        }
+
+        write_subagent_file(base_path, agent)?;
+        written.push(agent.name.clone());
+    }
+
+    Ok(written)
+}
+
 /// Write a sub-agent to the global Claude config (~/.claude/agents/)
 pub fn write_global_subagent(subagent: &SubAgent) -> Result<()> {
     let base_dirs =
@@ -84,27 +899,344 @@ pub fn write_project_subagent(project_path: &Path, subagent: &SubAgent) -> Resul
     write_subagent_file(project_path, subagent)
 }
 
+/// Settings key listing the agents a project enables
+pub const ENABLED_AGENTS_KEY: &str = "enabledAgents";
+
+/// Indentation of a JSON document's first indented line (two spaces if none)
+fn detect_json_indent(content: &str) -> String {
+    content
+        .lines()
+        .skip(1)
+        .map(|line| &line[..line.len() - line.trim_start().len()])
+        .find(|indent| !indent.is_empty())
+        .unwrap_or("  ")
+        .to_string()
+}
+
+/// Add `agent_names` to the `enabledAgents` list of {project}/.claude/settings.json,
+/// creating the file if needed. Other keys are kept in their original order,
+/// names already listed are not repeated, and the file's indentation and
+/// trailing newline are preserved.
+/// The file is left untouched when every name is already listed. Fails rather
+/// than overwrite a settings file that isn't a JSON object.
+pub fn update_project_settings_for_agents(
+    project_path: &Path,
+    agent_names: &[String],
+) -> Result<()> {
+    let settings_path = project_path.join(".claude").join("settings.json");
+    let existing = match std::fs::read_to_string(&settings_path) {
+        Ok(content) => Some(content),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+        Err(e) => return Err(e.into()),
+    };
+
+    let mut settings: serde_json::Value = match &existing {
+        Some(content) if !content.trim().is_empty() => serde_json::from_str(content)
+            .map_err(|e| anyhow::anyhow!("Invalid {}: {}", settings_path.display(), e))?,
+        _ => serde_json::json!({}),
+    };
+    let Some(object) = settings.as_object_mut() else {
+        return Err(anyhow::anyhow!(
+            "{} is not a JSON object",
+            settings_path.display()
+        ));
+    };
+
+    let enabled = object
+        .entry(ENABLED_AGENTS_KEY)
+        .or_insert_with(|| serde_json::json!([]));
+    let Some(enabled) = enabled.as_array_mut() else {
+        return Err(anyhow::anyhow!(
+            "`{}` in {} is not a list",
+            ENABLED_AGENTS_KEY,
+            settings_path.display()
+        ));
+    };
+    let before = enabled.len();
+    for name in agent_names {
+        if !enabled.iter().any(|v| v.as_str() == Some(name.as_str())) {
+            enabled.push(serde_json::Value::from(name.as_str()));
+        }
+    }
+    if existing.is_some() && enabled.len() == before {
+        return Ok(());
+    }
+
+    let original = existing.unwrap_or_default();
+    let indent = detect_json_indent(&original);
+    let mut content = Vec::new();
+    let formatter = serde_json::ser::PrettyFormatter::with_indent(indent.as_bytes());
+    let mut serializer = serde_json::Serializer::with_formatter(&mut content, formatter);
+    serde::Serialize::serialize(&settings, &mut serializer)?;
+    if original.is_empty() || original.ends_with('\n') {
+        content.push(b'\n');
+    }
+
+    if let Some(parent) = settings_path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    write_atomic(&settings_path, &content)
+}
+
+/// Fill a missing or empty `model` with the project's default model.
+/// Agents that pin their own model are returned unchanged.
+pub fn apply_project_model_default(subagent: &SubAgent, default_model: Option<&str>) -> SubAgent {
+    let mut subagent = subagent.clone();
+    let has_model = subagent
+        .model
+        .as_deref()
+        .is_some_and(|m| !m.trim().is_empty());
+
+    if !has_model {
+        subagent.model = default_model
+            .map(str::trim)
+            .filter(|m| !m.is_empty())
+            .map(str::to_string);
+    }
+
+    subagent
+}
+
+/// Write a sub-agent to a project, inheriting the project's default model if it has none
+pub fn write_project_subagent_with_default_model(
+    project_path: &Path,
+    subagent: &SubAgent,
+    default_model: Option<&str>,
+) -> Result<()> {
+    let subagent = apply_project_model_default(subagent, default_model);
+    write_subagent_file(project_path, &subagent)
+}
+
 /// Delete a sub-agent from a project's Claude config ({project}/.claude/agents/)
 pub fn delete_project_subagent(project_path: &Path, name: &str) -> Result<()> {
     delete_subagent_file(project_path, name)
 }
 
+/// Where a sub-agent is written
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AgentScope {
+    /// ~/.claude/agents/
+    Global,
+    /// {project}/.claude/agents/
+    Project,
+}
+
+/// A same-named agent in the other scope. Project agents take precedence over
+/// global ones, so one of the two is hidden.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ShadowInfo {
+    /// Scope of the existing same-named agent
+    pub other_scope: AgentScope,
+    /// Path of the existing same-named agent
+    pub other_path: PathBuf,
+    /// True if the agent being written would be hidden by the existing one;
+    /// false if it would hide the existing one
+    pub is_shadowed: bool,
+}
+
+/// Report whether writing `name` to `scope` conflicts with a same-named agent
+/// in the other scope (None when there is no conflict)
+pub fn shadow_warning(name: &str, scope: AgentScope, project_path: &Path) -> Option<ShadowInfo> {
+    let base_dirs = BaseDirs::new()?;
+    shadow_warning_with_home(name, scope, project_path, base_dirs.home_dir())
+}
+
+/// shadow_warning with an explicit home directory
+pub fn shadow_warning_with_home(
+    name: &str,
+    scope: AgentScope,
+    project_path: &Path,
+    home: &Path,
+) -> Option<ShadowInfo> {
+    let layout = ClaudeLayout::default();
+    let (other_scope, other_base) = match scope {
+        AgentScope::Global => (AgentScope::Project, project_path),
+        AgentScope::Project => (AgentScope::Global, home),
+    };
+    let other_path = existing_agent_file(&layout.agents_dir(other_base), name);
+
+    other_path.is_file().then(|| ShadowInfo {
+        other_scope,
+        other_path,
+        is_shadowed: scope == AgentScope::Global,
+    })
+}
+
+/// Tool list of the agent in effect for `name` in a project: the project's
+/// agent if it has one, otherwise the global agent. Tools are canonicalized
+/// (trimmed, sorted, deduped). Fails with WriterError::NotFound if neither
+/// scope has the agent.
+pub fn effective_agent_tools(name: &str, project_path: &Path) -> Result<Vec<String>> {
+    let base_dirs =
+        BaseDirs::new().ok_or_else(|| anyhow::anyhow!("Could not find home directory"))?;
+    effective_agent_tools_with_home(name, project_path, base_dirs.home_dir())
+}
+
+/// effective_agent_tools with an explicit home directory
+pub fn effective_agent_tools_with_home(
+    name: &str,
+    project_path: &Path,
+    home: &Path,
+) -> Result<Vec<String>> {
+    let layout = ClaudeLayout::default();
+
+    let agent_file = [project_path, home]
+        .iter()
+        .map(|base| existing_agent_file(&layout.agents_dir(base), name))
+        .find(|path| path.is_file())
+        .ok_or_else(|| WriterError::NotFound {
+            name: name.to_string(),
+        })?;
+
+    let agent = load_subagent_file(&agent_file)?;
+    Ok(canonicalize_subagent(&agent).tools.unwrap_or_default())
+}
+
 // ============================================================================
 // OpenCode Support
 // ============================================================================
 // OpenCode has a different frontmatter format:
 // - tools: object with tool names as keys and boolean values
-// - permission: object (not permissionMode string)
+// - permission: object (translated from permissionMode where possible)
 // - No "name" field (filename is the name)
 // - No "skills" field
+// - mode: "primary" or "subagent" (how the agent is invoked)
+
+/// OpenCode mode written for exported agents unless told otherwise
+pub const DEFAULT_OPENCODE_MODE: &str = "subagent";
+
+/// Options controlling how OpenCode agent markdown is generated
+#[derive(Debug, Clone)]
+pub struct OpenCodeOptions {
+    /// Lowercase tool names (OpenCode's built-in tools are lowercase).
+    /// Disable when a backend needs tool names with their exact casing.
+    pub lowercase_tools: bool,
+    /// `mode:` to emit (`primary` or `subagent`); None omits the line.
+    /// A `mode` parsed back into the agent's `extra` map takes precedence.
+    pub mode: Option<String>,
+}
+
+impl Default for OpenCodeOptions {
+    fn default() -> Self {
+        Self {
+            lowercase_tools: true,
+            mode: Some(DEFAULT_OPENCODE_MODE.to_string()),
+        }
+    }
+}
 
 /// Generate markdown content for an OpenCode agent (.opencode/agent/name.md)
 pub(crate) fn generate_subagent_markdown_opencode(subagent: &SubAgent) -> String {
+    generate_subagent_markdown_opencode_with_options(subagent, &OpenCodeOptions::default())
+}
+
+/// OpenCode's built-in tools, each with the Claude tool it stands for (None
+/// when Claude has none). The one mapping used both when generating OpenCode
+/// agents and when parsing them back. Claude tools missing here (e.g.
+/// TodoWrite, NotebookEdit) are dropped when generating OpenCode agents.
+pub const OPENCODE_TOOLS: &[(&str, Option<&str>)] = &[
+    ("bash", Some("Bash")),
+    ("edit", Some("Edit")),
+    ("write", Some("Write")),
+    ("read", Some("Read")),
+    ("grep", Some("Grep")),
+    ("glob", Some("Glob")),
+    ("list", Some("LS")),
+    ("patch", None),
+    ("webfetch", Some("WebFetch")),
+];
+
+/// OpenCode `permission` entries equivalent to a Claude permission mode.
+/// `default` needs none; None for modes OpenCode can't express.
+pub fn opencode_permissions_for_mode(
+    mode: &str,
+) -> Option<&'static [(&'static str, &'static str)]> {
+    match mode {
+        "default" => Some(&[]),
+        "acceptEdits" => Some(&[("edit", "allow")]),
+        "bypassPermissions" => Some(&[("bash", "allow"), ("edit", "allow"), ("webfetch", "allow")]),
+        // Plan mode is read-only
+        "plan" => Some(&[("bash", "deny"), ("edit", "deny")]),
+        _ => None,
+    }
+}
+
+/// Set of OpenCode's built-in tool names (lowercase)
+pub fn known_opencode_tools() -> HashSet<&'static str> {
+    OPENCODE_TOOLS
+        .iter()
+        .map(|(opencode, _)| *opencode)
+        .collect()
+}
+
+/// OpenCode's name for a Claude tool (`Read` -> `read`, `LS` -> `list`).
+/// OpenCode names are accepted too, in any case. None for tools OpenCode
+/// has no built-in equivalent of.
+pub(crate) fn opencode_tool_name(tool: &str) -> Option<&'static str> {
+    OPENCODE_TOOLS
+        .iter()
+        .find(|(opencode, claude)| {
+            opencode.eq_ignore_ascii_case(tool)
+                || claude.is_some_and(|c| c.eq_ignore_ascii_case(tool))
+        })
+        .map(|(opencode, _)| *opencode)
+}
+
+/// Claude's name for an OpenCode tool key (`read` -> `Read`, `list` -> `LS`).
+/// MCP tools and keys without a known Claude equivalent are kept as written.
+fn claude_tool_name(opencode_tool: &str) -> String {
+    OPENCODE_TOOLS
+        .iter()
+        .find(|(opencode, _)| opencode.eq_ignore_ascii_case(opencode_tool))
+        .and_then(|(_, claude)| *claude)
+        .unwrap_or(opencode_tool)
+        .to_string()
+}
+
+/// Whether a tool can be written to an OpenCode `tools:` map.
+/// MCP tools (`mcp__server__tool`) aren't built-ins and always pass through.
+pub(crate) fn is_opencode_tool(tool: &str, known: &HashSet<&str>) -> bool {
+    tool.starts_with("mcp__") || opencode_tool_name(tool).is_some_and(|t| known.contains(t))
+}
+
+/// Generate markdown content for an OpenCode agent with explicit options.
+/// Dropped tools are logged; use generate_subagent_markdown_opencode_checked
+/// to get the warnings instead.
+pub(crate) fn generate_subagent_markdown_opencode_with_options(
+    subagent: &SubAgent,
+    options: &OpenCodeOptions,
+) -> String {
+    let (markdown, warnings) = generate_subagent_markdown_opencode_checked(subagent, options);
+    for warning in warnings {
+        log::warn!("[SubAgentWriter] {}: {}", subagent.name, warning);
+    }
+    markdown
+}
+
+/// Generate markdown content for an OpenCode agent, returning a warning for
+/// each tool or permission mode dropped because OpenCode has no equivalent
+pub(crate) fn generate_subagent_markdown_opencode_checked(
+    subagent: &SubAgent,
+    options: &OpenCodeOptions,
+) -> (String, Vec<String>) {
+    let mut warnings = Vec::new();
     let mut frontmatter = String::from("---\n");
 
     // OpenCode requires description
     frontmatter.push_str(&format!("description: \"{}\"\n", subagent.description));
 
+    let mode = subagent
+        .extra
+        .get("mode")
+        .and_then(|m| m.as_str())
+        .or(options.mode.as_deref())
+        .filter(|m| !m.is_empty());
+    if let Some(mode) = mode {
+        frontmatter.push_str(&format!("mode: {}\n", mode));
+    }
+
     // OpenCode uses model with provider prefix (e.g., "anthropic/claude-sonnet-4-20250514")
     if let Some(ref model) = subagent.model {
         if !model.is_empty() {
@@ -112,61 +1244,570 @@ pub(crate) fn generate_subagent_markdown_opencode(subagent: &SubAgent) -> String
         }
     }
 
+    match subagent.extra.get("temperature") {
+        Some(serde_json::Value::Number(t)) => {
+            frontmatter.push_str(&format!("temperature: {}\n", t));
+        }
+        Some(serde_json::Value::String(t)) if !t.is_empty() => {
+            frontmatter.push_str(&format!("temperature: {}\n", t));
+        }
+        _ => {}
+    }
+
     // OpenCode tools format: object with tool names as keys and boolean values
     if let Some(ref tools) = subagent.tools {
+        let known = known_opencode_tools();
+        let (tools, dropped): (Vec<&String>, Vec<&String>) =
+            tools.iter().partition(|t| is_opencode_tool(t, &known));
+        for tool in dropped {
+            warnings.push(format!(
+                "Tool '{}' has no OpenCode equivalent and was dropped",
+                tool
+            ));
+        }
+        if tools.is_empty() && !warnings.is_empty() {
+            warnings.push(
+                "No tools remain after conversion, so OpenCode's default tools apply".to_string(),
+            );
+        }
+
         if !tools.is_empty() {
             frontmatter.push_str("tools:\n");
             for tool in tools {
-                // Convert tool name to lowercase for OpenCode
-                let tool_lower = tool.to_lowercase();
-                frontmatter.push_str(&format!("  {}: true\n", tool_lower));
+                let tool_name = if options.lowercase_tools {
+                    opencode_tool_name(tool)
+                        .map(str::to_string)
+                        .unwrap_or_else(|| tool.to_lowercase())
+                } else {
+                    tool.clone()
+                };
+                frontmatter.push_str(&format!("  {}: true\n", tool_name));
             }
         }
     }
 
-    // Note: OpenCode uses "permission" object, not "permissionMode" string
-    // We skip permissionMode for OpenCode as the format is different
+    // OpenCode has no permissionMode string; translate it to a permission
+    // object, else keep the object an OpenCode agent was read with
+    let has_mode = subagent
+        .permission_mode
+        .as_deref()
+        .is_some_and(|m| !m.is_empty());
+    let permission = subagent
+        .extra
+        .get("permission")
+        .and_then(|p| p.as_object())
+        .filter(|p| !has_mode && !p.is_empty());
+    if let Some(permission) = permission {
+        frontmatter.push_str("permission:\n");
+        for (axis, action) in permission {
+            let action = action
+                .as_str()
+                .map(str::to_string)
+                .unwrap_or_else(|| action.to_string());
+            frontmatter.push_str(&format!("  {}: {}\n", axis, action));
+        }
+    }
+    if let Some(mode) = subagent
+        .permission_mode
+        .as_deref()
+        .filter(|m| !m.is_empty())
+    {
+        match opencode_permissions_for_mode(mode) {
+            Some(entries) => {
+                if !entries.is_empty() {
+                    frontmatter.push_str("permission:\n");
+                    for (axis, action) in entries {
+                        frontmatter.push_str(&format!("  {}: {}\n", axis, action));
+                    }
+                }
+            }
+            None => warnings.push(format!(
+                "Permission mode '{}' has no OpenCode equivalent and was dropped",
+                mode
+            )),
+        }
+    }
 
     frontmatter.push_str("---\n\n");
-    format!("{}{}", frontmatter, subagent.content)
+    (format!("{}{}", frontmatter, subagent.content), warnings)
 }
 
 /// Write a sub-agent to OpenCode's format
 /// OpenCode uses {base_path}/agent/{name}.md (singular "agent")
 pub fn write_subagent_file_opencode(base_path: &Path, subagent: &SubAgent) -> Result<()> {
-    let agents_dir = base_path.join("agent"); // OpenCode uses singular
-    std::fs::create_dir_all(&agents_dir)?;
-
-    let file_path = agents_dir.join(format!("{}.md", subagent.name));
-    let content = generate_subagent_markdown_opencode(subagent);
-    std::fs::write(file_path, content)?;
+    write_subagent_file_opencode_with_options(base_path, subagent, &OpenCodeOptions::default())
+}
 
-    Ok(())
+/// Write a sub-agent to OpenCode's format with explicit generation options
+pub fn write_subagent_file_opencode_with_options(
+    base_path: &Path,
+    subagent: &SubAgent,
+    options: &OpenCodeOptions,
+) -> Result<()> {
+    let formatter = OpenCodeFormatter {
+        options: options.clone(),
+    };
+    write_subagent_with_formatter(base_path, subagent, &formatter)
 }
 
-/// Delete a sub-agent file from OpenCode's format
-pub fn delete_subagent_file_opencode(base_path: &Path, name: &str) -> Result<()> {
-    let file_path = base_path.join("agent").join(format!("{}.md", name));
-    if file_path.exists() {
-        std::fs::remove_file(file_path)?;
+/// Enabled tools from OpenCode's nested `tools:` map (`  read: true`), as
+/// Claude tool names. Keys set to anything but `true` are left out.
+fn parse_opencode_tools(raw: &str) -> Vec<String> {
+    let mut tools = Vec::new();
+    let mut in_tools = false;
+    for line in raw.lines() {
+        if !line.starts_with(' ') && !line.starts_with('\t') {
+            in_tools = line.trim_end() == "tools:";
+            continue;
+        }
+        if in_tools {
+            if let Some((name, enabled)) = line.trim().split_once(':') {
+                if enabled.trim() == "true" {
+                    tools.push(claude_tool_name(name.trim()));
+                }
+            }
+        }
     }
-    Ok(())
+    tools
 }
 
-/// Write a sub-agent to the global OpenCode config (~/.config/opencode/agent/)
-pub fn write_global_subagent_opencode(subagent: &SubAgent) -> Result<()> {
-    let paths = get_opencode_paths()?;
-    write_subagent_file_opencode(&paths.config_dir, subagent)
+/// Entries of a nested `key:` map in OpenCode frontmatter (`  edit: deny`)
+fn parse_opencode_map(raw: &str, key: &str) -> serde_json::Map<String, serde_json::Value> {
+    let header = format!("{}:", key);
+    let mut map = serde_json::Map::new();
+    let mut in_map = false;
+    for line in raw.lines() {
+        if !line.starts_with(' ') && !line.starts_with('\t') {
+            in_map = line.trim_end() == header;
+            continue;
+        }
+        if in_map {
+            if let Some((name, value)) = line.trim().split_once(':') {
+                map.insert(
+                    name.trim().to_string(),
+                    serde_json::Value::from(value.trim()),
+                );
+            }
+        }
+    }
+    map
 }
 
-/// Delete a sub-agent from the global OpenCode config
-pub fn delete_global_subagent_opencode(name: &str) -> Result<()> {
-    let paths = get_opencode_paths()?;
-    delete_subagent_file_opencode(&paths.config_dir, name)
+/// Parse an OpenCode agent file back into a SubAgent.
+/// OpenCode has no `name` field (the filename is the name), so it is left empty.
+/// OpenCode-only fields (`mode`, `temperature` and the `permission` map) are
+/// kept in `extra` so they survive re-export and conversions can report them.
+pub fn parse_subagent_markdown_opencode(content: &str) -> Result<SubAgent> {
+    check_frontmatter(content)?;
+    let (raw, body) = split_frontmatter(content)?;
+    let frontmatter = parse_yaml_map(&raw)?;
+    let tools = parse_opencode_tools(&raw);
+
+    let description = frontmatter
+        .get("description")
+        .map(|d| {
+            d.strip_prefix('"')
+                .and_then(|d| d.strip_suffix('"'))
+                .unwrap_or(d)
+                .to_string()
+        })
+        .unwrap_or_default();
+
+    let mut extra = HashMap::new();
+    if let Some(mode) = frontmatter.get("mode").filter(|m| !m.is_empty()) {
+        extra.insert("mode".to_string(), serde_json::Value::from(mode.as_str()));
+    }
+    if let Some(temperature) = frontmatter.get("temperature") {
+        let value = temperature
+            .parse::<f64>()
+            .map(serde_json::Value::from)
+            .unwrap_or_else(|_| serde_json::Value::from(temperature.as_str()));
+        extra.insert("temperature".to_string(), value);
+    }
+    let permission = parse_opencode_map(&raw, "permission");
+    if !permission.is_empty() {
+        extra.insert(
+            "permission".to_string(),
+            serde_json::Value::Object(permission),
+        );
+    }
+
+    Ok(SubAgent {
+        id: 0,
+        name: String::new(),
+        description,
+        content: body,
+        tools: (!tools.is_empty()).then_some(tools),
+        model: frontmatter.get("model").cloned(),
+        permission_mode: None,
+        skills: None,
+        color: None,
+        tags: None,
+        source: "opencode".to_string(),
+        source_path: None,
+        is_favorite: false,
+        created_at: String::new(),
+        updated_at: String::new(),
+        env: None,
+        mcp_servers: None,
+        enabled: true,
+        extends: None,
+        extra,
+    })
 }
 
-/// Write a sub-agent to a project's OpenCode config ({project}/.opencode/agent/)
-pub fn write_project_subagent_opencode(project_path: &Path, subagent: &SubAgent) -> Result<()> {
+// ============================================================================
+// Format Abstraction
+// ============================================================================
+
+/// An agent file format. Implement this to add a format without new parallel functions.
+pub trait AgentFormatter {
+    /// Render a sub-agent as file content
+    fn generate(&self, subagent: &SubAgent) -> String;
+    /// Parse file content back into a sub-agent
+    fn parse(&self, content: &str) -> Result<SubAgent>;
+    /// Location of an agent's file under a base path
+    fn file_path(&self, base_path: &Path, name: &str) -> PathBuf;
+}
+
+/// Claude Code format ({base}/.claude/agents/{name}.md by default)
+#[derive(Debug, Clone, Default)]
+pub struct ClaudeFormatter {
+    pub layout: ClaudeLayout,
+    pub options: GenerateOptions,
+}
+
+impl AgentFormatter for ClaudeFormatter {
+    fn generate(&self, subagent: &SubAgent) -> String {
+        generate_subagent_markdown_with_options(subagent, &self.options)
+    }
+
+    fn parse(&self, content: &str) -> Result<SubAgent> {
+        parse_subagent_markdown(content)
+    }
+
+    fn file_path(&self, base_path: &Path, name: &str) -> PathBuf {
+        self.layout
+            .agents_dir(base_path)
+            .join(agent_file_name(name))
+    }
+}
+
+/// OpenCode format ({base}/agent/{name}.md, singular "agent")
+#[derive(Debug, Clone, Default)]
+pub struct OpenCodeFormatter {
+    pub options: OpenCodeOptions,
+}
+
+impl AgentFormatter for OpenCodeFormatter {
+    fn generate(&self, subagent: &SubAgent) -> String {
+        generate_subagent_markdown_opencode_with_options(subagent, &self.options)
+    }
+
+    fn parse(&self, content: &str) -> Result<SubAgent> {
+        parse_subagent_markdown_opencode(content)
+    }
+
+    fn file_path(&self, base_path: &Path, name: &str) -> PathBuf {
+        base_path.join("agent").join(agent_file_name(name))
+    }
+}
+
+/// Write a sub-agent using any format
+pub fn write_subagent_with_formatter(
+    base_path: &Path,
+    subagent: &SubAgent,
+    formatter: &dyn AgentFormatter,
+) -> Result<()> {
+    write_subagent_with_formatter_and_options(
+        base_path,
+        subagent,
+        formatter,
+        &WriteOptions::default(),
+    )
+}
+
+/// Write a sub-agent to {base_path}/.claude/agents/{name}.md with write options
+pub fn write_subagent_file_with_write_options(
+    base_path: &Path,
+    subagent: &SubAgent,
+    options: &WriteOptions,
+) -> Result<()> {
+    let formatter = ClaudeFormatter {
+        layout: ClaudeLayout::default(),
+        options: GenerateOptions::default(),
+    };
+    write_subagent_with_formatter_and_options(base_path, subagent, &formatter, options)
+}
+
+/// Write a sub-agent with any formatter, applying write options to the files
+pub fn write_subagent_with_formatter_and_options(
+    base_path: &Path,
+    subagent: &SubAgent,
+    formatter: &dyn AgentFormatter,
+    options: &WriteOptions,
+) -> Result<()> {
+    if options.strict {
+        if let Some(model) = subagent.model.as_deref().filter(|m| !m.is_empty()) {
+            validate_model(model, &options.known_models)?;
+        }
+    }
+
+    // An agent saved before names were encoded keeps its legacy file, so
+    // saving it doesn't leave a second copy behind
+    let file_path = formatter.file_path(base_path, &subagent.name);
+    let file_path = match file_path.parent() {
+        Some(parent) => {
+            std::fs::create_dir_all(parent)?;
+            existing_agent_file(parent, &subagent.name)
+        }
+        None => file_path,
+    };
+
+    write_atomic(&file_path, formatter.generate(subagent).as_bytes())?;
+    write_sidecar(&file_path, &subagent.extra)?;
+    record_original_name(&file_path, &subagent.name)?;
+
+    if let Some(mode) = options.mode {
+        set_file_mode(&file_path, mode)?;
+        let sidecar = sidecar_path(&file_path);
+        if sidecar.exists() {
+            set_file_mode(&sidecar, mode)?;
+        }
+    }
+    Ok(())
+}
+
+/// Temp files written so far by this process (part of their unique names)
+static TEMP_FILE_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+/// Unique temp file next to `path`, so concurrent writers in one directory
+/// (from this or another process) never share a temp file
+fn atomic_temp_path(path: &Path) -> PathBuf {
+    let file_name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+    path.with_file_name(format!(
+        ".{}.{}-{}-{}.tmp",
+        file_name,
+        std::process::id(),
+        TEMP_FILE_COUNTER.fetch_add(1, Ordering::Relaxed),
+        uuid::Uuid::new_v4().simple()
+    ))
+}
+
+/// Write a file by writing a temp file in the same directory and renaming it
+/// over `path`, so readers never see a partially written file
+pub(crate) fn write_atomic(path: &Path, contents: &[u8]) -> Result<()> {
+    let temp_path = atomic_temp_path(path);
+    if let Err(e) =
+        std::fs::write(&temp_path, contents).and_then(|_| std::fs::rename(&temp_path, path))
+    {
+        let _ = std::fs::remove_file(&temp_path);
+        return Err(e.into());
+    }
+    Ok(())
+}
+
+#[cfg(unix)]
+fn set_file_mode(path: &Path, mode: u32) -> Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+    std::fs::set_permissions(path, std::fs::Permissions::from_mode(mode))?;
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn set_file_mode(_path: &Path, _mode: u32) -> Result<()> {
+    Ok(())
+}
+
+/// Delete a sub-agent file from OpenCode's format
+pub fn delete_subagent_file_opencode(base_path: &Path, name: &str) -> Result<()> {
+    let file_path = existing_agent_file(&base_path.join("agent"), name);
+    if file_path.exists() {
+        std::fs::remove_file(&file_path)?;
+    }
+    remove_sidecar(&file_path)?;
+    forget_original_name(&file_path)
+}
+
+// ============================================================================
+// Metadata Sidecar
+// ============================================================================
+// Fields frontmatter can't hold (nested config, etc.) live in a JSON object
+// at {name}.meta.json next to the agent's markdown file.
+
+/// Path of the `{name}.meta.json` sidecar for an agent markdown file
+pub fn sidecar_path(agent_file: &Path) -> PathBuf {
+    let name = agent_file
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_default();
+    agent_file.with_file_name(format!("{}.meta.json", name))
+}
+
+/// OpenCode frontmatter fields parse_subagent_markdown_opencode keeps in
+/// `extra`. They are written to the OpenCode frontmatter, never the sidecar.
+pub const OPENCODE_EXTRA_FIELDS: [&str; 3] = ["mode", "temperature", "permission"];
+
+/// The `extra` fields that belong in the sidecar
+fn sidecar_fields(
+    extra: &HashMap<String, serde_json::Value>,
+) -> HashMap<String, serde_json::Value> {
+    extra
+        .iter()
+        .filter(|(key, _)| !OPENCODE_EXTRA_FIELDS.contains(&key.as_str()))
+        .map(|(key, value)| (key.clone(), value.clone()))
+        .collect()
+}
+
+/// Write the sidecar when `extra` has sidecar fields. Otherwise any existing
+/// sidecar is left alone: agents loaded from the database carry no extra
+/// fields, and saving them must not lose the user's file. Only deleting the
+/// agent removes its sidecar.
+fn write_sidecar(agent_file: &Path, extra: &HashMap<String, serde_json::Value>) -> Result<()> {
+    let fields = sidecar_fields(extra);
+    if fields.is_empty() {
+        return Ok(());
+    }
+
+    // Sorted keys keep the file stable across saves
+    let sorted: std::collections::BTreeMap<_, _> = fields.iter().collect();
+    let content = serde_json::to_string_pretty(&sorted)? + "\n";
+    write_atomic(&sidecar_path(agent_file), content.as_bytes())
+}
+
+fn remove_sidecar(agent_file: &Path) -> Result<()> {
+    let path = sidecar_path(agent_file);
+    if path.exists() {
+        std::fs::remove_file(path)?;
+    }
+    Ok(())
+}
+
+/// Read an agent's sidecar (empty if there is none)
+pub fn read_sidecar(agent_file: &Path) -> Result<HashMap<String, serde_json::Value>> {
+    let path = sidecar_path(agent_file);
+    if !path.exists() {
+        return Ok(HashMap::new());
+    }
+
+    let content = std::fs::read_to_string(&path)?;
+    serde_json::from_str(&content)
+        .map_err(|e| anyhow::anyhow!("Invalid sidecar {}: {}", path.display(), e))
+}
+
+/// Load a Claude-format agent file, merging its sidecar into `extra`
+pub fn load_subagent_file(agent_file: &Path) -> Result<SubAgent> {
+    let content = std::fs::read_to_string(agent_file)?;
+    let mut subagent = parse_subagent_markdown(&content)?;
+
+    if subagent.name.is_empty() {
+        subagent.name = original_name(agent_file);
+    }
+    subagent.source_path = Some(agent_file.to_string_lossy().to_string());
+    subagent.extra.extend(read_sidecar(agent_file)?);
+
+    Ok(subagent)
+}
+
+/// Load an OpenCode-format agent file, merging its sidecar into `extra`.
+/// OpenCode files carry no name, so it is recovered from the file name.
+pub fn load_subagent_file_opencode(agent_file: &Path) -> Result<SubAgent> {
+    let content = std::fs::read_to_string(agent_file)?;
+    let mut subagent = parse_subagent_markdown_opencode(&content)?;
+
+    subagent.name = original_name(agent_file);
+    subagent.source_path = Some(agent_file.to_string_lossy().to_string());
+    subagent.extra.extend(read_sidecar(agent_file)?);
+
+    Ok(subagent)
+}
+
+/// Load an agent file for import: as load_subagent_file, with `source` set
+/// to "imported". The name falls back to the file name when the frontmatter
+/// has none.
+pub fn parse_subagent_file(path: &Path) -> Result<SubAgent> {
+    let mut subagent = load_subagent_file(path)?;
+    subagent.source = "imported".to_string();
+    Ok(subagent)
+}
+
+/// List the Claude-format agents in {base_path}/.claude/agents, sorted by name.
+///
+/// With `modified_after`, files whose mtime is not later than it are skipped
+/// without being read, so a UI can refresh only what changed. Files that
+/// cannot be parsed are skipped with a warning.
+pub fn list_subagents(
+    base_path: &Path,
+    modified_after: Option<SystemTime>,
+) -> Result<Vec<SubAgent>> {
+    let agents_dir = ClaudeLayout::default().agents_dir(base_path);
+    if !agents_dir.is_dir() {
+        return Ok(Vec::new());
+    }
+
+    let mut agents = Vec::new();
+    for entry in std::fs::read_dir(&agents_dir)? {
+        let entry = entry?;
+        let path = entry.path();
+        if !path.is_file() || path.extension().is_none_or(|ext| ext != "md") {
+            continue;
+        }
+
+        if let Some(since) = modified_after {
+            let modified = entry.metadata()?.modified()?;
+            if modified <= since {
+                continue;
+            }
+        }
+
+        match load_subagent_file(&path) {
+            Ok(agent) => agents.push(agent),
+            Err(e) => log::warn!("[SubAgentWriter] Skipping {}: {}", path.display(), e),
+        }
+    }
+
+    agents.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(agents)
+}
+
+/// Names of the agents in {base_path}/.claude/agents (sorted), recovered from
+/// their file names. Only the directory listing and the filename map are read;
+/// no agent file is opened or parsed.
+pub fn list_subagent_names(base_path: &Path) -> Result<Vec<String>> {
+    let agents_dir = ClaudeLayout::default().agents_dir(base_path);
+    if !agents_dir.is_dir() {
+        return Ok(Vec::new());
+    }
+
+    let mut names = Vec::new();
+    for entry in std::fs::read_dir(&agents_dir)? {
+        let path = entry?.path();
+        if path.extension().is_some_and(|ext| ext == "md") {
+            names.push(original_name(&path));
+        }
+    }
+
+    names.sort();
+    Ok(names)
+}
+
+/// Write a sub-agent to the global OpenCode config (~/.config/opencode/agent/)
+pub fn write_global_subagent_opencode(subagent: &SubAgent) -> Result<()> {
+    let paths = get_opencode_paths()?;
+    write_subagent_file_opencode(&paths.config_dir, subagent)
+}
+
+/// Delete a sub-agent from the global OpenCode config
+pub fn delete_global_subagent_opencode(name: &str) -> Result<()> {
+    let paths = get_opencode_paths()?;
+    delete_subagent_file_opencode(&paths.config_dir, name)
+}
+
+/// Write a sub-agent to a project's OpenCode config ({project}/.opencode/agent/)
+pub fn write_project_subagent_opencode(project_path: &Path, subagent: &SubAgent) -> Result<()> {
     let opencode_dir = project_path.join(".opencode");
     write_subagent_file_opencode(&opencode_dir, subagent)
 }
@@ -177,9 +1818,90 @@ pub fn delete_project_subagent_opencode(project_path: &Path, name: &str) -> Resu
     delete_subagent_file_opencode(&opencode_dir, name)
 }
 
+/// Generate a project AGENTS.md listing the available agents after a custom preamble
+pub(crate) fn generate_agents_md(agents: &[SubAgent], preamble: &str) -> String {
+    let mut md = String::from(
+        "<!-- Generated by Claude Code Tool Manager. Manual edits will be overwritten. -->\n\n",
+    );
+
+    let preamble = preamble.trim();
+    if !preamble.is_empty() {
+        md.push_str(preamble);
+        md.push_str("\n\n");
+    }
+
+    md.push_str("## Agents\n\n");
+
+    let mut sorted: Vec<&SubAgent> = agents.iter().collect();
+    sorted.sort_by(|a, b| a.name.cmp(&b.name));
+
+    if sorted.is_empty() {
+        md.push_str("_No agents installed._\n");
+    }
+    for agent in sorted {
+        if agent.description.is_empty() {
+            md.push_str(&format!("- `@{}`\n", agent.name));
+        } else {
+            md.push_str(&format!("- `@{}`: {}\n", agent.name, agent.description));
+        }
+    }
+
+    md
+}
+
+/// Write a project-level AGENTS.md ({project}/AGENTS.md) for OpenCode.
+/// The file is regenerated in full on every call.
+pub fn write_agents_md(project_path: &Path, agents: &[SubAgent], preamble: &str) -> Result<()> {
+    std::fs::create_dir_all(project_path)?;
+    let content = generate_agents_md(agents, preamble);
+    std::fs::write(project_path.join("AGENTS.md"), content)?;
+    Ok(())
+}
+
+/// Export agents as {out_dir}/{name}.md with Claude frontmatter, without the
+/// `.claude/agents` nesting, for tools that read agents from a chosen folder.
+///
+/// Unless `overwrite` is set, an existing file or two agents with the same name
+/// is an error, and nothing is written. Returns the written paths.
+pub fn export_agents_flat(
+    agents: &[SubAgent],
+    out_dir: &Path,
+    overwrite: bool,
+) -> Result<Vec<PathBuf>> {
+    let paths: Vec<PathBuf> = agents
+        .iter()
+        .map(|agent| out_dir.join(agent_file_name(&agent.name)))
+        .collect();
+
+    if !overwrite {
+        let mut seen = HashSet::new();
+        for path in &paths {
+            if !seen.insert(path) {
+                return Err(anyhow::anyhow!(
+                    "Two agents would be exported to {}",
+                    path.display()
+                ));
+            }
+            if path.exists() {
+                return Err(anyhow::anyhow!(
+                    "{} already exists (set overwrite to replace it)",
+                    path.display()
+                ));
+            }
+        }
+    }
+
+    std::fs::create_dir_all(out_dir)?;
+    for (agent, path) in agents.iter().zip(&paths) {
+        std::fs::write(path, generate_subagent_markdown(agent))?;
+    }
+    Ok(paths)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::sync::Arc;
     use tempfile::TempDir;
 
     // =========================================================================
@@ -196,304 +1918,1836 @@ mod tests {
             model: Some("sonnet".to_string()),
             permission_mode: Some("bypassPermissions".to_string()),
             skills: Some(vec!["lint".to_string(), "format".to_string()]),
+            color: None,
             tags: Some(vec!["review".to_string(), "quality".to_string()]),
             source: "manual".to_string(),
             source_path: None,
             is_favorite: false,
             created_at: "2024-01-01".to_string(),
             updated_at: "2024-01-01".to_string(),
+            env: None,
+            mcp_servers: None,
+            enabled: true,
+            extends: None,
+            extra: Default::default(),
+        }
+    }
+
+    fn sample_minimal_subagent() -> SubAgent {
+        SubAgent {
+            id: 2,
+            name: "simple-agent".to_string(),
+            description: "A simple agent".to_string(),
+            content: "You are a helpful assistant.".to_string(),
+            tools: None,
+            model: None,
+            permission_mode: None,
+            skills: None,
+            color: None,
+            tags: None,
+            source: "manual".to_string(),
+            source_path: None,
+            is_favorite: false,
+            created_at: "2024-01-01".to_string(),
+            updated_at: "2024-01-01".to_string(),
+            env: None,
+            mcp_servers: None,
+            enabled: true,
+            extends: None,
+            extra: Default::default(),
+        }
+    }
+
+    // =========================================================================
+    // generate_subagent_markdown tests
+    // =========================================================================
+
+    #[test]
+    fn test_generate_subagent_markdown_full() {
+        let subagent = sample_full_subagent();
+        let md = generate_subagent_markdown(&subagent);
+
+        assert!(md.starts_with("---\n"));
+        assert!(md.contains("name: code-reviewer\n"));
+        assert!(md.contains("description: Reviews code for bugs and improvements\n"));
+        assert!(md.contains("tools: Read, Grep, Glob\n"));
+        assert!(md.contains("model: sonnet\n"));
+        assert!(md.contains("permissionMode: bypassPermissions\n"));
+        assert!(md.contains("skills: lint, format\n"));
+        assert!(md.contains("---\n\nYou are a code review expert."));
+    }
+
+    #[test]
+    fn test_generate_subagent_markdown_minimal() {
+        let subagent = sample_minimal_subagent();
+        let md = generate_subagent_markdown(&subagent);
+
+        assert!(md.contains("name: simple-agent\n"));
+        assert!(md.contains("description: A simple agent\n"));
+        assert!(!md.contains("tools:"));
+        assert!(!md.contains("model:"));
+        assert!(!md.contains("permissionMode:"));
+        assert!(!md.contains("skills:"));
+        assert!(md.contains("---\n\nYou are a helpful assistant."));
+    }
+
+    #[test]
+    fn test_generate_subagent_markdown_empty_tools_skipped() {
+        let mut subagent = sample_full_subagent();
+        subagent.tools = Some(vec![]);
+        let md = generate_subagent_markdown(&subagent);
+
+        assert!(!md.contains("tools:"));
+    }
+
+    #[test]
+    fn test_generate_subagent_markdown_empty_model_skipped() {
+        let mut subagent = sample_full_subagent();
+        subagent.model = Some("".to_string());
+        let md = generate_subagent_markdown(&subagent);
+
+        assert!(!md.contains("model:"));
+    }
+
+    #[test]
+    fn test_generate_subagent_markdown_empty_permission_mode_skipped() {
+        let mut subagent = sample_full_subagent();
+        subagent.permission_mode = Some("".to_string());
+        let md = generate_subagent_markdown(&subagent);
+
+        assert!(!md.contains("permissionMode:"));
+    }
+
+    #[test]
+    fn test_generate_subagent_markdown_empty_skills_skipped() {
+        let mut subagent = sample_full_subagent();
+        subagent.skills = Some(vec![]);
+        let md = generate_subagent_markdown(&subagent);
+
+        assert!(!md.contains("skills:"));
+    }
+
+    #[test]
+    fn test_parse_subagent_markdown_all_tools_wildcard() {
+        for line in ["tools: \"*\"", "tools: *", "tools: '*'"] {
+            let md = format!("---\nname: a\ndescription: b\n{}\n---\n\nBody", line);
+            let parsed = parse_subagent_markdown(&md).unwrap();
+            assert_eq!(parsed.tools, None, "{}", line);
+        }
+    }
+
+    #[test]
+    fn test_generate_subagent_markdown_explicit_all_tools() {
+        let mut subagent = sample_full_subagent();
+        subagent.tools = None;
+        assert!(!generate_subagent_markdown(&subagent).contains("tools:"));
+
+        let options = GenerateOptions {
+            explicit_all_tools: true,
+            ..Default::default()
+        };
+        let md = generate_subagent_markdown_with_options(&subagent, &options);
+        assert!(md.contains("tools: \"*\"\n"));
+        assert_eq!(parse_subagent_markdown(&md).unwrap().tools, None);
+
+        // A tool list is written as usual
+        subagent.tools = Some(vec!["Read".to_string()]);
+        let md = generate_subagent_markdown_with_options(&subagent, &options);
+        assert!(md.contains("tools: Read\n"));
+    }
+
+    #[test]
+    fn test_tool_order_preserved_unless_sorting() {
+        let content = "---\nname: finder\ndescription: Finds code\ntools: Grep, Read, Glob\n---\n\nYou find code.";
+        let agent = parse_subagent_markdown(content).unwrap();
+        let expected = vec!["Grep".to_string(), "Read".to_string(), "Glob".to_string()];
+        assert_eq!(agent.tools, Some(expected.clone()));
+
+        let rewritten = generate_subagent_markdown(&agent);
+        assert!(rewritten.contains("tools: Grep, Read, Glob\n"));
+        assert_eq!(
+            parse_subagent_markdown(&rewritten).unwrap().tools,
+            Some(expected)
+        );
+
+        let options = GenerateOptions {
+            sort_lists: true,
+            ..Default::default()
+        };
+        let sorted = generate_subagent_markdown_with_options(&agent, &options);
+        assert!(sorted.contains("tools: Glob, Grep, Read\n"));
+    }
+
+    #[test]
+    fn test_generate_subagent_markdown_crlf() {
+        let mut subagent = sample_full_subagent();
+        subagent.content = "Line one.\nLine two.".to_string();
+        let options = GenerateOptions {
+            line_ending: LineEnding::Crlf,
+            ..Default::default()
+        };
+        let md = generate_subagent_markdown_with_options(&subagent, &options);
+
+        assert!(md.starts_with("---\r\nname: code-reviewer\r\n"));
+        assert!(md.contains("---\r\n\r\nLine one.\r\nLine two."));
+        // Every newline is part of a CRLF pair
+        assert_eq!(md.matches('\n').count(), md.matches("\r\n").count());
+    }
+
+    #[test]
+    fn test_generate_subagent_markdown_lf_has_no_crlf() {
+        let mut subagent = sample_full_subagent();
+        subagent.content = "Line one.\nLine two.".to_string();
+        let md = generate_subagent_markdown_with_options(&subagent, &GenerateOptions::default());
+
+        assert!(!md.contains("\r\n"));
+        assert_eq!(md, generate_subagent_markdown(&subagent));
+    }
+
+    #[test]
+    fn test_blank_lines_after_frontmatter() {
+        let subagent = sample_minimal_subagent();
+        let fence = |blank_lines: usize| {
+            let options = GenerateOptions {
+                blank_lines_after_frontmatter: blank_lines,
+                ..Default::default()
+            };
+            generate_subagent_markdown_with_options(&subagent, &options)
+        };
+
+        assert!(fence(0).contains(&format!("\n---\n{}", subagent.content)));
+        assert!(fence(1).contains(&format!("\n---\n\n{}", subagent.content)));
+        assert!(fence(2).contains(&format!("\n---\n\n\n{}", subagent.content)));
+        assert_eq!(fence(1), generate_subagent_markdown(&subagent));
+        assert_eq!(fence(50), fence(MAX_BLANK_LINES_AFTER_FRONTMATTER));
+
+        for blank_lines in 0..=2 {
+            let parsed = parse_subagent_markdown(&fence(blank_lines)).unwrap();
+            assert_eq!(parsed.content, subagent.content);
+        }
+    }
+
+    #[test]
+    fn test_apply_line_ending_does_not_double_carriage_returns() {
+        let text = "a\r\nb\nc".to_string();
+        assert_eq!(apply_line_ending(text, LineEnding::Crlf), "a\r\nb\r\nc");
+    }
+
+    // =========================================================================
+    // color tests
+    // =========================================================================
+
+    #[test]
+    fn test_subagent_valid_color_round_trip() {
+        let mut subagent = sample_full_subagent();
+        subagent.color = Some("purple".to_string());
+
+        assert!(validate_subagent_color("purple").is_none());
+
+        let md = generate_subagent_markdown(&subagent);
+        assert!(md.contains("color: purple\n"));
+
+        let parsed = parse_subagent_markdown(&md).unwrap();
+        assert_eq!(parsed.color, Some("purple".to_string()));
+    }
+
+    #[test]
+    fn test_subagent_unknown_color_warns_but_is_kept() {
+        let mut subagent = sample_full_subagent();
+        subagent.color = Some("chartreuse".to_string());
+
+        let warning = validate_subagent_color("chartreuse").unwrap();
+        assert!(warning.contains("chartreuse"));
+
+        let md = generate_subagent_markdown(&subagent);
+        assert!(md.contains("color: chartreuse\n"));
+
+        let parsed = parse_subagent_markdown(&md).unwrap();
+        assert_eq!(parsed.color, Some("chartreuse".to_string()));
+    }
+
+    #[test]
+    fn test_generate_subagent_markdown_no_color() {
+        let md = generate_subagent_markdown(&sample_minimal_subagent());
+        assert!(!md.contains("color:"));
+    }
+
+    // =========================================================================
+    // parse_subagent_markdown tests
+    // =========================================================================
+
+    #[test]
+    fn test_parse_subagent_markdown_round_trip() {
+        let subagent = sample_full_subagent();
+        let parsed = parse_subagent_markdown(&generate_subagent_markdown(&subagent)).unwrap();
+
+        assert_eq!(parsed.name, subagent.name);
+        assert_eq!(parsed.description, subagent.description);
+        assert_eq!(parsed.content, subagent.content);
+        assert_eq!(parsed.tools, subagent.tools);
+        assert_eq!(parsed.model, subagent.model);
+        assert_eq!(parsed.permission_mode, subagent.permission_mode);
+        assert_eq!(parsed.skills, subagent.skills);
+    }
+
+    #[test]
+    fn test_parse_subagent_markdown_detects_unterminated_frontmatter() {
+        let content = "---\nname: broken\ndescription: Never closed\n\nYou are broken.";
+
+        let err = parse_subagent_markdown(content).unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<WriterError>(),
+            Some(&WriterError::MalformedFrontmatter { line: 1 })
+        );
+    }
+
+    #[test]
+    fn test_parse_many_subagents_isolates_failures() {
+        let good = "---\nname: helper\ndescription: Helps out\n---\n\nYou help.";
+        let malformed = "---\nname: broken\n\nNever closed.";
+
+        let results = parse_many_subagents(&[good, malformed]);
+
+        assert_eq!(results.len(), 2);
+        let agent = results[0].as_ref().unwrap();
+        assert_eq!(agent.name, "helper");
+        assert_eq!(agent.content, "You help.");
+        assert_eq!(
+            results[1]
+                .as_ref()
+                .unwrap_err()
+                .downcast_ref::<WriterError>(),
+            Some(&WriterError::MalformedFrontmatter { line: 1 })
+        );
+    }
+
+    #[test]
+    fn test_validate_subagent_shape_accepts_serialized_agent() {
+        let value = serde_json::to_value(sample_full_subagent()).unwrap();
+        assert!(validate_subagent_shape(&value).is_ok());
+        assert_eq!(subagent_from_value(value).unwrap().name, "code-reviewer");
+    }
+
+    #[test]
+    fn test_validate_subagent_shape_rejects_missing_or_empty_name() {
+        let mut value = serde_json::to_value(sample_full_subagent()).unwrap();
+        value.as_object_mut().unwrap().remove("name");
+        let err = validate_subagent_shape(&value).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Invalid agent: $.name: required property is missing"
+        );
+
+        value["name"] = serde_json::json!("");
+        let err = validate_subagent_shape(&value).unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("$.name: must be at least 1 character"));
+    }
+
+    #[test]
+    fn test_validate_subagent_shape_rejects_non_string_tool() {
+        let mut value = serde_json::to_value(sample_full_subagent()).unwrap();
+        value["tools"] = serde_json::json!(["Read", 42]);
+
+        let err = subagent_from_value(value).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Invalid agent: $.tools[1]: expected string, got number"
+        );
+    }
+
+    #[test]
+    fn test_update_project_settings_for_agents_keeps_other_keys() {
+        let temp_dir = TempDir::new().unwrap();
+        let settings_path = temp_dir.path().join(".claude").join("settings.json");
+        std::fs::create_dir_all(settings_path.parent().unwrap()).unwrap();
+        std::fs::write(
+            &settings_path,
+            "{\n    \"permissions\": {\n        \"allow\": [\"Bash(git:*)\"]\n    },\n    \"enabledAgents\": [\"planner\"],\n    \"model\": \"sonnet\"\n}\n",
+        )
+        .unwrap();
+
+        update_project_settings_for_agents(
+            temp_dir.path(),
+            &[
+                "reviewer".to_string(),
+                "planner".to_string(),
+                "tester".to_string(),
+            ],
+        )
+        .unwrap();
+
+        let content = std::fs::read_to_string(&settings_path).unwrap();
+        let settings: serde_json::Value = serde_json::from_str(&content).unwrap();
+        assert_eq!(
+            settings,
+            serde_json::json!({
+                "permissions": {"allow": ["Bash(git:*)"]},
+                "enabledAgents": ["planner", "reviewer", "tester"],
+                "model": "sonnet",
+            })
+        );
+        // Key order, four-space indentation and the trailing newline survive
+        assert_eq!(
+            content,
+            "{\n    \"permissions\": {\n        \"allow\": [\n            \"Bash(git:*)\"\n        ]\n    },\n    \"enabledAgents\": [\n        \"planner\",\n        \"reviewer\",\n        \"tester\"\n    ],\n    \"model\": \"sonnet\"\n}\n"
+        );
+    }
+
+    #[test]
+    fn test_update_project_settings_for_agents_creates_file() {
+        let temp_dir = TempDir::new().unwrap();
+        update_project_settings_for_agents(temp_dir.path(), &["reviewer".to_string()]).unwrap();
+
+        let content =
+            std::fs::read_to_string(temp_dir.path().join(".claude").join("settings.json")).unwrap();
+        assert_eq!(
+            content,
+            "{\n  \"enabledAgents\": [\n    \"reviewer\"\n  ]\n}\n"
+        );
+    }
+
+    #[test]
+    fn test_check_frontmatter_accepts_closed_or_missing_block() {
+        assert!(check_frontmatter("---\nname: ok\n---\n\nBody").is_ok());
+        assert!(check_frontmatter("Just a prompt, no frontmatter").is_ok());
+        assert!(check_frontmatter("").is_ok());
+    }
+
+    #[test]
+    fn test_check_frontmatter_needs_exact_closing_delimiter() {
+        assert_eq!(
+            check_frontmatter("---\nname: broken\n----\nBody"),
+            Err(WriterError::MalformedFrontmatter { line: 1 })
+        );
+        assert!(check_frontmatter("---\nname: ok\n---  \nBody").is_ok());
+    }
+
+    #[test]
+    fn test_try_repair_frontmatter_closes_block() {
+        let content =
+            "---\nname: broken\ndescription: Never closed\ntools: Read, Grep\n\nYou are broken.\n";
+
+        let repaired = try_repair_frontmatter(content).unwrap();
+        assert_eq!(
+            repaired,
+            "---\nname: broken\ndescription: Never closed\ntools: Read, Grep\n---\n\nYou are broken.\n"
+        );
+
+        let parsed = parse_subagent_markdown(&repaired).unwrap();
+        assert_eq!(parsed.name, "broken");
+        assert_eq!(parsed.content, "You are broken.\n");
+    }
+
+    #[test]
+    fn test_try_repair_frontmatter_leaves_valid_or_unclear_content() {
+        assert!(try_repair_frontmatter("---\nname: ok\n---\n\nBody").is_none());
+        // Nothing that looks like frontmatter follows the opener
+        assert!(try_repair_frontmatter("---\n\nJust a horizontal rule").is_none());
+    }
+
+    #[test]
+    fn test_parse_subagent_markdown_irregular_tool_separators() {
+        let md = "---\nname: a\ndescription: b\ntools: Read ,  Grep,\n---\n\nBody";
+        let parsed = parse_subagent_markdown(md).unwrap();
+        assert_eq!(
+            parsed.tools,
+            Some(vec!["Read".to_string(), "Grep".to_string()])
+        );
+    }
+
+    // =========================================================================
+    // write_subagent_file tests
+    // =========================================================================
+
+    #[test]
+    fn test_write_subagent_file_creates_correct_path() {
+        let temp_dir = TempDir::new().unwrap();
+        let subagent = sample_full_subagent();
+
+        write_subagent_file(temp_dir.path(), &subagent).unwrap();
+
+        let expected_path = temp_dir
+            .path()
+            .join(".claude")
+            .join("agents")
+            .join("code-reviewer.md");
+        assert!(expected_path.exists());
+    }
+
+    #[test]
+    fn test_write_subagent_file_content_matches() {
+        let temp_dir = TempDir::new().unwrap();
+        let subagent = sample_full_subagent();
+
+        write_subagent_file(temp_dir.path(), &subagent).unwrap();
+
+        let file_path = temp_dir
+            .path()
+            .join(".claude")
+            .join("agents")
+            .join("code-reviewer.md");
+        let content = std::fs::read_to_string(file_path).unwrap();
+
+        assert!(content.contains("name: code-reviewer"));
+        assert!(content.contains("You are a code review expert."));
+    }
+
+    #[test]
+    fn test_write_subagent_file_stamped_refreshes_updated_at() {
+        let temp_dir = TempDir::new().unwrap();
+        let subagent = sample_full_subagent();
+
+        let stamped = write_subagent_file_stamped(temp_dir.path(), &subagent).unwrap();
+
+        assert!(stamped.updated_at > subagent.updated_at);
+        assert!(
+            chrono::NaiveDateTime::parse_from_str(&stamped.updated_at, UPDATED_AT_FORMAT).is_ok()
+        );
+        assert_eq!(stamped.created_at, subagent.created_at);
+        assert_eq!(stamped.content, subagent.content);
+        assert!(temp_dir
+            .path()
+            .join(".claude/agents/code-reviewer.md")
+            .is_file());
+    }
+
+    #[test]
+    fn test_write_subagent_file_concurrent_writers_same_dir() {
+        let temp_dir = TempDir::new().unwrap();
+        let base = temp_dir.path().to_path_buf();
+
+        let handles: Vec<_> = (0..16)
+            .map(|i| {
+                let base = base.clone();
+                std::thread::spawn(move || {
+                    let mut subagent = sample_full_subagent();
+                    subagent.name = format!("agent-{}", i % 8);
+                    subagent.content = format!("Body {}", i % 8);
+                    subagent
+                        .extra
+                        .insert("index".to_string(), serde_json::json!(i % 8));
+                    write_subagent_file(&base, &subagent)
+                })
+            })
+            .collect();
+        for handle in handles {
+            handle.join().unwrap().unwrap();
+        }
+
+        let agents_dir = base.join(".claude").join("agents");
+        for i in 0..8 {
+            let path = agents_dir.join(format!("agent-{}.md", i));
+            let parsed = parse_subagent_markdown(&std::fs::read_to_string(&path).unwrap()).unwrap();
+            assert_eq!(parsed.content, format!("Body {}", i));
+            assert_eq!(read_sidecar(&path).unwrap()["index"], serde_json::json!(i));
+        }
+        // No temp files are left behind
+        let leftovers = std::fs::read_dir(&agents_dir)
+            .unwrap()
+            .filter(|e| {
+                e.as_ref()
+                    .unwrap()
+                    .path()
+                    .extension()
+                    .is_some_and(|x| x == "tmp")
+            })
+            .count();
+        assert_eq!(leftovers, 0);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_write_subagent_file_applies_mode() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let temp_dir = TempDir::new().unwrap();
+        let mut subagent = sample_full_subagent();
+        subagent
+            .extra
+            .insert("owner".to_string(), serde_json::json!("platform"));
+
+        write_subagent_file_with_write_options(
+            temp_dir.path(),
+            &subagent,
+            &WriteOptions {
+                mode: Some(0o600),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+        let path = temp_dir.path().join(".claude/agents/code-reviewer.md");
+        let mode = |p: &Path| std::fs::metadata(p).unwrap().permissions().mode() & 0o777;
+        assert_eq!(mode(&path), 0o600);
+        assert_eq!(mode(&sidecar_path(&path)), 0o600);
+    }
+
+    fn known_models() -> Vec<String> {
+        ["sonnet", "opus", "haiku", "claude-sonnet-4-20250514"]
+            .iter()
+            .map(|m| m.to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_validate_model_suggests_fix_for_typo() {
+        let err = validate_model("sonet", &known_models()).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Unknown model 'sonet'. Did you mean 'sonnet'?"
+        );
+
+        let err = validate_model("gpt-4o", &known_models()).unwrap_err();
+        assert!(err
+            .to_string()
+            .starts_with("Unknown model 'gpt-4o' (expected one of:"));
+
+        // Strict writes refuse the typo; non-strict writes keep it
+        let temp_dir = TempDir::new().unwrap();
+        let mut subagent = sample_full_subagent();
+        subagent.model = Some("sonet".to_string());
+        let strict = WriteOptions {
+            strict: true,
+            known_models: known_models(),
+            ..Default::default()
+        };
+        assert!(
+            write_subagent_file_with_write_options(temp_dir.path(), &subagent, &strict).is_err()
+        );
+        assert!(!temp_dir
+            .path()
+            .join(".claude/agents/code-reviewer.md")
+            .exists());
+        write_subagent_file_with_write_options(
+            temp_dir.path(),
+            &subagent,
+            &WriteOptions::default(),
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_validate_model_accepts_provider_prefixed_model() {
+        assert!(validate_model("opus", &known_models()).is_ok());
+        assert!(validate_model("anthropic/claude-sonnet-4-20250514", &known_models()).is_ok());
+        assert!(validate_model("anthropic/claude-sonnet-5", &known_models()).is_err());
+    }
+
+    #[test]
+    fn test_write_subagent_file_creates_directories() {
+        let temp_dir = TempDir::new().unwrap();
+        let subagent = sample_minimal_subagent();
+
+        // Directory doesn't exist yet
+        let agents_dir = temp_dir.path().join(".claude").join("agents");
+        assert!(!agents_dir.exists());
+
+        write_subagent_file(temp_dir.path(), &subagent).unwrap();
+
+        assert!(agents_dir.exists());
+    }
+
+    #[test]
+    fn test_write_subagent_file_custom_layout() {
+        let temp_dir = TempDir::new().unwrap();
+        let subagent = sample_full_subagent();
+        let layout = ClaudeLayout {
+            agents_subdir: Path::new(".claude-code").join("agents"),
+            ..ClaudeLayout::default()
+        };
+
+        write_subagent_file_with_layout(temp_dir.path(), &subagent, &layout).unwrap();
+
+        let expected_path = temp_dir
+            .path()
+            .join(".claude-code")
+            .join("agents")
+            .join("code-reviewer.md");
+        assert!(expected_path.exists());
+        assert!(!temp_dir.path().join(".claude").exists());
+
+        delete_subagent_file_with_layout(temp_dir.path(), &subagent.name, &layout).unwrap();
+        assert!(!expected_path.exists());
+    }
+
+    // =========================================================================
+    // delete_subagent_file tests
+    // =========================================================================
+
+    #[test]
+    fn test_delete_subagent_file() {
+        let temp_dir = TempDir::new().unwrap();
+        let subagent = sample_full_subagent();
+
+        // Write first
+        write_subagent_file(temp_dir.path(), &subagent).unwrap();
+        let file_path = temp_dir
+            .path()
+            .join(".claude")
+            .join("agents")
+            .join("code-reviewer.md");
+        assert!(file_path.exists());
+
+        // Delete
+        delete_subagent_file(temp_dir.path(), &subagent.name).unwrap();
+        assert!(!file_path.exists());
+    }
+
+    #[test]
+    fn test_delete_nonexistent_subagent_succeeds() {
+        let temp_dir = TempDir::new().unwrap();
+
+        // Should not error when file doesn't exist
+        let result = delete_subagent_file(temp_dir.path(), "nonexistent");
+        assert!(result.is_ok());
+    }
+
+    // =========================================================================
+    // OpenCode format tests
+    // =========================================================================
+
+    #[test]
+    fn test_write_subagent_file_opencode_creates_correct_path() {
+        let temp_dir = TempDir::new().unwrap();
+        let subagent = sample_full_subagent();
+
+        write_subagent_file_opencode(temp_dir.path(), &subagent).unwrap();
+
+        // OpenCode uses singular "agent" not "agents"
+        let expected_path = temp_dir.path().join("agent").join("code-reviewer.md");
+        assert!(expected_path.exists());
+    }
+
+    #[test]
+    fn test_delete_subagent_file_opencode() {
+        let temp_dir = TempDir::new().unwrap();
+        let subagent = sample_full_subagent();
+
+        write_subagent_file_opencode(temp_dir.path(), &subagent).unwrap();
+        let file_path = temp_dir.path().join("agent").join("code-reviewer.md");
+        assert!(file_path.exists());
+
+        delete_subagent_file_opencode(temp_dir.path(), &subagent.name).unwrap();
+        assert!(!file_path.exists());
+    }
+
+    // =========================================================================
+    // AgentFormatter tests
+    // =========================================================================
+
+    #[test]
+    fn test_agent_formatters_round_trip_via_trait_objects() {
+        let subagent = sample_full_subagent();
+        let base = Path::new("/project");
+        let formatters: Vec<(Box<dyn AgentFormatter>, PathBuf)> = vec![
+            (
+                Box::new(ClaudeFormatter::default()),
+                base.join(".claude").join("agents").join("code-reviewer.md"),
+            ),
+            (
+                Box::new(OpenCodeFormatter::default()),
+                base.join("agent").join("code-reviewer.md"),
+            ),
+        ];
+
+        for (formatter, expected_path) in &formatters {
+            assert_eq!(formatter.file_path(base, &subagent.name), *expected_path);
+
+            let parsed = formatter.parse(&formatter.generate(&subagent)).unwrap();
+            assert_eq!(parsed.description, subagent.description);
+            assert_eq!(parsed.model, subagent.model);
+            assert_eq!(parsed.content, subagent.content);
+            let tools: Vec<String> = parsed
+                .tools
+                .unwrap()
+                .iter()
+                .map(|t| t.to_lowercase())
+                .collect();
+            assert_eq!(tools, vec!["read", "grep", "glob"]);
+        }
+    }
+
+    #[test]
+    fn test_write_subagent_with_formatter_uses_formatter_path() {
+        let temp_dir = TempDir::new().unwrap();
+        let subagent = sample_full_subagent();
+
+        write_subagent_with_formatter(temp_dir.path(), &subagent, &OpenCodeFormatter::default())
+            .unwrap();
+
+        let content =
+            std::fs::read_to_string(temp_dir.path().join("agent").join("code-reviewer.md"))
+                .unwrap();
+        assert!(content.contains("  read: true\n"));
+    }
+
+    // =========================================================================
+    // Project default model tests
+    // =========================================================================
+
+    #[test]
+    fn test_apply_project_model_default_fills_missing_model() {
+        let mut subagent = sample_minimal_subagent();
+        subagent.model = None;
+        let resolved = apply_project_model_default(&subagent, Some("opus"));
+        assert_eq!(resolved.model, Some("opus".to_string()));
+
+        subagent.model = Some("  ".to_string());
+        let resolved = apply_project_model_default(&subagent, Some("opus"));
+        assert_eq!(resolved.model, Some("opus".to_string()));
+    }
+
+    #[test]
+    fn test_apply_project_model_default_keeps_explicit_model() {
+        let subagent = sample_full_subagent();
+        let resolved = apply_project_model_default(&subagent, Some("opus"));
+        assert_eq!(resolved.model, Some("sonnet".to_string()));
+    }
+
+    #[test]
+    fn test_write_project_subagent_inherits_default_model() {
+        let temp_dir = TempDir::new().unwrap();
+        let subagent = sample_minimal_subagent();
+
+        write_project_subagent_with_default_model(temp_dir.path(), &subagent, Some("haiku"))
+            .unwrap();
+
+        let content = std::fs::read_to_string(
+            temp_dir
+                .path()
+                .join(".claude")
+                .join("agents")
+                .join("simple-agent.md"),
+        )
+        .unwrap();
+        assert!(content.contains("model: haiku\n"));
+    }
+
+    // =========================================================================
+    // OpenCode markdown format tests
+    // =========================================================================
+
+    #[test]
+    fn test_generate_subagent_markdown_opencode_tools_as_object() {
+        let subagent = sample_full_subagent();
+        let md = generate_subagent_markdown_opencode(&subagent);
+
+        // OpenCode tools should be an object with key:value pairs, NOT a comma-separated string
+        assert!(md.contains("tools:\n"));
+        assert!(md.contains("  read: true\n"));
+        assert!(md.contains("  grep: true\n"));
+        assert!(md.contains("  glob: true\n"));
+        // Should NOT have Claude Code's comma-separated format
+        assert!(!md.contains("tools: Read, Grep, Glob"));
+    }
+
+    #[test]
+    fn test_generate_subagent_markdown_opencode_tool_casing_option() {
+        let mut subagent = sample_full_subagent();
+        subagent.tools = Some(vec![
+            "Read".to_string(),
+            "mcp__GitHub__createIssue".to_string(),
+        ]);
+
+        let lowered = generate_subagent_markdown_opencode_with_options(
+            &subagent,
+            &OpenCodeOptions::default(),
+        );
+        assert!(lowered.contains("  read: true\n"));
+        assert!(lowered.contains("  mcp__github__createissue: true\n"));
+
+        let exact = generate_subagent_markdown_opencode_with_options(
+            &subagent,
+            &OpenCodeOptions {
+                lowercase_tools: false,
+                ..Default::default()
+            },
+        );
+        assert!(exact.contains("  Read: true\n"));
+        assert!(exact.contains("  mcp__GitHub__createIssue: true\n"));
+        assert!(!exact.contains("  read: true\n"));
+    }
+
+    #[test]
+    fn test_generate_subagent_markdown_opencode_no_name_field() {
+        let subagent = sample_full_subagent();
+        let md = generate_subagent_markdown_opencode(&subagent);
+
+        // OpenCode doesn't use name field (filename is the name)
+        assert!(!md.contains("name:"));
+    }
+
+    #[test]
+    fn test_generate_subagent_markdown_opencode_no_skills_field() {
+        let subagent = sample_full_subagent();
+        let md = generate_subagent_markdown_opencode(&subagent);
+
+        // OpenCode doesn't have skills field
+        assert!(!md.contains("skills:"));
+    }
+
+    #[test]
+    fn test_generate_subagent_markdown_opencode_no_permission_mode() {
+        let subagent = sample_full_subagent();
+        let md = generate_subagent_markdown_opencode(&subagent);
+
+        // OpenCode uses "permission" object not "permissionMode" string
+        assert!(!md.contains("permissionMode:"));
+    }
+
+    #[test]
+    fn test_generate_subagent_markdown_opencode_translates_permission_mode() {
+        let mut subagent = sample_full_subagent();
+        subagent.permission_mode = Some("plan".to_string());
+        let (md, warnings) =
+            generate_subagent_markdown_opencode_checked(&subagent, &OpenCodeOptions::default());
+        assert!(md.contains("permission:\n  bash: deny\n  edit: deny\n"));
+        assert!(warnings.is_empty());
+
+        subagent.permission_mode = Some("default".to_string());
+        let (md, _) =
+            generate_subagent_markdown_opencode_checked(&subagent, &OpenCodeOptions::default());
+        assert!(!md.contains("permission:"));
+
+        subagent.permission_mode = Some("yolo".to_string());
+        let (md, warnings) =
+            generate_subagent_markdown_opencode_checked(&subagent, &OpenCodeOptions::default());
+        assert!(!md.contains("permission:"));
+        assert_eq!(
+            warnings,
+            ["Permission mode 'yolo' has no OpenCode equivalent and was dropped"]
+        );
+    }
+
+    #[test]
+    fn test_generate_subagent_markdown_opencode_quoted_description() {
+        let subagent = sample_full_subagent();
+        let md = generate_subagent_markdown_opencode(&subagent);
+
+        // OpenCode description should be quoted
+        assert!(md.contains("description: \"Reviews code for bugs and improvements\"\n"));
+    }
+
+    #[test]
+    fn test_generate_subagent_markdown_opencode_has_model() {
+        let subagent = sample_full_subagent();
+        let md = generate_subagent_markdown_opencode(&subagent);
+
+        // Model should be included
+        assert!(md.contains("model: sonnet\n"));
+    }
+
+    #[test]
+    fn test_generate_subagent_markdown_opencode_minimal() {
+        let subagent = sample_minimal_subagent();
+        let md = generate_subagent_markdown_opencode(&subagent);
+
+        // Should have description and content
+        assert!(md.contains("description: \"A simple agent\"\n"));
+        assert!(md.contains("---\n\nYou are a helpful assistant."));
+        // Should not have empty tools block
+        assert!(!md.contains("tools:"));
+    }
+
+    #[test]
+    fn test_generate_subagent_markdown_opencode_drops_claude_only_tools() {
+        let mut subagent = sample_full_subagent();
+        subagent.tools = Some(vec![
+            "Read".to_string(),
+            "TodoWrite".to_string(),
+            "mcp__github__create_issue".to_string(),
+        ]);
+
+        let (md, warnings) =
+            generate_subagent_markdown_opencode_checked(&subagent, &OpenCodeOptions::default());
+
+        assert!(md.contains("tools:\n  read: true\n  mcp__github__create_issue: true\n"));
+        assert!(!md.contains("todowrite"));
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].contains("TodoWrite"));
+    }
+
+    #[test]
+    fn test_generate_subagent_markdown_opencode_mode_defaults_to_subagent() {
+        let subagent = sample_minimal_subagent();
+        let md = generate_subagent_markdown_opencode(&subagent);
+        assert!(md.contains("mode: subagent\n"));
+
+        let without_mode = generate_subagent_markdown_opencode_with_options(
+            &subagent,
+            &OpenCodeOptions {
+                mode: None,
+                ..Default::default()
+            },
+        );
+        assert!(!without_mode.contains("mode:"));
+    }
+
+    #[test]
+    fn test_parse_subagent_markdown_opencode_reads_enabled_tools() {
+        let parsed = parse_subagent_markdown_opencode(
+            "---\ndescription: Reviewer\ntools:\n  read: true\n  grep: false\n  Bash: true\n  webfetch: true\n  write: false\n  mcp__github__search: true\nmodel: sonnet\n---\n\nReview code.",
+        )
+        .unwrap();
+
+        assert_eq!(
+            parsed.tools.unwrap(),
+            ["Read", "Bash", "WebFetch", "mcp__github__search"]
+        );
+        assert_eq!(parsed.model.as_deref(), Some("sonnet"));
+
+        let all_disabled =
+            parse_subagent_markdown_opencode("---\ntools:\n  read: false\n---\n\nBody").unwrap();
+        assert_eq!(all_disabled.tools, None);
+    }
+
+    #[test]
+    fn test_opencode_tools_round_trip_through_one_mapping() {
+        let mut subagent = sample_minimal_subagent();
+        subagent.tools = Some(vec![
+            "Read".to_string(),
+            "LS".to_string(),
+            "WebFetch".to_string(),
+        ]);
+
+        let md = generate_subagent_markdown_opencode(&subagent);
+        assert!(md.contains("  list: true\n"));
+        assert!(!md.contains("ls: true"));
+
+        let parsed = parse_subagent_markdown_opencode(&md).unwrap();
+        assert_eq!(parsed.tools, subagent.tools);
+        for (opencode, claude) in OPENCODE_TOOLS {
+            if let Some(claude) = claude {
+                assert_eq!(opencode_tool_name(claude), Some(*opencode));
+                assert_eq!(claude_tool_name(opencode), *claude);
+            }
+        }
+    }
+
+    #[test]
+    fn test_parse_subagent_markdown_opencode_keeps_mode() {
+        let parsed = parse_subagent_markdown_opencode(
+            "---\ndescription: \"Orchestrates work\"\nmode: primary\n---\n\nLead.",
+        )
+        .unwrap();
+        assert_eq!(
+            parsed.extra.get("mode"),
+            Some(&serde_json::json!("primary"))
+        );
+
+        // Re-exporting keeps the parsed mode instead of the default
+        let md = generate_subagent_markdown_opencode(&parsed);
+        assert!(md.contains("mode: primary\n"));
+    }
+
+    #[test]
+    fn test_opencode_round_trip_writes_no_sidecar() {
+        let temp_dir = TempDir::new().unwrap();
+        let content = "---\ndescription: \"Reviews code\"\nmode: subagent\nmodel: anthropic/claude-sonnet-4-20250514\ntemperature: 0.2\ntools:\n  read: true\npermission:\n  edit: deny\n---\n\nReview it.";
+        let mut agent = parse_subagent_markdown_opencode(content).unwrap();
+        agent.name = "reviewer".to_string();
+
+        write_subagent_file_opencode(temp_dir.path(), &agent).unwrap();
+
+        let agent_file = temp_dir.path().join("agent").join("reviewer.md");
+        assert!(!sidecar_path(&agent_file).exists());
+        let written = std::fs::read_to_string(&agent_file).unwrap();
+        assert!(written.contains("mode: subagent\n"));
+        assert!(written.contains("temperature: 0.2\n"));
+        assert!(written.contains("permission:\n  edit: deny\n"));
+
+        // Converting to Claude doesn't leave a sidecar either
+        write_subagent_file(temp_dir.path(), &agent).unwrap();
+        assert!(!sidecar_path(&temp_dir.path().join(".claude/agents/reviewer.md")).exists());
+    }
+
+    #[test]
+    fn test_opencode_subagent_content_uses_correct_format() {
+        let temp_dir = TempDir::new().unwrap();
+        let subagent = sample_full_subagent();
+
+        write_subagent_file_opencode(temp_dir.path(), &subagent).unwrap();
+
+        let file_path = temp_dir.path().join("agent").join("code-reviewer.md");
+        let content = std::fs::read_to_string(file_path).unwrap();
+
+        // Verify OpenCode format in actual written file
+        assert!(content.contains("tools:\n  read: true\n"));
+        assert!(content.contains("description: \"Reviews code for bugs and improvements\""));
+        assert!(!content.contains("name:"));
+        assert!(!content.contains("skills:"));
+    }
+
+    // =========================================================================
+    // AGENTS.md tests
+    // =========================================================================
+
+    #[test]
+    fn test_write_agents_md_contains_preamble_and_agents() {
+        let temp_dir = TempDir::new().unwrap();
+        let agents = vec![sample_minimal_subagent(), sample_full_subagent()];
+
+        write_agents_md(temp_dir.path(), &agents, "# Conventions\n\nUse tabs.").unwrap();
+
+        let content = std::fs::read_to_string(temp_dir.path().join("AGENTS.md")).unwrap();
+        assert!(content.contains("# Conventions\n\nUse tabs."));
+        assert!(content.contains("`@code-reviewer`: Reviews code for bugs and improvements"));
+        assert!(content.contains("`@simple-agent`: A simple agent"));
+        // Sorted by name
+        assert!(content.find("code-reviewer").unwrap() < content.find("simple-agent").unwrap());
+    }
+
+    #[test]
+    fn test_write_agents_md_regenerates_fully() {
+        let temp_dir = TempDir::new().unwrap();
+
+        write_agents_md(temp_dir.path(), &[sample_full_subagent()], "First").unwrap();
+        write_agents_md(temp_dir.path(), &[sample_minimal_subagent()], "Second").unwrap();
+
+        let content = std::fs::read_to_string(temp_dir.path().join("AGENTS.md")).unwrap();
+        assert!(content.contains("Second"));
+        assert!(!content.contains("First"));
+        assert!(!content.contains("code-reviewer"));
+    }
+
+    #[test]
+    fn test_write_subagents_incremental_skips_unchanged() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut third = sample_minimal_subagent();
+        third.name = "third-agent".to_string();
+        let mut agents = vec![sample_full_subagent(), sample_minimal_subagent(), third];
+
+        let written = write_subagents_incremental(temp_dir.path(), &agents).unwrap();
+        assert_eq!(written.len(), 3);
+
+        agents[1].description = "A changed agent".to_string();
+        let written = write_subagents_incremental(temp_dir.path(), &agents).unwrap();
+        assert_eq!(written, vec!["simple-agent".to_string()]);
+
+        let path = temp_dir.path().join(".claude/agents/simple-agent.md");
+        let content = std::fs::read_to_string(path).unwrap();
+        assert!(content.contains("description: A changed agent"));
+
+        assert!(write_subagents_incremental(temp_dir.path(), &agents)
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_export_agents_flat_writes_directly_into_out_dir() {
+        let temp_dir = TempDir::new().unwrap();
+        let out_dir = temp_dir.path().join("agents-export");
+        let agents = vec![sample_full_subagent(), sample_minimal_subagent()];
+
+        let paths = export_agents_flat(&agents, &out_dir, false).unwrap();
+
+        assert_eq!(
+            paths,
+            vec![
+                out_dir.join("code-reviewer.md"),
+                out_dir.join("simple-agent.md")
+            ]
+        );
+        assert!(!out_dir.join(".claude").exists());
+        let content = std::fs::read_to_string(&paths[0]).unwrap();
+        assert!(content.starts_with("---\nname: code-reviewer\n"));
+
+        // Existing files are only replaced with overwrite set
+        let err = export_agents_flat(&agents, &out_dir, false).unwrap_err();
+        assert!(err.to_string().contains("already exists"));
+        assert!(export_agents_flat(&agents, &out_dir, true).is_ok());
+
+        let twins = vec![sample_minimal_subagent(), sample_minimal_subagent()];
+        let err = export_agents_flat(&twins, &temp_dir.path().join("twins"), false).unwrap_err();
+        assert!(err.to_string().contains("Two agents"));
+    }
+
+    // =========================================================================
+    // Tracing tests
+    // =========================================================================
+
+    /// Records each new span as `name key=value ...`
+    struct SpanCapture(Arc<std::sync::Mutex<Vec<String>>>);
+
+    struct FieldRecorder(String);
+
+    impl tracing::field::Visit for FieldRecorder {
+        fn record_debug(&mut self, field: &tracing::field::Field, value: &dyn std::fmt::Debug) {
+            self.0.push_str(&format!(" {}={:?}", field.name(), value));
         }
     }
 
-    fn sample_minimal_subagent() -> SubAgent {
-        SubAgent {
-            id: 2,
-            name: "simple-agent".to_string(),
-            description: "A simple agent".to_string(),
-            content: "You are a helpful assistant.".to_string(),
-            tools: None,
-            model: None,
-            permission_mode: None,
-            skills: None,
-            tags: None,
-            source: "manual".to_string(),
-            source_path: None,
-            is_favorite: false,
-            created_at: "2024-01-01".to_string(),
-            updated_at: "2024-01-01".to_string(),
+    impl<S: tracing::Subscriber> tracing_subscriber::Layer<S> for SpanCapture {
+        fn on_new_span(
+            &self,
+            attrs: &tracing::span::Attributes<'_>,
+            _id: &tracing::span::Id,
+            _ctx: tracing_subscriber::layer::Context<'_, S>,
+        ) {
+            let mut recorder = FieldRecorder(attrs.metadata().name().to_string());
+            attrs.record(&mut recorder);
+            self.0.lock().unwrap().push(recorder.0);
         }
     }
 
+    #[test]
+    fn test_write_subagent_file_emits_span() {
+        use tracing_subscriber::layer::SubscriberExt;
+
+        let spans = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let subscriber = tracing_subscriber::registry().with(SpanCapture(spans.clone()));
+        let temp_dir = TempDir::new().unwrap();
+
+        tracing::subscriber::with_default(subscriber, || {
+            write_subagent_file(temp_dir.path(), &sample_minimal_subagent()).unwrap();
+        });
+
+        let spans = spans.lock().unwrap();
+        let span = spans
+            .iter()
+            .find(|s| s.starts_with("write_subagent_file"))
+            .expect("write span emitted");
+        assert!(span.contains("agent=simple-agent"));
+        assert!(span.contains("scope=\"project\""));
+        assert!(span.contains("simple-agent.md"));
+    }
+
     // =========================================================================
-    // generate_subagent_markdown tests
+    // shadow_warning tests
     // =========================================================================
 
+    fn write_agent_at(base: &Path, name: &str) -> PathBuf {
+        let mut agent = sample_minimal_subagent();
+        agent.name = name.to_string();
+        write_subagent_file(base, &agent).unwrap();
+        base.join(".claude")
+            .join("agents")
+            .join(format!("{}.md", name))
+    }
+
     #[test]
-    fn test_generate_subagent_markdown_full() {
-        let subagent = sample_full_subagent();
-        let md = generate_subagent_markdown(&subagent);
+    fn test_shadow_warning_global_shadowed_by_project() {
+        let home = TempDir::new().unwrap();
+        let project = TempDir::new().unwrap();
+        let project_agent = write_agent_at(project.path(), "reviewer");
+
+        let info =
+            shadow_warning_with_home("reviewer", AgentScope::Global, project.path(), home.path())
+                .unwrap();
+
+        assert_eq!(info.other_scope, AgentScope::Project);
+        assert_eq!(info.other_path, project_agent);
+        assert!(info.is_shadowed);
+    }
 
-        assert!(md.starts_with("---\n"));
-        assert!(md.contains("name: code-reviewer\n"));
-        assert!(md.contains("description: Reviews code for bugs and improvements\n"));
-        assert!(md.contains("tools: Read, Grep, Glob\n"));
-        assert!(md.contains("model: sonnet\n"));
-        assert!(md.contains("permissionMode: bypassPermissions\n"));
-        assert!(md.contains("skills: lint, format\n"));
-        assert!(md.contains("---\n\nYou are a code review expert."));
+    #[test]
+    fn test_shadow_warning_project_shadows_global() {
+        let home = TempDir::new().unwrap();
+        let project = TempDir::new().unwrap();
+        let global_agent = write_agent_at(home.path(), "reviewer");
+
+        let info =
+            shadow_warning_with_home("reviewer", AgentScope::Project, project.path(), home.path())
+                .unwrap();
+
+        assert_eq!(info.other_scope, AgentScope::Global);
+        assert_eq!(info.other_path, global_agent);
+        assert!(!info.is_shadowed);
     }
 
     #[test]
-    fn test_generate_subagent_markdown_minimal() {
-        let subagent = sample_minimal_subagent();
-        let md = generate_subagent_markdown(&subagent);
+    fn test_shadow_warning_no_conflict() {
+        let home = TempDir::new().unwrap();
+        let project = TempDir::new().unwrap();
+        write_agent_at(home.path(), "planner");
+        write_agent_at(project.path(), "reviewer");
+
+        // Only a same-named agent in the other scope conflicts
+        assert!(shadow_warning_with_home(
+            "tester",
+            AgentScope::Global,
+            project.path(),
+            home.path()
+        )
+        .is_none());
+        assert!(shadow_warning_with_home(
+            "reviewer",
+            AgentScope::Project,
+            project.path(),
+            home.path()
+        )
+        .is_none());
+    }
 
-        assert!(md.contains("name: simple-agent\n"));
-        assert!(md.contains("description: A simple agent\n"));
-        assert!(!md.contains("tools:"));
-        assert!(!md.contains("model:"));
-        assert!(!md.contains("permissionMode:"));
-        assert!(!md.contains("skills:"));
-        assert!(md.contains("---\n\nYou are a helpful assistant."));
+    fn write_agent_with_tools(base: &Path, name: &str, tools: &[&str]) {
+        let mut agent = sample_minimal_subagent();
+        agent.name = name.to_string();
+        agent.tools = Some(tools.iter().map(|t| t.to_string()).collect());
+        write_subagent_file(base, &agent).unwrap();
     }
 
     #[test]
-    fn test_generate_subagent_markdown_empty_tools_skipped() {
-        let mut subagent = sample_full_subagent();
-        subagent.tools = Some(vec![]);
-        let md = generate_subagent_markdown(&subagent);
+    fn test_effective_agent_tools_project_overrides_global() {
+        let home = TempDir::new().unwrap();
+        let project = TempDir::new().unwrap();
+        write_agent_with_tools(home.path(), "reviewer", &["Read", "Bash"]);
+        write_agent_with_tools(project.path(), "reviewer", &["Grep", " Read", "Grep"]);
+
+        let tools =
+            effective_agent_tools_with_home("reviewer", project.path(), home.path()).unwrap();
+        assert_eq!(tools, vec!["Grep".to_string(), "Read".to_string()]);
+    }
 
-        assert!(!md.contains("tools:"));
+    #[test]
+    fn test_effective_agent_tools_global_only() {
+        let home = TempDir::new().unwrap();
+        let project = TempDir::new().unwrap();
+        write_agent_with_tools(home.path(), "reviewer", &["Read", "Bash"]);
+
+        let tools =
+            effective_agent_tools_with_home("reviewer", project.path(), home.path()).unwrap();
+        assert_eq!(tools, vec!["Bash".to_string(), "Read".to_string()]);
+
+        let err =
+            effective_agent_tools_with_home("missing", project.path(), home.path()).unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<WriterError>(),
+            Some(&WriterError::NotFound {
+                name: "missing".to_string()
+            })
+        );
     }
 
     #[test]
-    fn test_generate_subagent_markdown_empty_model_skipped() {
-        let mut subagent = sample_full_subagent();
-        subagent.model = Some("".to_string());
+    fn test_subagent_env_round_trips_as_sequence() {
+        let mut subagent = sample_minimal_subagent();
+        subagent.env = Some(vec!["GITHUB_TOKEN".to_string(), "AWS_REGION".to_string()]);
+
         let md = generate_subagent_markdown(&subagent);
+        assert!(md.contains("env:\n  - GITHUB_TOKEN\n  - AWS_REGION\n---"));
 
-        assert!(!md.contains("model:"));
+        let parsed = parse_subagent_markdown(&md).unwrap();
+        assert_eq!(parsed.env, subagent.env);
+        assert_eq!(parsed.content, subagent.content);
     }
 
     #[test]
-    fn test_generate_subagent_markdown_empty_permission_mode_skipped() {
+    fn test_subagent_mcp_servers_round_trip_as_sequence() {
         let mut subagent = sample_full_subagent();
-        subagent.permission_mode = Some("".to_string());
+        subagent.mcp_servers = Some(vec!["github".to_string(), "postgres".to_string()]);
+
         let md = generate_subagent_markdown(&subagent);
+        assert!(md.contains("mcpServers:\n  - github\n  - postgres\n"));
 
-        assert!(!md.contains("permissionMode:"));
+        let parsed = parse_subagent_markdown(&md).unwrap();
+        assert_eq!(parsed.mcp_servers, subagent.mcp_servers);
+        assert_eq!(parsed.tools, subagent.tools);
+
+        subagent.mcp_servers = Some(Vec::new());
+        let md = generate_subagent_markdown(&subagent);
+        assert!(!md.contains("mcpServers"));
+        assert_eq!(parse_subagent_markdown(&md).unwrap().mcp_servers, None);
     }
 
     #[test]
-    fn test_generate_subagent_markdown_empty_skills_skipped() {
+    fn test_disabled_agent_round_trips_via_frontmatter() {
         let mut subagent = sample_full_subagent();
-        subagent.skills = Some(vec![]);
+        subagent.enabled = false;
+
         let md = generate_subagent_markdown(&subagent);
+        assert!(md.contains("\ndisabled: true\n"));
+        assert!(!parse_subagent_markdown(&md).unwrap().enabled);
 
-        assert!(!md.contains("skills:"));
+        subagent.enabled = true;
+        let md = generate_subagent_markdown(&subagent);
+        assert!(!md.contains("disabled"));
+        assert!(parse_subagent_markdown(&md).unwrap().enabled);
+    }
+
+    #[test]
+    fn test_subagent_env_omitted_when_empty() {
+        let mut subagent = sample_minimal_subagent();
+        subagent.env = Some(Vec::new());
+
+        let md = generate_subagent_markdown(&subagent);
+        assert!(!md.contains("env:"));
+        assert_eq!(parse_subagent_markdown(&md).unwrap().env, None);
     }
 
     // =========================================================================
-    // write_subagent_file tests
+    // Trash tests
     // =========================================================================
 
     #[test]
-    fn test_write_subagent_file_creates_correct_path() {
+    fn test_delete_subagent_trashed_moves_file_to_trash() {
         let temp_dir = TempDir::new().unwrap();
-        let subagent = sample_full_subagent();
+        let agent_file = write_agent_at(temp_dir.path(), "reviewer");
 
-        write_subagent_file(temp_dir.path(), &subagent).unwrap();
+        let trashed_name = delete_subagent_trashed(temp_dir.path(), "reviewer").unwrap();
 
-        let expected_path = temp_dir
+        assert!(!agent_file.exists());
+        assert!(trashed_name.ends_with("-reviewer.md"));
+        let trashed = temp_dir
             .path()
-            .join(".claude")
-            .join("agents")
-            .join("code-reviewer.md");
-        assert!(expected_path.exists());
+            .join(".claude/agents/.trash")
+            .join(&trashed_name);
+        assert!(trashed.is_file());
+        // The trash is not listed as an agent
+        assert!(list_subagents(temp_dir.path(), None).unwrap().is_empty());
+
+        // Recent trash survives a purge with a longer age
+        assert_eq!(
+            purge_trash(temp_dir.path(), chrono::Duration::days(30)).unwrap(),
+            0
+        );
+        assert_eq!(
+            purge_trash(temp_dir.path(), chrono::Duration::zero()).unwrap(),
+            1
+        );
+        assert!(!trashed.exists());
+
+        assert!(delete_subagent_trashed(temp_dir.path(), "reviewer").is_err());
     }
 
     #[test]
-    fn test_write_subagent_file_content_matches() {
+    fn test_restore_from_trash() {
         let temp_dir = TempDir::new().unwrap();
-        let subagent = sample_full_subagent();
+        let agent_file = write_agent_at(temp_dir.path(), "reviewer");
+        let original = std::fs::read_to_string(&agent_file).unwrap();
+        let trashed_name = delete_subagent_trashed(temp_dir.path(), "reviewer").unwrap();
 
-        write_subagent_file(temp_dir.path(), &subagent).unwrap();
+        let restored = restore_from_trash(temp_dir.path(), &trashed_name).unwrap();
 
-        let file_path = temp_dir
-            .path()
-            .join(".claude")
-            .join("agents")
-            .join("code-reviewer.md");
-        let content = std::fs::read_to_string(file_path).unwrap();
+        assert_eq!(restored, agent_file);
+        assert_eq!(std::fs::read_to_string(&agent_file).unwrap(), original);
+        assert!(restore_from_trash(temp_dir.path(), &trashed_name).is_err());
 
-        assert!(content.contains("name: code-reviewer"));
-        assert!(content.contains("You are a code review expert."));
+        // Restoring over an agent that was recreated is refused
+        let trashed_name = delete_subagent_trashed(temp_dir.path(), "reviewer").unwrap();
+        write_agent_at(temp_dir.path(), "reviewer");
+        let err = restore_from_trash(temp_dir.path(), &trashed_name).unwrap_err();
+        assert!(err.to_string().contains("already exists"));
     }
 
     #[test]
-    fn test_write_subagent_file_creates_directories() {
+    fn test_restore_from_trash_rejects_traversal() {
         let temp_dir = TempDir::new().unwrap();
-        let subagent = sample_minimal_subagent();
-
-        // Directory doesn't exist yet
-        let agents_dir = temp_dir.path().join(".claude").join("agents");
-        assert!(!agents_dir.exists());
-
-        write_subagent_file(temp_dir.path(), &subagent).unwrap();
-
-        assert!(agents_dir.exists());
+        let outside = temp_dir.path().join("20240101T000000-secret.md");
+        std::fs::write(&outside, "---\nname: secret\n---\n").unwrap();
+        write_agent_at(temp_dir.path(), "reviewer");
+        delete_subagent_trashed(temp_dir.path(), "reviewer").unwrap();
+        let absolute = outside.to_string_lossy().to_string();
+
+        for trashed_name in [
+            "../../20240101T000000-secret.md",
+            "20240101T000000-x/../../../20240101T000000-secret.md",
+            "sub\\20240101T000000-secret.md",
+            "..",
+            absolute.as_str(),
+        ] {
+            let err = restore_from_trash(temp_dir.path(), trashed_name).unwrap_err();
+            assert!(
+                err.to_string().contains("Invalid trashed agent name"),
+                "{}: {}",
+                trashed_name,
+                err
+            );
+        }
+        assert!(outside.exists());
+        assert!(!ClaudeLayout::default()
+            .agents_dir(temp_dir.path())
+            .join("secret.md")
+            .exists());
     }
 
     // =========================================================================
-    // delete_subagent_file tests
+    // Metadata sidecar tests
     // =========================================================================
 
+    fn agent_with_extra() -> SubAgent {
+        let mut agent = sample_minimal_subagent();
+        agent.extra.insert(
+            "config".to_string(),
+            serde_json::json!({"retries": 3, "targets": ["api", "web"]}),
+        );
+        agent
+    }
+
     #[test]
-    fn test_delete_subagent_file() {
+    fn test_sidecar_round_trip() {
         let temp_dir = TempDir::new().unwrap();
-        let subagent = sample_full_subagent();
+        let agent = agent_with_extra();
 
-        // Write first
-        write_subagent_file(temp_dir.path(), &subagent).unwrap();
-        let file_path = temp_dir
+        write_subagent_file(temp_dir.path(), &agent).unwrap();
+
+        let agent_file = temp_dir.path().join(".claude/agents/simple-agent.md");
+        assert!(temp_dir
             .path()
-            .join(".claude")
-            .join("agents")
-            .join("code-reviewer.md");
-        assert!(file_path.exists());
+            .join(".claude/agents/simple-agent.meta.json")
+            .exists());
 
-        // Delete
-        delete_subagent_file(temp_dir.path(), &subagent.name).unwrap();
-        assert!(!file_path.exists());
+        let loaded = load_subagent_file(&agent_file).unwrap();
+        assert_eq!(loaded.name, "simple-agent");
+        assert_eq!(loaded.extra, agent.extra);
     }
 
     #[test]
-    fn test_delete_nonexistent_subagent_succeeds() {
+    fn test_no_sidecar_written_for_empty_extra() {
         let temp_dir = TempDir::new().unwrap();
+        write_subagent_file(temp_dir.path(), &sample_minimal_subagent()).unwrap();
 
-        // Should not error when file doesn't exist
-        let result = delete_subagent_file(temp_dir.path(), "nonexistent");
-        assert!(result.is_ok());
+        assert!(!temp_dir
+            .path()
+            .join(".claude/agents/simple-agent.meta.json")
+            .exists());
     }
 
-    // =========================================================================
-    // OpenCode format tests
-    // =========================================================================
-
     #[test]
-    fn test_write_subagent_file_opencode_creates_correct_path() {
+    fn test_save_without_extra_keeps_sidecar() {
         let temp_dir = TempDir::new().unwrap();
-        let subagent = sample_full_subagent();
+        let agent = agent_with_extra();
+        write_subagent_file(temp_dir.path(), &agent).unwrap();
 
-        write_subagent_file_opencode(temp_dir.path(), &subagent).unwrap();
+        // Saving again without extra fields (as the app does for agents
+        // loaded from the database) leaves the sidecar in place
+        write_subagent_file(temp_dir.path(), &sample_minimal_subagent()).unwrap();
 
-        // OpenCode uses singular "agent" not "agents"
-        let expected_path = temp_dir.path().join("agent").join("code-reviewer.md");
-        assert!(expected_path.exists());
+        let agent_file = temp_dir.path().join(".claude/agents/simple-agent.md");
+        assert_eq!(read_sidecar(&agent_file).unwrap(), agent.extra);
     }
 
     #[test]
-    fn test_delete_subagent_file_opencode() {
+    fn test_delete_removes_sidecar() {
         let temp_dir = TempDir::new().unwrap();
-        let subagent = sample_full_subagent();
+        write_subagent_file(temp_dir.path(), &agent_with_extra()).unwrap();
 
-        write_subagent_file_opencode(temp_dir.path(), &subagent).unwrap();
-        let file_path = temp_dir.path().join("agent").join("code-reviewer.md");
-        assert!(file_path.exists());
+        delete_subagent_file(temp_dir.path(), "simple-agent").unwrap();
 
-        delete_subagent_file_opencode(temp_dir.path(), &subagent.name).unwrap();
-        assert!(!file_path.exists());
+        let agents_dir = temp_dir.path().join(".claude/agents");
+        assert!(!agents_dir.join("simple-agent.md").exists());
+        assert!(!agents_dir.join("simple-agent.meta.json").exists());
     }
 
     // =========================================================================
-    // OpenCode markdown format tests
+    // list_subagents tests
     // =========================================================================
 
+    fn set_mtime(path: &Path, time: SystemTime) {
+        std::fs::File::options()
+            .write(true)
+            .open(path)
+            .unwrap()
+            .set_modified(time)
+            .unwrap();
+    }
+
     #[test]
-    fn test_generate_subagent_markdown_opencode_tools_as_object() {
-        let subagent = sample_full_subagent();
-        let md = generate_subagent_markdown_opencode(&subagent);
+    fn test_parse_subagent_file_without_name_key() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("release-helper.md");
+        std::fs::write(
+            &path,
+            "---\ndescription: Cuts releases\ntools: Bash\n---\n\nYou cut releases.",
+        )
+        .unwrap();
+
+        let parsed = parse_subagent_file(&path).unwrap();
+
+        assert_eq!(parsed.name, "release-helper");
+        assert_eq!(parsed.source, "imported");
+        assert_eq!(
+            parsed.source_path.as_deref(),
+            Some(path.to_string_lossy().as_ref())
+        );
+        assert_eq!(parsed.description, "Cuts releases");
+        assert_eq!(parsed.content, "You cut releases.");
+    }
 
-        // OpenCode tools should be an object with key:value pairs, NOT a comma-separated string
-        assert!(md.contains("tools:\n"));
-        assert!(md.contains("  read: true\n"));
-        assert!(md.contains("  grep: true\n"));
-        assert!(md.contains("  glob: true\n"));
-        // Should NOT have Claude Code's comma-separated format
-        assert!(!md.contains("tools: Read, Grep, Glob"));
+    #[test]
+    fn test_list_subagents_modified_after() {
+        let temp_dir = TempDir::new().unwrap();
+        let agents_dir = temp_dir.path().join(".claude/agents");
+        let mut old = sample_minimal_subagent();
+        old.name = "old-agent".to_string();
+        let mut new = sample_minimal_subagent();
+        new.name = "new-agent".to_string();
+        write_subagent_file(temp_dir.path(), &old).unwrap();
+        write_subagent_file(temp_dir.path(), &new).unwrap();
+
+        let now = SystemTime::now();
+        let hour = std::time::Duration::from_secs(3600);
+        set_mtime(&agents_dir.join("old-agent.md"), now - 2 * hour);
+        set_mtime(&agents_dir.join("new-agent.md"), now);
+
+        let all = list_subagents(temp_dir.path(), None).unwrap();
+        let names: Vec<&str> = all.iter().map(|a| a.name.as_str()).collect();
+        assert_eq!(names, vec!["new-agent", "old-agent"]);
+
+        let recent = list_subagents(temp_dir.path(), Some(now - hour)).unwrap();
+        assert_eq!(recent.len(), 1);
+        assert_eq!(recent[0].name, "new-agent");
     }
 
     #[test]
-    fn test_generate_subagent_markdown_opencode_no_name_field() {
-        let subagent = sample_full_subagent();
-        let md = generate_subagent_markdown_opencode(&subagent);
+    fn test_safe_filename_round_trip() {
+        assert_eq!(safe_filename("code-reviewer"), "code-reviewer");
+        assert_eq!(safe_filename("rocket 🚀"), "rocket%20%F0%9F%9A%80");
+        assert_eq!(safe_filename("team/lead"), "team%2Flead");
+        assert_eq!(safe_filename(".hidden"), "%2Ehidden");
+
+        for name in ["rocket 🚀", "team/lead", "50% off", ".hidden", "plain"] {
+            assert_eq!(decode_filename(&safe_filename(name)).as_deref(), Some(name));
+        }
+        assert_eq!(decode_filename("bad%2"), None);
+    }
 
-        // OpenCode doesn't use name field (filename is the name)
-        assert!(!md.contains("name:"));
+    #[test]
+    fn test_emoji_agent_name_recovered_from_disk() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut subagent = sample_full_subagent();
+        subagent.name = "rocket 🚀".to_string();
+
+        write_subagent_file(temp_dir.path(), &subagent).unwrap();
+
+        let agents_dir = temp_dir.path().join(".claude").join("agents");
+        assert!(agents_dir.join("rocket%20%F0%9F%9A%80.md").is_file());
+        assert_eq!(
+            list_subagent_names(temp_dir.path()).unwrap(),
+            vec!["rocket 🚀".to_string()]
+        );
+        let listed = list_subagents(temp_dir.path(), None).unwrap();
+        assert_eq!(listed[0].name, "rocket 🚀");
+
+        delete_subagent_file(temp_dir.path(), "rocket 🚀").unwrap();
+        assert!(list_subagent_names(temp_dir.path()).unwrap().is_empty());
+        assert!(!agents_dir.join(".filenames.json").exists());
     }
 
     #[test]
-    fn test_generate_subagent_markdown_opencode_no_skills_field() {
-        let subagent = sample_full_subagent();
-        let md = generate_subagent_markdown_opencode(&subagent);
+    fn test_opencode_agent_name_is_encoded() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut subagent = sample_full_subagent();
+        subagent.name = "team/lead".to_string();
 
-        // OpenCode doesn't have skills field
-        assert!(!md.contains("skills:"));
+        write_subagent_file_opencode(temp_dir.path(), &subagent).unwrap();
+
+        let agent_dir = temp_dir.path().join("agent");
+        let file_path = agent_dir.join("team%2Flead.md");
+        assert!(file_path.is_file());
+        assert!(!agent_dir.join("team").exists());
+        assert_eq!(
+            load_subagent_file_opencode(&file_path).unwrap().name,
+            "team/lead"
+        );
+
+        delete_subagent_file_opencode(temp_dir.path(), "team/lead").unwrap();
+        assert!(!file_path.exists());
+        assert!(!agent_dir.join(".filenames.json").exists());
     }
 
     #[test]
-    fn test_generate_subagent_markdown_opencode_no_permission_mode() {
-        let subagent = sample_full_subagent();
-        let md = generate_subagent_markdown_opencode(&subagent);
+    fn test_legacy_unencoded_agent_file_is_found() {
+        let home = TempDir::new().unwrap();
+        let project = TempDir::new().unwrap();
+        let agents_dir = project.path().join(".claude/agents");
+        std::fs::create_dir_all(&agents_dir).unwrap();
+        let legacy = agents_dir.join("code reviewer.md");
+        std::fs::write(
+            &legacy,
+            "---\nname: code reviewer\ndescription: Reviews\ntools: Read, Grep\n---\n\nReview.\n",
+        )
+        .unwrap();
+
+        let tools =
+            effective_agent_tools_with_home("code reviewer", project.path(), home.path()).unwrap();
+        assert_eq!(tools, vec!["Grep".to_string(), "Read".to_string()]);
+
+        delete_subagent_file(project.path(), "code reviewer").unwrap();
+        assert!(!legacy.exists());
+
+        let opencode_dir = project.path().join("agent");
+        std::fs::create_dir_all(&opencode_dir).unwrap();
+        let legacy = opencode_dir.join("code reviewer.md");
+        std::fs::write(&legacy, "---\ndescription: Reviews\n---\n\nReview.\n").unwrap();
+        delete_subagent_file_opencode(project.path(), "code reviewer").unwrap();
+        assert!(!legacy.exists());
+    }
 
-        // OpenCode uses "permission" object not "permissionMode" string
-        assert!(!md.contains("permissionMode:"));
+    #[test]
+    fn test_saving_legacy_named_agent_keeps_one_file() {
+        let project = TempDir::new().unwrap();
+        let agents_dir = project.path().join(".claude/agents");
+        std::fs::create_dir_all(&agents_dir).unwrap();
+        let legacy = agents_dir.join("code reviewer.md");
+        std::fs::write(
+            &legacy,
+            "---\nname: code reviewer\ndescription: Reviews\n---\n\nReview.\n",
+        )
+        .unwrap();
+
+        let mut subagent = sample_minimal_subagent();
+        subagent.name = "code reviewer".to_string();
+        subagent.content = "Review carefully.".to_string();
+        write_subagent_file(project.path(), &subagent).unwrap();
+        upsert_subagent(project.path(), &subagent, None).unwrap();
+        write_subagents_incremental(project.path(), std::slice::from_ref(&subagent)).unwrap();
+
+        let files: Vec<_> = std::fs::read_dir(&agents_dir)
+            .unwrap()
+            .map(|e| e.unwrap().file_name())
+            .collect();
+        assert_eq!(files, vec![std::ffi::OsString::from("code reviewer.md")]);
+        assert!(std::fs::read_to_string(&legacy)
+            .unwrap()
+            .contains("Review carefully."));
     }
 
     #[test]
-    fn test_generate_subagent_markdown_opencode_quoted_description() {
-        let subagent = sample_full_subagent();
-        let md = generate_subagent_markdown_opencode(&subagent);
+    fn test_delete_prunes_only_its_filename_map_entry() {
+        let temp_dir = TempDir::new().unwrap();
+        for name in ["rocket 🚀", "team/lead"] {
+            let mut subagent = sample_minimal_subagent();
+            subagent.name = name.to_string();
+            write_subagent_file(temp_dir.path(), &subagent).unwrap();
+        }
 
-        // OpenCode description should be quoted
-        assert!(md.contains("description: \"Reviews code for bugs and improvements\"\n"));
+        delete_subagent_file(temp_dir.path(), "rocket 🚀").unwrap();
+
+        let map = read_filename_map(&temp_dir.path().join(".claude/agents"));
+        assert_eq!(
+            map,
+            HashMap::from([("team%2Flead".to_string(), "team/lead".to_string())])
+        );
     }
 
     #[test]
-    fn test_generate_subagent_markdown_opencode_has_model() {
-        let subagent = sample_full_subagent();
-        let md = generate_subagent_markdown_opencode(&subagent);
+    fn test_list_subagent_names_reads_file_stems() {
+        let temp_dir = TempDir::new().unwrap();
+        let agents_dir = temp_dir.path().join(".claude/agents");
+        std::fs::create_dir_all(&agents_dir).unwrap();
+        for file in [
+            "reviewer.md",
+            "planner.md",
+            "planner.meta.json",
+            "notes.txt",
+        ] {
+            std::fs::write(agents_dir.join(file), "not parsed").unwrap();
+        }
 
-        // Model should be included
-        assert!(md.contains("model: sonnet\n"));
+        let names = list_subagent_names(temp_dir.path()).unwrap();
+
+        assert_eq!(names, vec!["planner", "reviewer"]);
+        assert!(list_subagent_names(&temp_dir.path().join("missing"))
+            .unwrap()
+            .is_empty());
+    }
+
+    // =========================================================================
+    // upsert_subagent tests
+    // =========================================================================
+
+    fn on_disk_hash(base_path: &Path, name: &str) -> String {
+        let path = base_path
+            .join(".claude/agents")
+            .join(format!("{}.md", name));
+        subagent_content_hash(&std::fs::read(path).unwrap())
     }
 
     #[test]
-    fn test_generate_subagent_markdown_opencode_minimal() {
+    fn test_upsert_subagent_creates_missing_file() {
+        let temp_dir = TempDir::new().unwrap();
         let subagent = sample_minimal_subagent();
-        let md = generate_subagent_markdown_opencode(&subagent);
 
-        // Should have description and content
-        assert!(md.contains("description: \"A simple agent\"\n"));
-        assert!(md.contains("---\n\nYou are a helpful assistant."));
-        // Should not have empty tools block
-        assert!(!md.contains("tools:"));
+        let outcome = upsert_subagent(temp_dir.path(), &subagent, None).unwrap();
+
+        assert_eq!(outcome, UpsertOutcome::Created);
+        assert!(temp_dir
+            .path()
+            .join(".claude/agents/simple-agent.md")
+            .exists());
     }
 
     #[test]
-    fn test_opencode_subagent_content_uses_correct_format() {
+    fn test_upsert_subagent_writes_when_hash_matches() {
         let temp_dir = TempDir::new().unwrap();
-        let subagent = sample_full_subagent();
+        let mut subagent = sample_minimal_subagent();
+        write_subagent_file(temp_dir.path(), &subagent).unwrap();
+        let hash = on_disk_hash(temp_dir.path(), &subagent.name);
 
-        write_subagent_file_opencode(temp_dir.path(), &subagent).unwrap();
+        subagent.description = "Updated".to_string();
+        let outcome = upsert_subagent(temp_dir.path(), &subagent, Some(hash)).unwrap();
 
-        let file_path = temp_dir.path().join("agent").join("code-reviewer.md");
-        let content = std::fs::read_to_string(file_path).unwrap();
+        assert_eq!(outcome, UpsertOutcome::Written);
+        let content =
+            std::fs::read_to_string(temp_dir.path().join(".claude/agents/simple-agent.md"))
+                .unwrap();
+        assert!(content.contains("description: Updated\n"));
+    }
 
-        // Verify OpenCode format in actual written file
-        assert!(content.contains("tools:\n  read: true\n"));
-        assert!(content.contains("description: \"Reviews code for bugs and improvements\""));
-        assert!(!content.contains("name:"));
-        assert!(!content.contains("skills:"));
+    #[test]
+    fn test_upsert_subagent_stale_hash_conflicts() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut subagent = sample_minimal_subagent();
+        write_subagent_file(temp_dir.path(), &subagent).unwrap();
+        let stale = on_disk_hash(temp_dir.path(), &subagent.name);
+
+        // Someone else edits the file after we read it
+        subagent.description = "Edited elsewhere".to_string();
+        write_subagent_file(temp_dir.path(), &subagent).unwrap();
+        let current = on_disk_hash(temp_dir.path(), &subagent.name);
+
+        subagent.description = "Our edit".to_string();
+        let outcome = upsert_subagent(temp_dir.path(), &subagent, Some(stale)).unwrap();
+
+        assert_eq!(
+            outcome,
+            UpsertOutcome::Conflict {
+                on_disk_hash: current.clone()
+            }
+        );
+        assert_eq!(on_disk_hash(temp_dir.path(), &subagent.name), current);
+    }
+
+    #[test]
+    fn test_canonicalize_subagent_ignores_cosmetic_differences() {
+        let tidy = sample_full_subagent();
+        let mut messy = sample_full_subagent();
+        messy.description = format!("  {}\n", tidy.description);
+        messy.content = format!("{}\r\n", tidy.content.replace(". ", ".\r\n"));
+        messy.tools = Some(vec![
+            " Glob".to_string(),
+            "Read".to_string(),
+            "Grep ".to_string(),
+            "Read".to_string(),
+        ]);
+        messy.skills = Some(vec!["format".to_string(), "lint".to_string()]);
+        messy.model = Some(" sonnet ".to_string());
+        messy.color = Some("  ".to_string());
+        let mut tidy_lines = tidy.clone();
+        tidy_lines.content = tidy.content.replace(". ", ".\n");
+
+        let a = canonicalize_subagent(&tidy_lines);
+        let b = canonicalize_subagent(&messy);
+
+        assert_eq!(
+            generate_subagent_markdown(&a),
+            generate_subagent_markdown(&b)
+        );
+        assert_eq!(
+            canonical_subagent_hash(&tidy_lines),
+            canonical_subagent_hash(&messy)
+        );
+        assert_eq!(b.tools.as_deref().unwrap(), ["Glob", "Grep", "Read"]);
+        assert_eq!(b.color, None);
+
+        // Writes keep the agent as given
+        let temp_dir = TempDir::new().unwrap();
+        write_subagent_file(temp_dir.path(), &messy).unwrap();
+        let written =
+            std::fs::read_to_string(temp_dir.path().join(".claude/agents/code-reviewer.md"))
+                .unwrap();
+        assert!(written.contains("\r\n"));
     }
 }
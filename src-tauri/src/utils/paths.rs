@@ -1,6 +1,7 @@
 use anyhow::Result;
 use directories::BaseDirs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
 
 pub struct ClaudePathsInternal {
     #[allow(dead_code)]
@@ -35,6 +36,94 @@ pub fn get_claude_paths() -> Result<ClaudePathsInternal> {
     })
 }
 
+/// Subdirectory layout for Claude-style configs, relative to a home or project path.
+/// Some Claude forks read from `.claude-code/agents` instead of `.claude/agents`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClaudeLayout {
+    pub agents_subdir: PathBuf, // .claude/agents by default
+    pub skills_subdir: PathBuf, // .claude/skills by default
+}
+
+impl Default for ClaudeLayout {
+    fn default() -> Self {
+        Self {
+            agents_subdir: Path::new(".claude").join("agents"),
+            skills_subdir: Path::new(".claude").join("skills"),
+        }
+    }
+}
+
+impl ClaudeLayout {
+    /// Resolve the agents directory under a base path
+    pub fn agents_dir(&self, base_path: &Path) -> PathBuf {
+        base_path.join(&self.agents_subdir)
+    }
+
+    /// Resolve the skills directory under a base path
+    pub fn skills_dir(&self, base_path: &Path) -> PathBuf {
+        base_path.join(&self.skills_subdir)
+    }
+}
+
+/// Default number of directory levels below the search root to look for projects
+pub const DEFAULT_DISCOVERY_DEPTH: usize = 4;
+
+/// Directories never descended into while discovering projects
+const DISCOVERY_SKIP_DIRS: &[&str] = &["node_modules", ".git", "target"];
+
+/// Find project paths containing a `.claude` directory under `search_root`.
+/// Uses DEFAULT_DISCOVERY_DEPTH; see discover_claude_roots_with_depth.
+pub fn discover_claude_roots(search_root: &Path) -> Vec<PathBuf> {
+    discover_claude_roots_with_depth(search_root, DEFAULT_DISCOVERY_DEPTH)
+}
+
+/// Find project paths containing a `.claude` directory, at most `max_depth` levels
+/// below `search_root` (0 checks only the root itself). Symlinks are not followed,
+/// so link cycles cannot loop the walk. Returns the project paths sorted.
+pub fn discover_claude_roots_with_depth(search_root: &Path, max_depth: usize) -> Vec<PathBuf> {
+    let mut roots: Vec<PathBuf> = WalkDir::new(search_root)
+        // +1 so the `.claude` directory of a project at max_depth is still seen
+        .max_depth(max_depth + 1)
+        .follow_links(false)
+        .into_iter()
+        .filter_entry(|entry| {
+            if entry.depth() == 0 {
+                return true;
+            }
+            // Don't descend into .claude dirs (they are matched, not searched)
+            let in_claude_dir = entry.depth() > 1
+                && entry
+                    .path()
+                    .parent()
+                    .and_then(Path::file_name)
+                    .is_some_and(|name| name == ".claude");
+            // ...or into heavy dependency/VCS dirs
+            let skipped = entry.file_type().is_dir()
+                && entry
+                    .file_name()
+                    .to_str()
+                    .is_some_and(|name| DISCOVERY_SKIP_DIRS.contains(&name));
+            !in_claude_dir && !skipped
+        })
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_type().is_dir() && entry.file_name() == ".claude")
+        .filter_map(|entry| entry.path().parent().map(Path::to_path_buf))
+        .collect();
+
+    roots.sort();
+    roots
+}
+
+/// Label a config base directory for logging: "global" for the home directory, else "project"
+pub fn config_scope(base_path: &Path) -> &'static str {
+    let is_home = BaseDirs::new().is_some_and(|d| d.home_dir() == base_path);
+    if is_home {
+        "global"
+    } else {
+        "project"
+    }
+}
+
 /// Normalize a path to use forward slashes for consistent comparison
 pub fn normalize_path(path: &str) -> String {
     path.replace('\\', "/")
@@ -124,6 +213,77 @@ mod tests {
         assert!(settings_file.to_string_lossy().contains(".claude"));
     }
 
+    #[test]
+    fn test_claude_layout_default() {
+        let layout = ClaudeLayout::default();
+        let base = PathBuf::from("/home/user/myproject");
+
+        assert_eq!(
+            layout.agents_dir(&base),
+            base.join(".claude").join("agents")
+        );
+        assert_eq!(
+            layout.skills_dir(&base),
+            base.join(".claude").join("skills")
+        );
+    }
+
+    #[test]
+    fn test_discover_claude_roots_nested_projects() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let root = temp_dir.path();
+        for project in ["alpha", "work/beta", "work/beta/packages/gamma"] {
+            std::fs::create_dir_all(root.join(project).join(".claude")).unwrap();
+        }
+        std::fs::create_dir_all(root.join("work/beta/node_modules/dep/.claude")).unwrap();
+        // Directories inside a .claude dir are never searched
+        std::fs::create_dir_all(root.join("alpha/.claude/worktree/.claude")).unwrap();
+
+        let roots = discover_claude_roots(root);
+
+        assert_eq!(
+            roots,
+            vec![
+                root.join("alpha"),
+                root.join("work/beta"),
+                root.join("work/beta/packages/gamma"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_discover_claude_roots_respects_depth_cap() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let root = temp_dir.path();
+        std::fs::create_dir_all(root.join(".claude")).unwrap();
+        std::fs::create_dir_all(root.join("a/.claude")).unwrap();
+        std::fs::create_dir_all(root.join("a/b/c/.claude")).unwrap();
+
+        assert_eq!(
+            discover_claude_roots_with_depth(root, 0),
+            vec![root.to_path_buf()]
+        );
+        assert_eq!(
+            discover_claude_roots_with_depth(root, 2),
+            vec![root.to_path_buf(), root.join("a")]
+        );
+        assert_eq!(discover_claude_roots_with_depth(root, 3).len(), 3);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_discover_claude_roots_does_not_follow_symlinks() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let root = temp_dir.path();
+        std::fs::create_dir_all(root.join("project/.claude")).unwrap();
+        // A link back to the root would loop forever if followed
+        std::os::unix::fs::symlink(root, root.join("project/loop")).unwrap();
+
+        let roots = discover_claude_roots_with_depth(root, 10);
+
+        assert_eq!(roots, vec![root.join("project")]);
+    }
+
     #[test]
     fn test_claude_paths_internal_fields() {
         let paths = get_claude_paths().expect("Should get claude paths");
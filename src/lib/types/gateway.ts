@@ -23,6 +23,13 @@ export interface BackendInfo {
 		name: string;
 		version?: string;
 	} | null;
+	capabilities?: {
+		protocolVersion: string | null;
+		tools: boolean;
+		resources: boolean;
+		prompts: boolean;
+		logging: boolean;
+	} | null;
 	errorMessage: string | null;
 	restartCount: number;
 }
@@ -31,6 +38,16 @@ export interface GatewayServerConfig {
 	enabled: boolean;
 	port: number;
 	autoStart: boolean;
+	/** Let clients add MCPs through the register_backend meta-tool */
+	allowBackendRegistration: boolean;
+	/** File the tool-call audit log is appended to (empty disables the log) */
+	auditLogPath: string;
+	resultCacheTtlSecs: number;
+	/** Disconnect backends unused for this long (0 disables eviction) */
+	idleTimeoutSecs: number;
+	circuitBreakerThreshold: number;
+	circuitBreakerCooldownSecs: number;
+	httpPoolSize: number;
 }
 
 export interface GatewayServerStatus {
@@ -91,7 +91,8 @@ export interface StartSessionResult {
 export type ToolContent =
 	| { type: 'text'; text: string }
 	| { type: 'image'; data: string; mimeType: string }
-	| { type: 'resource'; uri: string; mimeType?: string; text?: string };
+	| { type: 'resource'; uri: string; mimeType?: string; text?: string }
+	| { type: 'resource_link'; uri: string; name?: string; description?: string; mimeType?: string };
 
 export interface ToolCallResult {
 	success: boolean;
@@ -12,6 +12,10 @@ export interface Skill {
 	isFavorite: boolean;
 	createdAt: string;
 	updatedAt: string;
+	whenToUse?: string;
+	examples?: string[];
+	referenceFiles?: [string, string][];
+	sharedAssets?: [string, string][];
 }
 
 export interface CreateSkillRequest {
@@ -7,12 +7,18 @@ export interface SubAgent {
 	model?: string;
 	permissionMode?: string;
 	skills?: string[];
+	color?: string;
 	tags?: string[];
+	env?: string[];
+	mcpServers?: string[];
 	source: string;
 	sourcePath?: string;
 	isFavorite: boolean;
 	createdAt: string;
 	updatedAt: string;
+	enabled?: boolean;
+	extends?: string;
+	extra?: Record<string, unknown>;
 }
 
 export interface CreateSubAgentRequest {
@@ -23,7 +29,9 @@ export interface CreateSubAgentRequest {
 	model?: string;
 	permissionMode?: string;
 	skills?: string[];
+	color?: string;
 	tags?: string[];
+	env?: string[];
 }
 
 export interface ProjectSubAgent {